@@ -0,0 +1,41 @@
+CREATE TABLE IF NOT EXISTS worlds (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    seed INTEGER NOT NULL,
+    game_mode TEXT NOT NULL,
+    max_players INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    last_active TEXT NOT NULL,
+    is_online BOOLEAN NOT NULL DEFAULT 0,
+    settings TEXT NOT NULL,
+    spawn_point TEXT NOT NULL,
+    border TEXT,
+    whitelist TEXT,
+    whitelist_enabled BOOLEAN NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS worlds_created_at ON worlds (created_at, id);
+
+CREATE TABLE IF NOT EXISTS players (
+    id TEXT PRIMARY KEY NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    role TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    statistics TEXT,
+    data TEXT
+);
+
+CREATE TABLE IF NOT EXISTS credentials (
+    player_id TEXT PRIMARY KEY NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS entities (
+    id TEXT PRIMARY KEY NOT NULL,
+    world_id TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS entities_world_id ON entities (world_id);
@@ -0,0 +1 @@
+ALTER TABLE worlds ADD COLUMN player_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS bans (
+    player_id TEXT PRIMARY KEY NOT NULL,
+    reason TEXT NOT NULL,
+    until TEXT,
+    banned_by TEXT NOT NULL
+);
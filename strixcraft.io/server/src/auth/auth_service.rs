@@ -0,0 +1,113 @@
+use std::sync::Arc;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use log::{info, warn, error};
+
+use crate::auth::jwt_service::JwtService;
+use crate::database::player_repository::PlayerRepository;
+
+const HASH_PREFIX: &str = "$argon2";
+
+#[derive(Debug)]
+pub struct AuthService {
+    player_repository: Arc<PlayerRepository>,
+    jwt_service: Arc<JwtService>,
+}
+
+impl AuthService {
+    pub fn new(player_repository: Arc<PlayerRepository>, jwt_service: Arc<JwtService>) -> Self {
+        Self {
+            player_repository,
+            jwt_service,
+        }
+    }
+
+    pub async fn create_user(
+        &self,
+        username: &str,
+        password: &str,
+        player_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let password_hash = Self::hash_password(password)?;
+        self.player_repository.save_credentials(player_id, username, &password_hash).await?;
+        Ok(())
+    }
+
+    // Returns the player id when the username exists and the password matches its hash
+    pub async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let credentials = match self.player_repository.get_credentials(username).await? {
+            Some(credentials) => credentials,
+            None => return Ok(None),
+        };
+
+        if !credentials.password_hash.starts_with(HASH_PREFIX) {
+            warn!("Rejected login for {}: password has not been migrated to a hash", username);
+            return Ok(None);
+        }
+
+        if Self::verify_password(password, &credentials.password_hash) {
+            Ok(Some(credentials.player_id))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn verify_token(&self, token: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        Ok(self.jwt_service.verify_token(token).ok())
+    }
+
+    // Returns (access token, refresh token)
+    pub fn issue_tokens(&self, player_id: &str) -> Result<(String, String), String> {
+        self.jwt_service.create_token_pair(player_id)
+    }
+
+    pub fn refresh_tokens(&self, refresh_token: &str) -> Result<(String, String), String> {
+        self.jwt_service.refresh(refresh_token)
+    }
+
+    // Argon2id with a random per-password salt, stored as a PHC string
+    pub fn hash_password(password: &str) -> Result<String, String> {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| format!("Failed to hash password: {}", e))
+    }
+
+    pub fn verify_password(password: &str, password_hash: &str) -> bool {
+        match PasswordHash::new(password_hash) {
+            Ok(parsed) => Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok(),
+            Err(e) => {
+                error!("Stored password hash is malformed: {}", e);
+                false
+            }
+        }
+    }
+
+    // Older databases stored passwords as-is, hash them in place before anyone logs in
+    pub async fn migrate_plaintext_passwords(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut migrated = 0;
+
+        for credentials in self.player_repository.get_all_credentials().await? {
+            if credentials.password_hash.starts_with(HASH_PREFIX) {
+                continue;
+            }
+
+            let password_hash = Self::hash_password(&credentials.password_hash)?;
+            self.player_repository.update_password_hash(&credentials.player_id, &password_hash).await?;
+            migrated += 1;
+        }
+
+        if migrated > 0 {
+            info!("Hashed {} plaintext passwords", migrated);
+        }
+
+        Ok(migrated)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,150 @@
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+pub const MIN_SECRET_LENGTH: usize = 32;
+const ACCESS_TOKEN_LIFETIME_MINUTES: i64 = 15;
+const REFRESH_TOKEN_LIFETIME_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TokenType {
+    Access,
+    Refresh,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String, // player id
+    pub iat: i64,
+    pub exp: i64,
+    pub token_type: TokenType,
+}
+
+pub struct JwtService {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl std::fmt::Debug for JwtService {
+    // Keep the keys out of logs
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtService").finish_non_exhaustive()
+    }
+}
+
+impl JwtService {
+    pub fn new(secret: String) -> Result<Self, String> {
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(format!(
+                "JWT secret must be at least {} bytes long",
+                MIN_SECRET_LENGTH
+            ));
+        }
+
+        Ok(Self {
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+        })
+    }
+
+    // Returns (access token, refresh token)
+    pub fn create_token_pair(&self, player_id: &str) -> Result<(String, String), String> {
+        let access = self.create_token(player_id, TokenType::Access, Duration::minutes(ACCESS_TOKEN_LIFETIME_MINUTES))?;
+        let refresh = self.create_token(player_id, TokenType::Refresh, Duration::days(REFRESH_TOKEN_LIFETIME_DAYS))?;
+        Ok((access, refresh))
+    }
+
+    // Returns the player id the access token was issued for
+    pub fn verify_token(&self, token: &str) -> Result<String, String> {
+        self.decode_token(token, TokenType::Access)
+    }
+
+    // Trades a valid refresh token for a fresh pair
+    pub fn refresh(&self, refresh_token: &str) -> Result<(String, String), String> {
+        let player_id = self.decode_token(refresh_token, TokenType::Refresh)?;
+        self.create_token_pair(&player_id)
+    }
+
+    fn create_token(&self, player_id: &str, token_type: TokenType, lifetime: Duration) -> Result<String, String> {
+        let now = Utc::now();
+        let claims = Claims {
+            sub: player_id.to_string(),
+            iat: now.timestamp(),
+            exp: (now + lifetime).timestamp(),
+            token_type,
+        };
+
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| format!("Failed to create token: {}", e))
+    }
+
+    fn decode_token(&self, token: &str, expected: TokenType) -> Result<String, String> {
+        // Expired tokens are rejected outright, no grace period
+        let mut validation = Validation::default();
+        validation.leeway = 0;
+
+        let claims = decode::<Claims>(token, &self.decoding_key, &validation)
+            .map_err(|e| format!("Invalid token: {}", e))?
+            .claims;
+
+        if claims.token_type != expected {
+            return Err("Invalid token: wrong token type".to_string());
+        }
+
+        Ok(claims.sub)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> JwtService {
+        JwtService::new("0123456789abcdef0123456789abcdef".to_string()).unwrap()
+    }
+
+    #[test]
+    fn rejects_short_secrets() {
+        assert!(JwtService::new("too short".to_string()).is_err());
+    }
+
+    #[test]
+    fn issued_access_token_verifies() {
+        let jwt = service();
+        let (access, _) = jwt.create_token_pair("player-1").unwrap();
+        assert_eq!(jwt.verify_token(&access).unwrap(), "player-1");
+    }
+
+    #[test]
+    fn refresh_token_is_not_an_access_token() {
+        let jwt = service();
+        let (access, refresh) = jwt.create_token_pair("player-1").unwrap();
+        assert!(jwt.verify_token(&refresh).is_err());
+        assert!(jwt.refresh(&access).is_err());
+    }
+
+    #[test]
+    fn refresh_issues_a_new_pair_for_the_same_player() {
+        let jwt = service();
+        let (_, refresh) = jwt.create_token_pair("player-1").unwrap();
+        let (access, next_refresh) = jwt.refresh(&refresh).unwrap();
+        assert_eq!(jwt.verify_token(&access).unwrap(), "player-1");
+        assert!(jwt.refresh(&next_refresh).is_ok());
+    }
+
+    #[test]
+    fn expired_tokens_are_rejected() {
+        let jwt = service();
+        let access = jwt.create_token("player-1", TokenType::Access, Duration::seconds(-5)).unwrap();
+        let refresh = jwt.create_token("player-1", TokenType::Refresh, Duration::seconds(-5)).unwrap();
+        assert!(jwt.verify_token(&access).is_err());
+        assert!(jwt.refresh(&refresh).is_err());
+    }
+
+    #[test]
+    fn tokens_from_another_secret_are_rejected() {
+        let other = JwtService::new("fedcba9876543210fedcba9876543210".to_string()).unwrap();
+        let (access, _) = other.create_token_pair("player-1").unwrap();
+        assert!(service().verify_token(&access).is_err());
+    }
+}
\ No newline at end of file
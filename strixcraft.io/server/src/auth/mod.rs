@@ -0,0 +1,2 @@
+pub mod auth_service;
+pub mod jwt_service;
\ No newline at end of file
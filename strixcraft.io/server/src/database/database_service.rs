@@ -0,0 +1,108 @@
+use std::time::Duration;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use log::{info, error};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub timeout: u64, // Seconds to wait for a connection, also bounds the startup check
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite://data/strixcraft.db?mode=rwc".to_string(),
+            max_connections: 10,
+            timeout: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStats {
+    pub size: u32, // Open connections, busy or idle
+    pub idle: usize,
+    pub max_connections: u32,
+}
+
+#[derive(Debug)]
+pub struct DatabaseService {
+    pool: SqlitePool,
+    max_connections: u32,
+}
+
+impl DatabaseService {
+    // Connects eagerly so a bad URL or an unreachable database stops startup
+    // instead of failing the first request that needs it
+    pub async fn new(config: &DatabaseConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(Duration::from_secs(config.timeout))
+            .connect(&config.url)
+            .await
+            .map_err(|e| format!("Database at {} is unreachable: {}", config.url, e))?;
+
+        sqlx::query("SELECT 1")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("Database at {} is not responding: {}", config.url, e))?;
+
+        // Schema lives in migrations/, embedded at compile time and applied in order
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .map_err(|e| format!("Failed to migrate database at {}: {}", config.url, e))?;
+
+        info!("Connected to database with a pool of up to {} connections", config.max_connections);
+
+        Ok(Self {
+            pool,
+            max_connections: config.max_connections,
+        })
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    // Runs every write in the closure on one transaction, committed if it returns Ok and
+    // rolled back otherwise. The closure gets owned data so its future can borrow the connection
+    pub async fn with_transaction<T, F>(&self, f: F) -> Result<T, Box<dyn std::error::Error>>
+    where
+        F: for<'c> FnOnce(&'c mut SqliteConnection) -> BoxFuture<'c, Result<T, Box<dyn std::error::Error>>>,
+    {
+        let mut tx = self.pool.begin().await?;
+
+        // Flattened to a String because Box<dyn Error> isn't Send and the rollback awaits
+        match f(&mut tx).await.map_err(|e| e.to_string()) {
+            Ok(value) => {
+                tx.commit().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                if let Err(rollback_error) = tx.rollback().await {
+                    error!("Failed to roll back transaction: {}", rollback_error);
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    pub fn pool_stats(&self) -> PoolStats {
+        PoolStats {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+            max_connections: self.max_connections,
+        }
+    }
+
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use sqlx::{Row, SqliteConnection};
+use log::{info, warn};
+
+use crate::database::database_service::DatabaseService;
+use crate::entities::mob::Entity;
+
+#[derive(Debug)]
+pub struct EntityRepository {
+    database_service: Arc<DatabaseService>,
+}
+
+impl EntityRepository {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+
+    // Runs on a caller's connection so the snapshot joins the world's save transaction
+    pub async fn save_world_entities_in(
+        &self,
+        conn: &mut SqliteConnection,
+        world_id: &str,
+        entities: &[Entity],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Replace the world's snapshot wholesale so despawned entities don't linger
+        sqlx::query("DELETE FROM entities WHERE world_id = ?")
+            .bind(world_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for entity in entities {
+            sqlx::query("INSERT INTO entities (id, world_id, data) VALUES (?, ?, ?)")
+                .bind(&entity.id)
+                .bind(world_id)
+                .bind(serde_json::to_string(entity)?)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn load_world_entities(
+        &self,
+        world_id: &str,
+    ) -> Result<Vec<Entity>, Box<dyn std::error::Error>> {
+        let rows = sqlx::query("SELECT data FROM entities WHERE world_id = ?")
+            .bind(world_id)
+            .fetch_all(self.database_service.pool())
+            .await?;
+
+        let mut entities = Vec::with_capacity(rows.len());
+        for row in rows {
+            let data: String = row.try_get("data")?;
+            match serde_json::from_str(&data) {
+                Ok(entity) => entities.push(entity),
+                Err(e) => warn!("Skipping unreadable entity in world {}: {}", world_id, e),
+            }
+        }
+
+        Ok(entities)
+    }
+
+    pub async fn delete_world_entities(&self, world_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("DELETE FROM entities WHERE world_id = ?")
+            .bind(world_id)
+            .execute(self.database_service.pool())
+            .await?;
+
+        info!("Deleted saved entities for world {}", world_id);
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,4 @@
+pub mod database_service;
+pub mod world_repository;
+pub mod player_repository;
+pub mod entity_repository;
\ No newline at end of file
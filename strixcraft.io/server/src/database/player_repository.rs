@@ -0,0 +1,275 @@
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqliteConnection};
+use sqlx::sqlite::SqliteRow;
+use log::{info, warn};
+
+use crate::database::database_service::DatabaseService;
+use crate::entities::player::{Player, PlayerStatistics};
+use crate::systems::player_manager::{BanInfo, PlayerRole};
+
+// A player row as stored, PlayerManager turns it into a Player
+#[derive(Debug, Clone)]
+pub struct PlayerRecord {
+    pub id: String,
+    pub username: String,
+    pub role: String,
+    pub last_seen: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub statistics: PlayerStatistics,
+    pub data: Option<Player>, // Last saved state, None if it was never written or can't be read
+}
+
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub player_id: String,
+    pub password_hash: String,
+}
+
+#[derive(Debug)]
+pub struct PlayerRepository {
+    database_service: Arc<DatabaseService>,
+}
+
+impl PlayerRepository {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+
+    pub async fn get_all_players(&self) -> Result<Vec<PlayerRecord>, Box<dyn std::error::Error>> {
+        let rows = sqlx::query("SELECT id, username, role, last_seen, created_at, statistics, data FROM players")
+            .fetch_all(self.database_service.pool())
+            .await?;
+
+        let mut players = Vec::with_capacity(rows.len());
+        for row in rows {
+            match Self::record_from_row(&row) {
+                Ok(player) => players.push(player),
+                Err(e) => warn!("Skipping unreadable player row: {}", e),
+            }
+        }
+
+        Ok(players)
+    }
+
+    pub async fn create_player(&self, player: &Player) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query(
+            "INSERT INTO players (id, username, role, last_seen, created_at, statistics, data) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&player.id)
+        .bind(&player.username)
+        .bind(Self::role_name(player.role))
+        .bind(player.last_seen)
+        .bind(player.created_at)
+        .bind(serde_json::to_string(&player.statistics)?)
+        .bind(serde_json::to_string(player)?)
+        .execute(self.database_service.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn save_player(&self, player: &Player) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.database_service.pool().acquire().await?;
+        self.save_player_in(&mut conn, player).await
+    }
+
+    // Same as save_player on a caller's connection, so it can join a transaction
+    pub async fn save_player_in(&self, conn: &mut SqliteConnection, player: &Player) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("UPDATE players SET role = ?, last_seen = ?, statistics = ?, data = ? WHERE id = ?")
+            .bind(Self::role_name(player.role))
+            .bind(player.last_seen)
+            .bind(serde_json::to_string(&player.statistics)?)
+            .bind(serde_json::to_string(player)?)
+            .bind(&player.id)
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_player_last_seen(&self, player_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("UPDATE players SET last_seen = ? WHERE id = ?")
+            .bind(Utc::now())
+            .bind(player_id)
+            .execute(self.database_service.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_player_role(&self, player_id: &str, role: PlayerRole) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("UPDATE players SET role = ? WHERE id = ?")
+            .bind(Self::role_name(role))
+            .bind(player_id)
+            .execute(self.database_service.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_player(&self, player_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut tx = self.database_service.pool().begin().await?;
+
+        sqlx::query("DELETE FROM credentials WHERE player_id = ?")
+            .bind(player_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM bans WHERE player_id = ?")
+            .bind(player_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM players WHERE id = ?")
+            .bind(player_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        info!("Deleted player {} from the database", player_id);
+        Ok(())
+    }
+
+    pub async fn save_ban(&self, player_id: &str, ban: &BanInfo) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("INSERT OR REPLACE INTO bans (player_id, reason, until, banned_by) VALUES (?, ?, ?, ?)")
+            .bind(player_id)
+            .bind(&ban.reason)
+            .bind(ban.until)
+            .bind(&ban.by)
+            .execute(self.database_service.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_ban(&self, player_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("DELETE FROM bans WHERE player_id = ?")
+            .bind(player_id)
+            .execute(self.database_service.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_expired_bans(&self, now: DateTime<Utc>) -> Result<u64, Box<dyn std::error::Error>> {
+        let deleted = sqlx::query("DELETE FROM bans WHERE until IS NOT NULL AND until < ?")
+            .bind(now)
+            .execute(self.database_service.pool())
+            .await?
+            .rows_affected();
+
+        Ok(deleted)
+    }
+
+    pub async fn get_all_bans(&self) -> Result<Vec<(String, BanInfo)>, Box<dyn std::error::Error>> {
+        let rows = sqlx::query("SELECT player_id, reason, until, banned_by FROM bans")
+            .fetch_all(self.database_service.pool())
+            .await?;
+
+        let mut bans = Vec::with_capacity(rows.len());
+        for row in rows {
+            let ban = BanInfo {
+                reason: row.try_get("reason")?,
+                until: row.try_get("until")?,
+                by: row.try_get("banned_by")?,
+            };
+            bans.push((row.try_get("player_id")?, ban));
+        }
+
+        Ok(bans)
+    }
+
+    pub async fn save_credentials(&self, player_id: &str, username: &str, password_hash: &str) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("INSERT INTO credentials (player_id, username, password_hash) VALUES (?, ?, ?)")
+            .bind(player_id)
+            .bind(username)
+            .bind(password_hash)
+            .execute(self.database_service.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_credentials(&self, username: &str) -> Result<Option<Credentials>, Box<dyn std::error::Error>> {
+        let row = sqlx::query("SELECT player_id, password_hash FROM credentials WHERE username = ?")
+            .bind(username)
+            .fetch_optional(self.database_service.pool())
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(Self::credentials_from_row(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_all_credentials(&self) -> Result<Vec<Credentials>, Box<dyn std::error::Error>> {
+        let rows = sqlx::query("SELECT player_id, password_hash FROM credentials")
+            .fetch_all(self.database_service.pool())
+            .await?;
+
+        let mut credentials = Vec::with_capacity(rows.len());
+        for row in rows {
+            credentials.push(Self::credentials_from_row(&row)?);
+        }
+
+        Ok(credentials)
+    }
+
+    pub async fn update_password_hash(&self, player_id: &str, password_hash: &str) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("UPDATE credentials SET password_hash = ? WHERE player_id = ?")
+            .bind(password_hash)
+            .bind(player_id)
+            .execute(self.database_service.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    fn record_from_row(row: &SqliteRow) -> Result<PlayerRecord, Box<dyn std::error::Error>> {
+        let id: String = row.try_get("id")?;
+        let statistics: Option<String> = row.try_get("statistics")?;
+        let data: Option<String> = row.try_get("data")?;
+
+        // A bad snapshot only costs the player their saved state, not their account
+        let data = match data.map(|data| serde_json::from_str::<Player>(&data)) {
+            Some(Ok(player)) => Some(player),
+            Some(Err(e)) => {
+                warn!("Unreadable saved state for player {}, using defaults: {}", id, e);
+                None
+            }
+            None => {
+                warn!("No saved state for player {}, using defaults", id);
+                None
+            }
+        };
+
+        Ok(PlayerRecord {
+            id,
+            username: row.try_get("username")?,
+            role: row.try_get("role")?,
+            last_seen: row.try_get("last_seen")?,
+            created_at: row.try_get("created_at")?,
+            statistics: match statistics {
+                Some(statistics) => serde_json::from_str(&statistics)?,
+                None => PlayerStatistics::default(),
+            },
+            data,
+        })
+    }
+
+    fn credentials_from_row(row: &SqliteRow) -> Result<Credentials, Box<dyn std::error::Error>> {
+        Ok(Credentials {
+            player_id: row.try_get("player_id")?,
+            password_hash: row.try_get("password_hash")?,
+        })
+    }
+
+    fn role_name(role: PlayerRole) -> &'static str {
+        match role {
+            PlayerRole::Guest => "guest",
+            PlayerRole::Player => "player",
+            PlayerRole::Moderator => "moderator",
+            PlayerRole::Admin => "admin",
+        }
+    }
+}
\ No newline at end of file
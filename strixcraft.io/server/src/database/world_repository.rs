@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use sqlx::{QueryBuilder, Row, Sqlite, SqliteConnection};
+use sqlx::sqlite::SqliteRow;
+use log::{info, warn};
+
+use crate::database::database_service::DatabaseService;
+use crate::systems::world_manager::{GameMode, WorldBorder, WorldInfo, WorldUpdate};
+
+const WORLD_COLUMNS: &str = "id, name, seed, game_mode, max_players, created_at, last_active, \
+    settings, spawn_point, border, whitelist, whitelist_enabled";
+
+// A world row as stored, WorldManager turns it into a WorldInfo
+#[derive(Debug, Clone)]
+pub struct WorldRecord {
+    pub id: String,
+    pub name: String,
+    pub seed: i64,
+    pub game_mode: String,
+    pub max_players: usize,
+    pub created_at: DateTime<Utc>,
+    pub last_active: DateTime<Utc>,
+    pub settings: serde_json::Value,
+    pub spawn_point: [f64; 3],
+    pub border: Option<WorldBorder>,
+    pub whitelist: Option<HashSet<String>>,
+    pub whitelist_enabled: bool,
+}
+
+// Applied in the WHERE clause, None matches everything
+#[derive(Debug, Clone, Default)]
+pub struct WorldFilter {
+    pub is_online: Option<bool>,
+    pub game_mode: Option<GameMode>,
+}
+
+#[derive(Debug)]
+pub struct WorldRepository {
+    database_service: Arc<DatabaseService>,
+}
+
+impl WorldRepository {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+
+    pub async fn create_world(&self, world: &WorldInfo) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query(
+            "INSERT INTO worlds (id, name, seed, game_mode, max_players, created_at, last_active, is_online, \
+             settings, spawn_point, border, whitelist, whitelist_enabled) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&world.id)
+        .bind(&world.name)
+        .bind(world.seed)
+        .bind(Self::game_mode_name(&world.game_mode))
+        .bind(world.max_players as i64)
+        .bind(world.created_at)
+        .bind(world.last_active)
+        .bind(world.is_online)
+        .bind(serde_json::to_string(&world.settings)?)
+        .bind(serde_json::to_string(&world.spawn_point)?)
+        .bind(serde_json::to_string(&world.border)?)
+        .bind(serde_json::to_string(&world.whitelist)?)
+        .bind(world.whitelist_enabled)
+        .execute(self.database_service.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_world(&self, world_id: &str, update: &WorldUpdate) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.database_service.pool().acquire().await?;
+        self.update_world_in(&mut conn, world_id, update).await
+    }
+
+    // Same as update_world on a caller's connection, so it can join a transaction
+    pub async fn update_world_in(
+        &self,
+        conn: &mut SqliteConnection,
+        world_id: &str,
+        update: &WorldUpdate,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let query = match update {
+            WorldUpdate::PlayerCount(count) => {
+                sqlx::query("UPDATE worlds SET player_count = ? WHERE id = ?").bind(*count as i64)
+            }
+            WorldUpdate::LastActive(time) => {
+                sqlx::query("UPDATE worlds SET last_active = ? WHERE id = ?").bind(*time)
+            }
+            WorldUpdate::IsOnline(online) => {
+                sqlx::query("UPDATE worlds SET is_online = ? WHERE id = ?").bind(*online)
+            }
+            WorldUpdate::Settings(settings) => {
+                sqlx::query("UPDATE worlds SET settings = ? WHERE id = ?").bind(serde_json::to_string(settings)?)
+            }
+            WorldUpdate::Border(border) => {
+                sqlx::query("UPDATE worlds SET border = ? WHERE id = ?").bind(serde_json::to_string(border)?)
+            }
+            WorldUpdate::SpawnPoint(spawn_point) => {
+                sqlx::query("UPDATE worlds SET spawn_point = ? WHERE id = ?").bind(serde_json::to_string(spawn_point)?)
+            }
+            WorldUpdate::Whitelist { entries, enabled } => {
+                sqlx::query("UPDATE worlds SET whitelist = ?, whitelist_enabled = ? WHERE id = ?")
+                    .bind(serde_json::to_string(entries)?)
+                    .bind(*enabled)
+            }
+        };
+
+        query.bind(world_id).execute(conn).await?;
+        Ok(())
+    }
+
+    pub async fn delete_world(&self, world_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // Chunks live on disk, the chunk manager removes them
+        sqlx::query("DELETE FROM worlds WHERE id = ?")
+            .bind(world_id)
+            .execute(self.database_service.pool())
+            .await?;
+
+        info!("Deleted world {} from the database", world_id);
+        Ok(())
+    }
+
+    // Oldest first so pages stay stable while new worlds are being created. Also returns the
+    // number of rows read, which can exceed the records when unreadable rows are skipped
+    pub async fn get_worlds_page(
+        &self,
+        offset: usize,
+        limit: usize,
+        filter: &WorldFilter,
+    ) -> Result<(Vec<WorldRecord>, usize), Box<dyn std::error::Error>> {
+        let mut query = QueryBuilder::<Sqlite>::new(format!("SELECT {} FROM worlds", WORLD_COLUMNS));
+        Self::push_filter(&mut query, filter);
+        query
+            .push(" ORDER BY created_at, id LIMIT ")
+            .push_bind(limit as i64)
+            .push(" OFFSET ")
+            .push_bind(offset as i64);
+
+        let rows = query.build().fetch_all(self.database_service.pool()).await?;
+        let rows_read = rows.len();
+
+        let mut worlds = Vec::with_capacity(rows.len());
+        for row in rows {
+            match Self::record_from_row(&row) {
+                Ok(world) => worlds.push(world),
+                Err(e) => warn!("Skipping unreadable world row: {}", e),
+            }
+        }
+
+        Ok((worlds, rows_read))
+    }
+
+    fn push_filter(query: &mut QueryBuilder<'_, Sqlite>, filter: &WorldFilter) {
+        let mut separator = " WHERE ";
+
+        if let Some(online) = filter.is_online {
+            query.push(separator).push("is_online = ").push_bind(online);
+            separator = " AND ";
+        }
+        if let Some(game_mode) = &filter.game_mode {
+            query.push(separator).push("game_mode = ").push_bind(Self::game_mode_name(game_mode));
+        }
+    }
+
+    fn record_from_row(row: &SqliteRow) -> Result<WorldRecord, Box<dyn std::error::Error>> {
+        let settings: String = row.try_get("settings")?;
+        let spawn_point: String = row.try_get("spawn_point")?;
+        let border: Option<String> = row.try_get("border")?;
+        let whitelist: Option<String> = row.try_get("whitelist")?;
+        let max_players: i64 = row.try_get("max_players")?;
+
+        Ok(WorldRecord {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            seed: row.try_get("seed")?,
+            game_mode: row.try_get("game_mode")?,
+            max_players: max_players as usize,
+            created_at: row.try_get("created_at")?,
+            last_active: row.try_get("last_active")?,
+            settings: serde_json::from_str(&settings)?,
+            spawn_point: serde_json::from_str(&spawn_point)?,
+            border: border.map(|b| serde_json::from_str(&b)).transpose()?.flatten(),
+            whitelist: whitelist.map(|w| serde_json::from_str(&w)).transpose()?.flatten(),
+            whitelist_enabled: row.try_get("whitelist_enabled")?,
+        })
+    }
+
+    fn game_mode_name(game_mode: &GameMode) -> &'static str {
+        match game_mode {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+        }
+    }
+}
\ No newline at end of file
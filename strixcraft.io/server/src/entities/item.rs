@@ -0,0 +1 @@
+pub use crate::systems::inventory_system::{Inventory, InventoryItem};
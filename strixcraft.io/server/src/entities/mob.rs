@@ -0,0 +1,2 @@
+// Mobs are plain entities told apart by their kind
+pub use crate::systems::entity_manager::Entity;
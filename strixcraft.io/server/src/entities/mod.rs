@@ -0,0 +1,4 @@
+// Game object types as they are stored and sent, the systems that simulate them live in crate::systems
+pub mod player;
+pub mod mob;
+pub mod item;
@@ -0,0 +1 @@
+pub use crate::systems::player_manager::{Player, PlayerStatistics};
@@ -1,13 +1,18 @@
 use actix_web::{web, App, HttpServer, middleware, HttpResponse};
 use actix_cors::Cors;
 use actix_files::Files;
-use log::{info, error};
+use log::{info, warn, error};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use sysinfo::System;
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
-use uuid::Uuid;
 
+use crate::metrics::Metrics;
+
+mod metrics;
 mod systems;
 mod worlds;
 mod entities;
@@ -16,37 +21,42 @@ mod auth;
 mod database;
 
 use crate::systems::{
-    world_manager::WorldManager,
-    player_manager::PlayerManager,
+    world_manager::{GameMode, GameRuleValue, WorldManager, WorldQuery, WorldSettings},
+    player_manager::{ExperienceCurve, MovementLimits, PlayerManager, PlayerRole, StatKind},
     chunk_manager::ChunkManager,
-    entity_manager::EntityManager,
+    entity_manager::{EntityManager, EntityProps, DEFAULT_ITEM_TTL_SECONDS, DEFAULT_MAX_ENTITIES_PER_WORLD},
     crafting_system::CraftingSystem,
-    inventory_system::InventorySystem,
-    chat_system::ChatSystem,
-    command_system::CommandSystem,
+    inventory_system::{InventorySystem, DEFAULT_INVENTORY_SLOTS},
+    item_registry::ItemRegistry,
+    trade_system::TradeSystem,
+    chat_system::{
+        ChatSystem, MessageType, DEFAULT_DUPLICATE_HISTORY, DEFAULT_DUPLICATE_WINDOW_SECONDS,
+        DEFAULT_MAX_MESSAGE_LENGTH, DEFAULT_RATE_LIMIT_SECONDS,
+    },
+    command_system::{CommandSystem, DEFAULT_MAX_SUGGESTIONS},
     physics_system::PhysicsSystem,
+    explosion_system::ExplosionSystem,
+    fluid_system::FluidSystem,
+    mining_system::MiningSystem,
+    status_effect_system::{EffectKind, StatusEffectSystem},
     mob_system::MobSystem,
-    weather_system::WeatherSystem,
+    weather_system::{Weather, WeatherSystem},
     time_system::TimeSystem,
     save_system::SaveSystem,
+    loot_system::LootSystem,
 };
 
 use crate::worlds::{
-    terrain_generator::TerrainGenerator,
+    terrain_generator::TerrainParams,
     biome_system::BiomeSystem,
     structure_generator::StructureGenerator,
-};
-
-use crate::entities::{
-    player::Player,
-    mob::Mob,
-    item::Item,
+    block_registry::BlockRegistry,
 };
 
 use crate::networking::{
     websocket_handler::WebSocketHandler,
     message_handler::MessageHandler,
-    protocol::Protocol,
+    protocol::{Protocol, ServerMessage},
 };
 
 use crate::auth::{
@@ -55,22 +65,50 @@ use crate::auth::{
 };
 
 use crate::database::{
-    database_service::DatabaseService,
+    database_service::{DatabaseConfig, DatabaseService},
     world_repository::WorldRepository,
     player_repository::PlayerRepository,
+    entity_repository::EntityRepository,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)] // Config files only need the keys they change
 pub struct ServerConfig {
     pub port: u16,
     pub host: String,
     pub max_players: usize,
     pub world_save_interval: u64,
+    pub player_save_interval: u64,
     pub chunk_load_distance: i32,
+    pub chunk_storage_path: String,
+    pub network_tick_millis: u64,
+    pub heartbeat_interval: u64,
+    pub client_timeout: u64,
     pub enable_physics: bool,
     pub enable_mobs: bool,
     pub enable_weather: bool,
     pub enable_time: bool,
+    pub enable_metrics: bool,
+    pub terrain: TerrainParams,
+    pub database: DatabaseConfig,
+    pub admin_api_key: Option<String>, // Admin endpoints are disabled without one
+    pub tls_cert_path: Option<String>, // PEM certificate chain, TLS is on when both paths are set
+    pub tls_key_path: Option<String>, // PEM private key
+    pub cors_allowed_origins: Vec<String>, // Empty allows any origin, without credentials
+    pub movement: MovementLimits,
+    pub item_ttl_seconds: i64, // Dropped items despawn after this long
+    pub max_entities_per_world: usize, // Worlds can override it through their settings
+    pub entity_kinds: HashMap<String, EntityProps>, // Custom mob kinds, keyed by id
+    pub inventory_slots: usize,
+    pub chat_rate_limit_seconds: i64, // 0 disables chat throttling
+    pub chat_rate_limit_overrides: HashMap<MessageType, i64>, // Per message type, e.g. a longer gap for commands
+    pub max_chat_message_length: usize, // System broadcasts are exempt
+    pub chat_duplicate_history: usize, // 0 turns the repeated line filter off
+    pub chat_duplicate_window_seconds: i64,
+    pub profanity_file: Option<String>, // One word per line, replaces the built-in list
+    pub experience_curve: ExperienceCurve,
+    pub player_retention_days: Option<u64>, // Offline players unseen this long are deleted, None keeps everyone
+    pub max_command_suggestions: usize, // Tab completions sent per request
 }
 
 impl Default for ServerConfig {
@@ -80,42 +118,328 @@ impl Default for ServerConfig {
             host: "127.0.0.1".to_string(),
             max_players: 100,
             world_save_interval: 300, // 5 minutes
+            player_save_interval: 60, // 1 minute
             chunk_load_distance: 8,
+            chunk_storage_path: "data/chunks".to_string(),
+            network_tick_millis: 50, // 20 updates per second
+            heartbeat_interval: 5, // 5 seconds
+            client_timeout: 30, // 30 seconds
             enable_physics: true,
             enable_mobs: true,
             enable_weather: true,
             enable_time: true,
+            enable_metrics: true,
+            terrain: TerrainParams::default(),
+            database: DatabaseConfig::default(),
+            admin_api_key: None,
+            tls_cert_path: None,
+            tls_key_path: None,
+            cors_allowed_origins: Vec::new(),
+            movement: MovementLimits::default(),
+            item_ttl_seconds: DEFAULT_ITEM_TTL_SECONDS,
+            max_entities_per_world: DEFAULT_MAX_ENTITIES_PER_WORLD,
+            entity_kinds: HashMap::new(),
+            inventory_slots: DEFAULT_INVENTORY_SLOTS,
+            chat_rate_limit_seconds: DEFAULT_RATE_LIMIT_SECONDS,
+            chat_rate_limit_overrides: HashMap::new(),
+            max_chat_message_length: DEFAULT_MAX_MESSAGE_LENGTH,
+            chat_duplicate_history: DEFAULT_DUPLICATE_HISTORY,
+            chat_duplicate_window_seconds: DEFAULT_DUPLICATE_WINDOW_SECONDS,
+            profanity_file: None,
+            experience_curve: ExperienceCurve::default(),
+            player_retention_days: None,
+            max_command_suggestions: DEFAULT_MAX_SUGGESTIONS,
+        }
+    }
+}
+
+impl ServerConfig {
+    // Precedence: CLI > env > file > default
+    pub fn load(args: &[String]) -> Result<Self, String> {
+        let mut config = match Self::cli_value(args, "--config")? {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+
+        config.apply_env_overrides()?;
+
+        if let Some(port) = Self::cli_value(args, "--port")? {
+            config.port = port.parse().map_err(|_| format!("Invalid --port value: {}", port))?;
+        }
+        if let Some(host) = Self::cli_value(args, "--host")? {
+            config.host = host;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path, e))?;
+
+        if path.ends_with(".toml") {
+            toml::from_str(&contents).map_err(|e| format!("Invalid config file {}: {}", path, e))
+        } else {
+            serde_json::from_str(&contents).map_err(|e| format!("Invalid config file {}: {}", path, e))
+        }
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        Self::env_override("STRIX_PORT", &mut self.port)?;
+        Self::env_override("STRIX_HOST", &mut self.host)?;
+        Self::env_override("STRIX_MAX_PLAYERS", &mut self.max_players)?;
+        Self::env_override("STRIX_WORLD_SAVE_INTERVAL", &mut self.world_save_interval)?;
+        Self::env_override("STRIX_PLAYER_SAVE_INTERVAL", &mut self.player_save_interval)?;
+        Self::env_override("STRIX_CHUNK_LOAD_DISTANCE", &mut self.chunk_load_distance)?;
+        Self::env_override("STRIX_CHUNK_STORAGE_PATH", &mut self.chunk_storage_path)?;
+        Self::env_override("STRIX_NETWORK_TICK_MILLIS", &mut self.network_tick_millis)?;
+        Self::env_override("STRIX_HEARTBEAT_INTERVAL", &mut self.heartbeat_interval)?;
+        Self::env_override("STRIX_CLIENT_TIMEOUT", &mut self.client_timeout)?;
+        Self::env_override("STRIX_ENABLE_PHYSICS", &mut self.enable_physics)?;
+        Self::env_override("STRIX_ENABLE_MOBS", &mut self.enable_mobs)?;
+        Self::env_override("STRIX_ENABLE_WEATHER", &mut self.enable_weather)?;
+        Self::env_override("STRIX_ENABLE_TIME", &mut self.enable_time)?;
+        Self::env_override("STRIX_ENABLE_METRICS", &mut self.enable_metrics)?;
+        Self::env_override("STRIX_ITEM_TTL_SECONDS", &mut self.item_ttl_seconds)?;
+        Self::env_override("STRIX_MAX_ENTITIES_PER_WORLD", &mut self.max_entities_per_world)?;
+        Self::env_override("STRIX_INVENTORY_SLOTS", &mut self.inventory_slots)?;
+        Self::env_override("STRIX_CHAT_RATE_LIMIT_SECONDS", &mut self.chat_rate_limit_seconds)?;
+        Self::env_override("STRIX_MAX_CHAT_MESSAGE_LENGTH", &mut self.max_chat_message_length)?;
+        Self::env_override("STRIX_CHAT_DUPLICATE_HISTORY", &mut self.chat_duplicate_history)?;
+        Self::env_override("STRIX_CHAT_DUPLICATE_WINDOW_SECONDS", &mut self.chat_duplicate_window_seconds)?;
+        Self::env_override("STRIX_MAX_COMMAND_SUGGESTIONS", &mut self.max_command_suggestions)?;
+        Self::env_override("STRIX_DATABASE_URL", &mut self.database.url)?;
+        Self::env_override("STRIX_DATABASE_MAX_CONNECTIONS", &mut self.database.max_connections)?;
+        Self::env_override("STRIX_DATABASE_TIMEOUT", &mut self.database.timeout)?;
+        if let Ok(days) = std::env::var("STRIX_PLAYER_RETENTION_DAYS") {
+            let days = days.parse().map_err(|_| format!("Invalid value for STRIX_PLAYER_RETENTION_DAYS: {}", days))?;
+            self.player_retention_days = Some(days);
+        }
+        if let Ok(api_key) = std::env::var("STRIX_ADMIN_API_KEY") {
+            self.admin_api_key = Some(api_key);
+        }
+        if let Ok(path) = std::env::var("STRIX_PROFANITY_FILE") {
+            self.profanity_file = Some(path);
+        }
+        if let Ok(path) = std::env::var("STRIX_TLS_CERT_PATH") {
+            self.tls_cert_path = Some(path);
+        }
+        if let Ok(path) = std::env::var("STRIX_TLS_KEY_PATH") {
+            self.tls_key_path = Some(path);
+        }
+        if let Ok(origins) = std::env::var("STRIX_CORS_ORIGINS") {
+            // Comma separated, e.g. "https://play.example.com,https://example.com"
+            self.cors_allowed_origins = origins
+                .split(',')
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn env_override<T: std::str::FromStr>(name: &str, field: &mut T) -> Result<(), String> {
+        if let Ok(value) = std::env::var(name) {
+            *field = value.parse().map_err(|_| format!("Invalid value for {}: {}", name, value))?;
+        }
+        Ok(())
+    }
+
+    // Accepts both "--flag value" and "--flag=value"
+    fn cli_value(args: &[String], flag: &str) -> Result<Option<String>, String> {
+        let mut value = None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == flag {
+                let next = iter.next().ok_or_else(|| format!("Missing value for {}", flag))?;
+                value = Some(next.clone());
+            } else if let Some(inline) = arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')) {
+                value = Some(inline.to_string());
+            }
+        }
+        Ok(value)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port == 0 {
+            return Err("port must be non-zero".to_string());
+        }
+        if self.max_players == 0 {
+            return Err("max_players must be at least 1".to_string());
+        }
+        if !(1..=32).contains(&self.chunk_load_distance) {
+            return Err("chunk_load_distance must be between 1 and 32".to_string());
+        }
+        if self.network_tick_millis == 0 || self.heartbeat_interval == 0 {
+            return Err("network_tick_millis and heartbeat_interval must be non-zero".to_string());
+        }
+        if self.client_timeout <= self.heartbeat_interval {
+            return Err("client_timeout must be longer than heartbeat_interval".to_string());
+        }
+        if self.terrain.octaves == 0 || self.terrain.frequency <= 0.0 {
+            return Err("terrain octaves and frequency must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.terrain.cave_density) {
+            return Err("terrain cave_density must be between 0 and 1".to_string());
+        }
+        if self.item_ttl_seconds <= 0 {
+            return Err("item_ttl_seconds must be positive".to_string());
+        }
+        if self.max_entities_per_world == 0 {
+            return Err("max_entities_per_world must be positive".to_string());
+        }
+        if self.max_chat_message_length == 0 {
+            return Err("max_chat_message_length must be positive".to_string());
+        }
+        if self.chat_duplicate_window_seconds < 0 {
+            return Err("chat_duplicate_window_seconds can't be negative".to_string());
+        }
+        if self.inventory_slots == 0 {
+            return Err("inventory_slots must be positive".to_string());
+        }
+        if self.player_retention_days == Some(0) {
+            return Err("player_retention_days must be positive".to_string());
+        }
+        if let ExperienceCurve::Table(thresholds) = &self.experience_curve {
+            if thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err("experience_curve thresholds must be increasing".to_string());
+            }
+        }
+        if self.database.url.is_empty() {
+            return Err("database url must be set".to_string());
+        }
+        if self.database.max_connections == 0 || self.database.timeout == 0 {
+            return Err("database max_connections and timeout must be non-zero".to_string());
+        }
+        if self.admin_api_key.as_ref().is_some_and(|key| key.len() < 16) {
+            return Err("admin_api_key must be at least 16 characters".to_string());
+        }
+        if self.tls_cert_path.is_some() != self.tls_key_path.is_some() {
+            return Err("tls_cert_path and tls_key_path must be set together".to_string());
         }
+        for origin in &self.cors_allowed_origins {
+            Self::validate_origin(origin)?;
+        }
+        Ok(())
+    }
+
+    // Browsers send the bare scheme://host[:port], anything else would never match
+    fn validate_origin(origin: &str) -> Result<(), String> {
+        let host = origin
+            .strip_prefix("https://")
+            .or_else(|| origin.strip_prefix("http://"))
+            .ok_or_else(|| format!("CORS origin {} must start with http:// or https://", origin))?;
+
+        if host.is_empty() || host.contains('/') || host.contains('*') {
+            return Err(format!("CORS origin {} must be a bare scheme://host[:port] without paths or wildcards", origin));
+        }
+        Ok(())
+    }
+
+    // None when TLS isn't configured. Loads the pair up front so a missing file or a key
+    // that doesn't parse stops startup instead of surfacing on the first handshake
+    pub fn load_tls(&self) -> Result<Option<rustls::ServerConfig>, String> {
+        let (cert_path, key_path) = match (&self.tls_cert_path, &self.tls_key_path) {
+            (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+            _ => return Ok(None),
+        };
+
+        let cert_file = std::fs::File::open(cert_path)
+            .map_err(|e| format!("Failed to open TLS certificate {}: {}", cert_path, e))?;
+        let certs = rustls_pemfile::certs(&mut std::io::BufReader::new(cert_file))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid TLS certificate {}: {}", cert_path, e))?;
+        if certs.is_empty() {
+            return Err(format!("No certificates found in {}", cert_path));
+        }
+
+        let key_file = std::fs::File::open(key_path)
+            .map_err(|e| format!("Failed to open TLS key {}: {}", key_path, e))?;
+        let key = rustls_pemfile::private_key(&mut std::io::BufReader::new(key_file))
+            .map_err(|e| format!("Invalid TLS key {}: {}", key_path, e))?
+            .ok_or_else(|| format!("No private key found in {}", key_path))?;
+
+        let tls_config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| format!("TLS certificate {} and key {} don't form a usable pair: {}", cert_path, key_path, e))?;
+
+        Ok(Some(tls_config))
     }
 }
 
+const SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+
+// Process-level facts the stats endpoint reports alongside the game systems
+#[derive(Debug)]
+pub struct ServerStatus {
+    started_at: std::time::Instant,
+    max_players: usize,
+    system: std::sync::Mutex<System>, // Kept between reads so CPU usage has a baseline
+}
+
+// Checked against the X-Api-Key header on every admin endpoint
+#[derive(Debug)]
+pub struct AdminAccess {
+    api_key: Option<String>,
+}
+
+impl AdminAccess {
+    fn authorize(&self, req: &actix_web::HttpRequest) -> Result<(), Box<HttpResponse>> {
+        let expected = match &self.api_key {
+            Some(api_key) => api_key,
+            None => return Err(Box::new(HttpResponse::Forbidden().json(serde_json::json!({"success": false, "error": "Admin API is disabled"})))),
+        };
+
+        let provided = req
+            .headers()
+            .get("X-Api-Key")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+
+        // Compare every byte so the response time doesn't leak how much of the key matched
+        let matches = provided.len() == expected.len()
+            && provided.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0;
+        if matches {
+            Ok(())
+        } else {
+            Err(Box::new(HttpResponse::Unauthorized().json(serde_json::json!({"success": false, "error": "Invalid API key"}))))
+        }
+    }
+}
+
+const LEADERBOARD_CACHE_SECS: u64 = 10;
+
+// Recently computed leaderboards by stat and limit, sorting every player per request is wasteful
+#[derive(Debug, Default)]
+pub struct LeaderboardCache {
+    entries: std::sync::Mutex<HashMap<(StatKind, usize), (std::time::Instant, serde_json::Value)>>,
+}
+
 #[derive(Debug)]
 pub struct StrixCraftServer {
     config: ServerConfig,
+    status: Arc<ServerStatus>,
+    metrics: Option<Arc<Metrics>>,
     world_manager: Arc<RwLock<WorldManager>>,
     player_manager: Arc<RwLock<PlayerManager>>,
     chunk_manager: Arc<RwLock<ChunkManager>>,
     entity_manager: Arc<RwLock<EntityManager>>,
+    chat_system: Arc<RwLock<ChatSystem>>,
     crafting_system: Arc<RwLock<CraftingSystem>>,
     inventory_system: Arc<RwLock<InventorySystem>>,
-    chat_system: Arc<RwLock<ChatSystem>>,
-    command_system: Arc<RwLock<CommandSystem>>,
     physics_system: Arc<RwLock<PhysicsSystem>>,
+    explosion_system: Arc<RwLock<ExplosionSystem>>,
+    fluid_system: Arc<RwLock<FluidSystem>>,
     mob_system: Arc<RwLock<MobSystem>>,
     weather_system: Arc<RwLock<WeatherSystem>>,
     time_system: Arc<RwLock<TimeSystem>>,
     save_system: Arc<RwLock<SaveSystem>>,
-    terrain_generator: Arc<TerrainGenerator>,
-    biome_system: Arc<BiomeSystem>,
-    structure_generator: Arc<StructureGenerator>,
     auth_service: Arc<AuthService>,
-    jwt_service: Arc<JwtService>,
     database_service: Arc<DatabaseService>,
-    world_repository: Arc<WorldRepository>,
-    player_repository: Arc<PlayerRepository>,
     websocket_handler: Arc<WebSocketHandler>,
     message_handler: Arc<MessageHandler>,
-    protocol: Arc<Protocol>,
 }
 
 impl StrixCraftServer {
@@ -123,51 +447,96 @@ impl StrixCraftServer {
         info!("Initializing StrixCraft.io server...");
 
         // Initialize database
-        let database_service = Arc::new(DatabaseService::new().await?);
+        let database_service = Arc::new(DatabaseService::new(&config.database).await?);
         let world_repository = Arc::new(WorldRepository::new(database_service.clone()));
         let player_repository = Arc::new(PlayerRepository::new(database_service.clone()));
+        let entity_repository = Arc::new(EntityRepository::new(database_service.clone()));
 
         // Initialize services
-        let jwt_service = Arc::new(JwtService::new("your-secret-key".to_string()));
+        // Never fall back to a built-in secret, anyone could forge tokens with it
+        let jwt_secret = std::env::var("JWT_SECRET")
+            .map_err(|_| "JWT_SECRET environment variable must be set")?;
+        let jwt_service = Arc::new(JwtService::new(jwt_secret)?);
         let auth_service = Arc::new(AuthService::new(
             player_repository.clone(),
             jwt_service.clone(),
         ));
+        auth_service.migrate_plaintext_passwords().await?;
 
         // Initialize world generation systems
-        let terrain_generator = Arc::new(TerrainGenerator::new());
         let biome_system = Arc::new(BiomeSystem::new());
-        let structure_generator = Arc::new(StructureGenerator::new());
+        let block_registry = Arc::new(BlockRegistry::new());
+        let loot_system = Arc::new(LootSystem::new());
+        let structure_generator = Arc::new(StructureGenerator::new(loot_system.clone()));
 
         // Initialize game systems
         let world_manager = Arc::new(RwLock::new(WorldManager::new(
             world_repository.clone(),
-            terrain_generator.clone(),
-            biome_system.clone(),
-            structure_generator.clone(),
+            config.terrain.clone(),
+            config.max_players,
         )));
 
-        let player_manager = Arc::new(RwLock::new(PlayerManager::new(
+        let mut player_manager = PlayerManager::new(
             player_repository.clone(),
             auth_service.clone(),
-        )));
+        );
+        player_manager.set_movement_limits(config.movement.clone());
+        player_manager.set_inventory_slots(config.inventory_slots);
+        player_manager.set_experience_curve(config.experience_curve.clone());
+        // Load saved players and their bans
+        player_manager.initialize().await?;
+        let player_manager = Arc::new(RwLock::new(player_manager));
 
         let chunk_manager = Arc::new(RwLock::new(ChunkManager::new(
             config.chunk_load_distance,
-            terrain_generator.clone(),
+            config.terrain.clone(),
+            biome_system.clone(),
+            structure_generator.clone(),
+            block_registry.clone(),
+            std::path::PathBuf::from(&config.chunk_storage_path),
         )));
 
-        let entity_manager = Arc::new(RwLock::new(EntityManager::new()));
+        let mut entity_manager = EntityManager::new(entity_repository.clone(), loot_system.clone());
+        entity_manager.set_max_entities_per_world(config.max_entities_per_world);
+        // Registered before any world loads its saved entities
+        for (id, props) in &config.entity_kinds {
+            entity_manager.register_entity_kind(id, props.clone())?;
+        }
+        let entity_manager = Arc::new(RwLock::new(entity_manager));
+
+        // Load saved worlds with their entities and give each one its seeded terrain generator
+        world_manager
+            .write()
+            .await
+            .initialize(&mut *chunk_manager.write().await, &mut *entity_manager.write().await)
+            .await?;
+        let item_registry = Arc::new(ItemRegistry::new());
         let crafting_system = Arc::new(RwLock::new(CraftingSystem::new()));
-        let inventory_system = Arc::new(RwLock::new(InventorySystem::new()));
-        let chat_system = Arc::new(RwLock::new(ChatSystem::new()));
-        let command_system = Arc::new(RwLock::new(CommandSystem::new()));
+        let inventory_system = Arc::new(RwLock::new(InventorySystem::new(item_registry.clone())));
+        let trade_system = Arc::new(RwLock::new(TradeSystem::new()));
+        let mut chat_system = ChatSystem::new();
+        chat_system.set_rate_limit(config.chat_rate_limit_seconds);
+        for (message_type, seconds) in &config.chat_rate_limit_overrides {
+            chat_system.set_message_type_rate_limit(message_type.clone(), *seconds);
+        }
+        chat_system.set_max_message_length(config.max_chat_message_length);
+        chat_system.set_duplicate_filter(config.chat_duplicate_history, config.chat_duplicate_window_seconds);
+        if let Some(path) = &config.profanity_file {
+            chat_system.load_profanity_file(path)?;
+        }
+        let chat_system = Arc::new(RwLock::new(chat_system));
+        let mut command_system = CommandSystem::new();
+        command_system.set_max_suggestions(config.max_command_suggestions);
+        let command_system = Arc::new(RwLock::new(command_system));
 
         let physics_system = if config.enable_physics {
-            Arc::new(RwLock::new(PhysicsSystem::new()))
+            Arc::new(RwLock::new(PhysicsSystem::new(block_registry.clone())))
         } else {
-            Arc::new(RwLock::new(PhysicsSystem::new_disabled()))
+            Arc::new(RwLock::new(PhysicsSystem::new_disabled(block_registry.clone())))
         };
+        let explosion_system = Arc::new(RwLock::new(ExplosionSystem::new(block_registry.clone())));
+        let fluid_system = Arc::new(RwLock::new(FluidSystem::new()));
+        let mining_system = Arc::new(RwLock::new(MiningSystem::new(block_registry.clone(), item_registry.clone())));
 
         let mob_system = if config.enable_mobs {
             Arc::new(RwLock::new(MobSystem::new()))
@@ -188,8 +557,14 @@ impl StrixCraftServer {
         };
 
         let save_system = Arc::new(RwLock::new(SaveSystem::new(
+            world_manager.clone(),
+            player_manager.clone(),
+            chunk_manager.clone(),
+            entity_manager.clone(),
+            database_service.clone(),
             world_repository.clone(),
             player_repository.clone(),
+            entity_repository.clone(),
             config.world_save_interval,
         )));
 
@@ -202,179 +577,1351 @@ impl StrixCraftServer {
             entity_manager.clone(),
             crafting_system.clone(),
             inventory_system.clone(),
+            trade_system.clone(),
             chat_system.clone(),
             command_system.clone(),
-            protocol.clone(),
+            fluid_system.clone(),
+            mining_system,
+            explosion_system.clone(),
         ));
 
         let websocket_handler = Arc::new(WebSocketHandler::new(
             message_handler.clone(),
-            protocol.clone(),
+            protocol,
+            std::time::Duration::from_secs(config.heartbeat_interval),
+            std::time::Duration::from_secs(config.client_timeout),
         ));
 
+        let status = Arc::new(ServerStatus {
+            started_at: std::time::Instant::now(),
+            max_players: config.max_players,
+            system: std::sync::Mutex::new(System::new()),
+        });
+
+        let metrics = if config.enable_metrics {
+            Some(Arc::new(Metrics::new()?))
+        } else {
+            None
+        };
+
         info!("StrixCraft.io server initialized successfully!");
 
         Ok(Self {
             config,
+            status,
+            metrics,
             world_manager,
             player_manager,
             chunk_manager,
             entity_manager,
+            chat_system,
             crafting_system,
             inventory_system,
-            chat_system,
-            command_system,
             physics_system,
+            explosion_system,
+            fluid_system,
             mob_system,
             weather_system,
             time_system,
             save_system,
-            terrain_generator,
-            biome_system,
-            structure_generator,
             auth_service,
-            jwt_service,
             database_service,
-            world_repository,
-            player_repository,
             websocket_handler,
             message_handler,
-            protocol,
         })
     }
 
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Starting StrixCraft.io server on {}:{}", self.config.host, self.config.port);
 
+        // Before anything is spawned, a broken certificate should stop startup cleanly
+        let tls_config = self.config.load_tls()?;
+
         // Start background tasks
-        self.start_background_tasks().await;
+        let tasks = self.start_background_tasks().await;
 
         // Start HTTP server
-        HttpServer::new(move || {
-            let cors = Cors::default()
-                .allow_any_origin()
-                .allow_any_method()
-                .allow_any_header()
-                .supports_credentials();
+        let websocket_handler = self.websocket_handler.clone();
+        let world_manager = self.world_manager.clone();
+        let player_manager = self.player_manager.clone();
+        let chunk_manager = self.chunk_manager.clone();
+        let message_handler = self.message_handler.clone();
+        let status = self.status.clone();
+        let entity_manager = self.entity_manager.clone();
+        let metrics = self.metrics.clone();
+        let database_service = self.database_service.clone();
+        let leaderboard_cache = Arc::new(LeaderboardCache::default());
+        let chat_system = self.chat_system.clone();
+        let crafting_system = self.crafting_system.clone();
+        let inventory_system = self.inventory_system.clone();
+        let auth_service = self.auth_service.clone();
+        let time_system = self.time_system.clone();
+        let weather_system = self.weather_system.clone();
+        let admin_access = Arc::new(AdminAccess {
+            api_key: self.config.admin_api_key.clone(),
+        });
+        if admin_access.api_key.is_none() {
+            warn!("No admin API key configured, admin endpoints are disabled");
+        }
+        let cors_allowed_origins = self.config.cors_allowed_origins.clone();
+        if cors_allowed_origins.is_empty() {
+            warn!("No CORS origins configured, allowing any origin without credentials");
+        }
+        let server = HttpServer::new(move || {
+            // Credentials are only allowed for an explicit allowlist, never alongside a wildcard
+            let cors = if cors_allowed_origins.is_empty() {
+                Cors::default()
+                    .allow_any_origin()
+                    .send_wildcard()
+                    .allow_any_method()
+                    .allow_any_header()
+            } else {
+                cors_allowed_origins
+                    .iter()
+                    .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+                    .allow_any_method()
+                    .allow_any_header()
+                    .supports_credentials()
+            };
 
             App::new()
-                .wrap(middleware::Logger::default())
+                .wrap(
+                    middleware::Logger::new(ACCESS_LOG_FORMAT)
+                        .custom_request_replace("request_line", access_log_request_line),
+                )
                 .wrap(cors)
+                .app_data(web::Data::from(websocket_handler.clone()))
+                .app_data(web::Data::from(world_manager.clone()))
+                .app_data(web::Data::from(player_manager.clone()))
+                .app_data(web::Data::from(chunk_manager.clone()))
+                .app_data(web::Data::from(message_handler.clone()))
+                .app_data(web::Data::from(status.clone()))
+                .app_data(web::Data::from(entity_manager.clone()))
+                .app_data(web::Data::from(database_service.clone()))
+                .app_data(web::Data::from(leaderboard_cache.clone()))
+                .app_data(web::Data::from(chat_system.clone()))
+                .app_data(web::Data::from(admin_access.clone()))
+                .app_data(web::Data::from(auth_service.clone()))
+                .app_data(web::Data::from(time_system.clone()))
+                .app_data(web::Data::from(weather_system.clone()))
+                .app_data(web::Data::from(crafting_system.clone()))
+                .app_data(web::Data::from(inventory_system.clone()))
+                .configure(|cfg| {
+                    // Only exposed when enabled in the config
+                    if let Some(metrics) = &metrics {
+                        cfg.app_data(web::Data::from(metrics.clone()))
+                            .route("/metrics", web::get().to(get_metrics));
+                    }
+                })
                 .service(
                     web::scope("/api")
                         .route("/worlds", web::get().to(get_worlds))
                         .route("/worlds", web::post().to(create_world))
                         .route("/worlds/{id}", web::get().to(get_world))
                         .route("/worlds/{id}", web::delete().to(delete_world))
+                        .route("/worlds/{id}/clone", web::post().to(clone_world))
+                        .route("/admin/worlds/{id}/rules/{key}", web::get().to(admin_get_rule))
+                        .route("/admin/worlds/{id}/rules/{key}", web::put().to(admin_set_rule))
+                        .route("/admin/worlds/{id}/settings", web::put().to(admin_set_settings))
+                        .route("/admin/worlds/{id}/time", web::get().to(admin_get_time))
+                        .route("/admin/worlds/{id}/time", web::put().to(admin_set_time))
+                        .route("/admin/worlds/{id}/weather", web::get().to(admin_get_weather))
+                        .route("/admin/worlds/{id}/weather", web::put().to(admin_set_weather))
+                        .route("/admin/worlds/{id}/border", web::put().to(admin_set_border))
+                        .route("/admin/worlds/{id}/spawn", web::get().to(admin_get_spawn))
+                        .route("/admin/worlds/{id}/spawn", web::put().to(admin_set_spawn))
+                        .route("/admin/worlds/{id}/whitelist", web::put().to(admin_set_whitelist_enabled))
+                        .route("/admin/worlds/{id}/whitelist/{player_id}", web::post().to(admin_whitelist_add))
+                        .route("/admin/worlds/{id}/whitelist/{player_id}", web::delete().to(admin_whitelist_remove))
                         .route("/auth/login", web::post().to(login))
                         .route("/auth/register", web::post().to(register))
                         .route("/auth/verify", web::post().to(verify_token))
+                        .route("/auth/refresh", web::post().to(refresh_token))
                         .route("/stats", web::get().to(get_server_stats))
+                        .route("/leaderboard", web::get().to(get_leaderboard))
+                        .route("/recipes", web::get().to(get_recipes))
+                        .route("/admin/recipes/{id}", web::delete().to(admin_remove_recipe))
+                        .route("/admin/kick", web::post().to(admin_kick))
+                        .route("/admin/players/{id}/role", web::put().to(admin_set_role))
+                        .route("/admin/players/{id}/inventory", web::get().to(admin_get_inventory))
+                        .route("/admin/players/{id}/inventory", web::put().to(admin_set_inventory))
+                        .route("/admin/players/{id}/effects", web::put().to(admin_player_effect))
+                        .route("/admin/players/{id}/mute", web::put().to(admin_mute))
+                        .route("/admin/entities/{id}/effects", web::put().to(admin_entity_effect))
+                        .route("/admin/broadcast", web::post().to(admin_broadcast))
                 )
                 .service(
                     web::scope("/ws")
                         .route("/game", web::get().to(websocket_route))
                 )
                 .service(Files::new("/", "../client/dist").index_file("index.html"))
-        })
-        .bind((self.config.host.clone(), self.config.port))?
-        .run()
-        .await?;
+        });
+
+        // The API and the game socket share the listener, so TLS covers both https:// and wss://
+        let address = (self.config.host.clone(), self.config.port);
+        let server = match tls_config {
+            Some(tls_config) => {
+                info!("TLS enabled, serving https:// and wss://");
+                server.bind_rustls_0_22(address, tls_config)?
+            }
+            None => {
+                warn!("No TLS certificate configured, serving plain http:// and ws://");
+                server.bind(address)?
+            }
+        };
+        server.run().await?;
+
+        // actix stops the HTTP server on SIGINT/SIGTERM, persist everything before exiting
+        info!("HTTP server stopped, shutting down...");
+        for task in &tasks {
+            task.abort();
+        }
+
+        match tokio::time::timeout(Duration::from_secs(SHUTDOWN_TIMEOUT_SECS), self.flush_state()).await {
+            Ok(()) => info!("Shutdown complete"),
+            Err(_) => error!("Shutdown flush timed out after {} seconds, some state may not be saved", SHUTDOWN_TIMEOUT_SECS),
+        }
+        self.database_service.close().await;
 
         Ok(())
     }
 
-    async fn start_background_tasks(&self) {
+    async fn flush_state(&self) {
+        match self.save_system.read().await.save_now().await {
+            Ok(report) => info!(
+                "Saved {} chunks, {} players, {} worlds and {} entities",
+                report.chunks, report.players, report.worlds, report.entities
+            ),
+            Err(e) => error!("Failed to flush dirty state: {}", e),
+        }
+
+        // Catch anything the dirty-only save skipped or failed on
+        if let Err(e) = self.player_manager.read().await.save_all_players().await {
+            error!("Failed to save players: {}", e);
+        }
+        if let Err(e) = self.chunk_manager.write().await.save_modified_chunks().await {
+            error!("Failed to save modified chunks: {}", e);
+        }
+    }
+
+    // Handles are kept so shutdown can cancel the loops before flushing
+    async fn start_background_tasks(&self) -> Vec<JoinHandle<()>> {
         let save_system = self.save_system.clone();
         let time_system = self.time_system.clone();
+        let time_world_manager = self.world_manager.clone();
         let weather_system = self.weather_system.clone();
+        let weather_world_manager = self.world_manager.clone();
+        let weather_message_handler = self.message_handler.clone();
         let mob_system = self.mob_system.clone();
+        let mob_world_manager = self.world_manager.clone();
+        let mob_chunk_manager = self.chunk_manager.clone();
+        let mob_entity_manager = self.entity_manager.clone();
+        let mob_player_manager = self.player_manager.clone();
+        let mob_time_system = self.time_system.clone();
+        let mob_weather_system = self.weather_system.clone();
         let physics_system = self.physics_system.clone();
+        let physics_world_manager = self.world_manager.clone();
+        let physics_chunk_manager = self.chunk_manager.clone();
+        let physics_entity_manager = self.entity_manager.clone();
+        let physics_player_manager = self.player_manager.clone();
+        let explosion_system = self.explosion_system.clone();
+        let explosion_world_manager = self.world_manager.clone();
+        let explosion_chunk_manager = self.chunk_manager.clone();
+        let explosion_entity_manager = self.entity_manager.clone();
+        let explosion_player_manager = self.player_manager.clone();
+        let explosion_fluid_system = self.fluid_system.clone();
+        let fluid_system = self.fluid_system.clone();
+        let fluid_chunk_manager = self.chunk_manager.clone();
+        let cleanup_entity_manager = self.entity_manager.clone();
+        let item_ttl_seconds = self.config.item_ttl_seconds;
+        let effect_entity_manager = self.entity_manager.clone();
+        let effect_player_manager = self.player_manager.clone();
+        let survival_player_manager = self.player_manager.clone();
+        let survival_world_manager = self.world_manager.clone();
+        let player_manager = self.player_manager.clone();
+        let player_save_interval = self.config.player_save_interval;
+        let player_retention = self.config.player_retention_days.map(|days| chrono::Duration::days(days as i64));
+        let message_handler = self.message_handler.clone();
+        let network_tick_millis = self.config.network_tick_millis;
+        let mut tasks = Vec::new();
 
         // Start save system
-        tokio::spawn(async move {
-            save_system.read().await.run().await;
-        });
+        tasks.push(tokio::spawn(async move {
+            SaveSystem::run(save_system).await;
+        }));
 
         // Start time system
-        tokio::spawn(async move {
-            time_system.read().await.run().await;
-        });
+        tasks.push(tokio::spawn(async move {
+            TimeSystem::run(time_system, time_world_manager).await;
+        }));
 
         // Start weather system
-        tokio::spawn(async move {
-            weather_system.read().await.run().await;
-        });
+        tasks.push(tokio::spawn(async move {
+            WeatherSystem::run(weather_system, weather_world_manager, weather_message_handler).await;
+        }));
 
         // Start mob system
-        tokio::spawn(async move {
-            mob_system.read().await.run().await;
-        });
+        tasks.push(tokio::spawn(async move {
+            MobSystem::run(
+                mob_system,
+                mob_world_manager,
+                mob_chunk_manager,
+                mob_entity_manager,
+                mob_player_manager,
+                mob_time_system,
+                mob_weather_system,
+            )
+            .await;
+        }));
 
         // Start physics system
-        tokio::spawn(async move {
-            physics_system.read().await.run().await;
-        });
+        tasks.push(tokio::spawn(async move {
+            PhysicsSystem::run(physics_system, physics_world_manager, physics_chunk_manager, physics_entity_manager, physics_player_manager).await;
+        }));
+
+        // Start creepers and TNT
+        tasks.push(tokio::spawn(async move {
+            ExplosionSystem::run(
+                explosion_system,
+                explosion_world_manager,
+                explosion_chunk_manager,
+                explosion_entity_manager,
+                explosion_player_manager,
+                explosion_fluid_system,
+            )
+            .await;
+        }));
+
+        // Start fluid flow
+        tasks.push(tokio::spawn(async move {
+            FluidSystem::run(fluid_system, fluid_chunk_manager).await;
+        }));
+
+        // Start item despawning
+        tasks.push(tokio::spawn(async move {
+            EntityManager::run(cleanup_entity_manager, item_ttl_seconds).await;
+        }));
+
+        // Start status effects
+        tasks.push(tokio::spawn(async move {
+            StatusEffectSystem::run(effect_entity_manager, effect_player_manager).await;
+        }));
+
+        // Start hunger and regeneration
+        tasks.push(tokio::spawn(async move {
+            PlayerManager::run_survival(survival_player_manager, survival_world_manager).await;
+        }));
+
+        // Start player autosave
+        tasks.push(tokio::spawn(async move {
+            PlayerManager::run(player_manager, player_save_interval, player_retention).await;
+        }));
+
+        // Start chunk update broadcasts
+        tasks.push(tokio::spawn(async move {
+            MessageHandler::run(message_handler, network_tick_millis).await;
+        }));
+
+        tasks
     }
 }
 
 // HTTP API endpoints
-async fn get_worlds() -> HttpResponse {
-    // Implementation for getting world list
-    HttpResponse::Ok().json(vec![])
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateWorldRequest {
+    name: String,
+    seed: Option<i64>,
+    game_mode: GameMode,
+    settings: WorldSettings,
+    max_players: Option<usize>,
 }
 
-async fn create_world() -> HttpResponse {
-    // Implementation for creating a new world
-    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+const MAX_WORLD_NAME_LENGTH: usize = 32;
+const DEFAULT_WORLD_PAGE_SIZE: usize = 50;
+const MAX_WORLD_PAGE_SIZE: usize = 200;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListWorldsQuery {
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+    name: Option<String>,
+    is_online: Option<bool>,
+    game_mode: Option<GameMode>,
+    #[serde(default)]
+    has_space: bool,
+}
+
+async fn get_worlds(
+    world_manager: web::Data<RwLock<WorldManager>>,
+    query: web::Query<ListWorldsQuery>,
+) -> HttpResponse {
+    let query = query.into_inner();
+    let limit = query.limit.unwrap_or(DEFAULT_WORLD_PAGE_SIZE).clamp(1, MAX_WORLD_PAGE_SIZE);
+    let filter = WorldQuery {
+        name: query.name.filter(|name| !name.trim().is_empty()),
+        game_mode: query.game_mode,
+        is_online: query.is_online,
+        has_space: query.has_space,
+    };
+
+    HttpResponse::Ok().json(world_manager.read().await.query_worlds(filter, query.offset, limit).await)
+}
+
+async fn create_world(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    chunk_manager: web::Data<RwLock<ChunkManager>>,
+    entity_manager: web::Data<RwLock<EntityManager>>,
+    body: web::Json<CreateWorldRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let request = body.into_inner();
+
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_WORLD_NAME_LENGTH {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "error": format!("World name must be 1-{} characters", MAX_WORLD_NAME_LENGTH),
+        }));
+    }
+
+    let mut world_manager = world_manager.write().await;
+    let max_players = request.max_players.unwrap_or_else(|| world_manager.get_server_max_players());
+    let seed = request.seed.unwrap_or_else(rand::random);
+
+    let mut chunk_manager = chunk_manager.write().await;
+    let mut entity_manager = entity_manager.write().await;
+    match world_manager
+        .create_world(name, seed, request.game_mode, request.settings, max_players, &mut chunk_manager, &mut entity_manager)
+        .await
+    {
+        Ok(world) => HttpResponse::Ok().json(serde_json::json!({"success": true, "world": world})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
 }
 
-async fn get_world(path: web::Path<String>) -> HttpResponse {
+async fn get_world(
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<String>,
+) -> HttpResponse {
     let world_id = path.into_inner();
-    // Implementation for getting world details
-    HttpResponse::Ok().json(serde_json::json!({"id": world_id}))
+    match world_manager.read().await.get_world(&world_id).await {
+        Some(world) => HttpResponse::Ok().json(world),
+        None => HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "World not found"})),
+    }
 }
 
-async fn delete_world(path: web::Path<String>) -> HttpResponse {
+async fn delete_world(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    message_handler: web::Data<MessageHandler>,
+    time_system: web::Data<RwLock<TimeSystem>>,
+    weather_system: web::Data<RwLock<WeatherSystem>>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
     let world_id = path.into_inner();
-    // Implementation for deleting a world
-    HttpResponse::Ok().json(serde_json::json!({"success": true}))
+    match message_handler.delete_world(&world_id).await {
+        Ok(true) => {
+            time_system.write().await.remove_world(&world_id);
+            weather_system.write().await.remove_world(&world_id);
+            HttpResponse::Ok().json(serde_json::json!({"success": true}))
+        }
+        Ok(false) => HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "World not found"})),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CloneWorldRequest {
+    name: String,
+}
+
+async fn clone_world(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    chunk_manager: web::Data<RwLock<ChunkManager>>,
+    entity_manager: web::Data<RwLock<EntityManager>>,
+    path: web::Path<String>,
+    body: web::Json<CloneWorldRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let name = body.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_WORLD_NAME_LENGTH {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "error": format!("World name must be 1-{} characters", MAX_WORLD_NAME_LENGTH),
+        }));
+    }
+
+    let mut world_manager = world_manager.write().await;
+    let mut chunk_manager = chunk_manager.write().await;
+    let mut entity_manager = entity_manager.write().await;
+    match world_manager
+        .clone_world(&path.into_inner(), name, &mut chunk_manager, &mut entity_manager)
+        .await
+    {
+        Ok(world) => HttpResponse::Ok().json(serde_json::json!({"success": true, "world": world})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RuleKind {
+    Bool,
+    Int,
+    String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleQuery {
+    #[serde(rename = "type")]
+    kind: RuleKind,
+}
+
+async fn admin_get_rule(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<(String, String)>,
+    query: web::Query<RuleQuery>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let (world_id, key) = path.into_inner();
+    let world_manager = world_manager.read().await;
+    let value = match query.kind {
+        RuleKind::Bool => world_manager.get_bool_rule(&world_id, &key).map(serde_json::Value::from),
+        RuleKind::Int => world_manager.get_int_rule(&world_id, &key).map(serde_json::Value::from),
+        RuleKind::String => world_manager.get_string_rule(&world_id, &key).map(serde_json::Value::from),
+    };
+    match value {
+        Some(value) => HttpResponse::Ok().json(serde_json::json!({"success": true, "key": key, "value": value})),
+        // Also covers a rule that exists with a different type
+        None => HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "Rule not found"})),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SetRuleRequest {
+    value: GameRuleValue,
+}
+
+async fn admin_set_rule(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<(String, String)>,
+    body: web::Json<SetRuleRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let (world_id, key) = path.into_inner();
+    match world_manager.write().await.set_rule(&world_id, &key, body.into_inner().value).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"success": true})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn admin_set_settings(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    entity_manager: web::Data<RwLock<EntityManager>>,
+    path: web::Path<String>,
+    body: web::Json<WorldSettings>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let mut world_manager = world_manager.write().await;
+    let mut entity_manager = entity_manager.write().await;
+    match world_manager.set_world_settings(&path.into_inner(), body.into_inner(), &mut entity_manager).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"success": true})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn admin_get_time(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    time_system: web::Data<RwLock<TimeSystem>>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let world_id = path.into_inner();
+    if world_manager.read().await.get_world(&world_id).await.is_none() {
+        return HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "World not found"}));
+    }
+
+    let time_system = time_system.read().await;
+    HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "time": time_system.get_time(&world_id),
+        "day": time_system.get_day(&world_id),
+        "phase": time_system.get_phase(&world_id),
+        "night": time_system.is_night(&world_id),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct SetTimeRequest {
+    time: u64, // Ticks into the day, wrapped to TICKS_PER_DAY
 }
 
-async fn login() -> HttpResponse {
-    // Implementation for user login
+async fn admin_set_time(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    time_system: web::Data<RwLock<TimeSystem>>,
+    path: web::Path<String>,
+    body: web::Json<SetTimeRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let world_id = path.into_inner();
+    if world_manager.read().await.get_world(&world_id).await.is_none() {
+        return HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "World not found"}));
+    }
+
+    let mut time_system = time_system.write().await;
+    time_system.set_time(&world_id, body.time);
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "phase": time_system.get_phase(&world_id)}))
+}
+
+async fn admin_get_weather(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    weather_system: web::Data<RwLock<WeatherSystem>>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let world_id = path.into_inner();
+    if world_manager.read().await.get_world(&world_id).await.is_none() {
+        return HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "World not found"}));
+    }
+
+    let weather = weather_system.read().await.get_weather(&world_id);
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "weather": weather}))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetWeatherRequest {
+    weather: Weather,
+    duration_seconds: Option<u64>, // A random spell when omitted
+}
+
+async fn admin_set_weather(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    weather_system: web::Data<RwLock<WeatherSystem>>,
+    message_handler: web::Data<MessageHandler>,
+    path: web::Path<String>,
+    body: web::Json<SetWeatherRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let world_id = path.into_inner();
+    match world_manager.read().await.get_world(&world_id).await {
+        Some(world) if !world.settings.weather_enabled => {
+            return HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": "Weather is disabled in this world"}));
+        }
+        Some(_) => {}
+        None => return HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "World not found"})),
+    }
+
+    weather_system.write().await.set_weather(&world_id, body.weather, body.duration_seconds);
+    message_handler
+        .broadcast_to_world(&world_id, ServerMessage::WeatherChange { world_id: world_id.clone(), weather: body.weather }, None)
+        .await;
     HttpResponse::Ok().json(serde_json::json!({"success": true}))
 }
 
-async fn register() -> HttpResponse {
-    // Implementation for user registration
+#[derive(Debug, Deserialize)]
+struct SetBorderRequest {
+    center: [f64; 2], // x, z
+    radius: f64, // 0 removes the border
+}
+
+async fn admin_set_border(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<String>,
+    body: web::Json<SetBorderRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    if !body.radius.is_finite() || body.radius < 0.0 || !body.center.iter().all(|c| c.is_finite()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": "Invalid border"}));
+    }
+
+    match world_manager.write().await.set_world_border(&path.into_inner(), body.center, body.radius).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"success": true})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn admin_get_spawn(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    match world_manager.read().await.get_spawn_point(&path.into_inner()) {
+        Some(spawn_point) => HttpResponse::Ok().json(serde_json::json!({"success": true, "spawnPoint": spawn_point})),
+        None => HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "World not found"})),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetSpawnRequest {
+    spawn_point: [f64; 3],
+}
+
+async fn admin_set_spawn(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<String>,
+    body: web::Json<SetSpawnRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    if !body.spawn_point.iter().all(|c| c.is_finite()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": "Invalid spawn point"}));
+    }
+
+    match world_manager.write().await.set_spawn_point(&path.into_inner(), body.spawn_point).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"success": true})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WhitelistToggleRequest {
+    enabled: bool,
+}
+
+async fn admin_set_whitelist_enabled(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<String>,
+    body: web::Json<WhitelistToggleRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    match world_manager.write().await.set_whitelist_enabled(&path.into_inner(), body.enabled).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"success": true})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn admin_whitelist_add(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let (world_id, player_id) = path.into_inner();
+    match world_manager.write().await.add_to_whitelist(&world_id, &player_id).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"success": true})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn admin_whitelist_remove(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let (world_id, player_id) = path.into_inner();
+    match world_manager.write().await.remove_from_whitelist(&world_id, &player_id).await {
+        Ok(true) => HttpResponse::Ok().json(serde_json::json!({"success": true})),
+        Ok(false) => HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "Player is not whitelisted"})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn get_recipes(crafting_system: web::Data<RwLock<CraftingSystem>>) -> HttpResponse {
+    let crafting_system = crafting_system.read().await;
+    let mut recipes = crafting_system.get_all_recipes();
+    recipes.sort_by(|a, b| a.id.cmp(&b.id));
+    HttpResponse::Ok().json(recipes)
+}
+
+async fn admin_remove_recipe(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    crafting_system: web::Data<RwLock<CraftingSystem>>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    if crafting_system.write().await.remove_recipe(&path.into_inner()) {
+        HttpResponse::Ok().json(serde_json::json!({"success": true}))
+    } else {
+        HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "Recipe not found"}))
+    }
+}
+
+const DEFAULT_LEADERBOARD_SIZE: usize = 10;
+const MAX_LEADERBOARD_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct LeaderboardQuery {
+    stat: String,
+    limit: Option<usize>,
+}
+
+async fn get_leaderboard(
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    cache: web::Data<LeaderboardCache>,
+    query: web::Query<LeaderboardQuery>,
+) -> HttpResponse {
+    let stat = match StatKind::from_name(&query.stat) {
+        Some(stat) => stat,
+        None => {
+            let known: Vec<&str> = StatKind::ALL.iter().map(|stat| stat.name()).collect();
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "success": false,
+                "error": format!("Unknown stat {}, expected one of: {}", query.stat, known.join(", ")),
+            }));
+        }
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LEADERBOARD_SIZE).clamp(1, MAX_LEADERBOARD_SIZE);
+
+    if let Some((computed_at, body)) = cache.entries.lock().unwrap().get(&(stat, limit)) {
+        if computed_at.elapsed().as_secs() < LEADERBOARD_CACHE_SECS {
+            return HttpResponse::Ok().json(body);
+        }
+    }
+
+    let players = player_manager.read().await.get_leaderboard(stat, limit).await;
+    let entries: Vec<serde_json::Value> = players
+        .iter()
+        .enumerate()
+        .map(|(index, player)| serde_json::json!({
+            "rank": index + 1,
+            "username": player.username,
+            "value": player.statistics.get(stat),
+        }))
+        .collect();
+    let body = serde_json::json!({"stat": stat.name(), "entries": entries});
+
+    cache.entries.lock().unwrap().insert((stat, limit), (std::time::Instant::now(), body.clone()));
+    HttpResponse::Ok().json(body)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KickRequest {
+    player_id: String,
+    reason: Option<String>,
+}
+
+async fn admin_kick(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    message_handler: web::Data<MessageHandler>,
+    body: web::Json<KickRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let reason = body.reason.clone().unwrap_or_else(|| "Kicked by an operator".to_string());
+    let kicked = player_manager.write().await.kick_player(&body.player_id, &reason).await;
+    match kicked {
+        Ok(true) => {
+            // Close the socket now rather than on the next network tick
+            message_handler.close_kicked_sessions().await;
+            HttpResponse::Ok().json(serde_json::json!({"success": true}))
+        }
+        Ok(false) => HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "Player is not online"})),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MuteRequest {
+    minutes: u32, // 0 lifts the mute
+}
+
+async fn admin_mute(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    chat_system: web::Data<RwLock<ChatSystem>>,
+    path: web::Path<String>,
+    body: web::Json<MuteRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let player_id = path.into_inner();
+    let mut chat_system = chat_system.write().await;
+    let changed = if body.minutes > 0 {
+        chat_system.mute_player(&player_id, body.minutes);
+        true
+    } else {
+        chat_system.unmute_player(&player_id)
+    };
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "changed": changed}))
+}
+
+#[derive(Debug, Deserialize)]
+struct SetRoleRequest {
+    role: PlayerRole,
+}
+
+async fn admin_set_role(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    path: web::Path<String>,
+    body: web::Json<SetRoleRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    match player_manager.write().await.set_player_role(&path.into_inner(), body.role).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({"success": true})),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn admin_get_inventory(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    inventory_system: web::Data<RwLock<InventorySystem>>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let inventory_system = inventory_system.read().await;
+    match player_manager.read().await.get_player(&path.into_inner()).await {
+        Some(player) => {
+            // Extra keys are ignored when the same data is sent back
+            let mut data = inventory_system.serialize_inventory(&player.inventory);
+            data["weight"] = serde_json::json!(inventory_system.get_inventory_weight(&player.inventory));
+            data["value"] = serde_json::json!(inventory_system.get_inventory_value(&player.inventory));
+            HttpResponse::Ok().json(data)
+        }
+        None => HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "Player is not online"})),
+    }
+}
+
+// Takes the same shape GET returns, malformed data is rejected before it reaches the player
+async fn admin_set_inventory(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    inventory_system: web::Data<RwLock<InventorySystem>>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    message_handler: web::Data<MessageHandler>,
+    path: web::Path<String>,
+    body: web::Json<serde_json::Value>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let player_id = path.into_inner();
+    let inventory = match inventory_system.read().await.deserialize_inventory(body.into_inner()) {
+        Ok(inventory) => inventory,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e})),
+    };
+    {
+        let mut player_manager = player_manager.write().await;
+        if player_manager.get_player(&player_id).await.is_none() {
+            return HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "Player is not online"}));
+        }
+        if let Err(e) = player_manager.update_player_inventory(&player_id, inventory.clone()).await {
+            return HttpResponse::InternalServerError().json(serde_json::json!({"success": false, "error": e.to_string()}));
+        }
+    }
+
+    message_handler.send_to_player(&player_id, ServerMessage::InventoryUpdate(inventory)).await;
     HttpResponse::Ok().json(serde_json::json!({"success": true}))
 }
 
-async fn verify_token() -> HttpResponse {
-    // Implementation for token verification
+#[derive(Debug, Deserialize)]
+struct EffectRequest {
+    kind: EffectKind,
+    #[serde(default)]
+    amplifier: u8,
+    seconds: i64, // 0 removes the effect
+}
+
+async fn admin_player_effect(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    path: web::Path<String>,
+    body: web::Json<EffectRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let player_id = path.into_inner();
+    let mut player_manager = player_manager.write().await;
+    let changed = if body.seconds > 0 {
+        player_manager.apply_effect(&player_id, body.kind, body.amplifier, chrono::Duration::seconds(body.seconds)).await
+    } else {
+        player_manager.remove_effect(&player_id, body.kind).await
+    };
+    match changed {
+        Ok(changed) => HttpResponse::Ok().json(serde_json::json!({"success": true, "changed": changed})),
+        Err(e) => HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn admin_entity_effect(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    entity_manager: web::Data<RwLock<EntityManager>>,
+    path: web::Path<String>,
+    body: web::Json<EffectRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let entity_id = path.into_inner();
+    let mut entity_manager = entity_manager.write().await;
+    if entity_manager.get_entity(&entity_id).await.is_none() {
+        return HttpResponse::NotFound().json(serde_json::json!({"success": false, "error": "Entity not found"}));
+    }
+    let changed = if body.seconds > 0 {
+        entity_manager.apply_effect(&entity_id, body.kind, body.amplifier, chrono::Duration::seconds(body.seconds)).await
+    } else {
+        entity_manager.remove_effect(&entity_id, body.kind).await
+    };
+    HttpResponse::Ok().json(serde_json::json!({"success": true, "changed": changed}))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BroadcastRequest {
+    message: String,
+    world_id: Option<String>, // Every connected player when omitted
+}
+
+async fn admin_broadcast(
+    req: actix_web::HttpRequest,
+    admin_access: web::Data<AdminAccess>,
+    chat_system: web::Data<RwLock<ChatSystem>>,
+    message_handler: web::Data<MessageHandler>,
+    body: web::Json<BroadcastRequest>,
+) -> HttpResponse {
+    if let Err(response) = admin_access.authorize(&req) {
+        return *response;
+    }
+
+    let content = body.message.trim();
+    if content.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": "Message is empty"}));
+    }
+
+    let message = match chat_system.write().await.broadcast_system_message(content, body.world_id.clone()) {
+        Ok(message) => message,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e})),
+    };
+    match &body.world_id {
+        Some(world_id) => message_handler.broadcast_to_world(world_id, ServerMessage::Chat(message), None).await,
+        None => message_handler.broadcast_to_all(ServerMessage::Chat(message)).await,
+    }
+
     HttpResponse::Ok().json(serde_json::json!({"success": true}))
 }
 
-async fn get_server_stats() -> HttpResponse {
-    // Implementation for getting server statistics
+const MIN_USERNAME_LENGTH: usize = 3;
+const MAX_USERNAME_LENGTH: usize = 16;
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(Debug, Deserialize)]
+struct CredentialsRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshRequest {
+    refresh_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifyRequest {
+    token: String,
+}
+
+fn token_response(player_id: &str, tokens: Result<(String, String), String>) -> HttpResponse {
+    match tokens {
+        Ok((access_token, refresh_token)) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "playerId": player_id,
+            "accessToken": access_token,
+            "refreshToken": refresh_token,
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"success": false, "error": e})),
+    }
+}
+
+async fn login(
+    auth_service: web::Data<AuthService>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    body: web::Json<CredentialsRequest>,
+) -> HttpResponse {
+    let player_id = match auth_service.authenticate(body.username.trim(), &body.password).await {
+        Ok(Some(player_id)) => player_id,
+        Ok(None) => return HttpResponse::Unauthorized().json(serde_json::json!({"success": false, "error": "Invalid username or password"})),
+        Err(e) => return HttpResponse::InternalServerError().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    };
+
+    if player_manager.read().await.is_banned(&player_id) {
+        return HttpResponse::Forbidden().json(serde_json::json!({"success": false, "error": "You are banned"}));
+    }
+
+    token_response(&player_id, auth_service.issue_tokens(&player_id))
+}
+
+async fn register(
+    auth_service: web::Data<AuthService>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    body: web::Json<CredentialsRequest>,
+) -> HttpResponse {
+    let username = body.username.trim();
+    let valid_username = (MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&username.chars().count())
+        && username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_username {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "error": format!("Username must be {}-{} letters, digits or underscores", MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH),
+        }));
+    }
+    if body.password.chars().count() < MIN_PASSWORD_LENGTH {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "error": format!("Password must be at least {} characters", MIN_PASSWORD_LENGTH),
+        }));
+    }
+
+    let player = match player_manager.write().await.register_player(username, &body.password).await {
+        Ok(player) => player,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    };
+
+    token_response(&player.id, auth_service.issue_tokens(&player.id))
+}
+
+async fn refresh_token(
+    auth_service: web::Data<AuthService>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    body: web::Json<RefreshRequest>,
+) -> HttpResponse {
+    let (access_token, refresh_token) = match auth_service.refresh_tokens(&body.refresh_token) {
+        Ok(tokens) => tokens,
+        Err(e) => return HttpResponse::Unauthorized().json(serde_json::json!({"success": false, "error": e})),
+    };
+
+    // Bans take effect on the next refresh at the latest
+    let player_id = match auth_service.verify_token(&access_token).await {
+        Ok(Some(player_id)) => player_id,
+        _ => return HttpResponse::InternalServerError().json(serde_json::json!({"success": false, "error": "Failed to issue tokens"})),
+    };
+    if player_manager.read().await.is_banned(&player_id) {
+        return HttpResponse::Forbidden().json(serde_json::json!({"success": false, "error": "You are banned"}));
+    }
+
+    token_response(&player_id, Ok((access_token, refresh_token)))
+}
+
+async fn verify_token(
+    auth_service: web::Data<AuthService>,
+    body: web::Json<VerifyRequest>,
+) -> HttpResponse {
+    match auth_service.verify_token(&body.token).await {
+        Ok(Some(player_id)) => HttpResponse::Ok().json(serde_json::json!({"success": true, "playerId": player_id})),
+        Ok(None) => HttpResponse::Unauthorized().json(serde_json::json!({"success": false, "error": "Invalid or expired token"})),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"success": false, "error": e.to_string()})),
+    }
+}
+
+async fn get_metrics(
+    metrics: web::Data<Metrics>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    chunk_manager: web::Data<RwLock<ChunkManager>>,
+    entity_manager: web::Data<RwLock<EntityManager>>,
+    message_handler: web::Data<MessageHandler>,
+) -> HttpResponse {
+    let player_stats = player_manager.read().await.get_player_stats().await;
+    let chunk_stats = chunk_manager.read().await.get_chunk_stats().await;
+    let latency_stats = message_handler.get_latency_stats().await;
+
+    metrics.online_players.set(player_stats.online_players as i64);
+    metrics.loaded_chunks.set(chunk_stats.total_chunks as i64);
+    metrics.websocket_connections.set(latency_stats.connected_sessions as i64);
+    metrics.sync_messages_received(message_handler.get_messages_received());
+
+    let worlds = world_manager.read().await.get_all_worlds().await;
+    let entity_manager = entity_manager.read().await;
+    metrics.entities.reset(); // Drop series for deleted worlds
+    for world in worlds {
+        metrics.entities
+            .with_label_values(&[&world.id])
+            .set(entity_manager.get_entity_count(&world.id) as i64);
+    }
+
+    match metrics.render() {
+        Ok(body) => HttpResponse::Ok().content_type("text/plain; version=0.0.4").body(body),
+        Err(e) => HttpResponse::InternalServerError().body(e),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn get_server_stats(
+    status: web::Data<ServerStatus>,
+    world_manager: web::Data<RwLock<WorldManager>>,
+    player_manager: web::Data<RwLock<PlayerManager>>,
+    chunk_manager: web::Data<RwLock<ChunkManager>>,
+    entity_manager: web::Data<RwLock<EntityManager>>,
+    chat_system: web::Data<RwLock<ChatSystem>>,
+    message_handler: web::Data<MessageHandler>,
+    database_service: web::Data<DatabaseService>,
+) -> HttpResponse {
+    let player_stats = player_manager.read().await.get_player_stats().await;
+    let world_stats = world_manager.read().await.get_world_stats().await;
+    let chunk_stats = chunk_manager.read().await.get_chunk_stats().await;
+    let entity_stats = entity_manager.read().await.get_entity_stats().await;
+    let chat_stats = chat_system.read().await.get_chat_stats();
+    let latency_stats = message_handler.get_latency_stats().await;
+
+    let (memory_usage, cpu_usage) = {
+        let mut system = status.system.lock().unwrap();
+        match sysinfo::get_current_pid() {
+            Ok(pid) if system.refresh_process(pid) => system
+                .process(pid)
+                .map_or((0, 0.0), |process| (process.memory(), process.cpu_usage())),
+            _ => (0, 0.0),
+        }
+    };
+
     HttpResponse::Ok().json(serde_json::json!({
-        "uptime": 0,
-        "playerCount": 0,
-        "maxPlayers": 100,
-        "worlds": 0,
-        "chunksLoaded": 0,
-        "memoryUsage": 0,
-        "cpuUsage": 0
+        "uptime": status.started_at.elapsed().as_secs(),
+        "playerCount": player_stats.online_players,
+        "totalPlayers": player_stats.total_players,
+        "maxPlayers": status.max_players,
+        "totalExperience": player_stats.total_experience,
+        "averageLevel": player_stats.average_level,
+        "worlds": world_stats.total_worlds,
+        "onlineWorlds": world_stats.online_worlds,
+        "playersInWorlds": world_stats.total_players,
+        "chunksLoaded": chunk_stats.total_chunks,
+        "chunksModified": chunk_stats.modified_chunks,
+        "chunksGenerated": chunk_stats.generated_chunks,
+        "maxCachedChunks": chunk_stats.max_cached_chunks,
+        "entities": entity_stats.total_entities,
+        "activeEntities": entity_stats.active_entities,
+        "entityTypes": entity_stats.type_counts,
+        "registeredEntityKinds": entity_stats.registered_kinds,
+        "chatMessages": chat_stats.total_messages,
+        "chatChannels": chat_stats.total_channels,
+        "mutedPlayers": chat_stats.muted_players,
+        "chatMessageTypes": chat_stats.message_type_counts,
+        "averageLatency": latency_stats.average_latency_ms,
+        "maxLatency": latency_stats.max_latency_ms,
+        "memoryUsage": memory_usage,
+        "cpuUsage": cpu_usage,
+        "databasePool": database_service.pool_stats()
     }))
 }
 
+// Logger::default() with the request line built by access_log_request_line
+const ACCESS_LOG_FORMAT: &str = r#"%a "%{request_line}xi" %s %b "%{Referer}i" "%{User-Agent}i" %T"#;
+
+// The websocket handshake carries the access token in its query string, keep it out of the log
+fn access_log_request_line(req: &actix_web::dev::ServiceRequest) -> String {
+    let query = req.query_string();
+    let target = if query.is_empty() || req.path().starts_with("/ws/") {
+        req.path().to_string()
+    } else {
+        format!("{}?{}", req.path(), query)
+    };
+
+    format!("{} {} {:?}", req.method(), target, req.version())
+}
+
 async fn websocket_route(
     req: actix_web::HttpRequest,
     stream: web::Payload,
+    websocket_handler: web::Data<WebSocketHandler>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    // Implementation for WebSocket connection
-    Ok(HttpResponse::Ok().finish())
+    websocket_handler.handle(req, stream).await
 }
 
 #[actix_web::main]
@@ -398,10 +1945,80 @@ async fn main() -> std::io::Result<()> {
 
     info!("Starting StrixCraft.io server...");
 
-    let config = ServerConfig::default();
-    let server = StrixCraftServer::new(config).await.unwrap();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = match ServerConfig::load(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let server = match StrixCraftServer::new(config).await {
+        Ok(server) => server,
+        Err(e) => {
+            error!("Failed to initialize server: {}", e);
+            std::process::exit(1);
+        }
+    };
     
     server.start().await.unwrap();
 
     Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_log_leaves_out_the_websocket_token() {
+        let req = actix_web::test::TestRequest::with_uri("/ws/game?token=secret.jwt.value").to_srv_request();
+        assert_eq!(access_log_request_line(&req), "GET /ws/game HTTP/1.1");
+
+        let req = actix_web::test::TestRequest::with_uri("/api/leaderboard?stat=deaths").to_srv_request();
+        assert_eq!(access_log_request_line(&req), "GET /api/leaderboard?stat=deaths HTTP/1.1");
+    }
+
+    #[test]
+    fn chat_rate_limit_overrides_are_keyed_by_message_type() {
+        let config: ServerConfig = serde_json::from_str(r#"{"chat_rate_limit_overrides": {"Command": 3}}"#).unwrap();
+        assert_eq!(config.chat_rate_limit_overrides.get(&MessageType::Command), Some(&3));
+        assert_eq!(config.chat_rate_limit_seconds, DEFAULT_RATE_LIMIT_SECONDS);
+    }
+
+    #[tokio::test]
+    async fn shutdown_flush_saves_offline_players_and_modified_chunks() {
+        std::env::set_var("JWT_SECRET", "x".repeat(32));
+        let config = ServerConfig {
+            chunk_storage_path: std::env::temp_dir().join("strixcraft-flush-test").to_string_lossy().into_owned(),
+            database: DatabaseConfig {
+                url: "sqlite::memory:".to_string(),
+                max_connections: 1,
+                ..DatabaseConfig::default()
+            },
+            ..ServerConfig::default()
+        };
+        let server = StrixCraftServer::new(config).await.unwrap();
+
+        let player_id = {
+            let mut player_manager = server.player_manager.write().await;
+            let player_id = player_manager.register_player("alex", "correct horse battery").await.unwrap().id;
+            player_manager.teleport_player(&player_id, [8.0, 90.0, 8.0]).await.unwrap();
+            player_id
+        };
+        {
+            let mut chunk_manager = server.chunk_manager.write().await;
+            chunk_manager.register_world("flush", 3);
+            chunk_manager.get_chunk("flush", 0, 0).await.unwrap();
+            chunk_manager.set_block("flush", 1, 100, 1, 1).await.unwrap();
+        }
+
+        server.flush_state().await;
+
+        assert!(server.chunk_manager.read().await.modified_world_ids().is_empty());
+        let player_repository = Arc::new(PlayerRepository::new(server.database_service.clone()));
+        let mut reloaded = PlayerManager::new(player_repository, server.auth_service.clone());
+        reloaded.initialize().await.unwrap();
+        assert_eq!(reloaded.get_player(&player_id).await.unwrap().position, [8.0, 90.0, 8.0]);
+    }
 }
\ No newline at end of file
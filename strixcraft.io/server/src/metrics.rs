@@ -0,0 +1,60 @@
+use prometheus::{Encoder, IntCounter, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+
+// Register new metrics here, the /metrics route encodes everything in the registry
+pub struct Metrics {
+    registry: Registry,
+    pub online_players: IntGauge,
+    pub loaded_chunks: IntGauge,
+    pub entities: IntGaugeVec,
+    pub messages_received: IntCounter,
+    pub websocket_connections: IntGauge,
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new_custom(Some("strixcraft".to_string()), None)?;
+
+        let online_players = IntGauge::new("online_players", "Players currently connected")?;
+        let loaded_chunks = IntGauge::new("loaded_chunks", "Chunks held in the chunk cache")?;
+        let entities = IntGaugeVec::new(Opts::new("entities", "Entities per world"), &["world"])?;
+        let messages_received = IntCounter::new("messages_received_total", "Client messages handled")?;
+        let websocket_connections = IntGauge::new("websocket_connections", "Open websocket sessions")?;
+
+        registry.register(Box::new(online_players.clone()))?;
+        registry.register(Box::new(loaded_chunks.clone()))?;
+        registry.register(Box::new(entities.clone()))?;
+        registry.register(Box::new(messages_received.clone()))?;
+        registry.register(Box::new(websocket_connections.clone()))?;
+
+        Ok(Self {
+            registry,
+            online_players,
+            loaded_chunks,
+            entities,
+            messages_received,
+            websocket_connections,
+        })
+    }
+
+    // Counters only move forward, so catch up to the handler's running total
+    pub fn sync_messages_received(&self, total: u64) {
+        let current = self.messages_received.get();
+        if total > current {
+            self.messages_received.inc_by(total - current);
+        }
+    }
+
+    pub fn render(&self) -> Result<String, String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(|e| format!("Failed to encode metrics: {}", e))?;
+        String::from_utf8(buffer).map_err(|e| format!("Failed to encode metrics: {}", e))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,1510 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use actix::Recipient;
+use log::error;
+
+use crate::networking::{
+    protocol::{ClientMessage, InventoryAction, ServerMessage, TradeAction},
+    websocket_handler::Outbound,
+};
+use crate::systems::{
+    world_manager::WorldManager,
+    player_manager::{Player, PlayerManager},
+    chunk_manager::ChunkManager,
+    entity_manager::{Entity, EntityManager, EntityType},
+    crafting_system::CraftingSystem,
+    inventory_system::{Inventory, InventoryItem, InventorySystem, ToolState},
+    item_registry::CRAFTING_TABLE,
+    trade_system::{Trade, TradeSystem},
+    chat_system::{ChatDispatch, ChatSystem},
+    command_system::CommandSystem,
+    explosion_system::ExplosionSystem,
+    fluid_system::FluidSystem,
+    mining_system::MiningSystem,
+};
+use crate::worlds::block_registry::{AIR, TNT};
+
+const ENTITY_VIEW_DISTANCE: f64 = 64.0;
+const MAX_REACH: f64 = 6.0; // From the player's feet to the block's center
+const ATTACK_DAMAGE: f32 = 2.0; // Per hit, whatever the player holds
+const MOB_KILL_EXPERIENCE: i32 = 5;
+const CHAT_HISTORY_SIZE: usize = 50;
+
+struct Session {
+    player_id: String,
+    recipient: Recipient<Outbound>,
+    latency_ms: Option<u64>, // Last measured ping round trip
+}
+
+pub struct MessageHandler {
+    world_manager: Arc<RwLock<WorldManager>>,
+    player_manager: Arc<RwLock<PlayerManager>>,
+    chunk_manager: Arc<RwLock<ChunkManager>>,
+    entity_manager: Arc<RwLock<EntityManager>>,
+    crafting_system: Arc<RwLock<CraftingSystem>>,
+    inventory_system: Arc<RwLock<InventorySystem>>,
+    trade_system: Arc<RwLock<TradeSystem>>,
+    chat_system: Arc<RwLock<ChatSystem>>,
+    command_system: Arc<RwLock<CommandSystem>>,
+    fluid_system: Arc<RwLock<FluidSystem>>,
+    mining_system: Arc<RwLock<MiningSystem>>,
+    explosion_system: Arc<RwLock<ExplosionSystem>>,
+    sessions: RwLock<HashMap<String, Session>>, // session_id -> session
+    interests: RwLock<HashMap<String, HashSet<String>>>, // player_id -> entity ids the client knows about
+    messages_received: AtomicU64,
+}
+
+impl std::fmt::Debug for MessageHandler {
+    // Sessions hold actor recipients, which don't implement Debug
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageHandler").finish_non_exhaustive()
+    }
+}
+
+impl MessageHandler {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        world_manager: Arc<RwLock<WorldManager>>,
+        player_manager: Arc<RwLock<PlayerManager>>,
+        chunk_manager: Arc<RwLock<ChunkManager>>,
+        entity_manager: Arc<RwLock<EntityManager>>,
+        crafting_system: Arc<RwLock<CraftingSystem>>,
+        inventory_system: Arc<RwLock<InventorySystem>>,
+        trade_system: Arc<RwLock<TradeSystem>>,
+        chat_system: Arc<RwLock<ChatSystem>>,
+        command_system: Arc<RwLock<CommandSystem>>,
+        fluid_system: Arc<RwLock<FluidSystem>>,
+        mining_system: Arc<RwLock<MiningSystem>>,
+        explosion_system: Arc<RwLock<ExplosionSystem>>,
+    ) -> Self {
+        Self {
+            world_manager,
+            player_manager,
+            chunk_manager,
+            entity_manager,
+            crafting_system,
+            inventory_system,
+            trade_system,
+            chat_system,
+            command_system,
+            fluid_system,
+            mining_system,
+            explosion_system,
+            sessions: RwLock::new(HashMap::new()),
+            interests: RwLock::new(HashMap::new()),
+            messages_received: AtomicU64::new(0),
+        }
+    }
+
+    pub async fn authenticate(
+        &self,
+        session_id: &str,
+        token: &str,
+    ) -> Result<Player, Box<dyn std::error::Error>> {
+        self.player_manager.write().await.connect_session(session_id, token).await
+    }
+
+    pub async fn register_session(&self, session_id: &str, player_id: &str, recipient: Recipient<Outbound>) {
+        self.sessions.write().await.insert(
+            session_id.to_string(),
+            Session {
+                player_id: player_id.to_string(),
+                recipient,
+                latency_ms: None,
+            },
+        );
+    }
+
+    pub async fn disconnect_session(&self, session_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut cancelled_trades = Vec::new();
+        {
+            let mut sessions = self.sessions.write().await;
+            if let Some(session) = sessions.remove(session_id) {
+                // Another tab may still be connected as the same player
+                if !sessions.values().any(|s| s.player_id == session.player_id) {
+                    self.interests.write().await.remove(&session.player_id);
+                    self.mining_system.write().await.forget_player(&session.player_id);
+                    cancelled_trades = self.trade_system.write().await.cancel_trades_for_player(&session.player_id);
+                }
+            }
+        }
+        for trade in &cancelled_trades {
+            self.notify_trade_closed(trade, false).await;
+        }
+        self.player_manager.write().await.disconnect_session(session_id).await?;
+        self.leave_worlds().await
+    }
+
+    // Lowers the player count of every world a disconnected or kicked player was in
+    async fn leave_worlds(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let left_worlds = self.player_manager.write().await.take_left_worlds();
+        if left_worlds.is_empty() {
+            return Ok(());
+        }
+
+        let mut world_manager = self.world_manager.write().await;
+        for world_id in &left_worlds {
+            world_manager.leave_world(world_id).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn record_latency(&self, session_id: &str, latency_ms: u64) {
+        if let Some(session) = self.sessions.write().await.get_mut(session_id) {
+            session.latency_ms = Some(latency_ms);
+        }
+    }
+
+    pub async fn get_latency_stats(&self) -> LatencyStats {
+        let sessions = self.sessions.read().await;
+        let samples: Vec<u64> = sessions.values().filter_map(|s| s.latency_ms).collect();
+
+        LatencyStats {
+            connected_sessions: sessions.len(),
+            average_latency_ms: if samples.is_empty() {
+                0
+            } else {
+                samples.iter().sum::<u64>() / samples.len() as u64
+            },
+            max_latency_ms: samples.iter().copied().max().unwrap_or(0),
+        }
+    }
+
+    pub fn get_messages_received(&self) -> u64 {
+        self.messages_received.load(Ordering::Relaxed)
+    }
+
+    pub async fn handle_message(&self, session_id: &str, message: ClientMessage) -> Vec<ServerMessage> {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+
+        let player_id = match self.sessions.read().await.get(session_id) {
+            Some(session) => session.player_id.clone(),
+            None => {
+                return vec![ServerMessage::Error {
+                    message: "Not authenticated".to_string(),
+                }]
+            }
+        };
+
+        let result = match message {
+            ClientMessage::Move { position, rotation } => self.handle_move(&player_id, position, rotation).await,
+            ClientMessage::PlaceBlock { x, y, z, block_id } => self.handle_block_change(&player_id, x, y, z, block_id).await,
+            ClientMessage::BreakBlock { x, y, z } => self.handle_block_change(&player_id, x, y, z, 0).await,
+            ClientMessage::Chat { content, channel_id } => self.handle_chat(&player_id, &content, channel_id).await,
+            ClientMessage::Inventory(action) => self.handle_inventory_action(&player_id, action).await,
+            ClientMessage::Trade(action) => self.handle_trade_action(&player_id, action).await,
+            ClientMessage::ChunkRequest { x, z } => self.handle_chunk_request(&player_id, x, z).await,
+            ClientMessage::Ping => Ok(vec![ServerMessage::Pong]),
+            ClientMessage::CompleteCommand { partial } => {
+                let command_system = self.command_system.read().await;
+                let player_manager = self.player_manager.read().await;
+                let suggestions = command_system.complete(&partial, &player_id, &player_manager).await;
+                Ok(vec![ServerMessage::CommandSuggestions { suggestions }])
+            }
+            ClientMessage::StartBreaking { x, y, z } => self.handle_start_breaking(&player_id, x, y, z).await,
+            ClientMessage::JoinWorld { world_id } => self.handle_join_world(&player_id, &world_id).await,
+            ClientMessage::Respawn => self.handle_respawn(&player_id).await,
+            ClientMessage::Attack { entity_id } => self.handle_attack(&player_id, &entity_id).await,
+            ClientMessage::RecipeBook => self.handle_recipe_book(&player_id).await,
+            ClientMessage::ChatHistory { channel_id } => self.handle_chat_history(&player_id, channel_id.as_deref()).await,
+        };
+
+        result.unwrap_or_else(|e| vec![ServerMessage::Error { message: e.to_string() }])
+    }
+
+    async fn handle_move(
+        &self,
+        player_id: &str,
+        position: [f64; 3],
+        rotation: [f64; 3],
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let accepted = {
+            let world_manager = self.world_manager.read().await;
+            let mut player_manager = self.player_manager.write().await;
+            player_manager.update_player_position(player_id, position, rotation, &world_manager).await?
+        };
+        if !accepted {
+            // Put the client back where the server thinks it is, nobody else hears about the attempt
+            let player = self.player_manager.read().await.get_player(player_id).await.ok_or("Player not found")?;
+            return Ok(vec![ServerMessage::PlayerUpdate {
+                player_id: player_id.to_string(),
+                position: player.position,
+                rotation: player.rotation,
+            }]);
+        }
+
+        let world_id = self.current_world(player_id).await?;
+        self.broadcast_to_world(
+            &world_id,
+            ServerMessage::PlayerUpdate {
+                player_id: player_id.to_string(),
+                position,
+                rotation,
+            },
+            Some(player_id),
+        )
+        .await;
+        self.update_player_interest(player_id, &world_id, position).await;
+
+        Ok(Vec::new())
+    }
+
+    async fn handle_join_world(
+        &self,
+        player_id: &str,
+        world_id: &str,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let player = {
+            let mut world_manager = self.world_manager.write().await;
+            let mut chunk_manager = self.chunk_manager.write().await;
+            let mut player_manager = self.player_manager.write().await;
+            player_manager.teleport_to_world(player_id, world_id, &mut world_manager, &mut chunk_manager).await?;
+            player_manager.get_player(player_id).await.ok_or("Player not found")?
+        };
+
+        // Entities of the old world are forgotten by the interest diff
+        self.update_player_interest(player_id, world_id, player.position).await;
+
+        Ok(vec![ServerMessage::PlayerUpdate {
+            player_id: player_id.to_string(),
+            position: player.position,
+            rotation: player.rotation,
+        }])
+    }
+
+    async fn handle_respawn(&self, player_id: &str) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+        let player = {
+            let world_manager = self.world_manager.read().await;
+            let inventory_system = self.inventory_system.read().await;
+            let mut player_manager = self.player_manager.write().await;
+            player_manager.respawn_player(player_id, &world_manager, &inventory_system).await?;
+            player_manager.get_player(player_id).await.ok_or("Player not found")?
+        };
+
+        let update = ServerMessage::PlayerUpdate {
+            player_id: player_id.to_string(),
+            position: player.position,
+            rotation: player.rotation,
+        };
+        self.broadcast_to_world(&world_id, update.clone(), Some(player_id)).await;
+        self.update_player_interest(player_id, &world_id, player.position).await;
+
+        Ok(vec![update, ServerMessage::InventoryUpdate(player.inventory)])
+    }
+
+    async fn handle_attack(&self, player_id: &str, entity_id: &str) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+        let player = self.player_manager.read().await.get_player(player_id).await.ok_or("Player not found")?;
+        if player.health <= 0.0 {
+            return Err("Dead players can't attack".into());
+        }
+
+        let killed = {
+            let mut entity_manager = self.entity_manager.write().await;
+            let entity = entity_manager.get_entity(entity_id).await.ok_or("Entity not found")?;
+            if entity.world_id != world_id || !entity.is_active || !entity.entity_type.is_mob() {
+                return Err("That entity can't be attacked".into());
+            }
+
+            let dx = entity.position[0] - player.position[0];
+            let dy = entity.position[1] - player.position[1];
+            let dz = entity.position[2] - player.position[2];
+            if (dx * dx + dy * dy + dz * dz).sqrt() > MAX_REACH {
+                return Err("That entity is out of reach".into());
+            }
+
+            entity_manager.damage_entity(entity_id, ATTACK_DAMAGE).await.is_some_and(|health| health <= 0.0)
+        };
+
+        if killed {
+            let mut player_manager = self.player_manager.write().await;
+            player_manager.record_mob_kill(player_id);
+            player_manager.add_experience(player_id, MOB_KILL_EXPERIENCE).await?;
+        }
+        Ok(Vec::new())
+    }
+
+    async fn handle_start_breaking(
+        &self,
+        player_id: &str,
+        x: i32,
+        y: i32,
+        z: i32,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+        self.mining_system.write().await.start_breaking(player_id, &world_id, x, y, z);
+        Ok(Vec::new())
+    }
+
+    async fn handle_block_change(
+        &self,
+        player_id: &str,
+        x: i32,
+        y: i32,
+        z: i32,
+        block_id: u8,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+
+        let center = [x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5];
+        if !self.world_manager.read().await.is_within_border(&world_id, center) {
+            let current = self.chunk_manager.read().await.get_block(&world_id, x, y, z).await.unwrap_or(AIR);
+            return Ok(vec![
+                ServerMessage::BlockUpdate { x, y, z, block_id: current },
+                ServerMessage::Error { message: "That block is outside the world border".to_string() },
+            ]);
+        }
+
+        let mut ignited = false;
+        if block_id == 0 {
+            let current = self.chunk_manager.read().await.get_block(&world_id, x, y, z).await.ok_or("Chunk not loaded")?;
+            ignited = current == TNT;
+            let player = self.player_manager.read().await.get_player(player_id).await.ok_or("Player not found")?;
+            if let Err(e) = self.mining_system.write().await.validate_break(&player, &world_id, x, y, z, current) {
+                // The client already removed the block locally, put it back
+                return Ok(vec![
+                    ServerMessage::BlockUpdate { x, y, z, block_id: current },
+                    ServerMessage::Error { message: e },
+                ]);
+            }
+        } else {
+            let current = self.chunk_manager.read().await.get_block(&world_id, x, y, z).await.ok_or("Chunk not loaded")?;
+            let placed = {
+                let inventory_system = self.inventory_system.read().await;
+                let mut player_manager = self.player_manager.write().await;
+                let player = player_manager.get_player(player_id).await.ok_or("Player not found")?;
+                Self::validate_place(&player, x, y, z, current)
+                    .and_then(|_| player_manager.take_placed_block(player_id, block_id, &inventory_system))
+            };
+            if let Err(e) = placed {
+                // The client already placed the block locally, take it back
+                return Ok(vec![
+                    ServerMessage::BlockUpdate { x, y, z, block_id: current },
+                    ServerMessage::Error { message: e },
+                ]);
+            }
+        }
+
+        // Clients hear about the change through the next chunk delta broadcast
+        let neighbors = self.chunk_manager.write().await.set_block(&world_id, x, y, z, block_id).await?;
+
+        // Flint and steel has no block interaction yet, so breaking TNT lights it instead of dropping it
+        if ignited {
+            self.explosion_system.write().await.prime(&world_id, x, y, z);
+        }
+
+        // Placed fluids start flowing and removed blocks let neighboring fluids in
+        {
+            let mut fluid_system = self.fluid_system.write().await;
+            fluid_system.schedule(&world_id, neighbors);
+            fluid_system.schedule(&world_id, [(x, y, z)]);
+        }
+
+        let inventory_system = self.inventory_system.read().await;
+        let mut player_manager = self.player_manager.write().await;
+        if block_id == 0 {
+            player_manager.record_block_break(player_id);
+            if player_manager.damage_held_tool(player_id, 1, &inventory_system).await? == ToolState::Exempt {
+                return Ok(Vec::new());
+            }
+        } else {
+            player_manager.record_block_place(player_id);
+        }
+
+        // The placed block or the tool's wear changed a slot
+        let inventory = player_manager.get_inventory_mut(player_id).ok_or("Player not found")?;
+        Ok(vec![ServerMessage::InventoryUpdate(inventory.clone())])
+    }
+
+    fn validate_place(player: &Player, x: i32, y: i32, z: i32, current: u8) -> Result<(), String> {
+        if current != AIR {
+            return Err("Blocks can only be placed in empty space".to_string());
+        }
+
+        if !Self::within_reach(player, x, y, z) {
+            return Err("That block is out of reach".to_string());
+        }
+
+        Ok(())
+    }
+
+    fn within_reach(player: &Player, x: i32, y: i32, z: i32) -> bool {
+        let center = [x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5];
+        let distance = center
+            .iter()
+            .zip(player.position.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt();
+        distance <= MAX_REACH
+    }
+
+    async fn handle_chat(
+        &self,
+        player_id: &str,
+        content: &str,
+        channel_id: Option<String>,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+        let dispatch = self.chat_system.write().await.process_input(player_id, content, Some(world_id.clone()), channel_id)?;
+
+        match dispatch {
+            ChatDispatch::Message(message) => {
+                self.broadcast_to_world(&world_id, ServerMessage::Chat(message), None).await;
+                Ok(Vec::new())
+            }
+            ChatDispatch::Command { name, args } if matches!(name.to_lowercase().as_str(), "msg" | "tell" | "w" | "r") => {
+                self.handle_whisper(player_id, &name, &args).await
+            }
+            ChatDispatch::Command { name, args } if name.eq_ignore_ascii_case("channel") => {
+                self.handle_channel_command(player_id, &args).await
+            }
+            ChatDispatch::Command { name, args } => {
+                let result = {
+                    let mut command_system = self.command_system.write().await;
+                    let world_manager = self.world_manager.read().await;
+                    let mut player_manager = self.player_manager.write().await;
+                    command_system.execute(player_id, &name, &args, &mut player_manager, &world_manager).await
+                };
+                Ok(vec![match result {
+                    Ok(message) => ServerMessage::CommandResult { success: true, message },
+                    Err(e) => ServerMessage::CommandResult { success: false, message: e.to_string() },
+                }])
+            }
+        }
+    }
+
+    // /msg <player> <message> and /r <message>, answered with the whisper so the sender sees it too
+    async fn handle_whisper(
+        &self,
+        player_id: &str,
+        name: &str,
+        args: &[String],
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let message = if name.eq_ignore_ascii_case("r") {
+            if args.is_empty() {
+                return Err("Usage: /r <message>".into());
+            }
+            self.chat_system.write().await.reply_whisper(player_id, &args.join(" "))?
+        } else {
+            if args.len() < 2 {
+                return Err(format!("Usage: /{} <player> <message>", name).into());
+            }
+            let target = self
+                .player_manager
+                .read()
+                .await
+                .get_player_by_username(&args[0])
+                .await
+                .filter(|target| target.is_online)
+                .ok_or_else(|| format!("{} is not online", args[0]))?;
+            self.chat_system.write().await.send_whisper(player_id, &target.id, &args[1..].join(" "))?
+        };
+
+        if let Some(target) = &message.target_player {
+            self.send_to_player(target, ServerMessage::Chat(message.clone())).await;
+        }
+        Ok(vec![ServerMessage::Chat(message)])
+    }
+
+    // Channel membership lives in the chat system, so these never reach the command system
+    async fn handle_channel_command(
+        &self,
+        player_id: &str,
+        args: &[String],
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let mut chat_system = self.chat_system.write().await;
+        let message = match (args.first().map(|arg| arg.to_lowercase()).as_deref(), args.get(1)) {
+            (Some("list"), _) => {
+                let mut joined: Vec<&str> = chat_system.get_player_channels(player_id).iter().map(|channel| channel.id.as_str()).collect();
+                let mut open: Vec<&str> = chat_system
+                    .get_all_channels()
+                    .into_iter()
+                    .filter(|channel| !channel.is_private && !joined.contains(&channel.id.as_str()))
+                    .map(|channel| channel.id.as_str())
+                    .collect();
+                joined.sort();
+                open.sort();
+                format!("Joined: {}. Open: {}", joined.join(", "), open.join(", "))
+            }
+            (Some("join"), Some(channel_id)) => {
+                let channel = chat_system.get_channel(channel_id).ok_or_else(|| format!("There is no #{} channel", channel_id))?;
+                if channel.is_private {
+                    return Err("That channel is invite only".into());
+                }
+                chat_system.join_channel(channel_id, player_id)?;
+                format!("Joined #{}", channel_id)
+            }
+            (Some("leave"), Some(channel_id)) => {
+                chat_system.leave_channel(channel_id, player_id)?;
+                format!("Left #{}", channel_id)
+            }
+            _ => return Err("Usage: /channel <list|join|leave> [channel]".into()),
+        };
+        Ok(vec![ServerMessage::CommandResult { success: true, message }])
+    }
+
+    async fn handle_chat_history(
+        &self,
+        player_id: &str,
+        channel_id: Option<&str>,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+        let mut messages = self.chat_system.read().await.get_recent_messages(CHAT_HISTORY_SIZE, Some(&world_id), channel_id, player_id);
+        messages.reverse();
+        Ok(vec![ServerMessage::ChatHistory { messages }])
+    }
+
+    async fn handle_inventory_action(
+        &self,
+        player_id: &str,
+        action: InventoryAction,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        match action {
+            InventoryAction::SelectSlot { slot } => {
+                self.update_inventory(player_id, |inventory_system, inventory| inventory_system.set_selected_slot(inventory, slot)).await
+            }
+            InventoryAction::Move { from_slot, to_slot } => {
+                self.update_inventory(player_id, |inventory_system, inventory| inventory_system.move_item(inventory, from_slot, to_slot)).await
+            }
+            InventoryAction::Split { slot, amount, target } => {
+                self.update_inventory(player_id, |inventory_system, inventory| inventory_system.split_stack(inventory, slot, amount, target)).await
+            }
+            InventoryAction::SplitHalf { slot } => {
+                self.update_inventory(player_id, |inventory_system, inventory| inventory_system.split_half(inventory, slot)).await
+            }
+            InventoryAction::Drop { slot, count } => self.handle_drop(player_id, slot, count).await,
+            InventoryAction::Sort { preserve_hotbar } => {
+                self.update_inventory(player_id, |inventory_system, inventory| {
+                    inventory_system.sort_inventory(inventory, preserve_hotbar);
+                    Ok(())
+                })
+                .await
+            }
+            InventoryAction::Transfer { x, y, z, slot, to_container } => {
+                self.handle_transfer(player_id, [x, y, z], slot, to_container).await
+            }
+            InventoryAction::Equip { armor_slot, item_id } => {
+                self.update_inventory(player_id, |inventory_system, inventory| {
+                    // The replaced piece goes back into the inventory, so there must be room for it
+                    let worn = inventory.armor.get(armor_slot).ok_or("No such armor slot")?.is_some();
+                    if worn && inventory_system.find_empty_slot(inventory).is_none() {
+                        return Err("No room for the armor being replaced".to_string());
+                    }
+                    if let Some(previous) = inventory_system.equip_armor(inventory, armor_slot, item_id)? {
+                        let slot = inventory_system.find_empty_slot(inventory).ok_or("Inventory is full")?;
+                        inventory.items[slot] = Some(InventoryItem { slot, ..previous });
+                    }
+                    Ok(())
+                })
+                .await
+            }
+            InventoryAction::Unequip { armor_slot } => {
+                self.update_inventory(player_id, |inventory_system, inventory| inventory_system.unequip_armor(inventory, armor_slot)).await
+            }
+            InventoryAction::Craft { recipe_id, times } => self.handle_craft(player_id, &recipe_id, times).await,
+            InventoryAction::CraftGrid { grid } => self.handle_craft_grid(player_id, grid).await,
+            InventoryAction::Smelt { item_id, count } => self.handle_smelt(player_id, item_id, count).await,
+        }
+    }
+
+    // The client always gets the resulting inventory back, so a rejected change is undone on its side too
+    async fn update_inventory(
+        &self,
+        player_id: &str,
+        change: impl FnOnce(&InventorySystem, &mut Inventory) -> Result<(), String>,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let inventory_system = self.inventory_system.read().await;
+        let mut player_manager = self.player_manager.write().await;
+        let inventory = player_manager.get_inventory_mut(player_id).ok_or("Player not found")?;
+
+        let result = change(&inventory_system, inventory);
+        let mut messages = vec![ServerMessage::InventoryUpdate(inventory.clone())];
+        if let Err(message) = result {
+            messages.push(ServerMessage::Error { message });
+        }
+        Ok(messages)
+    }
+
+    async fn handle_craft(
+        &self,
+        player_id: &str,
+        recipe_id: &str,
+        times: u32,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let infinite_items = self.player_manager.read().await.get_player(player_id).await.ok_or("Player not found")?.infinite_items;
+        let crafting_system = self.crafting_system.read().await;
+        let recipe = crafting_system.get_recipe(recipe_id).ok_or("Unknown recipe")?;
+
+        self.update_inventory(player_id, |inventory_system, inventory| {
+            // Crafting tables have no block interaction yet, carrying one unlocks their recipes
+            if recipe.crafting_table && !inventory_system.has_item(inventory, CRAFTING_TABLE, 1) {
+                return Err("That recipe needs a crafting table".to_string());
+            }
+            crafting_system.craft_item_n(inventory, recipe, times, infinite_items, inventory_system).map(|_| ())
+        })
+        .await
+    }
+
+    async fn handle_smelt(
+        &self,
+        player_id: &str,
+        item_id: u32,
+        count: u32,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let infinite_items = self.player_manager.read().await.get_player(player_id).await.ok_or("Player not found")?.infinite_items;
+        let crafting_system = self.crafting_system.read().await;
+
+        let mut experience = 0.0;
+        let messages = self
+            .update_inventory(player_id, |inventory_system, inventory| {
+                experience = crafting_system.smelt_item(inventory, item_id, count, infinite_items, inventory_system)?;
+                Ok(())
+            })
+            .await?;
+
+        if experience > 0.0 {
+            self.player_manager.write().await.add_experience(player_id, experience.round() as i32).await?;
+        }
+        Ok(messages)
+    }
+
+    async fn handle_recipe_book(&self, player_id: &str) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let crafting_system = self.crafting_system.read().await;
+        let inventory_system = self.inventory_system.read().await;
+        let player = self.player_manager.read().await.get_player(player_id).await.ok_or("Player not found")?;
+
+        let use_crafting_table = inventory_system.has_item(&player.inventory, CRAFTING_TABLE, 1);
+        let mut recipe_ids: Vec<String> = crafting_system
+            .get_craftable_recipes(&player.inventory, use_crafting_table, &inventory_system)
+            .into_iter()
+            .map(|recipe| recipe.id.clone())
+            .collect();
+        recipe_ids.sort();
+        Ok(vec![ServerMessage::CraftableRecipes { recipe_ids }])
+    }
+
+    // The grid is only the pattern, the ingredients still come out of the inventory
+    async fn handle_craft_grid(
+        &self,
+        player_id: &str,
+        grid: [[Option<u32>; 3]; 3],
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let infinite_items = self.player_manager.read().await.get_player(player_id).await.ok_or("Player not found")?.infinite_items;
+        let crafting_system = self.crafting_system.read().await;
+
+        self.update_inventory(player_id, |inventory_system, inventory| {
+            let use_crafting_table = inventory_system.has_item(inventory, CRAFTING_TABLE, 1);
+            let recipe = crafting_system
+                .find_matching_recipe(&grid, use_crafting_table)
+                .ok_or_else(|| "No recipe matches that pattern".to_string())?;
+            crafting_system.craft_item(inventory, recipe, infinite_items, inventory_system).map(|_| ())
+        })
+        .await
+    }
+
+    // Moves as much of the stack as fits, the rest stays in its slot
+    async fn handle_transfer(
+        &self,
+        player_id: &str,
+        position: [i32; 3],
+        slot: usize,
+        to_container: bool,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+        let [x, y, z] = position;
+
+        let (inventory, container) = {
+            let mut chunk_manager = self.chunk_manager.write().await;
+            let inventory_system = self.inventory_system.read().await;
+            let mut player_manager = self.player_manager.write().await;
+            let player = player_manager.get_player(player_id).await.ok_or("Player not found")?;
+            if !Self::within_reach(&player, x, y, z) {
+                return Err("That container is out of reach".into());
+            }
+
+            let mut container = chunk_manager.get_container(&world_id, position).ok_or("There is no container there")?;
+            let inventory = player_manager.get_inventory_mut(player_id).ok_or("Player not found")?;
+            let (from, to) = if to_container {
+                (&mut *inventory, &mut container)
+            } else {
+                (&mut container, &mut *inventory)
+            };
+
+            // A destination with no room at all is an error, not a transfer that quietly moves nothing
+            let no_room = from
+                .items
+                .get(slot)
+                .and_then(|item| item.as_ref())
+                .is_some_and(|item| inventory_system.count_free_space(to, item.id) == 0);
+            if no_room {
+                return Err("There's no room for that item".into());
+            }
+            inventory_system.transfer_item(from, slot, to)?;
+            chunk_manager.set_container(&world_id, position, &container)?;
+            (inventory.clone(), container)
+        };
+
+        // Anyone else looking into the chest sees it change too
+        self.broadcast_to_world(&world_id, ServerMessage::ContainerUpdate { x, y, z, inventory: container }, None).await;
+        Ok(vec![ServerMessage::InventoryUpdate(inventory)])
+    }
+
+    // The dropped stack becomes an item entity at the player's feet
+    async fn handle_drop(
+        &self,
+        player_id: &str,
+        slot: usize,
+        count: Option<u32>,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+
+        let mut entity_manager = self.entity_manager.write().await;
+        let inventory_system = self.inventory_system.read().await;
+        let mut player_manager = self.player_manager.write().await;
+        let player = player_manager.get_player(player_id).await.ok_or("Player not found")?;
+        let inventory = player_manager.get_inventory_mut(player_id).ok_or("Player not found")?;
+
+        let item = inventory_system.drop_item(inventory, slot, count).ok_or("That slot is empty")?;
+        let mut metadata = serde_json::json!({ "item_id": item.id, "count": item.count });
+        // A dropped tool keeps its durability
+        if let Some(item_metadata) = item.metadata {
+            metadata["item_metadata"] = item_metadata;
+        }
+        if let Err(e) = entity_manager.spawn_entity(EntityType::Item.into(), player.position, world_id, Some(metadata)).await {
+            // Nothing landed in the world, so nothing leaves the inventory either
+            player_manager.update_player_inventory(player_id, player.inventory).await?;
+            return Err(e.into());
+        }
+
+        Ok(vec![ServerMessage::InventoryUpdate(inventory.clone())])
+    }
+
+    async fn handle_trade_action(
+        &self,
+        player_id: &str,
+        action: TradeAction,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        match action {
+            TradeAction::Propose { recipient, offered, requested } => {
+                let trade = {
+                    let inventory_system = self.inventory_system.read().await;
+                    let mut trade_system = self.trade_system.write().await;
+                    let player_manager = self.player_manager.read().await;
+                    let proposer = player_manager.get_player(player_id).await.ok_or("Player not found")?;
+                    let recipient = player_manager
+                        .get_player_by_username(&recipient)
+                        .await
+                        .filter(|p| p.is_online)
+                        .ok_or("That player is not online")?;
+
+                    // An expired offer shouldn't block proposing again
+                    trade_system.cleanup_expired_trades();
+                    trade_system.propose_trade(
+                        player_id,
+                        &recipient.id,
+                        offered,
+                        requested,
+                        &proposer.inventory,
+                        proposer.infinite_items,
+                        &inventory_system,
+                    )?
+                };
+
+                self.send_to_player(&trade.recipient, ServerMessage::TradeOffer(trade.clone())).await;
+                Ok(vec![ServerMessage::TradeOffer(trade)])
+            }
+            TradeAction::Accept { trade_id } => {
+                let (trade, proposer_inventory, recipient_inventory) = {
+                    let inventory_system = self.inventory_system.read().await;
+                    let mut trade_system = self.trade_system.write().await;
+                    let mut player_manager = self.player_manager.write().await;
+                    let proposer_id = trade_system
+                        .get_pending_trades(player_id)
+                        .into_iter()
+                        .find(|t| t.id == trade_id)
+                        .ok_or("Trade not found")?
+                        .proposer;
+                    let proposer = player_manager.get_player(&proposer_id).await.ok_or("Player not found")?;
+                    let recipient = player_manager.get_player(player_id).await.ok_or("Player not found")?;
+
+                    // Both sides are written back only once the swap went through
+                    let mut proposer_inventory = proposer.inventory;
+                    let mut recipient_inventory = recipient.inventory;
+                    let trade = trade_system.accept_trade(
+                        &trade_id,
+                        player_id,
+                        &mut proposer_inventory,
+                        proposer.infinite_items,
+                        &mut recipient_inventory,
+                        recipient.infinite_items,
+                        &inventory_system,
+                    )?;
+                    player_manager.update_player_inventory(&proposer_id, proposer_inventory.clone()).await?;
+                    player_manager.update_player_inventory(player_id, recipient_inventory.clone()).await?;
+                    (trade, proposer_inventory, recipient_inventory)
+                };
+
+                self.send_to_player(&trade.proposer, ServerMessage::InventoryUpdate(proposer_inventory)).await;
+                self.notify_trade_closed(&trade, true).await;
+                Ok(vec![ServerMessage::InventoryUpdate(recipient_inventory)])
+            }
+            TradeAction::Cancel { trade_id } => {
+                let trade = self.trade_system.write().await.cancel_trade(&trade_id, player_id)?;
+                self.notify_trade_closed(&trade, false).await;
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    async fn notify_trade_closed(&self, trade: &Trade, completed: bool) {
+        for player_id in [&trade.proposer, &trade.recipient] {
+            let message = ServerMessage::TradeClosed { trade_id: trade.id.clone(), completed };
+            self.send_to_player(player_id, message).await;
+        }
+    }
+
+    async fn handle_chunk_request(
+        &self,
+        player_id: &str,
+        x: i32,
+        z: i32,
+    ) -> Result<Vec<ServerMessage>, Box<dyn std::error::Error>> {
+        let world_id = self.current_world(player_id).await?;
+
+        // Cached chunks are served under the shared lock, only a miss needs the write lock to load or generate
+        let cached = self.chunk_manager.read().await.peek_chunk(&world_id, x, z);
+        let chunk = match cached {
+            Some(chunk) => chunk,
+            None => self
+                .chunk_manager
+                .write()
+                .await
+                .get_chunk(&world_id, x, z)
+                .await
+                .ok_or("Chunk unavailable")?,
+        };
+
+        Ok(vec![ServerMessage::ChunkData { world_id, chunk }])
+    }
+
+    async fn current_world(&self, player_id: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let player = self.player_manager.read().await.get_player(player_id).await.ok_or("Player not found")?;
+        player.world_id.ok_or_else(|| "Player is not in a world".into())
+    }
+
+    // Sends each delta only to players whose load radius covers the chunk
+    pub async fn broadcast_chunk_deltas(&self) {
+        let (deltas, load_distance) = {
+            let mut chunk_manager = self.chunk_manager.write().await;
+            (chunk_manager.take_chunk_deltas(), chunk_manager.load_distance())
+        };
+        if deltas.is_empty() {
+            return;
+        }
+
+        let player_manager = self.player_manager.read().await;
+        let sessions = self.sessions.read().await;
+
+        for delta in deltas {
+            let viewers: HashSet<String> = player_manager
+                .get_players_in_world(&delta.world_id)
+                .await
+                .into_iter()
+                .filter(|p| {
+                    let player_chunk_x = (p.position[0].floor() as i32) >> 4;
+                    let player_chunk_z = (p.position[2].floor() as i32) >> 4;
+                    (player_chunk_x - delta.chunk.0).abs() <= load_distance
+                        && (player_chunk_z - delta.chunk.1).abs() <= load_distance
+                })
+                .map(|p| p.id)
+                .collect();
+
+            let message = ServerMessage::ChunkDelta(delta);
+            for session in sessions.values().filter(|s| viewers.contains(&s.player_id)) {
+                session.recipient.do_send(Outbound(message.clone()));
+            }
+        }
+    }
+
+    pub async fn run(message_handler: Arc<MessageHandler>, tick_millis: u64) {
+        let mut ticker = interval(Duration::from_millis(tick_millis));
+        loop {
+            ticker.tick().await;
+            message_handler.broadcast_chunk_deltas().await;
+            message_handler.broadcast_entity_events().await;
+            message_handler.close_kicked_sessions().await;
+        }
+    }
+
+    // Diff what the player can see now against what their client already knows about
+    pub async fn update_player_interest(&self, player_id: &str, world_id: &str, position: [f64; 3]) {
+        let visible: HashMap<String, Entity> = self
+            .entity_manager
+            .read()
+            .await
+            .get_entities_in_radius(position, ENTITY_VIEW_DISTANCE, world_id)
+            .await
+            .into_iter()
+            .filter(|entity| entity.id != player_id)
+            .map(|entity| (entity.id.clone(), entity))
+            .collect();
+
+        let mut messages = Vec::new();
+        {
+            let mut interests = self.interests.write().await;
+            let known = interests.entry(player_id.to_string()).or_default();
+
+            known.retain(|entity_id| {
+                let still_visible = visible.contains_key(entity_id);
+                if !still_visible {
+                    messages.push(ServerMessage::EntityDespawn { entity_id: entity_id.clone() });
+                }
+                still_visible
+            });
+
+            for (entity_id, entity) in visible {
+                if known.insert(entity_id) {
+                    messages.push(ServerMessage::EntitySpawn(entity));
+                }
+            }
+        }
+
+        for message in messages {
+            self.send_to_player(player_id, message).await;
+        }
+    }
+
+    // Entity, physics and mob ticks only record what changed, players hear about it here
+    pub async fn broadcast_entity_events(&self) {
+        let (updated, despawned) = self.entity_manager.write().await.take_entity_events();
+
+        for entity in &updated {
+            self.notify_entity_updated(entity).await;
+        }
+        for entity_id in &despawned {
+            self.notify_entity_despawned(entity_id).await;
+        }
+    }
+
+    // Covers both spawns and moves: nearby players get a spawn the first time they see the entity
+    pub async fn notify_entity_updated(&self, entity: &Entity) {
+        let nearby = self.players_near(&entity.world_id, entity.position).await;
+
+        let mut outgoing = Vec::new();
+        {
+            let mut interests = self.interests.write().await;
+            for player_id in &nearby {
+                interests.entry(player_id.clone()).or_default();
+            }
+
+            for (player_id, known) in interests.iter_mut() {
+                let in_range = nearby.contains(player_id) && *player_id != entity.id;
+                let message = match (in_range, known.contains(&entity.id)) {
+                    (true, true) => ServerMessage::EntityMove {
+                        entity_id: entity.id.clone(),
+                        position: entity.position,
+                        rotation: entity.rotation,
+                    },
+                    (true, false) => {
+                        known.insert(entity.id.clone());
+                        ServerMessage::EntitySpawn(entity.clone())
+                    }
+                    (false, true) => {
+                        known.remove(&entity.id);
+                        ServerMessage::EntityDespawn { entity_id: entity.id.clone() }
+                    }
+                    (false, false) => continue,
+                };
+                outgoing.push((player_id.clone(), message));
+            }
+        }
+
+        for (player_id, message) in outgoing {
+            self.send_to_player(&player_id, message).await;
+        }
+    }
+
+    pub async fn notify_entity_despawned(&self, entity_id: &str) {
+        let mut watchers = Vec::new();
+        for (player_id, known) in self.interests.write().await.iter_mut() {
+            if known.remove(entity_id) {
+                watchers.push(player_id.clone());
+            }
+        }
+
+        for player_id in watchers {
+            self.send_to_player(&player_id, ServerMessage::EntityDespawn { entity_id: entity_id.to_string() }).await;
+        }
+    }
+
+    async fn players_near(&self, world_id: &str, position: [f64; 3]) -> HashSet<String> {
+        self.player_manager
+            .read()
+            .await
+            .get_players_in_world(world_id)
+            .await
+            .into_iter()
+            .filter(|p| {
+                let dx = p.position[0] - position[0];
+                let dy = p.position[1] - position[1];
+                let dz = p.position[2] - position[2];
+                (dx * dx + dy * dy + dz * dz).sqrt() <= ENTITY_VIEW_DISTANCE
+            })
+            .map(|p| p.id)
+            .collect()
+    }
+
+    // Kicks can come from commands or the admin API, both leave the socket to us
+    pub async fn close_kicked_sessions(&self) -> usize {
+        let kicked = self.player_manager.write().await.take_kicked_players();
+
+        for (player_id, reason) in &kicked {
+            self.send_to_player(player_id, ServerMessage::Kicked { reason: reason.clone() }).await;
+        }
+        if let Err(e) = self.leave_worlds().await {
+            error!("Failed to update worlds left by kicked players: {}", e);
+        }
+
+        kicked.len()
+    }
+
+    // Kicks everyone out first so nobody keeps editing chunks that are about to be deleted.
+    // The world manager isn't held while kicking, closing the sessions takes it again
+    pub async fn delete_world(&self, world_id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.world_manager.read().await.get_world(world_id).await.is_none() {
+            return Ok(false);
+        }
+
+        self.player_manager.write().await.evict_world(world_id, "This world was deleted").await?;
+        self.close_kicked_sessions().await;
+
+        let mut world_manager = self.world_manager.write().await;
+        let mut chunk_manager = self.chunk_manager.write().await;
+        let mut entity_manager = self.entity_manager.write().await;
+        world_manager.delete_world(world_id, &mut chunk_manager, &mut entity_manager).await
+    }
+
+    pub async fn broadcast_to_all(&self, message: ServerMessage) {
+        for session in self.sessions.read().await.values() {
+            session.recipient.do_send(Outbound(message.clone()));
+        }
+    }
+
+    pub async fn send_to_player(&self, player_id: &str, message: ServerMessage) {
+        for session in self.sessions.read().await.values().filter(|s| s.player_id == player_id) {
+            session.recipient.do_send(Outbound(message.clone()));
+        }
+    }
+
+    pub async fn broadcast_to_world(&self, world_id: &str, message: ServerMessage, exclude_player: Option<&str>) {
+        let player_ids: HashSet<String> = self
+            .player_manager
+            .read()
+            .await
+            .get_players_in_world(world_id)
+            .await
+            .into_iter()
+            .map(|p| p.id)
+            .filter(|id| Some(id.as_str()) != exclude_player)
+            .collect();
+
+        for session in self.sessions.read().await.values() {
+            if player_ids.contains(&session.player_id) {
+                session.recipient.do_send(Outbound(message.clone()));
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LatencyStats {
+    pub connected_sessions: usize,
+    pub average_latency_ms: u64,
+    pub max_latency_ms: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{auth_service::AuthService, jwt_service::JwtService};
+    use crate::database::{
+        database_service::{DatabaseConfig, DatabaseService},
+        entity_repository::EntityRepository,
+        player_repository::PlayerRepository,
+        world_repository::WorldRepository,
+    };
+    use crate::systems::{
+        item_registry::{ItemRegistry, COAL, GOLD_INGOT, GOLD_ORE, OAK_LOG, OAK_PLANKS, STICK},
+        loot_system::LootSystem,
+        world_manager::{Difficulty, GameMode, WorldSettings},
+    };
+    use crate::worlds::{
+        biome_system::BiomeSystem,
+        block_registry::BlockRegistry,
+        structure_generator::StructureGenerator,
+        terrain_generator::TerrainParams,
+    };
+
+    async fn test_handler() -> MessageHandler {
+        let config = DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            ..DatabaseConfig::default()
+        };
+        let database_service = Arc::new(DatabaseService::new(&config).await.unwrap());
+        let player_repository = Arc::new(PlayerRepository::new(database_service.clone()));
+        let jwt_service = Arc::new(JwtService::new("x".repeat(32)).unwrap());
+        let auth_service = Arc::new(AuthService::new(player_repository.clone(), jwt_service));
+
+        let loot_system = Arc::new(LootSystem::new());
+        let biome_system = Arc::new(BiomeSystem::new());
+        let structure_generator = Arc::new(StructureGenerator::new(loot_system.clone()));
+        let block_registry = Arc::new(BlockRegistry::new());
+        let item_registry = Arc::new(ItemRegistry::new());
+
+        MessageHandler::new(
+            Arc::new(RwLock::new(WorldManager::new(
+                Arc::new(WorldRepository::new(database_service.clone())),
+                TerrainParams::default(),
+                10,
+            ))),
+            Arc::new(RwLock::new(PlayerManager::new(player_repository, auth_service))),
+            Arc::new(RwLock::new(ChunkManager::new(
+                1,
+                TerrainParams::default(),
+                biome_system,
+                structure_generator,
+                block_registry.clone(),
+                std::env::temp_dir().join("strixcraft-message-handler-tests"),
+            ))),
+            Arc::new(RwLock::new(EntityManager::new(Arc::new(EntityRepository::new(database_service)), loot_system))),
+            Arc::new(RwLock::new(CraftingSystem::new())),
+            Arc::new(RwLock::new(InventorySystem::new(item_registry.clone()))),
+            Arc::new(RwLock::new(TradeSystem::new())),
+            Arc::new(RwLock::new(ChatSystem::new())),
+            Arc::new(RwLock::new(CommandSystem::new())),
+            Arc::new(RwLock::new(FluidSystem::new())),
+            Arc::new(RwLock::new(MiningSystem::new(block_registry.clone(), item_registry))),
+            Arc::new(RwLock::new(ExplosionSystem::new(block_registry))),
+        )
+    }
+
+    fn settings() -> WorldSettings {
+        WorldSettings {
+            allow_pvp: false,
+            allow_mob_griefing: false,
+            keep_inventory: false,
+            natural_regeneration: true,
+            difficulty: Difficulty::Peaceful,
+            weather_enabled: false,
+            time_enabled: false,
+            mobs_enabled: false,
+            physics_enabled: false,
+            game_rules: HashMap::new(),
+            max_entities: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn deleting_a_world_with_a_player_in_it_does_not_deadlock() {
+        let handler = test_handler().await;
+
+        let world = {
+            let mut world_manager = handler.world_manager.write().await;
+            let mut chunk_manager = handler.chunk_manager.write().await;
+            let mut entity_manager = handler.entity_manager.write().await;
+            let world = world_manager
+                .create_world("doomed".to_string(), 1, GameMode::Survival, settings(), 10, &mut chunk_manager, &mut entity_manager)
+                .await
+                .unwrap();
+            world_manager.join_world(&world.id, "player", &mut chunk_manager).await.unwrap();
+            world
+        };
+        let player_id = {
+            let mut player_manager = handler.player_manager.write().await;
+            let player = player_manager.register_player("player", "correct horse battery").await.unwrap();
+            player_manager.authenticate_player("player", "correct horse battery").await.unwrap().unwrap();
+            player_manager.set_player_world(&player.id, Some(world.id.clone())).await.unwrap();
+            player.id
+        };
+
+        let deleted = tokio::time::timeout(Duration::from_secs(10), handler.delete_world(&world.id))
+            .await
+            .expect("delete_world deadlocked");
+        assert!(deleted.unwrap());
+
+        assert!(handler.world_manager.read().await.get_world(&world.id).await.is_none());
+        let mut player_manager = handler.player_manager.write().await;
+        let player = player_manager.get_player(&player_id).await.unwrap();
+        assert!(!player.is_online);
+        assert_eq!(player.world_id, None);
+        assert!(player_manager.take_left_worlds().is_empty());
+    }
+
+    #[tokio::test]
+    async fn respawn_restores_a_dead_player_at_the_world_spawn() {
+        let handler = test_handler().await;
+
+        let world = {
+            let mut world_manager = handler.world_manager.write().await;
+            let mut chunk_manager = handler.chunk_manager.write().await;
+            let mut entity_manager = handler.entity_manager.write().await;
+            let world = world_manager
+                .create_world("respawn".to_string(), 1, GameMode::Survival, settings(), 10, &mut chunk_manager, &mut entity_manager)
+                .await
+                .unwrap();
+            world_manager.set_spawn_point(&world.id, [3.5, 90.0, -7.5]).await.unwrap();
+            world
+        };
+        let player_id = {
+            let mut player_manager = handler.player_manager.write().await;
+            let player = player_manager.register_player("player", "correct horse battery").await.unwrap();
+            player_manager.set_player_world(&player.id, Some(world.id.clone())).await.unwrap();
+            player_manager.teleport_player(&player.id, [40.0, 80.0, -12.0]).await.unwrap();
+            let inventory_system = handler.inventory_system.read().await;
+            inventory_system.add_item(player_manager.get_inventory_mut(&player.id).unwrap(), 1, 5, None).unwrap();
+            player.id
+        };
+
+        // Alive players can't skip back to spawn
+        assert!(handler.handle_respawn(&player_id).await.is_err());
+
+        handler.player_manager.write().await.update_player_health(&player_id, 0.0).await.unwrap();
+        let messages = handler.handle_respawn(&player_id).await.unwrap();
+
+        match &messages[0] {
+            ServerMessage::PlayerUpdate { position, .. } => assert_eq!(*position, [3.5, 90.0, -7.5]),
+            other => panic!("Expected a PlayerUpdate, got {:?}", other),
+        }
+        let player = handler.player_manager.read().await.get_player(&player_id).await.unwrap();
+        assert_eq!(player.health, player.max_health);
+        assert_eq!(player.position, [3.5, 90.0, -7.5]);
+        assert!(player.inventory.items.iter().all(|item| item.is_none()));
+        assert_eq!(player.statistics.deaths, 1);
+    }
+
+    #[tokio::test]
+    async fn killing_a_mob_counts_toward_the_players_statistics() {
+        let handler = test_handler().await;
+
+        let world = {
+            let mut world_manager = handler.world_manager.write().await;
+            let mut chunk_manager = handler.chunk_manager.write().await;
+            let mut entity_manager = handler.entity_manager.write().await;
+            world_manager
+                .create_world("arena".to_string(), 1, GameMode::Survival, settings(), 10, &mut chunk_manager, &mut entity_manager)
+                .await
+                .unwrap()
+        };
+        let player_id = {
+            let mut player_manager = handler.player_manager.write().await;
+            let player = player_manager.register_player("hunter", "correct horse battery").await.unwrap();
+            player_manager.set_player_world(&player.id, Some(world.id.clone())).await.unwrap();
+            player_manager.teleport_player(&player.id, [0.0, 80.0, 0.0]).await.unwrap();
+            player.id
+        };
+        let (zombie, far_cow, item) = {
+            let mut entity_manager = handler.entity_manager.write().await;
+            (
+                entity_manager.spawn_entity(EntityType::Zombie.into(), [1.0, 80.0, 1.0], world.id.clone(), None).await.unwrap(),
+                entity_manager.spawn_entity(EntityType::Cow.into(), [20.0, 80.0, 0.0], world.id.clone(), None).await.unwrap(),
+                entity_manager.spawn_entity(EntityType::Item.into(), [0.0, 80.0, 1.0], world.id.clone(), None).await.unwrap(),
+            )
+        };
+
+        assert!(handler.handle_attack(&player_id, &far_cow).await.is_err());
+        assert!(handler.handle_attack(&player_id, &item).await.is_err());
+
+        // A zombie has 20 health
+        for _ in 0..10 {
+            handler.handle_attack(&player_id, &zombie).await.unwrap();
+        }
+        assert!(!handler.entity_manager.read().await.get_entity(&zombie).await.unwrap().is_active);
+        // The corpse can't be killed twice
+        assert!(handler.handle_attack(&player_id, &zombie).await.is_err());
+
+        let player = handler.player_manager.read().await.get_player(&player_id).await.unwrap();
+        assert_eq!(player.statistics.mobs_killed, 1);
+        assert_eq!(player.experience, MOB_KILL_EXPERIENCE);
+    }
+
+    #[tokio::test]
+    async fn equipping_returns_the_replaced_piece_to_the_inventory() {
+        let handler = test_handler().await;
+        let player_id = {
+            let mut player_manager = handler.player_manager.write().await;
+            let player = player_manager.register_player("player", "correct horse battery").await.unwrap();
+            let inventory = player_manager.get_inventory_mut(&player.id).unwrap();
+            // 311 is the diamond chestplate
+            inventory.armor[1] = Some(InventoryItem { id: 311, count: 1, metadata: Some(serde_json::json!({ "durability": 40 })), slot: 1 });
+            inventory.items[4] = Some(InventoryItem { id: 311, count: 1, metadata: None, slot: 4 });
+            player.id
+        };
+
+        let messages = handler
+            .handle_inventory_action(&player_id, InventoryAction::Equip { armor_slot: 1, item_id: 311 })
+            .await
+            .unwrap();
+        let inventory = match &messages[0] {
+            ServerMessage::InventoryUpdate(inventory) => inventory.clone(),
+            other => panic!("Expected an InventoryUpdate, got {:?}", other),
+        };
+        assert_eq!(inventory.armor[1].as_ref().unwrap().metadata, None);
+        let returned: Vec<_> = inventory.items.iter().flatten().collect();
+        assert_eq!(returned.len(), 1);
+        assert_eq!(returned[0].metadata, Some(serde_json::json!({ "durability": 40 })));
+    }
+
+    #[tokio::test]
+    async fn grid_crafting_matches_the_pattern_anywhere() {
+        let handler = test_handler().await;
+        let player_id = {
+            let mut player_manager = handler.player_manager.write().await;
+            let player = player_manager.register_player("player", "correct horse battery").await.unwrap();
+            let inventory = player_manager.get_inventory_mut(&player.id).unwrap();
+            inventory.items[0] = Some(InventoryItem { id: OAK_LOG, count: 1, metadata: None, slot: 0 });
+            player.id
+        };
+
+        let mut grid = [[None; 3]; 3];
+        grid[2][2] = Some(OAK_LOG);
+        let messages = handler.handle_inventory_action(&player_id, InventoryAction::CraftGrid { grid }).await.unwrap();
+
+        let inventory = match &messages[..] {
+            [ServerMessage::InventoryUpdate(inventory)] => inventory.clone(),
+            other => panic!("Expected only an InventoryUpdate, got {:?}", other),
+        };
+        let items: Vec<_> = inventory.items.iter().flatten().map(|item| (item.id, item.count)).collect();
+        assert_eq!(items, vec![(OAK_PLANKS, 4)]);
+    }
+
+    #[tokio::test]
+    async fn smelting_awards_the_recipe_experience() {
+        let handler = test_handler().await;
+        let player_id = {
+            let mut player_manager = handler.player_manager.write().await;
+            let player = player_manager.register_player("player", "correct horse battery").await.unwrap();
+            let inventory = player_manager.get_inventory_mut(&player.id).unwrap();
+            inventory.items[0] = Some(InventoryItem { id: GOLD_ORE, count: 3, metadata: None, slot: 0 });
+            inventory.items[1] = Some(InventoryItem { id: COAL, count: 1, metadata: None, slot: 1 });
+            player.id
+        };
+
+        let messages = handler
+            .handle_inventory_action(&player_id, InventoryAction::Smelt { item_id: GOLD_ORE, count: 3 })
+            .await
+            .unwrap();
+
+        let inventory = match &messages[..] {
+            [ServerMessage::InventoryUpdate(inventory)] => inventory.clone(),
+            other => panic!("Expected only an InventoryUpdate, got {:?}", other),
+        };
+        let items: Vec<_> = inventory.items.iter().flatten().map(|item| (item.id, item.count)).collect();
+        assert_eq!(items, vec![(GOLD_INGOT, 3)]);
+        let player = handler.player_manager.read().await.get_player(&player_id).await.unwrap();
+        assert_eq!(player.experience, 3);
+    }
+
+    #[tokio::test]
+    async fn recipe_book_needs_a_crafting_table_for_table_recipes() {
+        let handler = test_handler().await;
+        let player_id = {
+            let mut player_manager = handler.player_manager.write().await;
+            let player = player_manager.register_player("player", "correct horse battery").await.unwrap();
+            let inventory = player_manager.get_inventory_mut(&player.id).unwrap();
+            inventory.items[0] = Some(InventoryItem { id: OAK_PLANKS, count: 3, metadata: None, slot: 0 });
+            inventory.items[1] = Some(InventoryItem { id: STICK, count: 2, metadata: None, slot: 1 });
+            player.id
+        };
+        let recipe_ids = |messages: Vec<ServerMessage>| match &messages[..] {
+            [ServerMessage::CraftableRecipes { recipe_ids }] => recipe_ids.clone(),
+            other => panic!("Expected CraftableRecipes, got {:?}", other),
+        };
+
+        let messages = handler.handle_recipe_book(&player_id).await.unwrap();
+        assert!(!recipe_ids(messages).contains(&"wooden_pickaxe".to_string()));
+
+        {
+            let mut player_manager = handler.player_manager.write().await;
+            let inventory = player_manager.get_inventory_mut(&player_id).unwrap();
+            inventory.items[2] = Some(InventoryItem { id: CRAFTING_TABLE, count: 1, metadata: None, slot: 2 });
+        }
+        let messages = handler.handle_recipe_book(&player_id).await.unwrap();
+        assert!(recipe_ids(messages).contains(&"wooden_pickaxe".to_string()));
+    }
+
+    #[tokio::test]
+    async fn whispers_can_be_answered_with_r() {
+        let handler = test_handler().await;
+        let mut ids = Vec::new();
+        {
+            let mut player_manager = handler.player_manager.write().await;
+            for username in ["alice", "bob", "carol"] {
+                let player = player_manager.register_player(username, "correct horse battery").await.unwrap();
+                player_manager.authenticate_player(username, "correct horse battery").await.unwrap().unwrap();
+                player_manager.set_player_world(&player.id, Some("world".to_string())).await.unwrap();
+                ids.push(player.id);
+            }
+        }
+        let whisper_target = |messages: Vec<ServerMessage>| match &messages[..] {
+            [ServerMessage::Chat(message)] => message.target_player.clone(),
+            other => panic!("Expected the whisper back, got {:?}", other),
+        };
+
+        let sent = handler.handle_chat(&ids[0], "/msg bob meet at spawn", None).await.unwrap();
+        assert_eq!(whisper_target(sent), Some(ids[1].clone()));
+
+        let replied = handler.handle_chat(&ids[1], "/r on my way", None).await.unwrap();
+        assert_eq!(whisper_target(replied), Some(ids[0].clone()));
+
+        let nobody = handler.handle_chat(&ids[2], "/r hello?", None).await;
+        assert_eq!(nobody.unwrap_err().to_string(), "Nobody has whispered you yet");
+        let offline = handler.handle_chat(&ids[2], "/msg dave hello?", None).await;
+        assert_eq!(offline.unwrap_err().to_string(), "dave is not online");
+    }
+
+    #[tokio::test]
+    async fn private_channels_are_invite_only_and_hidden_from_history() {
+        let handler = test_handler().await;
+        let mut ids = Vec::new();
+        {
+            let mut player_manager = handler.player_manager.write().await;
+            for username in ["alice", "bob"] {
+                let player = player_manager.register_player(username, "correct horse battery").await.unwrap();
+                player_manager.set_player_world(&player.id, Some("world".to_string())).await.unwrap();
+                ids.push(player.id);
+            }
+        }
+        {
+            let mut chat_system = handler.chat_system.write().await;
+            chat_system.set_rate_limit(0);
+            chat_system.create_channel("staff".to_string(), "Staff".to_string(), String::new(), false, true, ids[0].clone()).unwrap();
+        }
+        let history = |messages: Vec<ServerMessage>| match &messages[..] {
+            [ServerMessage::ChatHistory { messages }] => messages.iter().map(|message| message.content.clone()).collect::<Vec<_>>(),
+            other => panic!("Expected ChatHistory, got {:?}", other),
+        };
+
+        let denied = handler.handle_chat(&ids[1], "/channel join staff", None).await;
+        assert_eq!(denied.unwrap_err().to_string(), "That channel is invite only");
+        handler.handle_chat(&ids[1], "/channel join local", None).await.unwrap();
+        let listed = handler.handle_chat(&ids[1], "/channel list", None).await.unwrap();
+        assert!(matches!(&listed[..], [ServerMessage::CommandResult { message, .. }] if message == "Joined: local. Open: global"));
+
+        handler.handle_chat(&ids[0], "staff only", Some("staff".to_string())).await.unwrap();
+        handler.handle_chat(&ids[0], "hello all", None).await.unwrap();
+        assert_eq!(history(handler.handle_chat_history(&ids[0], None).await.unwrap()), vec!["staff only", "hello all"]);
+        assert_eq!(history(handler.handle_chat_history(&ids[1], None).await.unwrap()), vec!["hello all"]);
+    }
+
+    #[tokio::test]
+    async fn deleting_a_missing_world_reports_not_found() {
+        let handler = test_handler().await;
+        assert!(!handler.delete_world("missing").await.unwrap());
+    }
+}
\ No newline at end of file
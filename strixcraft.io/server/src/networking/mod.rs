@@ -0,0 +1,3 @@
+pub mod websocket_handler;
+pub mod message_handler;
+pub mod protocol;
\ No newline at end of file
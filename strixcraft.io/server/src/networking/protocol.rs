@@ -0,0 +1,474 @@
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::entities::{item::Inventory, mob::Entity};
+use crate::systems::{
+    chat_system::ChatMessage,
+    chunk_manager::{Chunk, ChunkDelta},
+    trade_system::{Trade, TradeStack},
+    weather_system::Weather,
+};
+
+// Bump whenever a message layout changes so stale clients are rejected instead of misread
+pub const PROTOCOL_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Move {
+        position: [f64; 3],
+        rotation: [f64; 3],
+    },
+    PlaceBlock {
+        x: i32,
+        y: i32,
+        z: i32,
+        block_id: u8,
+    },
+    BreakBlock {
+        x: i32,
+        y: i32,
+        z: i32,
+    },
+    Chat {
+        content: String,
+        channel_id: Option<String>,
+    },
+    Inventory(InventoryAction),
+    Trade(TradeAction),
+    ChunkRequest {
+        x: i32,
+        z: i32,
+    },
+    Ping,
+    CompleteCommand {
+        partial: String,
+    },
+    // Sent when the player starts hitting a block, the BreakBlock that follows is timed from it
+    StartBreaking {
+        x: i32,
+        y: i32,
+        z: i32,
+    },
+    JoinWorld {
+        world_id: String,
+    },
+    // Only accepted while the player is dead
+    Respawn,
+    Attack {
+        entity_id: String,
+    },
+    // Asks which recipes the player's inventory can make right now
+    RecipeBook,
+    // Recent chat in the player's world, None covers every channel they can read
+    ChatHistory {
+        channel_id: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum InventoryAction {
+    SelectSlot { slot: usize },
+    Move { from_slot: usize, to_slot: usize },
+    Split { slot: usize, amount: u32, target: Option<usize> }, // No target picks the first empty slot
+    SplitHalf { slot: usize }, // Right-click split into the first empty slot
+    Drop { slot: usize, count: Option<u32> },
+    Sort { preserve_hotbar: bool },
+    // Shift-click between the player's inventory and the container block at x, y, z
+    Transfer { x: i32, y: i32, z: i32, slot: usize, to_container: bool },
+    Equip { armor_slot: usize, item_id: u32 },
+    Unequip { armor_slot: usize },
+    Craft { recipe_id: String, times: u32 },
+    // Crafts once from whatever recipe the laid out pattern matches
+    CraftGrid { grid: [[Option<u32>; 3]; 3] },
+    Smelt { item_id: u32, count: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TradeAction {
+    Propose {
+        recipient: String, // Username
+        offered: Vec<TradeStack>,
+        requested: Vec<TradeStack>,
+    },
+    Accept { trade_id: String },
+    Cancel { trade_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerMessage {
+    PlayerUpdate {
+        player_id: String,
+        position: [f64; 3],
+        rotation: [f64; 3],
+    },
+    BlockUpdate {
+        x: i32,
+        y: i32,
+        z: i32,
+        block_id: u8,
+    },
+    Chat(ChatMessage),
+    InventoryUpdate(Inventory),
+    ContainerUpdate {
+        x: i32,
+        y: i32,
+        z: i32,
+        inventory: Inventory,
+    },
+    ChunkData {
+        world_id: String,
+        chunk: Chunk,
+    },
+    ChunkDelta(ChunkDelta),
+    EntitySpawn(Entity),
+    EntityMove {
+        entity_id: String,
+        position: [f64; 3],
+        rotation: [f64; 3],
+    },
+    EntityDespawn {
+        entity_id: String,
+    },
+    WeatherChange {
+        world_id: String,
+        weather: Weather,
+    },
+    CommandResult {
+        success: bool,
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+    Pong,
+    CommandSuggestions {
+        suggestions: Vec<String>,
+    },
+    // Last message on a socket, the server closes it right after
+    Kicked {
+        reason: String,
+    },
+    // Sent to both sides when a trade is proposed
+    TradeOffer(Trade),
+    TradeClosed {
+        trade_id: String,
+        completed: bool, // False if it was cancelled
+    },
+    CraftableRecipes {
+        recipe_ids: Vec<String>,
+    },
+    ChatHistory {
+        messages: Vec<ChatMessage>, // Oldest first
+    },
+}
+
+// For serde_json::Value fields. bincode can't deserialize a Value, so binary formats
+// carry it as a JSON string while JSON storage keeps it inline
+pub mod json_field {
+    use serde_json::Value;
+
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return value.serialize(serializer);
+        }
+
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        if deserializer.is_human_readable() {
+            return Value::deserialize(deserializer);
+        }
+
+        let json = String::deserialize(deserializer)?;
+        serde_json::from_str(&json).map_err(serde::de::Error::custom)
+    }
+
+    // Same for Option<Value>, None keeps its plain encoding so stored chunks still load
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(value: &Option<Value>, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                return value.serialize(serializer);
+            }
+
+            value.as_ref().map(|v| v.to_string()).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+            if deserializer.is_human_readable() {
+                return Option::<Value>::deserialize(deserializer);
+            }
+
+            Option::<String>::deserialize(deserializer)?
+                .map(|json| serde_json::from_str(&json).map_err(serde::de::Error::custom))
+                .transpose()
+        }
+    }
+}
+
+// Frame layout: [version: u8][bincode payload]
+#[derive(Debug)]
+pub struct Protocol {
+    version: u8,
+}
+
+impl Protocol {
+    pub fn new() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+        }
+    }
+
+    pub fn decode_client(&self, frame: &[u8]) -> Result<ClientMessage, String> {
+        self.decode(frame)
+    }
+
+    pub fn encode_server(&self, message: &ServerMessage) -> Result<Vec<u8>, String> {
+        self.encode(message)
+    }
+
+    // The client's half of the codec, only the tests speak it from this side
+    #[cfg(test)]
+    pub fn encode_client(&self, message: &ClientMessage) -> Result<Vec<u8>, String> {
+        self.encode(message)
+    }
+
+    #[cfg(test)]
+    pub fn decode_server(&self, frame: &[u8]) -> Result<ServerMessage, String> {
+        self.decode(frame)
+    }
+
+    fn encode<T: Serialize>(&self, message: &T) -> Result<Vec<u8>, String> {
+        let payload = bincode::serialize(message)
+            .map_err(|e| format!("Failed to encode message: {}", e))?;
+
+        let mut frame = Vec::with_capacity(payload.len() + 1);
+        frame.push(self.version);
+        frame.extend_from_slice(&payload);
+        Ok(frame)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, frame: &[u8]) -> Result<T, String> {
+        let (&version, payload) = frame.split_first().ok_or("Empty frame")?;
+
+        if version != self.version {
+            return Err(format!(
+                "Protocol version mismatch: expected {}, got {}",
+                self.version, version
+            ));
+        }
+
+        bincode::deserialize(payload).map_err(|e| format!("Failed to decode message: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::systems::{
+        chat_system::MessageType,
+        entity_manager::{EntityKind, EntityType},
+        inventory_system::{InventoryItem, InventorySystem},
+        status_effect_system::{EffectKind, StatusEffect},
+    };
+
+    // Decoding and re-encoding must give back the exact frame, so no field is lost on the way
+    fn round_trip_client(message: ClientMessage) -> ClientMessage {
+        let protocol = Protocol::new();
+        let frame = protocol.encode_client(&message).unwrap();
+        let decoded = protocol.decode_client(&frame).unwrap();
+        assert_eq!(protocol.encode_client(&decoded).unwrap(), frame, "{:?}", message);
+        decoded
+    }
+
+    fn round_trip_server(message: ServerMessage) -> ServerMessage {
+        let protocol = Protocol::new();
+        let frame = protocol.encode_server(&message).unwrap();
+        let decoded = protocol.decode_server(&frame).unwrap();
+        assert_eq!(protocol.encode_server(&decoded).unwrap(), frame, "{:?}", message);
+        decoded
+    }
+
+    fn entity(entity_type: EntityKind) -> Entity {
+        Entity {
+            id: "entity-1".to_string(),
+            entity_type,
+            position: [1.5, 64.0, -2.5],
+            rotation: [0.0, 90.0, 0.0],
+            velocity: [0.0, -0.08, 0.0],
+            health: 20.0,
+            max_health: 20.0,
+            metadata: serde_json::json!({ "item_id": 264, "count": 3 }),
+            world_id: "world".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            effects: vec![StatusEffect {
+                kind: EffectKind::Poison,
+                amplifier: 1,
+                expires_at: Utc::now(),
+            }],
+        }
+    }
+
+    #[test]
+    fn every_client_message_round_trips() {
+        let messages = vec![
+            ClientMessage::Move { position: [1.0, 2.0, 3.0], rotation: [0.5, 0.0, -0.5] },
+            ClientMessage::PlaceBlock { x: -1, y: 64, z: 17, block_id: 4 },
+            ClientMessage::BreakBlock { x: 3, y: 0, z: -30 },
+            ClientMessage::Chat { content: "hello".to_string(), channel_id: Some("trade".to_string()) },
+            ClientMessage::Chat { content: "hi".to_string(), channel_id: None },
+            ClientMessage::ChatHistory { channel_id: Some("trade".to_string()) },
+            ClientMessage::RecipeBook,
+            ClientMessage::Inventory(InventoryAction::SelectSlot { slot: 2 }),
+            ClientMessage::Inventory(InventoryAction::Move { from_slot: 0, to_slot: 35 }),
+            ClientMessage::Inventory(InventoryAction::Split { slot: 4, amount: 16, target: Some(9) }),
+            ClientMessage::Inventory(InventoryAction::Split { slot: 4, amount: 1, target: None }),
+            ClientMessage::Inventory(InventoryAction::SplitHalf { slot: 4 }),
+            ClientMessage::Inventory(InventoryAction::Drop { slot: 1, count: Some(3) }),
+            ClientMessage::Inventory(InventoryAction::Drop { slot: 1, count: None }),
+            ClientMessage::Inventory(InventoryAction::Sort { preserve_hotbar: true }),
+            ClientMessage::Inventory(InventoryAction::Transfer { x: -3, y: 70, z: 4, slot: 5, to_container: false }),
+            ClientMessage::Inventory(InventoryAction::Equip { armor_slot: 1, item_id: 311 }),
+            ClientMessage::Inventory(InventoryAction::Unequip { armor_slot: 3 }),
+            ClientMessage::Inventory(InventoryAction::Craft { recipe_id: "stick".to_string(), times: 2 }),
+            ClientMessage::Inventory(InventoryAction::CraftGrid { grid: [[None, Some(5), None], [None; 3], [None; 3]] }),
+            ClientMessage::Inventory(InventoryAction::Smelt { item_id: 15, count: 8 }),
+            ClientMessage::Trade(TradeAction::Propose {
+                recipient: "bob".to_string(),
+                offered: vec![TradeStack { item_id: 264, count: 3 }],
+                requested: Vec::new(),
+            }),
+            ClientMessage::Trade(TradeAction::Accept { trade_id: "trade-1".to_string() }),
+            ClientMessage::Trade(TradeAction::Cancel { trade_id: "trade-1".to_string() }),
+            ClientMessage::ChunkRequest { x: -4, z: 9 },
+            ClientMessage::Ping,
+            ClientMessage::CompleteCommand { partial: "/te".to_string() },
+            ClientMessage::StartBreaking { x: 5, y: 70, z: 5 },
+            ClientMessage::JoinWorld { world_id: "nether".to_string() },
+            ClientMessage::Respawn,
+            ClientMessage::Attack { entity_id: "zombie_1".to_string() },
+        ];
+
+        for message in messages {
+            let decoded = round_trip_client(message.clone());
+            assert_eq!(std::mem::discriminant(&decoded), std::mem::discriminant(&message));
+        }
+    }
+
+    #[test]
+    fn join_world_keeps_its_world_id() {
+        match round_trip_client(ClientMessage::JoinWorld { world_id: "my-world_2".to_string() }) {
+            ClientMessage::JoinWorld { world_id } => assert_eq!(world_id, "my-world_2"),
+            other => panic!("Decoded as {:?}", other),
+        }
+    }
+
+    #[test]
+    fn every_server_message_round_trips() {
+        let mut inventory = InventorySystem::create_inventory(36, 9);
+        inventory.items[0] = Some(InventoryItem {
+            id: 278,
+            count: 1,
+            metadata: Some(serde_json::json!({ "durability": 1200 })),
+            slot: 0,
+        });
+        inventory.items[1] = Some(InventoryItem { id: 1, count: 64, metadata: None, slot: 1 });
+
+        let mut blocks = vec![0u8; 16 * 16 * 256];
+        blocks[0] = 7;
+        blocks[70 * 256 + 3] = 1;
+        let metadata = vec![0u8; blocks.len()];
+        let light = vec![15u8; blocks.len()];
+        let chunk = Chunk::from_dense(2, -3, &blocks, &metadata, &light, vec![70; 256]);
+
+        let messages = vec![
+            ServerMessage::PlayerUpdate {
+                player_id: "player-1".to_string(),
+                position: [0.0, 65.0, 0.0],
+                rotation: [0.0, 180.0, 0.0],
+            },
+            ServerMessage::BlockUpdate { x: 1, y: 2, z: 3, block_id: 9 },
+            ServerMessage::Chat(ChatMessage {
+                id: "msg-1".to_string(),
+                sender: "alice".to_string(),
+                content: "psst".to_string(),
+                message_type: MessageType::Whisper,
+                timestamp: Utc::now(),
+                world_id: Some("world".to_string()),
+                target_player: Some("bob".to_string()),
+                channel_id: None,
+            }),
+            ServerMessage::InventoryUpdate(inventory.clone()),
+            ServerMessage::ContainerUpdate { x: -3, y: 70, z: 4, inventory },
+            ServerMessage::ChunkData { world_id: "world".to_string(), chunk },
+            ServerMessage::ChunkDelta(ChunkDelta {
+                world_id: "world".to_string(),
+                chunk: (-1, 0),
+                changes: vec![(0, 1), (4095, 0)],
+            }),
+            ServerMessage::EntitySpawn(entity(EntityType::Zombie.into())),
+            ServerMessage::EntitySpawn(entity(EntityKind::Custom("golem".to_string()))),
+            ServerMessage::EntityMove {
+                entity_id: "entity-1".to_string(),
+                position: [1.0, 64.0, 1.0],
+                rotation: [0.0, 45.0, 0.0],
+            },
+            ServerMessage::EntityDespawn { entity_id: "entity-1".to_string() },
+            ServerMessage::WeatherChange { world_id: "world".to_string(), weather: Weather::Thunder },
+            ServerMessage::CommandResult { success: true, message: "Done".to_string() },
+            ServerMessage::ChatHistory { messages: Vec::new() },
+            ServerMessage::CraftableRecipes { recipe_ids: vec!["stick".to_string()] },
+            ServerMessage::Error { message: "Nope".to_string() },
+            ServerMessage::Pong,
+            ServerMessage::CommandSuggestions { suggestions: vec!["/tp".to_string(), "/time".to_string()] },
+            ServerMessage::Kicked { reason: "Banned".to_string() },
+            ServerMessage::TradeOffer(Trade {
+                id: "trade-1".to_string(),
+                proposer: "player-1".to_string(),
+                recipient: "player-2".to_string(),
+                offered: vec![TradeStack { item_id: 264, count: 3 }],
+                requested: vec![TradeStack { item_id: 1, count: 64 }],
+                offered_value: 300,
+                requested_value: 64,
+                created_at: Utc::now(),
+            }),
+            ServerMessage::TradeClosed { trade_id: "trade-1".to_string(), completed: true },
+        ];
+
+        for message in messages {
+            let decoded = round_trip_server(message.clone());
+            assert_eq!(std::mem::discriminant(&decoded), std::mem::discriminant(&message));
+        }
+    }
+
+    #[test]
+    fn entity_kind_and_metadata_survive_the_binary_frame() {
+        for kind in [EntityKind::from(EntityType::Creeper), EntityKind::Custom("golem".to_string())] {
+            match round_trip_server(ServerMessage::EntitySpawn(entity(kind.clone()))) {
+                ServerMessage::EntitySpawn(decoded) => {
+                    assert_eq!(decoded.entity_type, kind);
+                    assert_eq!(decoded.metadata["item_id"], 264);
+                }
+                other => panic!("Decoded as {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn entity_json_storage_format_is_unchanged() {
+        let json = serde_json::to_value(entity(EntityType::Zombie.into())).unwrap();
+        assert_eq!(json["entity_type"], "Zombie");
+        assert_eq!(json["metadata"]["count"], 3);
+
+        let custom = serde_json::to_value(entity(EntityKind::Custom("golem".to_string()))).unwrap();
+        assert_eq!(custom["entity_type"], "golem");
+        let restored: Entity = serde_json::from_value(custom).unwrap();
+        assert_eq!(restored.entity_type, EntityKind::Custom("golem".to_string()));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,238 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use actix::{Actor, ActorContext, ActorFutureExt, AsyncContext, Handler, Message, StreamHandler, WrapFuture};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use serde::Deserialize;
+use uuid::Uuid;
+use log::{info, warn, error};
+
+use crate::networking::{
+    message_handler::MessageHandler,
+    protocol::{Protocol, ServerMessage},
+};
+
+// Pushed to a session from anywhere on the server, e.g. world broadcasts
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Outbound(pub ServerMessage);
+
+#[derive(Debug, Deserialize)]
+struct ConnectQuery {
+    token: String,
+}
+
+#[derive(Debug)]
+pub struct WebSocketHandler {
+    message_handler: Arc<MessageHandler>,
+    protocol: Arc<Protocol>,
+    heartbeat_interval: Duration,
+    client_timeout: Duration,
+}
+
+impl WebSocketHandler {
+    pub fn new(
+        message_handler: Arc<MessageHandler>,
+        protocol: Arc<Protocol>,
+        heartbeat_interval: Duration,
+        client_timeout: Duration,
+    ) -> Self {
+        Self {
+            message_handler,
+            protocol,
+            heartbeat_interval,
+            client_timeout,
+        }
+    }
+
+    pub async fn handle(
+        &self,
+        req: HttpRequest,
+        stream: web::Payload,
+    ) -> Result<HttpResponse, actix_web::Error> {
+        let query = web::Query::<ConnectQuery>::from_query(req.query_string())
+            .map_err(|_| actix_web::error::ErrorUnauthorized("Missing token"))?;
+
+        // Authenticate before upgrading so a bad token never gets a socket
+        let session_id = Uuid::new_v4().to_string();
+        let player = self
+            .message_handler
+            .authenticate(&session_id, &query.token)
+            .await
+            .map_err(|e| actix_web::error::ErrorUnauthorized(e.to_string()))?;
+
+        let session = GameSession {
+            id: session_id.clone(),
+            player_id: player.id,
+            message_handler: self.message_handler.clone(),
+            protocol: self.protocol.clone(),
+            heartbeat_interval: self.heartbeat_interval,
+            client_timeout: self.client_timeout,
+            last_heartbeat: Instant::now(),
+            last_ping_sent: None,
+        };
+
+        let response = ws::start(session, &req, stream);
+        if response.is_err() {
+            // The upgrade failed, so the actor never started and won't clean up after itself
+            if let Err(e) = self.message_handler.disconnect_session(&session_id).await {
+                error!("Failed to release session {}: {}", session_id, e);
+            }
+        }
+        response
+    }
+}
+
+struct GameSession {
+    id: String,
+    player_id: String,
+    message_handler: Arc<MessageHandler>,
+    protocol: Arc<Protocol>,
+    heartbeat_interval: Duration,
+    client_timeout: Duration,
+    last_heartbeat: Instant, // Last time anything arrived from the client
+    last_ping_sent: Option<Instant>,
+}
+
+impl GameSession {
+    fn send(&self, ctx: &mut ws::WebsocketContext<Self>, message: &ServerMessage) {
+        match self.protocol.encode_server(message) {
+            Ok(frame) => ctx.binary(frame),
+            Err(e) => error!("Failed to encode message for session {}: {}", self.id, e),
+        }
+    }
+
+    fn dispatch(&mut self, frame: &[u8], ctx: &mut ws::WebsocketContext<Self>) {
+        self.last_heartbeat = Instant::now();
+
+        let message = match self.protocol.decode_client(frame) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Bad frame from session {}: {}", self.id, e);
+                self.send(ctx, &ServerMessage::Error { message: e });
+                return;
+            }
+        };
+
+        // wait rather than spawn, so frames are handled one at a time in the order they arrived
+        let message_handler = self.message_handler.clone();
+        let session_id = self.id.clone();
+        ctx.wait(
+            async move { message_handler.handle_message(&session_id, message).await }
+                .into_actor(self)
+                .map(|responses, act, ctx| {
+                    for response in &responses {
+                        act.send(ctx, response);
+                    }
+                }),
+        );
+    }
+
+    // A client that vanishes without closing the socket is dropped once it stops answering,
+    // stopping the actor runs the usual disconnect path
+    fn start_heartbeat(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        ctx.run_interval(self.heartbeat_interval, |act, ctx| {
+            if Instant::now().duration_since(act.last_heartbeat) > act.client_timeout {
+                warn!("Session {} timed out", act.id);
+                ctx.stop();
+                return;
+            }
+            act.last_ping_sent = Some(Instant::now());
+            ctx.ping(b"");
+        });
+    }
+
+    fn record_pong(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        self.last_heartbeat = Instant::now();
+
+        let sent = match self.last_ping_sent.take() {
+            Some(sent) => sent,
+            None => return,
+        };
+        let latency_ms = sent.elapsed().as_millis() as u64;
+
+        let message_handler = self.message_handler.clone();
+        let session_id = self.id.clone();
+        ctx.spawn(
+            async move { message_handler.record_latency(&session_id, latency_ms).await }
+                .into_actor(self),
+        );
+    }
+}
+
+impl Actor for GameSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.start_heartbeat(ctx);
+
+        let message_handler = self.message_handler.clone();
+        let session_id = self.id.clone();
+        let player_id = self.player_id.clone();
+        let recipient = ctx.address().recipient();
+        ctx.spawn(
+            async move { message_handler.register_session(&session_id, &player_id, recipient).await }
+                .into_actor(self),
+        );
+
+        info!("Session {} opened for player {}", self.id, self.player_id);
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        let message_handler = self.message_handler.clone();
+        let session_id = self.id.clone();
+        actix::spawn(async move {
+            if let Err(e) = message_handler.disconnect_session(&session_id).await {
+                error!("Failed to disconnect session {}: {}", session_id, e);
+            }
+        });
+
+        info!("Session {} closed", self.id);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for GameSession {
+    fn handle(&mut self, message: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Protocol error on session {}: {}", self.id, e);
+                ctx.stop();
+                return;
+            }
+        };
+
+        match message {
+            ws::Message::Binary(frame) => self.dispatch(&frame, ctx),
+            ws::Message::Ping(bytes) => {
+                self.last_heartbeat = Instant::now();
+                ctx.pong(&bytes);
+            }
+            ws::Message::Pong(_) => self.record_pong(ctx),
+            ws::Message::Text(_) => self.send(ctx, &ServerMessage::Error {
+                message: "Only binary frames are supported".to_string(),
+            }),
+            ws::Message::Close(reason) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Handler<Outbound> for GameSession {
+    type Result = ();
+
+    fn handle(&mut self, message: Outbound, ctx: &mut Self::Context) {
+        self.send(ctx, &message.0);
+
+        if let ServerMessage::Kicked { reason } = message.0 {
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Policy,
+                description: Some(reason),
+            }));
+            ctx.stop();
+        }
+    }
+}
\ No newline at end of file
@@ -1,8 +1,15 @@
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use log::{info, warn, error};
+use log::info;
+
+pub const DEFAULT_RATE_LIMIT_SECONDS: i64 = 1; // Minimum gap between a player's messages
+pub const DEFAULT_MAX_MESSAGE_LENGTH: usize = 256; // In characters, not bytes
+pub const DEFAULT_DUPLICATE_HISTORY: usize = 5; // Recent lines per player checked for repeats
+pub const DEFAULT_DUPLICATE_WINDOW_SECONDS: i64 = 30;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -13,9 +20,10 @@ pub struct ChatMessage {
     pub timestamp: DateTime<Utc>,
     pub world_id: Option<String>,
     pub target_player: Option<String>,
+    pub channel_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MessageType {
     Chat,
     System,
@@ -25,6 +33,12 @@ pub enum MessageType {
     Team,
 }
 
+#[derive(Debug, Clone)]
+pub enum ChatDispatch {
+    Message(ChatMessage),
+    Command { name: String, args: Vec<String> },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatChannel {
     pub id: String,
@@ -41,9 +55,17 @@ pub struct ChatSystem {
     messages: Vec<ChatMessage>,
     channels: HashMap<String, ChatChannel>,
     max_messages: usize,
+    max_message_length: usize,
     profanity_filter: bool,
+    profane_words: HashSet<String>,
     rate_limiting: HashMap<String, DateTime<Utc>>,
+    rate_limit_seconds: i64,
+    rate_limit_overrides: HashMap<MessageType, i64>,
+    recent_hashes: HashMap<String, VecDeque<(u64, DateTime<Utc>)>>,
+    duplicate_history: usize,
+    duplicate_window_seconds: i64,
     muted_players: HashMap<String, DateTime<Utc>>,
+    last_whisper_from: HashMap<String, String>, // recipient -> sender
 }
 
 impl ChatSystem {
@@ -52,15 +74,50 @@ impl ChatSystem {
             messages: Vec::new(),
             channels: HashMap::new(),
             max_messages: 1000,
+            max_message_length: DEFAULT_MAX_MESSAGE_LENGTH,
             profanity_filter: true,
+            profane_words: HashSet::new(),
             rate_limiting: HashMap::new(),
+            rate_limit_seconds: DEFAULT_RATE_LIMIT_SECONDS,
+            rate_limit_overrides: HashMap::from([(MessageType::System, 0)]), // Server broadcasts are never throttled
+            recent_hashes: HashMap::new(),
+            duplicate_history: DEFAULT_DUPLICATE_HISTORY,
+            duplicate_window_seconds: DEFAULT_DUPLICATE_WINDOW_SECONDS,
             muted_players: HashMap::new(),
+            last_whisper_from: HashMap::new(),
         };
         
         system.initialize_default_channels();
+        system.load_profanity_list(vec![
+            "badword1".to_string(),
+            "badword2".to_string(),
+            "badword3".to_string(), // Add actual profane words here
+        ]);
         system
     }
 
+    pub fn process_input(
+        &mut self,
+        sender: &str,
+        content: &str,
+        world_id: Option<String>,
+        channel_id: Option<String>,
+    ) -> Result<ChatDispatch, String> {
+        // Hand commands back to the caller instead of storing them as chat
+        if let Some(command) = content.strip_prefix('/') {
+            let mut parts = command.split_whitespace();
+            if let Some(name) = parts.next() {
+                return Ok(ChatDispatch::Command {
+                    name: name.to_string(),
+                    args: parts.map(|arg| arg.to_string()).collect(),
+                });
+            }
+        }
+
+        self.send_message(sender, content, MessageType::Chat, world_id, None, channel_id)
+            .map(ChatDispatch::Message)
+    }
+
     pub fn send_message(
         &mut self,
         sender: &str,
@@ -68,17 +125,40 @@ impl ChatSystem {
         message_type: MessageType,
         world_id: Option<String>,
         target_player: Option<String>,
+        channel_id: Option<String>,
     ) -> Result<ChatMessage, String> {
-        // Check if player is muted
-        if self.is_player_muted(sender) {
+        // Check if player is muted, the server itself can't be
+        if message_type != MessageType::System && self.is_player_muted(sender) {
             return Err("You are currently muted".to_string());
         }
 
+        // Count characters rather than bytes; system broadcasts are exempt
+        let length = content.chars().count();
+        if message_type != MessageType::System && length > self.max_message_length {
+            return Err(format!(
+                "Message is too long ({} characters, maximum is {})",
+                length, self.max_message_length
+            ));
+        }
+
+        if let Some(channel_id) = &channel_id {
+            let channel = self.channels.get(channel_id).ok_or("Channel not found")?;
+            if channel.is_private && !channel.members.iter().any(|m| m == sender) {
+                return Err("You are not a member of this channel".to_string());
+            }
+        }
+
         // Rate limiting
-        if !self.check_rate_limit(sender) {
+        if !self.check_rate_limit(sender, &message_type) {
             return Err("You are sending messages too quickly".to_string());
         }
 
+        // Duplicate detection
+        let content_hash = Self::hash_content(content);
+        if message_type != MessageType::System && self.is_duplicate(sender, content_hash) {
+            return Err("Duplicate message".to_string());
+        }
+
         // Profanity filter
         let filtered_content = if self.profanity_filter {
             self.filter_profanity(content)
@@ -94,6 +174,7 @@ impl ChatSystem {
             timestamp: Utc::now(),
             world_id,
             target_player,
+            channel_id,
         };
 
         // Add to message history
@@ -107,27 +188,74 @@ impl ChatSystem {
         // Update rate limiting
         self.rate_limiting.insert(sender.to_string(), Utc::now());
 
-        info!("Chat message from {}: {}", sender, filtered_content);
+        if message_type != MessageType::System {
+            self.record_message_hash(sender, content_hash);
+        }
+
+        info!("Chat message from {}: {}", sender, message.content);
         
         Ok(message)
     }
 
+    pub fn set_rate_limit(&mut self, seconds: i64) {
+        self.rate_limit_seconds = seconds;
+    }
+
+    pub fn set_message_type_rate_limit(&mut self, message_type: MessageType, seconds: i64) {
+        self.rate_limit_overrides.insert(message_type, seconds);
+    }
+
+    pub fn set_max_message_length(&mut self, max_length: usize) {
+        self.max_message_length = max_length;
+    }
+
+    pub fn set_duplicate_filter(&mut self, history: usize, window_seconds: i64) {
+        self.duplicate_history = history;
+        self.duplicate_window_seconds = window_seconds;
+    }
+
+    pub fn load_profanity_list(&mut self, words: Vec<String>) {
+        self.profane_words = words
+            .into_iter()
+            .map(|word| word.trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        info!("Loaded {} profanity filter words", self.profane_words.len());
+    }
+
+    pub fn load_profanity_file(&mut self, path: &str) -> Result<(), String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read profanity list {}: {}", path, e))?;
+
+        // One word per line, blank lines and # comments ignored
+        let words = contents
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .map(|line| line.to_string())
+            .collect();
+
+        self.load_profanity_list(words);
+        Ok(())
+    }
+
     pub fn get_recent_messages(
         &self,
         count: usize,
         world_id: Option<&str>,
         channel_id: Option<&str>,
+        requester: &str,
     ) -> Vec<ChatMessage> {
         self.messages
             .iter()
             .rev()
             .filter(|msg| {
-                let world_match = world_id.map_or(true, |id| msg.world_id.as_deref() == Some(id));
-                let channel_match = channel_id.map_or(true, |_| {
-                    // Channel filtering logic would go here
-                    true
-                });
-                world_match && channel_match
+                let world_match = world_id.is_none_or(|id| msg.world_id.as_deref() == Some(id));
+                let channel_match = channel_id.is_none_or(|id| msg.channel_id.as_deref() == Some(id));
+                world_match
+                    && channel_match
+                    && self.can_read_channel(msg.channel_id.as_deref(), requester)
+                    && Self::can_read_whisper(msg, requester)
             })
             .take(count)
             .cloned()
@@ -159,7 +287,7 @@ impl ChatSystem {
 
         self.channels.insert(id.clone(), channel.clone());
         
-        info!("Created chat channel: {}", name);
+        info!("Created chat channel: {}", channel.name);
         
         Ok(channel)
     }
@@ -207,6 +335,21 @@ impl ChatSystem {
         }
     }
 
+    // Whispers stay in the shared history but only their two ends ever see them
+    fn can_read_whisper(message: &ChatMessage, player: &str) -> bool {
+        message.message_type != MessageType::Whisper
+            || message.sender == player
+            || message.target_player.as_deref() == Some(player)
+    }
+
+    fn can_read_channel(&self, channel_id: Option<&str>, player: &str) -> bool {
+        // Private channel history is only visible to its members
+        match channel_id.and_then(|id| self.channels.get(id)) {
+            Some(channel) if channel.is_private => channel.members.iter().any(|m| m == player),
+            _ => true,
+        }
+    }
+
     pub fn get_channel(&self, channel_id: &str) -> Option<&ChatChannel> {
         self.channels.get(channel_id)
     }
@@ -226,14 +369,15 @@ impl ChatSystem {
         &mut self,
         content: &str,
         world_id: Option<String>,
-    ) -> ChatMessage {
+    ) -> Result<ChatMessage, String> {
         self.send_message(
             "SYSTEM",
             content,
             MessageType::System,
             world_id,
             None,
-        ).unwrap()
+            None,
+        )
     }
 
     pub fn send_whisper(
@@ -242,13 +386,29 @@ impl ChatSystem {
         target: &str,
         content: &str,
     ) -> Result<ChatMessage, String> {
-        self.send_message(
+        let message = self.send_message(
             sender,
             content,
             MessageType::Whisper,
             None,
             Some(target.to_string()),
-        )
+            None,
+        )?;
+
+        // Remember who to answer for /r
+        self.last_whisper_from.insert(target.to_string(), sender.to_string());
+
+        Ok(message)
+    }
+
+    pub fn reply_whisper(&mut self, sender: &str, content: &str) -> Result<ChatMessage, String> {
+        let target = self
+            .last_whisper_from
+            .get(sender)
+            .cloned()
+            .ok_or("Nobody has whispered you yet")?;
+
+        self.send_whisper(sender, &target, content)
     }
 
     pub fn get_chat_stats(&self) -> ChatStats {
@@ -269,30 +429,82 @@ impl ChatSystem {
         }
     }
 
-    fn check_rate_limit(&self, player: &str) -> bool {
+    fn check_rate_limit(&self, player: &str, message_type: &MessageType) -> bool {
+        let limit = self
+            .rate_limit_overrides
+            .get(message_type)
+            .copied()
+            .unwrap_or(self.rate_limit_seconds);
+
+        // A limit of 0 or less disables throttling
+        if limit <= 0 {
+            return true;
+        }
+
         if let Some(last_message) = self.rate_limiting.get(player) {
             let time_since = Utc::now().signed_duration_since(*last_message);
-            time_since.num_seconds() >= 1 // 1 second between messages
+            time_since.num_seconds() >= limit
         } else {
             true
         }
     }
 
+    fn hash_content(content: &str) -> u64 {
+        // Normalize so "HELLO" and "hello " count as the same line
+        let mut hasher = DefaultHasher::new();
+        content.trim().to_lowercase().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn is_duplicate(&self, player: &str, content_hash: u64) -> bool {
+        let cutoff = Utc::now() - chrono::Duration::seconds(self.duplicate_window_seconds);
+
+        self.recent_hashes.get(player).is_some_and(|hashes| {
+            hashes
+                .iter()
+                .any(|(hash, sent_at)| *hash == content_hash && *sent_at > cutoff)
+        })
+    }
+
+    fn record_message_hash(&mut self, player: &str, content_hash: u64) {
+        if self.duplicate_history == 0 {
+            return;
+        }
+
+        let hashes = self.recent_hashes.entry(player.to_string()).or_default();
+        hashes.push_back((content_hash, Utc::now()));
+        while hashes.len() > self.duplicate_history {
+            hashes.pop_front();
+        }
+    }
+
     fn filter_profanity(&self, content: &str) -> String {
-        // Simple profanity filter - in a real implementation, this would be more sophisticated
-        let mut filtered = content.to_lowercase();
-        
-        let profane_words = vec![
-            "badword1", "badword2", "badword3", // Add actual profane words here
-        ];
-        
-        for word in profane_words {
-            filtered = filtered.replace(word, &"*".repeat(word.len()));
+        let mut filtered = String::with_capacity(content.len());
+        let mut word = String::new();
+
+        // Only censor whole words so e.g. "classic" survives a filter on "ass"
+        for ch in content.chars() {
+            if ch.is_alphanumeric() {
+                word.push(ch);
+            } else {
+                self.push_filtered_word(&mut filtered, &word);
+                word.clear();
+                filtered.push(ch);
+            }
         }
-        
+        self.push_filtered_word(&mut filtered, &word);
+
         filtered
     }
 
+    fn push_filtered_word(&self, filtered: &mut String, word: &str) {
+        if self.profane_words.contains(&word.to_lowercase()) {
+            filtered.push_str(&"*".repeat(word.chars().count()));
+        } else {
+            filtered.push_str(word);
+        }
+    }
+
     fn initialize_default_channels(&mut self) {
         // Global channel
         self.create_channel(
@@ -324,4 +536,70 @@ pub struct ChatStats {
     pub total_channels: usize,
     pub muted_players: usize,
     pub message_type_counts: HashMap<MessageType, usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_messages_are_not_throttled_by_default() {
+        let mut chat = ChatSystem::new();
+        for i in 0..5 {
+            let content = format!("Restarting in {} minutes", 5 - i);
+            assert!(chat.send_message("SYSTEM", &content, MessageType::System, None, None, None).is_ok());
+        }
+    }
+
+    #[test]
+    fn system_messages_ignore_mutes() {
+        let mut chat = ChatSystem::new();
+        chat.mute_player("SYSTEM", 10);
+        assert!(chat.broadcast_system_message("Server restarting", None).is_ok());
+    }
+
+    #[test]
+    fn zero_message_type_rate_limit_disables_throttling() {
+        let mut chat = ChatSystem::new();
+        assert!(chat.send_message("alice", "one", MessageType::Chat, None, None, None).is_ok());
+        assert!(chat.send_message("alice", "two", MessageType::Chat, None, None, None).is_err());
+
+        chat.set_message_type_rate_limit(MessageType::Chat, 0);
+        for content in ["three", "four", "five"] {
+            assert!(chat.send_message("alice", content, MessageType::Chat, None, None, None).is_ok());
+        }
+    }
+
+    #[test]
+    fn zero_default_rate_limit_disables_throttling() {
+        let mut chat = ChatSystem::new();
+        chat.set_rate_limit(0);
+        for content in ["one", "two", "three"] {
+            assert!(chat.send_message("alice", content, MessageType::Chat, None, None, None).is_ok());
+        }
+        assert!(chat.send_message("alice", "four", MessageType::Whisper, None, Some("bob".to_string()), None).is_ok());
+    }
+
+    #[test]
+    fn override_applies_only_to_its_message_type() {
+        let mut chat = ChatSystem::new();
+        chat.set_message_type_rate_limit(MessageType::Whisper, 0);
+        assert!(chat.send_message("alice", "hi", MessageType::Whisper, None, Some("bob".to_string()), None).is_ok());
+        assert!(chat.send_message("alice", "hello", MessageType::Chat, None, None, None).is_err());
+    }
+
+    #[test]
+    fn whispers_are_only_in_their_participants_history() {
+        let mut chat = ChatSystem::new();
+        chat.send_whisper("alice", "bob", "meet at spawn").unwrap();
+
+        let sees_whisper = |chat: &ChatSystem, player: &str| {
+            chat.get_recent_messages(10, None, None, player)
+                .iter()
+                .any(|m| m.message_type == MessageType::Whisper)
+        };
+        assert!(sees_whisper(&chat, "alice"));
+        assert!(sees_whisper(&chat, "bob"));
+        assert!(!sees_whisper(&chat, "carol"));
+    }
 }
\ No newline at end of file
@@ -1,53 +1,509 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
 use log::{info, warn, error};
 
-use crate::worlds::terrain_generator::TerrainGenerator;
+use crate::worlds::{
+    terrain_generator::{TerrainGenerator, TerrainParams},
+    biome_system::{Biome, BiomeSystem},
+    structure_generator::StructureGenerator,
+    block_registry::{BlockRegistry, AIR, BEDROCK, DIRT, GRASS, SAND, SNOW, STONE},
+};
+use crate::systems::inventory_system::{Inventory, InventoryItem, InventorySystem};
+
+const CHUNK_HEIGHT: i32 = 256;
+const SECTION_HEIGHT: usize = 16;
+const SECTION_VOLUME: usize = 16 * 16 * SECTION_HEIGHT;
+const SECTIONS_PER_CHUNK: usize = CHUNK_HEIGHT as usize / SECTION_HEIGHT;
+const STALE_CHUNK_SECONDS: u64 = 300; // 5 minutes
+const MAX_LIGHT: u8 = 15;
+const FULL_SKY: u8 = MAX_LIGHT << 4; // Packed light of open air: full sky light, no block light
+const CONTAINER_SLOTS: usize = 27; // A single chest
+const NEIGHBOR_OFFSETS: [(i32, i32, i32); 6] = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+
+// Each light byte packs sky light in the high nibble and block light in the low one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LightChannel {
+    Sky,
+    Block,
+}
+
+impl LightChannel {
+    fn read(self, packed: u8) -> u8 {
+        match self {
+            LightChannel::Sky => packed >> 4,
+            LightChannel::Block => packed & 0x0F,
+        }
+    }
+
+    fn write(self, packed: u8, level: u8) -> u8 {
+        match self {
+            LightChannel::Sky => (packed & 0x0F) | (level << 4),
+            LightChannel::Block => (packed & 0xF0) | (level & 0x0F),
+        }
+    }
+
+    // Level a transparent neighbor receives. Full sky light falls straight down through air without fading
+    fn next_level(self, level: u8, dy: i32, block_id: u8) -> u8 {
+        if self == LightChannel::Sky && dy == -1 && level == MAX_LIGHT && block_id == AIR {
+            MAX_LIGHT
+        } else {
+            level.saturating_sub(1)
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chunk {
     pub x: i32,
     pub z: i32,
-    pub blocks: Vec<u8>,
-    pub metadata: Vec<u8>,
-    pub light: Vec<u8>,
-    pub height_map: Vec<u8>,
+    // Bottom to top, None for sections that are entirely air
+    pub sections: Vec<Option<ChunkSection>>,
+    pub height_map: Vec<u16>,
+    pub containers: Vec<BlockContainer>,
     pub is_generated: bool,
     pub is_modified: bool,
+    #[serde(skip, default = "std::time::Instant::now")]
     pub last_accessed: std::time::Instant,
+    #[serde(skip, default = "std::time::Instant::now")]
+    pub last_modified: std::time::Instant,
+    // Block changes not yet sent to clients, as (block index, block id)
+    #[serde(skip)]
+    pub dirty_blocks: Vec<(u16, u8)>,
+}
+
+// Items held by a block such as a chest, in world coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockContainer {
+    pub position: [i32; 3],
+    pub items: Vec<InventoryItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkDelta {
+    pub world_id: String,
+    pub chunk: (i32, i32),
+    pub changes: Vec<(u16, u8)>,
+}
+
+// Distinct block ids plus bit-packed indices into them, so a chunk of mostly
+// air costs a few bytes instead of one byte per block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PalettedBlocks {
+    palette: Vec<u8>,
+    bits_per_entry: u32,
+    data: Vec<u64>,
+    len: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkSection {
+    pub blocks: PalettedBlocks,
+    pub metadata: Vec<u8>,
+    pub light: Vec<u8>,
+}
+
+impl ChunkSection {
+    pub fn new() -> Self {
+        Self {
+            blocks: PalettedBlocks::new(SECTION_VOLUME, 0),
+            metadata: vec![0; SECTION_VOLUME],
+            light: vec![FULL_SKY; SECTION_VOLUME], // Same as the open air it replaces
+        }
+    }
+
+    // The palette never shrinks, so a stale non-air entry means scanning the blocks themselves
+    fn is_air(&self) -> bool {
+        self.blocks.palette.iter().all(|&id| id == 0) || (0..self.blocks.len).all(|index| self.blocks.get(index) == 0)
+    }
+
+    // Indistinguishable from the None a fresh chunk would have here
+    fn is_empty(&self) -> bool {
+        self.is_air() && self.metadata.iter().all(|&m| m == 0) && self.light.iter().all(|&l| l == FULL_SKY)
+    }
+}
+
+impl PalettedBlocks {
+    pub fn new(len: usize, block_id: u8) -> Self {
+        Self {
+            palette: vec![block_id],
+            bits_per_entry: 0,
+            data: Vec::new(),
+            len,
+        }
+    }
+
+    pub fn from_dense(blocks: &[u8]) -> Self {
+        let mut palette: Vec<u8> = Vec::new();
+        for &block_id in blocks {
+            if !palette.contains(&block_id) {
+                palette.push(block_id);
+            }
+        }
+        if palette.is_empty() {
+            palette.push(0);
+        }
+
+        let mut packed = Self {
+            bits_per_entry: Self::bits_for(palette.len()),
+            palette,
+            data: Vec::new(),
+            len: blocks.len(),
+        };
+        packed.data = vec![0; packed.words_for(packed.bits_per_entry)];
+
+        for (index, block_id) in blocks.iter().enumerate() {
+            let palette_index = packed.palette.iter().position(|id| id == block_id).unwrap_or(0);
+            packed.write_index(index, palette_index as u64);
+        }
+
+        packed
+    }
+
+    #[cfg(test)]
+    pub fn to_dense(&self) -> Vec<u8> {
+        (0..self.len).map(|index| self.get(index)).collect()
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        self.palette[self.read_index(index) as usize]
+    }
+
+    pub fn set(&mut self, index: usize, block_id: u8) {
+        let palette_index = match self.palette.iter().position(|&id| id == block_id) {
+            Some(palette_index) => palette_index,
+            None => {
+                self.palette.push(block_id);
+
+                // Repack with wider entries once the palette outgrows them
+                let bits = Self::bits_for(self.palette.len());
+                if bits != self.bits_per_entry {
+                    self.resize(bits);
+                }
+                self.palette.len() - 1
+            }
+        };
+
+        self.write_index(index, palette_index as u64);
+    }
+
+    fn bits_for(palette_len: usize) -> u32 {
+        if palette_len <= 1 {
+            0
+        } else {
+            usize::BITS - (palette_len - 1).leading_zeros()
+        }
+    }
+
+    fn words_for(&self, bits: u32) -> usize {
+        if bits == 0 {
+            return 0;
+        }
+        let per_word = (64 / bits) as usize;
+        self.len.div_ceil(per_word)
+    }
+
+    fn read_index(&self, index: usize) -> u64 {
+        if self.bits_per_entry == 0 {
+            return 0;
+        }
+
+        // Entries never straddle two words
+        let per_word = (64 / self.bits_per_entry) as usize;
+        let shift = (index % per_word) as u32 * self.bits_per_entry;
+        let mask = (1u64 << self.bits_per_entry) - 1;
+        (self.data[index / per_word] >> shift) & mask
+    }
+
+    fn write_index(&mut self, index: usize, value: u64) {
+        if self.bits_per_entry == 0 {
+            return;
+        }
+
+        let per_word = (64 / self.bits_per_entry) as usize;
+        let shift = (index % per_word) as u32 * self.bits_per_entry;
+        let mask = (1u64 << self.bits_per_entry) - 1;
+        let word = &mut self.data[index / per_word];
+        *word = (*word & !(mask << shift)) | ((value & mask) << shift);
+    }
+
+    fn resize(&mut self, bits: u32) {
+        let indices: Vec<u64> = (0..self.len).map(|index| self.read_index(index)).collect();
+
+        self.bits_per_entry = bits;
+        self.data = vec![0; self.words_for(bits)];
+
+        for (index, value) in indices.into_iter().enumerate() {
+            self.write_index(index, value);
+        }
+    }
+}
+
+impl Chunk {
+    pub fn from_dense(
+        x: i32,
+        z: i32,
+        blocks: &[u8],
+        metadata: &[u8],
+        light: &[u8],
+        height_map: Vec<u16>,
+    ) -> Self {
+        let sections = (0..SECTIONS_PER_CHUNK)
+            .map(|section_y| {
+                let range = section_y * SECTION_VOLUME..(section_y + 1) * SECTION_VOLUME;
+                let section = ChunkSection {
+                    blocks: PalettedBlocks::from_dense(&blocks[range.clone()]),
+                    metadata: metadata[range.clone()].to_vec(),
+                    light: light[range].to_vec(),
+                };
+                // Dark air (e.g. a large cave) still needs a section to hold its light
+                if section.is_air() && section.light.iter().all(|&l| l == FULL_SKY) { None } else { Some(section) }
+            })
+            .collect();
+
+        Self {
+            x,
+            z,
+            sections,
+            height_map,
+            containers: Vec::new(),
+            is_generated: true,
+            is_modified: false,
+            last_accessed: std::time::Instant::now(),
+            last_modified: std::time::Instant::now(),
+            dirty_blocks: Vec::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn to_dense(&self) -> Vec<u8> {
+        let mut blocks = Vec::with_capacity(SECTIONS_PER_CHUNK * SECTION_VOLUME);
+        for section in &self.sections {
+            match section {
+                Some(section) => blocks.extend(section.blocks.to_dense()),
+                None => blocks.extend(std::iter::repeat_n(0, SECTION_VOLUME)),
+            }
+        }
+        blocks
+    }
+
+    // Local coordinates: x and z in 0..16, y in 0..256
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> u8 {
+        match &self.sections[y / SECTION_HEIGHT] {
+            Some(section) => section.blocks.get(Self::section_index(x, y, z)),
+            None => 0,
+        }
+    }
+
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block_id: u8) {
+        let slot = &mut self.sections[y / SECTION_HEIGHT];
+
+        // Placing air into an empty section changes nothing
+        if slot.is_none() && block_id == 0 {
+            return;
+        }
+
+        let section = slot.get_or_insert_with(ChunkSection::new);
+        section.blocks.set(Self::section_index(x, y, z), block_id);
+
+        if block_id == 0 && section.is_empty() {
+            *slot = None;
+        }
+    }
+
+    // Drops sections that went back to plain open air, e.g. once relighting after a removal settles
+    pub fn compact_sections(&mut self) {
+        for slot in self.sections.iter_mut() {
+            if slot.as_ref().is_some_and(|section| section.is_empty()) {
+                *slot = None;
+            }
+        }
+    }
+
+    pub fn get_metadata(&self, x: usize, y: usize, z: usize) -> u8 {
+        match &self.sections[y / SECTION_HEIGHT] {
+            Some(section) => section.metadata[Self::section_index(x, y, z)],
+            None => 0,
+        }
+    }
+
+    pub fn set_metadata(&mut self, x: usize, y: usize, z: usize, value: u8) {
+        let slot = &mut self.sections[y / SECTION_HEIGHT];
+
+        if slot.is_none() && value == 0 {
+            return;
+        }
+
+        let section = slot.get_or_insert_with(ChunkSection::new);
+        section.metadata[Self::section_index(x, y, z)] = value;
+    }
+
+    // Brightest of sky and block light, with the sky dimmed by night or storms
+    pub fn get_light(&self, x: usize, y: usize, z: usize, sky_darkening: u8) -> u8 {
+        let sky = self.get_channel_light(x, y, z, LightChannel::Sky).saturating_sub(sky_darkening);
+        let block = self.get_channel_light(x, y, z, LightChannel::Block);
+        sky.max(block)
+    }
+
+    fn get_channel_light(&self, x: usize, y: usize, z: usize, channel: LightChannel) -> u8 {
+        match &self.sections[y / SECTION_HEIGHT] {
+            Some(section) => channel.read(section.light[Self::section_index(x, y, z)]),
+            None => channel.read(FULL_SKY), // Empty sections are open sky
+        }
+    }
+
+    fn set_channel_light(&mut self, x: usize, y: usize, z: usize, channel: LightChannel, level: u8) {
+        let slot = &mut self.sections[y / SECTION_HEIGHT];
+
+        if slot.is_none() && channel.read(FULL_SKY) == level {
+            return;
+        }
+
+        let section = slot.get_or_insert_with(ChunkSection::new);
+        let index = Self::section_index(x, y, z);
+        section.light[index] = channel.write(section.light[index], level);
+    }
+
+    // Lights a freshly generated chunk on its own, light from neighbors is stitched in once it's loaded
+    pub fn compute_light(&mut self, block_registry: &BlockRegistry) {
+        let top = self.height_map.iter().copied().max().unwrap_or(0) as usize;
+        let mut sky_queue = VecDeque::new();
+        let mut block_queue = VecDeque::new();
+
+        for x in 0..16 {
+            for z in 0..16 {
+                let mut sky = MAX_LIGHT;
+                for y in (0..CHUNK_HEIGHT as usize).rev() {
+                    let block_id = self.get_block(x, y, z);
+                    if !block_registry.is_transparent(block_id) {
+                        sky = 0;
+                    } else if block_id != AIR {
+                        sky = sky.saturating_sub(1);
+                    }
+                    self.set_channel_light(x, y, z, LightChannel::Sky, sky);
+                    self.set_channel_light(x, y, z, LightChannel::Block, 0);
+
+                    // Above the tallest column every cell is already full, nothing to spread
+                    if sky > 1 && y <= top + 1 {
+                        sky_queue.push_back((x, y, z));
+                    }
+
+                    let emission = block_registry.light_emission(block_id);
+                    if emission > 0 {
+                        self.set_channel_light(x, y, z, LightChannel::Block, emission);
+                        block_queue.push_back((x, y, z));
+                    }
+                }
+            }
+        }
+
+        self.spread_light(LightChannel::Sky, sky_queue, block_registry);
+        self.spread_light(LightChannel::Block, block_queue, block_registry);
+    }
+
+    fn spread_light(&mut self, channel: LightChannel, mut queue: VecDeque<(usize, usize, usize)>, block_registry: &BlockRegistry) {
+        while let Some((x, y, z)) = queue.pop_front() {
+            let level = self.get_channel_light(x, y, z, channel);
+            if level <= 1 {
+                continue;
+            }
+
+            for (dx, dy, dz) in NEIGHBOR_OFFSETS {
+                let (nx, ny, nz) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+                if !(0..16).contains(&nx) || !(0..16).contains(&nz) || !(0..CHUNK_HEIGHT).contains(&ny) {
+                    continue;
+                }
+                let (nx, ny, nz) = (nx as usize, ny as usize, nz as usize);
+
+                let block_id = self.get_block(nx, ny, nz);
+                if !block_registry.is_transparent(block_id) {
+                    continue;
+                }
+                let next = channel.next_level(level, dy, block_id);
+                if self.get_channel_light(nx, ny, nz, channel) < next {
+                    self.set_channel_light(nx, ny, nz, channel, next);
+                    queue.push_back((nx, ny, nz));
+                }
+            }
+        }
+    }
+
+    fn section_index(x: usize, y: usize, z: usize) -> usize {
+        (y % SECTION_HEIGHT) * 16 * 16 + z * 16 + x
+    }
+}
+
+type ChunkKey = (String, i32, i32); // world_id, chunk x, chunk z
+
+// A modified chunk written next to its file, moved into place once the rest of its world's save commits
+#[derive(Debug)]
+pub struct StagedChunk {
+    key: ChunkKey,
+    staged_path: PathBuf,
+    staged_at: std::time::Instant,
 }
 
 #[derive(Debug)]
 pub struct ChunkManager {
-    chunks: HashMap<(i32, i32), Chunk>,
+    chunks: HashMap<ChunkKey, Chunk>,
     load_distance: i32,
-    terrain_generator: Arc<TerrainGenerator>,
+    terrain_params: TerrainParams,
+    terrain_generators: HashMap<String, Arc<TerrainGenerator>>, // world_id -> generator seeded for that world
+    biome_system: Arc<BiomeSystem>,
+    structure_generator: Arc<StructureGenerator>,
+    block_registry: Arc<BlockRegistry>,
     max_cached_chunks: usize,
+    storage_path: PathBuf,
 }
 
 impl ChunkManager {
-    pub fn new(load_distance: i32, terrain_generator: Arc<TerrainGenerator>) -> Self {
+    pub fn new(
+        load_distance: i32,
+        terrain_params: TerrainParams,
+        biome_system: Arc<BiomeSystem>,
+        structure_generator: Arc<StructureGenerator>,
+        block_registry: Arc<BlockRegistry>,
+        storage_path: PathBuf,
+    ) -> Self {
         Self {
             chunks: HashMap::new(),
             load_distance,
-            terrain_generator,
+            terrain_params,
+            terrain_generators: HashMap::new(),
+            biome_system,
+            structure_generator,
+            block_registry,
             max_cached_chunks: 1000, // Adjust based on memory constraints
+            storage_path,
         }
     }
 
-    pub async fn get_chunk(&mut self, x: i32, z: i32) -> Option<Chunk> {
-        let key = (x, z);
+    // Worlds must be registered before any of their chunks can be generated
+    pub fn register_world(&mut self, world_id: &str, seed: i64) {
+        if self.terrain_generators.get(world_id).is_some_and(|g| g.seed() == seed) {
+            return;
+        }
+        let generator = TerrainGenerator::new(seed, self.terrain_params.clone());
+        self.terrain_generators.insert(world_id.to_string(), Arc::new(generator));
+    }
+
+    pub fn unregister_world(&mut self, world_id: &str) {
+        self.terrain_generators.remove(world_id);
+    }
+
+    pub async fn get_chunk(&mut self, world_id: &str, x: i32, z: i32) -> Option<Chunk> {
+        let key = (world_id.to_string(), x, z);
         
         if let Some(chunk) = self.chunks.get_mut(&key) {
             chunk.last_accessed = std::time::Instant::now();
             return Some(chunk.clone());
         }
 
-        // Generate new chunk if not found
-        let chunk = self.generate_chunk(x, z).await?;
-        self.chunks.insert(key, chunk.clone());
+        let chunk = self.load_or_generate_chunk(&key).await?;
+        self.chunks.insert(key.clone(), chunk);
+        self.stitch_light(world_id, x, z);
+        let chunk = self.chunks.get(&key)?.clone();
         
         // Clean up old chunks if we exceed the limit
         self.cleanup_old_chunks().await;
@@ -55,64 +511,354 @@ impl ChunkManager {
         Some(chunk)
     }
 
-    pub async fn get_chunks_in_radius(&mut self, center_x: i32, center_z: i32) -> Vec<Chunk> {
-        let mut chunks = Vec::new();
+    // Warm the cache around a point so the first player in doesn't pay for generation
+    pub async fn preload_area(&mut self, world_id: &str, center_x: i32, center_z: i32, radius: i32) -> usize {
+        let mut loaded = 0;
         
-        for x in (center_x - self.load_distance)..=(center_x + self.load_distance) {
-            for z in (center_z - self.load_distance)..=(center_z + self.load_distance) {
-                if let Some(chunk) = self.get_chunk(x, z).await {
-                    chunks.push(chunk);
+        for x in (center_x - radius)..=(center_x + radius) {
+            for z in (center_z - radius)..=(center_z + radius) {
+                let key = (world_id.to_string(), x, z);
+                if self.chunks.contains_key(&key) {
+                    continue;
+                }
+                if self.chunks.len() >= self.max_cached_chunks {
+                    warn!("Chunk cache full, stopped preloading world {} after {} chunks", world_id, loaded);
+                    return loaded;
+                }
+                
+                if let Some(chunk) = self.load_or_generate_chunk(&key).await {
+                    self.chunks.insert(key, chunk);
+                    self.stitch_light(world_id, x, z);
+                    loaded += 1;
                 }
             }
         }
         
-        chunks
+        info!("Preloaded {} chunks for world {}", loaded, world_id);
+        loaded
     }
 
-    pub async fn set_block(&mut self, x: i32, y: i32, z: i32, block_id: u8) -> Result<(), Box<dyn std::error::Error>> {
+    async fn load_or_generate_chunk(&self, key: &ChunkKey) -> Option<Chunk> {
+        let (world_id, x, z) = (key.0.as_str(), key.1, key.2);
+        
+        // Load saved edits first, only generate chunks that were never saved
+        match self.load_chunk_from_storage(key).await {
+            Ok(Some(chunk)) => Some(chunk),
+            Ok(None) => self.generate_chunk(world_id, x, z).await,
+            Err(e) => {
+                error!("Failed to load chunk ({}, {}), regenerating: {}", x, z, e);
+                self.generate_chunk(world_id, x, z).await
+            }
+        }
+    }
+
+    pub fn load_distance(&self) -> i32 {
+        self.load_distance
+    }
+
+    // Drains pending block changes, one delta per touched chunk
+    pub fn take_chunk_deltas(&mut self) -> Vec<ChunkDelta> {
+        self.chunks
+            .iter_mut()
+            .filter(|(_, chunk)| !chunk.dirty_blocks.is_empty())
+            .map(|((world_id, x, z), chunk)| ChunkDelta {
+                world_id: world_id.clone(),
+                chunk: (*x, *z),
+                changes: std::mem::take(&mut chunk.dirty_blocks),
+            })
+            .collect()
+    }
+
+    // Read-only lookup of a cached chunk, safe under a shared lock
+    pub fn peek_chunk(&self, world_id: &str, x: i32, z: i32) -> Option<Chunk> {
+        self.chunks.get(&(world_id.to_string(), x, z)).cloned()
+    }
+
+    // Returns the world positions of the adjacent blocks that should react to the change
+    pub async fn set_block(&mut self, world_id: &str, x: i32, y: i32, z: i32, block_id: u8) -> Result<Vec<(i32, i32, i32)>, Box<dyn std::error::Error>> {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return Err(format!("Y coordinate {} out of range", y).into());
+        }
+        
+        // Arithmetic shift and mask floor towards negative infinity, so x = -1 lands in chunk -1 at local 15
         let chunk_x = x >> 4; // Divide by 16
         let chunk_z = z >> 4;
         let local_x = x & 15; // Modulo 16
         let local_z = z & 15;
         
-        let key = (chunk_x, chunk_z);
+        let key = (world_id.to_string(), chunk_x, chunk_z);
         
-        if let Some(chunk) = self.chunks.get_mut(&key) {
-            let index = (y as usize * 16 * 16) + (local_z as usize * 16) + local_x as usize;
-            if index < chunk.blocks.len() {
-                chunk.blocks[index] = block_id;
-                chunk.is_modified = true;
-                chunk.last_accessed = std::time::Instant::now();
+        let chunk = match self.chunks.get_mut(&key) {
+            Some(chunk) => chunk,
+            None => return Ok(Vec::new()),
+        };
+        chunk.set_block(local_x as usize, y as usize, local_z as usize, block_id);
+        chunk.dirty_blocks.push(((y * 16 * 16 + local_z * 16 + local_x) as u16, block_id));
+        chunk.is_modified = true;
+        chunk.last_accessed = std::time::Instant::now();
+        chunk.last_modified = chunk.last_accessed;
+        
+        self.update_light(world_id, x, y, z);
+
+        if block_id == AIR {
+            if let Some(chunk) = self.chunks.get_mut(&key) {
+                chunk.compact_sections();
             }
         }
         
-        Ok(())
+        Ok(Self::neighbor_positions(x, y, z))
+    }
+
+    // Relights around a changed block: clears the light that depended on the old block,
+    // then floods back in from the surviving sources and from the new block itself
+    fn update_light(&mut self, world_id: &str, x: i32, y: i32, z: i32) {
+        let block_id = match self.block_id_at(world_id, x, y, z) {
+            Some(block_id) => block_id,
+            None => return,
+        };
+
+        for channel in [LightChannel::Block, LightChannel::Sky] {
+            let old_level = self.light_at(world_id, x, y, z, channel).unwrap_or(0);
+            self.set_light_at(world_id, x, y, z, channel, 0);
+            let mut sources = self.remove_light(world_id, (x, y, z), old_level, channel);
+
+            if channel == LightChannel::Block {
+                let emission = self.block_registry.light_emission(block_id);
+                if emission > 0 {
+                    self.set_light_at(world_id, x, y, z, channel, emission);
+                    sources.push_back((x, y, z));
+                }
+            }
+            if channel == LightChannel::Sky && y == CHUNK_HEIGHT - 1 && self.block_registry.is_transparent(block_id) {
+                self.set_light_at(world_id, x, y, z, channel, MAX_LIGHT);
+                sources.push_back((x, y, z));
+            }
+
+            // Neighbors relight the changed cell if it became transparent
+            for (dx, dy, dz) in NEIGHBOR_OFFSETS {
+                sources.push_back((x + dx, y + dy, z + dz));
+            }
+
+            self.propagate_light(world_id, channel, sources);
+        }
+    }
+
+    // Darkens everything lit only through the start cell, returns the brighter cells
+    // found at the edge so propagate_light can fill the hole back in
+    fn remove_light(
+        &mut self,
+        world_id: &str,
+        start: (i32, i32, i32),
+        start_level: u8,
+        channel: LightChannel,
+    ) -> VecDeque<(i32, i32, i32)> {
+        let mut queue = VecDeque::from([(start, start_level)]);
+        let mut sources = VecDeque::new();
+
+        while let Some(((x, y, z), level)) = queue.pop_front() {
+            for (dx, dy, dz) in NEIGHBOR_OFFSETS {
+                let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                let neighbor_level = match self.light_at(world_id, nx, ny, nz, channel) {
+                    Some(neighbor_level) => neighbor_level,
+                    None => continue,
+                };
+
+                let fed_by_sky_column = channel == LightChannel::Sky && dy == -1 && level == MAX_LIGHT;
+                if neighbor_level != 0 && (neighbor_level < level || fed_by_sky_column) {
+                    self.set_light_at(world_id, nx, ny, nz, channel, 0);
+                    queue.push_back(((nx, ny, nz), neighbor_level));
+                } else if neighbor_level >= level {
+                    sources.push_back((nx, ny, nz));
+                }
+            }
+        }
+
+        sources
+    }
+
+    fn propagate_light(&mut self, world_id: &str, channel: LightChannel, mut queue: VecDeque<(i32, i32, i32)>) {
+        while let Some((x, y, z)) = queue.pop_front() {
+            let level = match self.light_at(world_id, x, y, z, channel) {
+                Some(level) if level > 1 => level,
+                _ => continue,
+            };
+
+            for (dx, dy, dz) in NEIGHBOR_OFFSETS {
+                let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                let block_id = match self.block_id_at(world_id, nx, ny, nz) {
+                    Some(block_id) if self.block_registry.is_transparent(block_id) => block_id,
+                    _ => continue, // Opaque, out of range or in an unloaded chunk
+                };
+
+                let next = channel.next_level(level, dy, block_id);
+                if self.light_at(world_id, nx, ny, nz, channel).is_some_and(|current| current < next) {
+                    self.set_light_at(world_id, nx, ny, nz, channel, next);
+                    queue.push_back((nx, ny, nz));
+                }
+            }
+        }
+    }
+
+    // A chunk is lit on its own when generated, so let light flow both ways
+    // across its edges once it sits next to loaded neighbors
+    fn stitch_light(&mut self, world_id: &str, chunk_x: i32, chunk_z: i32) {
+        let (min_x, min_z) = (chunk_x * 16, chunk_z * 16);
+        let mut edges = Vec::new();
+        for i in 0..16 {
+            // Cells just inside this chunk's edges and just outside them in the neighbors
+            edges.extend([(min_x + i, min_z), (min_x + i, min_z - 1), (min_x + i, min_z + 15), (min_x + i, min_z + 16)]);
+            edges.extend([(min_x, min_z + i), (min_x - 1, min_z + i), (min_x + 15, min_z + i), (min_x + 16, min_z + i)]);
+        }
+
+        for channel in [LightChannel::Sky, LightChannel::Block] {
+            let mut queue = VecDeque::new();
+            for &(x, z) in &edges {
+                for y in 0..CHUNK_HEIGHT {
+                    if self.light_at(world_id, x, y, z, channel).is_some_and(|level| level > 1) {
+                        queue.push_back((x, y, z));
+                    }
+                }
+            }
+            self.propagate_light(world_id, channel, queue);
+        }
     }
 
-    pub async fn get_block(&self, x: i32, y: i32, z: i32) -> Option<u8> {
+    fn block_id_at(&self, world_id: &str, x: i32, y: i32, z: i32) -> Option<u8> {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return None;
+        }
+        let chunk = self.chunks.get(&(world_id.to_string(), x >> 4, z >> 4))?;
+        Some(chunk.get_block((x & 15) as usize, y as usize, (z & 15) as usize))
+    }
+
+    fn light_at(&self, world_id: &str, x: i32, y: i32, z: i32, channel: LightChannel) -> Option<u8> {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return None;
+        }
+        let chunk = self.chunks.get(&(world_id.to_string(), x >> 4, z >> 4))?;
+        Some(chunk.get_channel_light((x & 15) as usize, y as usize, (z & 15) as usize, channel))
+    }
+
+    fn set_light_at(&mut self, world_id: &str, x: i32, y: i32, z: i32, channel: LightChannel, level: u8) {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return;
+        }
+        // Light alone doesn't make a chunk worth saving, it's recomputed whenever a chunk loads
+        if let Some(chunk) = self.chunks.get_mut(&(world_id.to_string(), x >> 4, z >> 4)) {
+            chunk.set_channel_light((x & 15) as usize, y as usize, (z & 15) as usize, channel, level);
+        }
+    }
+
+    // World coordinates, so neighbors across a chunk edge fall out naturally
+    fn neighbor_positions(x: i32, y: i32, z: i32) -> Vec<(i32, i32, i32)> {
+        [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)]
+            .iter()
+            .map(|(dx, dy, dz)| (x + dx, y + dy, z + dz))
+            .filter(|(_, ny, _)| (0..CHUNK_HEIGHT).contains(ny))
+            .collect()
+    }
+
+    pub async fn get_block(&self, world_id: &str, x: i32, y: i32, z: i32) -> Option<u8> {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return None;
+        }
+        
         let chunk_x = x >> 4;
         let chunk_z = z >> 4;
         let local_x = x & 15;
         let local_z = z & 15;
         
-        let key = (chunk_x, chunk_z);
+        let key = (world_id.to_string(), chunk_x, chunk_z);
         
-        if let Some(chunk) = self.chunks.get(&key) {
-            let index = (y as usize * 16 * 16) + (local_z as usize * 16) + local_x as usize;
-            if index < chunk.blocks.len() {
-                return Some(chunk.blocks[index]);
-            }
+        self.chunks
+            .get(&key)
+            .map(|chunk| chunk.get_block(local_x as usize, y as usize, local_z as usize))
+    }
+
+    pub async fn set_block_metadata(&mut self, world_id: &str, x: i32, y: i32, z: i32, value: u8) -> Result<(), Box<dyn std::error::Error>> {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return Err(format!("Y coordinate {} out of range", y).into());
+        }
+        
+        let key = (world_id.to_string(), x >> 4, z >> 4);
+        
+        if let Some(chunk) = self.chunks.get_mut(&key) {
+            chunk.set_metadata((x & 15) as usize, y as usize, (z & 15) as usize, value);
+            chunk.is_modified = true;
+            chunk.last_accessed = std::time::Instant::now();
+            chunk.last_modified = chunk.last_accessed;
+        }
+        
+        Ok(())
+    }
+
+    pub async fn get_block_metadata(&self, world_id: &str, x: i32, y: i32, z: i32) -> Option<u8> {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return None;
+        }
+        
+        let key = (world_id.to_string(), x >> 4, z >> 4);
+        
+        self.chunks
+            .get(&key)
+            .map(|chunk| chunk.get_metadata((x & 15) as usize, y as usize, (z & 15) as usize))
+    }
+
+    // A container's items laid out as an Inventory, so InventorySystem can move stacks in and out
+    pub fn get_container(&self, world_id: &str, position: [i32; 3]) -> Option<Inventory> {
+        let key = (world_id.to_string(), position[0] >> 4, position[2] >> 4);
+        let container = self.chunks.get(&key)?.containers.iter().find(|c| c.position == position)?;
+
+        let size = container.items.iter().map(|item| item.slot + 1).max().unwrap_or(0).max(CONTAINER_SLOTS);
+        let mut inventory = InventorySystem::create_inventory(size, 0);
+        for item in &container.items {
+            inventory.items[item.slot] = Some(item.clone());
+        }
+        Some(inventory)
+    }
+
+    pub fn set_container(&mut self, world_id: &str, position: [i32; 3], inventory: &Inventory) -> Result<(), Box<dyn std::error::Error>> {
+        let key = (world_id.to_string(), position[0] >> 4, position[2] >> 4);
+        let chunk = self.chunks.get_mut(&key).ok_or("Chunk not loaded")?;
+        let container = chunk
+            .containers
+            .iter_mut()
+            .find(|c| c.position == position)
+            .ok_or("There is no container there")?;
+
+        container.items = inventory.items.iter().flatten().cloned().collect();
+        chunk.is_modified = true;
+        chunk.last_accessed = std::time::Instant::now();
+        chunk.last_modified = chunk.last_accessed;
+        Ok(())
+    }
+
+    pub async fn get_block_light(&self, world_id: &str, x: i32, y: i32, z: i32, sky_darkening: u8) -> Option<u8> {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return None;
         }
         
-        None
+        let key = (world_id.to_string(), x >> 4, z >> 4);
+        
+        self.chunks
+            .get(&key)
+            .map(|chunk| chunk.get_light((x & 15) as usize, y as usize, (z & 15) as usize, sky_darkening))
     }
 
-    async fn generate_chunk(&self, x: i32, z: i32) -> Option<Chunk> {
+    async fn generate_chunk(&self, world_id: &str, x: i32, z: i32) -> Option<Chunk> {
+        let terrain_generator = match self.terrain_generators.get(world_id) {
+            Some(generator) => generator,
+            None => {
+                warn!("No terrain generator registered for world {}", world_id);
+                return None;
+            }
+        };
+
         let chunk_size = 16 * 16 * 256; // 16x16 chunks, 256 blocks tall
         let mut blocks = vec![0u8; chunk_size];
-        let mut metadata = vec![0u8; chunk_size];
-        let mut light = vec![15u8; chunk_size]; // Full light by default
-        let mut height_map = vec![0u8; 16 * 16];
+        let metadata = vec![0u8; chunk_size];
+        let light = vec![FULL_SKY; chunk_size]; // Replaced by compute_light below
+        let mut height_map = vec![0u16; 16 * 16];
         
         // Generate terrain using the terrain generator
         for local_x in 0..16 {
@@ -121,43 +867,76 @@ impl ChunkManager {
                 let world_z = z * 16 + local_z;
                 
                 // Get height from terrain generator
-                let height = self.terrain_generator.get_height(world_x, world_z).await;
-                height_map[local_z as usize * 16 + local_x as usize] = height as u8;
+                let height = terrain_generator.get_height(world_x, world_z).await;
+                let biome = self.biome_system.get_biome(terrain_generator.seed(), world_x, world_z).await;
+                height_map[local_z as usize * 16 + local_x as usize] = height.clamp(0, u16::MAX as i32) as u16;
                 
-                // Fill blocks from bottom to height
+                // Fill blocks from bottom to height, leaving carved cave blocks as air
                 for y in 0..=height {
                     let index = (y as usize * 16 * 16) + (local_z as usize * 16) + local_x as usize;
-                    if index < blocks.len() {
-                        blocks[index] = self.get_block_type_for_height(y, height);
+                    if index < blocks.len() && !terrain_generator.is_cave(world_x, y, world_z, height) {
+                        blocks[index] = self.get_block_type_for_height(y, height, &biome);
                     }
                 }
             }
         }
         
-        Some(Chunk {
-            x,
-            z,
-            blocks,
-            metadata,
-            light,
-            height_map,
-            is_generated: true,
-            is_modified: false,
-            last_accessed: std::time::Instant::now(),
-        })
+        // Stamp structures after the terrain so they can replace or hollow out ground
+        let mut containers = Vec::new();
+        for structure in self.structure_generator.structures_overlapping(terrain_generator, x, z).await {
+            containers.extend(
+                structure
+                    .containers
+                    .into_iter()
+                    .filter(|([cx, _, cz], _)| cx >> 4 == x && cz >> 4 == z)
+                    .map(|(position, items)| BlockContainer { position, items }),
+            );
+            for ([world_x, y, world_z], block_id) in structure.blocks {
+                if world_x >> 4 != x || world_z >> 4 != z || !(1..CHUNK_HEIGHT).contains(&y) {
+                    continue; // Another chunk's part, or would replace bedrock
+                }
+                let (local_x, local_z) = ((world_x & 15) as usize, (world_z & 15) as usize);
+                blocks[(y as usize * 16 * 16) + (local_z * 16) + local_x] = block_id;
+
+                let column_height = &mut height_map[local_z * 16 + local_x];
+                if block_id != AIR && y as u16 > *column_height {
+                    *column_height = y as u16;
+                }
+            }
+        }
+        
+        let mut chunk = Chunk::from_dense(x, z, &blocks, &metadata, &light, height_map);
+        chunk.containers = containers;
+        chunk.compute_light(&self.block_registry);
+        Some(chunk)
     }
 
-    fn get_block_type_for_height(&self, y: i32, max_height: i32) -> u8 {
+    fn get_block_type_for_height(&self, y: i32, max_height: i32, biome: &Biome) -> u8 {
         if y == 0 {
-            7 // Bedrock
+            BEDROCK
         } else if y < max_height - 4 {
-            1 // Stone
+            STONE
         } else if y < max_height {
-            3 // Dirt
+            Self::get_subsurface_block(biome)
         } else if y == max_height {
-            2 // Grass
+            Self::get_surface_block(biome)
         } else {
-            0 // Air
+            AIR
+        }
+    }
+
+    fn get_surface_block(biome: &Biome) -> u8 {
+        match biome {
+            Biome::Desert => SAND,
+            Biome::Tundra => SNOW,
+            _ => GRASS,
+        }
+    }
+
+    fn get_subsurface_block(biome: &Biome) -> u8 {
+        match biome {
+            Biome::Desert => SAND,
+            _ => DIRT,
         }
     }
 
@@ -169,28 +948,40 @@ impl ChunkManager {
         let mut chunks_to_remove = Vec::new();
         let now = std::time::Instant::now();
         
-        // Find chunks that haven't been accessed recently
+        // Find chunks that haven't been accessed recently. Every edit also touches last_accessed,
+        // so a chunk still being edited is never stale
         for (key, chunk) in &self.chunks {
-            if !chunk.is_modified && now.duration_since(chunk.last_accessed).as_secs() > 300 { // 5 minutes
-                chunks_to_remove.push(*key);
+            if now.duration_since(chunk.last_accessed).as_secs() > STALE_CHUNK_SECONDS {
+                chunks_to_remove.push(key.clone());
             }
         }
         
-        // Remove old chunks
+        // Persist modified chunks before dropping them, keep any that fail to save
+        let mut removed_count = 0;
         for key in chunks_to_remove {
+            if let Some(chunk) = self.chunks.get(&key) {
+                if chunk.is_modified {
+                    if let Err(e) = Self::save_chunk_to_storage(&self.storage_path, &key, chunk).await {
+                        error!("Failed to save chunk ({}, {}) before eviction: {}", key.1, key.2, e);
+                        continue;
+                    }
+                }
+            }
             self.chunks.remove(&key);
+            removed_count += 1;
         }
         
-        info!("Cleaned up {} old chunks", chunks_to_remove.len());
+        info!("Cleaned up {} old chunks", removed_count);
     }
 
-    pub async fn save_modified_chunks(&self) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn save_modified_chunks(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
         let mut saved_count = 0;
         
-        for (key, chunk) in &self.chunks {
+        for (key, chunk) in self.chunks.iter_mut() {
             if chunk.is_modified {
-                // Save chunk to disk/database
-                self.save_chunk_to_storage(*key, chunk).await?;
+                // Save chunk to disk
+                Self::save_chunk_to_storage(&self.storage_path, key, chunk).await?;
+                chunk.is_modified = false;
                 saved_count += 1;
             }
         }
@@ -199,15 +990,199 @@ impl ChunkManager {
             info!("Saved {} modified chunks", saved_count);
         }
         
+        Ok(saved_count)
+    }
+
+    pub fn modified_world_ids(&self) -> HashSet<String> {
+        self.chunks
+            .iter()
+            .filter(|(_, chunk)| chunk.is_modified)
+            .map(|((world_id, _, _), _)| world_id.clone())
+            .collect()
+    }
+
+    // Writes every modified chunk of a world to a staged file without touching the live ones
+    pub async fn stage_world_chunks(&self, world_id: &str) -> Result<Vec<StagedChunk>, Box<dyn std::error::Error>> {
+        let mut staged = Vec::new();
+
+        for (key, chunk) in self.chunks.iter().filter(|(key, chunk)| key.0 == world_id && chunk.is_modified) {
+            let staged_at = std::time::Instant::now();
+            // Stringified so the save task stays Send across the discard below
+            match Self::write_staged_chunk(&self.storage_path, key, chunk).await.map_err(|e| e.to_string()) {
+                Ok(staged_path) => staged.push(StagedChunk { key: key.clone(), staged_path, staged_at }),
+                Err(e) => {
+                    Self::discard_staged_chunks(&staged).await;
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(staged)
+    }
+
+    // Moves staged files into place. A chunk edited after staging stays modified for the next save,
+    // one evicted in between was already written with newer data so its staged file is dropped
+    // Every chunk is attempted even after a failure, failed ones stay modified for the next save
+    pub async fn commit_staged_chunks(&mut self, staged: Vec<StagedChunk>) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut committed = 0;
+        let mut errors = Vec::new();
+
+        for staged_chunk in staged {
+            let chunk = match self.chunks.get_mut(&staged_chunk.key) {
+                Some(chunk) => chunk,
+                None => {
+                    let _ = tokio::fs::remove_file(&staged_chunk.staged_path).await;
+                    continue;
+                }
+            };
+
+            // Staging already checked the world id, this can't fail for a staged chunk
+            let path = match Self::chunk_file_path(&self.storage_path, &staged_chunk.key) {
+                Ok(path) => path,
+                Err(e) => {
+                    let _ = tokio::fs::remove_file(&staged_chunk.staged_path).await;
+                    errors.push(e);
+                    continue;
+                }
+            };
+            if let Err(e) = tokio::fs::rename(&staged_chunk.staged_path, &path).await {
+                let _ = tokio::fs::remove_file(&staged_chunk.staged_path).await;
+                errors.push(format!("{:?}: {}", path, e));
+                continue;
+            }
+            if chunk.last_modified <= staged_chunk.staged_at {
+                chunk.is_modified = false;
+            }
+            committed += 1;
+        }
+
+        if !errors.is_empty() {
+            error!("Committed {} chunks, {} failed", committed, errors.len());
+            return Err(format!("Failed to commit {} chunks: {}", errors.len(), errors.join("; ")).into());
+        }
+
+        Ok(committed)
+    }
+
+    pub async fn discard_staged_chunks(staged: &[StagedChunk]) {
+        for staged_chunk in staged {
+            if let Err(e) = tokio::fs::remove_file(&staged_chunk.staged_path).await {
+                warn!("Failed to remove staged chunk file {:?}: {}", staged_chunk.staged_path, e);
+            }
+        }
+    }
+
+    // Copies the source world's saved chunk files, plus any unsaved edits still in the cache
+    pub async fn copy_world_chunks(&self, source_id: &str, target_id: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let source_dir = Self::chunk_dir(&self.storage_path, source_id)?;
+        let target_dir = Self::chunk_dir(&self.storage_path, target_id)?;
+        tokio::fs::create_dir_all(&target_dir).await?;
+
+        let mut copied = 0;
+        match tokio::fs::read_dir(&source_dir).await {
+            Ok(mut entries) => {
+                while let Some(entry) = entries.next_entry().await? {
+                    let path = entry.path();
+                    if path.extension().is_some_and(|ext| ext == "chunk") {
+                        tokio::fs::copy(&path, target_dir.join(entry.file_name())).await?;
+                        copied += 1;
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        for ((_, x, z), chunk) in self.chunks.iter().filter(|(key, chunk)| key.0 == source_id && chunk.is_modified) {
+            Self::save_chunk_to_storage(&self.storage_path, &(target_id.to_string(), *x, *z), chunk).await?;
+            copied += 1;
+        }
+
+        info!("Copied {} chunks from world {} to {}", copied, source_id, target_id);
+        Ok(copied)
+    }
+
+    // Drops the world's cached chunks and its chunk directory
+    pub async fn delete_world_chunks(&mut self, world_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let dir = Self::chunk_dir(&self.storage_path, world_id)?;
+        self.chunks.retain(|key, _| key.0 != world_id);
+        self.unregister_world(world_id);
+
+        match tokio::fs::remove_dir_all(dir).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        info!("Deleted chunks for world {}", world_id);
         Ok(())
     }
 
-    async fn save_chunk_to_storage(&self, _key: (i32, i32), _chunk: &Chunk) -> Result<(), Box<dyn std::error::Error>> {
-        // Implementation for saving chunk to disk or database
-        // This would typically serialize the chunk data and write it to a file or database
+    // World ids become directory names, so anything that could climb out of storage_path
+    // (separators, "..", drive prefixes) is refused. Generated ids are uuids and always pass
+    fn chunk_dir(storage_path: &Path, world_id: &str) -> Result<PathBuf, String> {
+        let valid = !world_id.is_empty()
+            && world_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(format!("Invalid world id for chunk storage: {:?}", world_id));
+        }
+        Ok(storage_path.join(world_id))
+    }
+
+    fn chunk_file_path(storage_path: &Path, key: &ChunkKey) -> Result<PathBuf, String> {
+        Ok(Self::chunk_dir(storage_path, &key.0)?.join(format!("{}_{}.chunk", key.1, key.2)))
+    }
+
+    async fn save_chunk_to_storage(
+        storage_path: &Path,
+        key: &ChunkKey,
+        chunk: &Chunk,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        tokio::fs::create_dir_all(Self::chunk_dir(storage_path, &key.0)?).await?;
+
+        let data = bincode::serialize(chunk)?;
+        let path = Self::chunk_file_path(storage_path, key)?;
+
+        // Write to a temporary file first so a crash never leaves a half-written chunk
+        let tmp_path = path.with_extension("chunk.tmp");
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
         Ok(())
     }
 
+    // Separate from the .tmp files above so an eviction save can't pick up a staged write
+    async fn write_staged_chunk(
+        storage_path: &Path,
+        key: &ChunkKey,
+        chunk: &Chunk,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        tokio::fs::create_dir_all(Self::chunk_dir(storage_path, &key.0)?).await?;
+
+        let staged_path = Self::chunk_file_path(storage_path, key)?.with_extension("chunk.staged");
+        tokio::fs::write(&staged_path, bincode::serialize(chunk)?).await?;
+
+        Ok(staged_path)
+    }
+
+    async fn load_chunk_from_storage(&self, key: &ChunkKey) -> Result<Option<Chunk>, Box<dyn std::error::Error>> {
+        let path = Self::chunk_file_path(&self.storage_path, key)?;
+
+        let data = match tokio::fs::read(&path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut chunk: Chunk = bincode::deserialize(&data)?;
+        // Stored light may be stale where a neighbor changed after this chunk was saved
+        chunk.compute_light(&self.block_registry);
+        chunk.is_modified = false;
+        chunk.last_accessed = std::time::Instant::now();
+
+        Ok(Some(chunk))
+    }
+
     pub async fn get_chunk_stats(&self) -> ChunkStats {
         let total_chunks = self.chunks.len();
         let modified_chunks = self.chunks.values().filter(|c| c.is_modified).count();
@@ -228,4 +1203,309 @@ pub struct ChunkStats {
     pub modified_chunks: usize,
     pub generated_chunks: usize,
     pub max_cached_chunks: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::loot_system::LootSystem;
+    use crate::worlds::block_registry::TORCH;
+
+    fn test_manager() -> ChunkManager {
+        ChunkManager::new(
+            4,
+            TerrainParams::default(),
+            Arc::new(BiomeSystem::new()),
+            Arc::new(StructureGenerator::new(Arc::new(LootSystem::new()))),
+            Arc::new(BlockRegistry::new()),
+            std::env::temp_dir().join("strixcraft-chunk-tests"),
+        )
+    }
+
+    #[tokio::test]
+    async fn same_seed_generates_identical_chunks() {
+        let mut first = test_manager();
+        let mut second = test_manager();
+        first.register_world("a", 12345);
+        second.register_world("b", 12345);
+
+        for (x, z) in [(0, 0), (-3, 7), (20, -11)] {
+            let a = first.generate_chunk("a", x, z).await.expect("world a is registered");
+            let b = second.generate_chunk("b", x, z).await.expect("world b is registered");
+            assert_eq!(a.to_dense(), b.to_dense());
+            assert_eq!(a.height_map, b.height_map);
+        }
+    }
+
+    #[tokio::test]
+    async fn different_seeds_generate_different_terrain() {
+        let mut manager = test_manager();
+        manager.register_world("a", 1);
+        manager.register_world("b", 2);
+
+        let a = manager.generate_chunk("a", 0, 0).await.unwrap();
+        let b = manager.generate_chunk("b", 0, 0).await.unwrap();
+        assert_ne!(a.height_map, b.height_map);
+    }
+
+    #[tokio::test]
+    async fn a_biome_border_can_split_a_chunk() {
+        let mut manager = test_manager();
+        manager.register_world("w", 12345);
+        let biomes = BiomeSystem::new();
+
+        // First chunk along the x axis whose columns don't all share one surface block
+        let mut border_chunk = None;
+        for chunk_x in -256..256 {
+            let first = ChunkManager::get_surface_block(&biomes.get_biome(12345, chunk_x * 16, 0).await);
+            let last = ChunkManager::get_surface_block(&biomes.get_biome(12345, chunk_x * 16 + 15, 0).await);
+            if first != last {
+                border_chunk = Some(chunk_x);
+                break;
+            }
+        }
+        let chunk_x = border_chunk.expect("no biome border within 4k blocks");
+
+        let chunk = manager.generate_chunk("w", chunk_x, 0).await.unwrap();
+        let generator = manager.terrain_generators["w"].clone();
+        let mut surfaces = HashSet::new();
+        for local_x in 0..16 {
+            for local_z in 0..16 {
+                let (x, z) = (chunk_x * 16 + local_x, local_z);
+                let height = generator.get_height(x, z).await;
+                let expected = ChunkManager::get_surface_block(&biomes.get_biome(12345, x, z).await);
+                let top = chunk.get_block(local_x as usize, height.min(CHUNK_HEIGHT - 1) as usize, local_z as usize);
+                // Caves and structures may have replaced the top block, anything else follows its own column's biome
+                if [GRASS, SAND, SNOW].contains(&top) {
+                    assert_eq!(top, expected, "column {} {}", x, z);
+                    surfaces.insert(top);
+                }
+            }
+        }
+        assert!(surfaces.len() > 1, "the chunk should show both sides of the border");
+    }
+
+    #[tokio::test]
+    async fn unregistered_world_generates_nothing() {
+        let manager = test_manager();
+        assert!(manager.generate_chunk("missing", 0, 0).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn torch_light_fades_across_a_chunk_edge_without_dirtying_the_neighbor() {
+        let mut manager = test_manager();
+        manager.register_world("torch", 7);
+        manager.get_chunk("torch", 0, 0).await.unwrap();
+        manager.get_chunk("torch", 1, 0).await.unwrap();
+        let (y, z) = (200, 8); // Open air, far above any terrain
+
+        manager.set_block("torch", 15, y, z, TORCH).await.unwrap();
+        for distance in 0..=14 {
+            let level = manager.light_at("torch", 15 + distance, y, z, LightChannel::Block);
+            assert_eq!(level, Some(14 - distance as u8), "{} blocks from the torch", distance);
+        }
+        assert!(manager.chunks[&("torch".to_string(), 0, 0)].is_modified);
+        assert!(!manager.chunks[&("torch".to_string(), 1, 0)].is_modified);
+
+        manager.set_block("torch", 15, y, z, AIR).await.unwrap();
+        for distance in 0..=14 {
+            assert_eq!(manager.light_at("torch", 15 + distance, y, z, LightChannel::Block), Some(0));
+        }
+        assert!(!manager.chunks[&("torch".to_string(), 1, 0)].is_modified);
+    }
+
+    #[tokio::test]
+    async fn container_changes_are_written_back_to_their_chunk() {
+        let mut manager = test_manager();
+        manager.register_world("chest", 7);
+        manager.get_chunk("chest", -1, 0).await.unwrap();
+        let key = ("chest".to_string(), -1, 0);
+        let position = [-3, 70, 4];
+        manager.chunks.get_mut(&key).unwrap().containers.push(BlockContainer {
+            position,
+            items: vec![InventoryItem { id: 264, count: 3, metadata: None, slot: 5 }],
+        });
+        manager.chunks.get_mut(&key).unwrap().is_modified = false;
+
+        let mut chest = manager.get_container("chest", position).unwrap();
+        assert_eq!(chest.size, CONTAINER_SLOTS);
+        assert_eq!(chest.items[5].as_ref().map(|item| item.id), Some(264));
+        assert!(manager.get_container("chest", [-3, 71, 4]).is_none());
+
+        chest.items[5] = None;
+        chest.items[0] = Some(InventoryItem { id: 1, count: 64, metadata: None, slot: 0 });
+        manager.set_container("chest", position, &chest).unwrap();
+
+        let chunk = &manager.chunks[&key];
+        assert!(chunk.is_modified);
+        let container = chunk.containers.iter().find(|c| c.position == position).unwrap();
+        assert_eq!(container.items.iter().map(|item| (item.slot, item.id)).collect::<Vec<_>>(), vec![(0, 1)]);
+    }
+
+    #[tokio::test]
+    async fn section_collapses_once_it_is_air_again() {
+        let mut manager = test_manager();
+        manager.register_world("collapse", 7);
+        manager.get_chunk("collapse", 0, 0).await.unwrap();
+        let section = 200 / SECTION_HEIGHT;
+        let sections = |manager: &ChunkManager| manager.chunks[&("collapse".to_string(), 0, 0)].sections.clone();
+        assert!(sections(&manager)[section].is_none());
+
+        manager.set_block("collapse", 3, 200, 5, STONE).await.unwrap();
+        assert!(sections(&manager)[section].is_some());
+
+        manager.set_block("collapse", 3, 200, 5, AIR).await.unwrap();
+        assert!(sections(&manager)[section].is_none());
+        assert_eq!(manager.get_block("collapse", 3, 200, 5).await, Some(AIR));
+    }
+
+    #[test]
+    fn dark_air_keeps_its_section() {
+        // All air but pitch black, like the inside of a sealed cave
+        let volume = SECTIONS_PER_CHUNK * SECTION_VOLUME;
+        let mut chunk = Chunk::from_dense(0, 0, &vec![0; volume], &vec![0; volume], &vec![0; volume], vec![0; 256]);
+        chunk.set_block(0, 10, 0, STONE);
+        chunk.set_block(0, 10, 0, AIR);
+        assert!(chunk.sections[0].is_some());
+    }
+
+    #[test]
+    fn chunk_dir_rejects_ids_that_escape_storage() {
+        let storage = Path::new("/srv/chunks");
+        for world_id in ["", "..", ".", "../etc", "a/b", "a\\b", "C:", "world\0"] {
+            assert!(ChunkManager::chunk_dir(storage, world_id).is_err(), "{:?} should be rejected", world_id);
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        assert_eq!(ChunkManager::chunk_dir(storage, &id), Ok(storage.join(&id)));
+        assert_eq!(ChunkManager::chunk_dir(storage, "my_world-2"), Ok(storage.join("my_world-2")));
+    }
+
+    #[test]
+    fn paletted_blocks_round_trip_at_every_width() {
+        // 1, 2, 3, 5, 17 and 256 distinct ids cover widths 0 to 8, including ones that leave spare bits per word
+        for distinct in [1usize, 2, 3, 5, 17, 256] {
+            let blocks: Vec<u8> = (0..SECTION_VOLUME).map(|i| ((i * 7 + i / 13) % distinct) as u8).collect();
+            let packed = PalettedBlocks::from_dense(&blocks);
+            assert_eq!(packed.to_dense(), blocks, "{} distinct ids", distinct);
+
+            let stored: PalettedBlocks = bincode::deserialize(&bincode::serialize(&packed).unwrap()).unwrap();
+            assert_eq!(stored.to_dense(), blocks, "{} distinct ids after storage", distinct);
+        }
+    }
+
+    #[test]
+    fn paletted_blocks_keep_earlier_writes_when_the_palette_grows() {
+        let mut packed = PalettedBlocks::new(SECTION_VOLUME, 0);
+        let mut expected = vec![0u8; SECTION_VOLUME];
+
+        // Each new id may widen the entries and repack everything written so far
+        for block_id in 1..=40u8 {
+            let index = (block_id as usize * 97) % SECTION_VOLUME;
+            packed.set(index, block_id);
+            expected[index] = block_id;
+            assert_eq!(packed.to_dense(), expected, "after adding id {}", block_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn y_outside_the_world_is_rejected() {
+        let mut manager = test_manager();
+        manager.register_world("bounds", 7);
+        manager.get_chunk("bounds", 0, 0).await.unwrap();
+
+        for y in [-1, CHUNK_HEIGHT, i32::MIN, i32::MAX] {
+            assert!(manager.set_block("bounds", 0, y, 0, STONE).await.is_err(), "y = {}", y);
+            assert!(manager.set_block_metadata("bounds", 0, y, 0, 1).await.is_err(), "y = {}", y);
+            assert_eq!(manager.get_block("bounds", 0, y, 0).await, None, "y = {}", y);
+            assert_eq!(manager.get_block_metadata("bounds", 0, y, 0).await, None, "y = {}", y);
+        }
+        assert!(manager.take_chunk_deltas().is_empty());
+    }
+
+    #[tokio::test]
+    async fn negative_coordinates_land_in_the_chunk_below() {
+        let mut manager = test_manager();
+        manager.register_world("negative", 7);
+        for (chunk_x, chunk_z) in [(-1, -2), (0, -2), (-1, -1)] {
+            manager.get_chunk("negative", chunk_x, chunk_z).await.unwrap();
+        }
+        let block = |manager: &ChunkManager, chunk: (i32, i32), x, z| {
+            manager.chunks[&("negative".to_string(), chunk.0, chunk.1)].get_block(x, 200, z)
+        };
+        let east = block(&manager, (0, -2), 0, 15);
+        let south = block(&manager, (-1, -1), 15, 0);
+
+        // x = -1, z = -17 is the last column of chunk (-1, -2), not the first of chunk (0, -1)
+        manager.set_block("negative", -1, 200, -17, TORCH).await.unwrap();
+
+        assert_eq!(block(&manager, (-1, -2), 15, 15), TORCH);
+        assert_eq!(manager.get_block("negative", -1, 200, -17).await, Some(TORCH));
+        assert_eq!(block(&manager, (0, -2), 0, 15), east);
+        assert_eq!(block(&manager, (-1, -1), 15, 0), south);
+
+        let deltas = manager.take_chunk_deltas();
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].chunk, (-1, -2));
+        assert_eq!(deltas[0].changes, vec![((200 * 16 * 16 + 15 * 16 + 15) as u16, TORCH)]);
+    }
+
+    #[tokio::test]
+    async fn height_above_255_survives_storage() {
+        let manager = test_manager();
+        let volume = SECTIONS_PER_CHUNK * SECTION_VOLUME;
+        let mut height_map = vec![64u16; 256];
+        height_map[17] = 300;
+        let chunk = Chunk::from_dense(0, 0, &vec![0; volume], &vec![0; volume], &vec![FULL_SKY; volume], height_map);
+
+        let key = ("tall".to_string(), 0, 0);
+        ChunkManager::save_chunk_to_storage(&manager.storage_path, &key, &chunk).await.unwrap();
+        let loaded = manager.load_chunk_from_storage(&key).await.unwrap().unwrap();
+
+        assert_eq!(loaded.height_map[17], 300);
+        assert_eq!(loaded.height_map[16], 64);
+    }
+
+    #[tokio::test]
+    async fn terrain_height_of_300_is_stored_at_the_world_top() {
+        let params = TerrainParams { base_height: 300, amplitude: 0.0, cave_density: 0.0, ..TerrainParams::default() };
+        let mut manager = ChunkManager::new(
+            4,
+            params,
+            Arc::new(BiomeSystem::new()),
+            Arc::new(StructureGenerator::new(Arc::new(LootSystem::new()))),
+            Arc::new(BlockRegistry::new()),
+            std::env::temp_dir().join("strixcraft-chunk-tests"),
+        );
+        manager.register_world("peak", 7);
+
+        // A u8 map would wrap 300 to 44, the generator clamps it to the last buildable layer
+        let chunk = manager.generate_chunk("peak", 0, 0).await.unwrap();
+        assert!(chunk.height_map.iter().all(|&height| height == (CHUNK_HEIGHT - 1) as u16));
+    }
+
+    #[tokio::test]
+    async fn set_block_reports_neighbors_across_chunk_edges() {
+        let mut manager = test_manager();
+        manager.register_world("edges", 7);
+        manager.get_chunk("edges", 0, 0).await.unwrap();
+
+        // Local (15, 0) and (0, 15) border chunks (1, 0), (-1, 0) and (0, 1)
+        let neighbors = manager.set_block("edges", 15, 200, 0, STONE).await.unwrap();
+        assert!(neighbors.contains(&(16, 200, 0)));
+        assert!(neighbors.contains(&(15, 200, -1)));
+        assert_eq!(neighbors.len(), 6);
+
+        let neighbors = manager.set_block("edges", 0, 200, 15, STONE).await.unwrap();
+        assert!(neighbors.contains(&(-1, 200, 15)));
+        assert!(neighbors.contains(&(0, 200, 16)));
+
+        // Nothing is reported above the world top or below bedrock
+        let neighbors = manager.set_block("edges", 15, CHUNK_HEIGHT - 1, 15, STONE).await.unwrap();
+        assert_eq!(neighbors.len(), 5);
+        assert!(!neighbors.contains(&(15, CHUNK_HEIGHT, 15)));
+        let neighbors = manager.set_block("edges", 0, 0, 0, BEDROCK).await.unwrap();
+        assert_eq!(neighbors.len(), 5);
+        assert!(neighbors.contains(&(-1, 0, 0)) && neighbors.contains(&(0, 0, -1)));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+
+use crate::systems::player_manager::{GameMode, PlayerManager};
+use crate::systems::world_manager::WorldManager;
+
+pub const DEFAULT_MAX_SUGGESTIONS: usize = 20;
+const MIN_TELEPORT_Y: f64 = 0.0;
+const MAX_TELEPORT_Y: f64 = 256.0; // World height
+
+// How a positional argument should be tab-completed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ArgumentCompleter {
+    OnlinePlayer,
+    Choices(Vec<String>),
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandInfo {
+    pub name: String,
+    pub description: String,
+    pub usage: String,
+    pub aliases: Vec<String>,
+    pub required_permission: Option<String>, // None means anyone can run it
+    pub arguments: Vec<ArgumentCompleter>,
+}
+
+#[derive(Debug)]
+pub struct CommandSystem {
+    commands: HashMap<String, CommandInfo>,
+    aliases: HashMap<String, String>, // alias -> command name
+    max_suggestions: usize,
+}
+
+impl CommandSystem {
+    pub fn new() -> Self {
+        let mut system = Self {
+            commands: HashMap::new(),
+            aliases: HashMap::new(),
+            max_suggestions: DEFAULT_MAX_SUGGESTIONS,
+        };
+
+        system.register_default_commands();
+        system
+    }
+
+    fn register_default_commands(&mut self) {
+        let player = || ArgumentCompleter::OnlinePlayer;
+        let game_modes = || ArgumentCompleter::Choices(vec!["survival".to_string(), "creative".to_string()]);
+        let channel_actions = ArgumentCompleter::Choices(vec!["list".to_string(), "join".to_string(), "leave".to_string()]);
+
+        let defaults = [
+            ("help", "List the commands you can run", "/help", vec![], None, vec![]),
+            ("list", "Show who is online", "/list", vec!["who"], None, vec![]),
+            ("xp", "Show your level and the experience the next one needs", "/xp", vec!["level"], None, vec![]),
+            // Whispers are sent by the message handler, these entries only serve /help and completion
+            ("msg", "Whisper to a player", "/msg <player> <message>", vec!["tell", "w"], None, vec![player()]),
+            ("r", "Answer the last whisper you got", "/r <message>", vec![], None, vec![]),
+            ("channel", "List, join or leave chat channels", "/channel <list|join|leave> [channel]", vec![], None, vec![channel_actions]),
+            ("tp", "Teleport to a player or coordinates", "/tp <player> | /tp <x> <y> <z>", vec!["teleport"], Some("teleport"), vec![player()]),
+            ("kick", "Disconnect a player", "/kick <player> [reason]", vec![], Some("kick"), vec![player()]),
+            ("ban", "Ban a player", "/ban <player> [minutes] [reason]", vec![], Some("ban"), vec![player(), ArgumentCompleter::None]),
+            // Banned players are offline, so there's nobody to complete
+            ("unban", "Lift a player's ban", "/unban <player>", vec!["pardon"], Some("ban"), vec![ArgumentCompleter::None]),
+            ("gamemode", "Change a player's game mode", "/gamemode <survival|creative> [player]", vec!["gm"], Some("gamemode"), vec![game_modes(), player()]),
+        ];
+
+        for (name, description, usage, aliases, permission, arguments) in defaults {
+            self.register_command(CommandInfo {
+                name: name.to_string(),
+                description: description.to_string(),
+                usage: usage.to_string(),
+                aliases: aliases.into_iter().map(String::from).collect(),
+                required_permission: permission.map(String::from),
+                arguments,
+            });
+        }
+    }
+
+    pub fn set_max_suggestions(&mut self, max_suggestions: usize) {
+        self.max_suggestions = max_suggestions;
+    }
+
+    pub fn register_command(&mut self, command: CommandInfo) {
+        for alias in &command.aliases {
+            self.aliases.insert(alias.to_lowercase(), command.name.clone());
+        }
+        self.commands.insert(command.name.to_lowercase(), command);
+    }
+
+    pub fn get_command(&self, name: &str) -> Option<&CommandInfo> {
+        let name = name.to_lowercase();
+        let name = self.aliases.get(&name).cloned().unwrap_or(name);
+        self.commands.get(&name)
+    }
+
+    pub fn can_run(&self, command: &CommandInfo, player_id: &str, player_manager: &PlayerManager) -> bool {
+        command
+            .required_permission
+            .as_ref()
+            .is_none_or(|permission| player_manager.has_permission(player_id, permission))
+    }
+
+    pub async fn execute(
+        &mut self,
+        player_id: &str,
+        name: &str,
+        args: &[String],
+        player_manager: &mut PlayerManager,
+        world_manager: &WorldManager,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let command = self.get_command(name).ok_or_else(|| format!("Unknown command: /{}", name))?.clone();
+
+        if !self.can_run(&command, player_id, player_manager) {
+            warn!("Player {} was denied /{}", player_id, command.name);
+            return Err("insufficient permissions".into());
+        }
+
+        match command.name.as_str() {
+            "help" => Ok(self.help(player_id, player_manager)),
+            "list" => {
+                let names: Vec<String> = player_manager.get_online_players().await.into_iter().map(|p| p.username).collect();
+                Ok(format!("{} online: {}", names.len(), names.join(", ")))
+            }
+            "xp" => {
+                let player = player_manager.get_player(player_id).await.ok_or("Player not found")?;
+                match player_manager.experience_for_level(player.level + 1) {
+                    // The end of a table curve
+                    i32::MAX => Ok(format!("Level {}, {} experience", player.level, player.experience)),
+                    next => Ok(format!("Level {}, {}/{} experience", player.level, player.experience, next)),
+                }
+            }
+            "tp" => Self::teleport(player_id, args, player_manager, world_manager).await,
+            "kick" => {
+                let target = Self::resolve_player(args.first(), player_manager).await?;
+                let reason = if args.len() > 1 { args[1..].join(" ") } else { "Kicked by an operator".to_string() };
+                if player_manager.kick_player(&target, &reason).await? {
+                    Ok(format!("Kicked {}", args[0]))
+                } else {
+                    Err(format!("{} is not online", args[0]).into())
+                }
+            }
+            "ban" => {
+                let target = Self::resolve_player(args.first(), player_manager).await?;
+                // An optional duration in minutes may precede the reason
+                let (duration, reason_start) = match args.get(1).and_then(|a| a.parse::<u32>().ok()) {
+                    Some(minutes) => (Some(minutes), 2),
+                    None => (None, 1),
+                };
+                let reason = if args.len() > reason_start { args[reason_start..].join(" ") } else { "Banned by an operator".to_string() };
+                // Banning also kicks the player if they're online
+                player_manager.ban_player(&target, &reason, duration, player_id).await?;
+                info!("Player {} banned {}", player_id, target);
+                Ok(format!("Banned {}", args[0]))
+            }
+            "unban" => {
+                let target = Self::resolve_player(args.first(), player_manager).await?;
+                if !player_manager.unban_player(&target).await? {
+                    return Err(format!("{} is not banned", args[0]).into());
+                }
+                info!("Player {} unbanned {}", player_id, target);
+                Ok(format!("Unbanned {}", args[0]))
+            }
+            "gamemode" => {
+                let mode = match args.first().map(|a| a.to_lowercase()).as_deref() {
+                    Some("survival") | Some("s") | Some("0") => GameMode::Survival,
+                    Some("creative") | Some("c") | Some("1") => GameMode::Creative,
+                    _ => return Err(format!("Usage: {}", command.usage).into()),
+                };
+                let target = match args.get(1) {
+                    Some(_) => Self::resolve_player(args.get(1), player_manager).await?,
+                    None => player_id.to_string(),
+                };
+                player_manager.set_game_mode(&target, mode.clone()).await?;
+                Ok(format!("Game mode set to {:?}", mode))
+            }
+            // Registered without a built-in handler
+            _ => Err(format!("/{} is not implemented", command.name).into()),
+        }
+    }
+
+    // `partial` is everything typed after the slash. The last token is the one being
+    // completed, an empty last token (trailing space) lists every option
+    pub async fn complete(&self, partial: &str, player_id: &str, player_manager: &PlayerManager) -> Vec<String> {
+        let partial = partial.trim_start_matches('/');
+        let mut tokens: Vec<&str> = partial.split_whitespace().collect();
+        if partial.is_empty() || partial.ends_with(char::is_whitespace) {
+            tokens.push("");
+        }
+
+        let current = tokens.last().copied().unwrap_or("").to_lowercase();
+        let mut suggestions: Vec<String> = if tokens.len() <= 1 {
+            self.commands
+                .values()
+                .filter(|c| self.can_run(c, player_id, player_manager))
+                .flat_map(|c| std::iter::once(&c.name).chain(c.aliases.iter()))
+                .filter(|name| name.to_lowercase().starts_with(&current))
+                .cloned()
+                .collect()
+        } else {
+            let command = match self.get_command(tokens[0]) {
+                Some(command) if self.can_run(command, player_id, player_manager) => command,
+                _ => return Vec::new(),
+            };
+
+            let options = match command.arguments.get(tokens.len() - 2) {
+                Some(ArgumentCompleter::OnlinePlayer) => player_manager
+                    .get_online_players()
+                    .await
+                    .into_iter()
+                    .map(|p| p.username)
+                    .collect(),
+                Some(ArgumentCompleter::Choices(choices)) => choices.clone(),
+                Some(ArgumentCompleter::None) | None => Vec::new(),
+            };
+
+            options
+                .into_iter()
+                .filter(|option| option.to_lowercase().starts_with(&current))
+                .collect()
+        };
+
+        suggestions.sort();
+        suggestions.dedup();
+        suggestions.truncate(self.max_suggestions);
+        suggestions
+    }
+
+    fn help(&self, player_id: &str, player_manager: &PlayerManager) -> String {
+        let mut commands: Vec<&CommandInfo> = self
+            .commands
+            .values()
+            .filter(|c| self.can_run(c, player_id, player_manager))
+            .collect();
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+
+        commands
+            .iter()
+            .map(|c| format!("{} - {}", c.usage, c.description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    async fn teleport(
+        player_id: &str,
+        args: &[String],
+        player_manager: &mut PlayerManager,
+        world_manager: &WorldManager,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let player = player_manager.get_player(player_id).await.ok_or("Player not found")?;
+
+        let position = match args.len() {
+            1 => {
+                let target_id = Self::resolve_player(args.first(), player_manager).await?;
+                let target = player_manager.get_player(&target_id).await.ok_or("Player not found")?;
+                if target.world_id != player.world_id {
+                    return Err(format!("{} is in another world", target.username).into());
+                }
+                target.position
+            }
+            3 => {
+                let mut position = [0.0; 3];
+                for (axis, arg) in args.iter().enumerate() {
+                    position[axis] = arg
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|value| value.is_finite())
+                        .ok_or_else(|| format!("Invalid coordinate: {}", arg))?;
+                }
+                position[1] = position[1].clamp(MIN_TELEPORT_Y, MAX_TELEPORT_Y);
+                position
+            }
+            _ => return Err("Usage: /tp <player> | /tp <x> <y> <z>".into()),
+        };
+
+        if let Some(world_id) = &player.world_id {
+            if !world_manager.is_within_border(world_id, position) {
+                return Err("That position is outside the world border".into());
+            }
+        }
+
+        player_manager.teleport_player(player_id, position).await?;
+        Ok(format!("Teleported to {:.1}, {:.1}, {:.1}", position[0], position[1], position[2]))
+    }
+
+    async fn resolve_player(
+        username: Option<&String>,
+        player_manager: &PlayerManager,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let username = username.ok_or("Missing player name")?;
+        let player = player_manager
+            .get_player_by_username(username)
+            .await
+            .ok_or_else(|| format!("Unknown player: {}", username))?;
+        Ok(player.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::auth::{auth_service::AuthService, jwt_service::JwtService};
+    use crate::database::{
+        database_service::{DatabaseConfig, DatabaseService},
+        entity_repository::EntityRepository,
+        player_repository::PlayerRepository,
+        world_repository::WorldRepository,
+    };
+    use crate::systems::{
+        chunk_manager::ChunkManager,
+        entity_manager::EntityManager,
+        loot_system::LootSystem,
+        player_manager::PlayerRole,
+        world_manager::{self, Difficulty, WorldSettings},
+    };
+    use crate::worlds::{
+        biome_system::BiomeSystem,
+        block_registry::BlockRegistry,
+        structure_generator::StructureGenerator,
+        terrain_generator::TerrainParams,
+    };
+
+    async fn test_database() -> Arc<DatabaseService> {
+        let config = DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            ..DatabaseConfig::default()
+        };
+        Arc::new(DatabaseService::new(&config).await.unwrap())
+    }
+
+    async fn test_managers() -> (PlayerManager, WorldManager) {
+        let database_service = test_database().await;
+        let player_repository = Arc::new(PlayerRepository::new(database_service.clone()));
+        let jwt_service = Arc::new(JwtService::new("x".repeat(32)).unwrap());
+        let auth_service = Arc::new(AuthService::new(player_repository.clone(), jwt_service));
+        let world_manager = WorldManager::new(
+            Arc::new(WorldRepository::new(database_service)),
+            TerrainParams::default(),
+            10,
+        );
+        (PlayerManager::new(player_repository, auth_service), world_manager)
+    }
+
+    async fn player_with_role(player_manager: &mut PlayerManager, username: &str, role: PlayerRole) -> String {
+        let player = player_manager.register_player(username, "correct horse battery").await.unwrap();
+        player_manager.set_player_role(&player.id, role).await.unwrap();
+        player.id
+    }
+
+    // Puts the player in a fresh world whose border reaches 100 blocks from the origin
+    async fn join_bordered_world(player_manager: &mut PlayerManager, world_manager: &mut WorldManager, player_id: &str) {
+        let loot_system = Arc::new(LootSystem::new());
+        let mut chunk_manager = ChunkManager::new(
+            1,
+            TerrainParams::default(),
+            Arc::new(BiomeSystem::new()),
+            Arc::new(StructureGenerator::new(loot_system.clone())),
+            Arc::new(BlockRegistry::new()),
+            std::env::temp_dir().join("strixcraft-command-tests"),
+        );
+        let mut entity_manager = EntityManager::new(Arc::new(EntityRepository::new(test_database().await)), loot_system);
+        let settings = WorldSettings {
+            allow_pvp: false,
+            allow_mob_griefing: false,
+            keep_inventory: false,
+            natural_regeneration: true,
+            difficulty: Difficulty::Peaceful,
+            weather_enabled: false,
+            time_enabled: false,
+            mobs_enabled: false,
+            physics_enabled: false,
+            game_rules: HashMap::new(),
+            max_entities: None,
+        };
+        let world = world_manager
+            .create_world("bordered".to_string(), 1, world_manager::GameMode::Survival, settings, 10, &mut chunk_manager, &mut entity_manager)
+            .await
+            .unwrap();
+        world_manager.set_world_border(&world.id, [0.0, 0.0], 100.0).await.unwrap();
+        player_manager.teleport_to_world(player_id, &world.id, world_manager, &mut chunk_manager).await.unwrap();
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn guest_is_denied_ban() {
+        let (mut player_manager, world_manager) = test_managers().await;
+        let guest = player_with_role(&mut player_manager, "guest", PlayerRole::Guest).await;
+        let target = player_with_role(&mut player_manager, "target", PlayerRole::Player).await;
+        let mut commands = CommandSystem::new();
+
+        let result = commands.execute(&guest, "ban", &args(&["target"]), &mut player_manager, &world_manager).await;
+
+        assert_eq!(result.unwrap_err().to_string(), "insufficient permissions");
+        assert!(!player_manager.is_banned(&target));
+    }
+
+    #[tokio::test]
+    async fn admin_can_ban() {
+        let (mut player_manager, world_manager) = test_managers().await;
+        let admin = player_with_role(&mut player_manager, "admin", PlayerRole::Admin).await;
+        let target = player_with_role(&mut player_manager, "target", PlayerRole::Player).await;
+        let mut commands = CommandSystem::new();
+
+        let result = commands
+            .execute(&admin, "ban", &args(&["target", "griefing"]), &mut player_manager, &world_manager)
+            .await;
+
+        assert_eq!(result.unwrap(), "Banned target");
+        assert!(player_manager.is_banned(&target));
+    }
+
+    #[tokio::test]
+    async fn unban_lifts_the_ban() {
+        let (mut player_manager, world_manager) = test_managers().await;
+        let admin = player_with_role(&mut player_manager, "admin", PlayerRole::Admin).await;
+        let player = player_with_role(&mut player_manager, "player", PlayerRole::Player).await;
+        let target = player_with_role(&mut player_manager, "target", PlayerRole::Player).await;
+        let mut commands = CommandSystem::new();
+
+        commands.execute(&admin, "ban", &args(&["target"]), &mut player_manager, &world_manager).await.unwrap();
+        let result = commands.execute(&player, "unban", &args(&["target"]), &mut player_manager, &world_manager).await;
+        assert_eq!(result.unwrap_err().to_string(), "insufficient permissions");
+
+        let result = commands.execute(&admin, "pardon", &args(&["target"]), &mut player_manager, &world_manager).await;
+        assert_eq!(result.unwrap(), "Unbanned target");
+        assert!(!player_manager.is_banned(&target));
+
+        let result = commands.execute(&admin, "unban", &args(&["target"]), &mut player_manager, &world_manager).await;
+        assert_eq!(result.unwrap_err().to_string(), "target is not banned");
+    }
+
+    #[tokio::test]
+    async fn completion_hides_forbidden_commands_and_is_capped() {
+        let (mut player_manager, _) = test_managers().await;
+        let guest = player_with_role(&mut player_manager, "guest", PlayerRole::Guest).await;
+        let admin = player_with_role(&mut player_manager, "admin", PlayerRole::Admin).await;
+        let mut commands = CommandSystem::new();
+
+        assert_eq!(commands.complete("/g", &admin, &player_manager).await, vec!["gamemode", "gm"]);
+        assert!(commands.complete("/g", &guest, &player_manager).await.is_empty());
+        assert!(commands.complete("/ban ", &guest, &player_manager).await.is_empty());
+        assert_eq!(commands.complete("/gm c", &admin, &player_manager).await, vec!["creative"]);
+
+        commands.set_max_suggestions(3);
+        assert_eq!(commands.complete("/", &admin, &player_manager).await, vec!["ban", "channel", "gamemode"]);
+    }
+
+    #[tokio::test]
+    async fn gamemode_reports_the_new_mode() {
+        let (mut player_manager, world_manager) = test_managers().await;
+        let admin = player_with_role(&mut player_manager, "admin", PlayerRole::Admin).await;
+        let mut commands = CommandSystem::new();
+
+        let result = commands.execute(&admin, "gamemode", &args(&["creative"]), &mut player_manager, &world_manager).await;
+
+        assert_eq!(result.unwrap(), "Game mode set to Creative");
+    }
+
+    #[tokio::test]
+    async fn xp_shows_progress_to_the_next_level() {
+        let (mut player_manager, world_manager) = test_managers().await;
+        let player = player_with_role(&mut player_manager, "player", PlayerRole::Player).await;
+        player_manager.add_experience(&player, 150).await.unwrap();
+        let mut commands = CommandSystem::new();
+
+        let result = commands.execute(&player, "level", &[], &mut player_manager, &world_manager).await;
+
+        assert_eq!(result.unwrap(), "Level 2, 150/200 experience");
+    }
+
+    #[tokio::test]
+    async fn tp_rejects_bad_coordinates_and_respects_the_border() {
+        let (mut player_manager, mut world_manager) = test_managers().await;
+        let admin = player_with_role(&mut player_manager, "admin", PlayerRole::Admin).await;
+        join_bordered_world(&mut player_manager, &mut world_manager, &admin).await;
+        let mut commands = CommandSystem::new();
+
+        for bad in [["NaN", "64", "0"], ["0", "inf", "0"], ["0", "64", "-inf"]] {
+            let result = commands.execute(&admin, "tp", &args(&bad), &mut player_manager, &world_manager).await;
+            assert!(result.unwrap_err().to_string().starts_with("Invalid coordinate"));
+        }
+
+        let result = commands.execute(&admin, "tp", &args(&["500", "64", "0"]), &mut player_manager, &world_manager).await;
+        assert!(result.is_err());
+
+        commands.execute(&admin, "tp", &args(&["10", "9000", "-10"]), &mut player_manager, &world_manager).await.unwrap();
+        assert_eq!(player_manager.get_player(&admin).await.unwrap().position, [10.0, MAX_TELEPORT_Y, -10.0]);
+    }
+}
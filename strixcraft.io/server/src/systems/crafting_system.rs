@@ -1,6 +1,12 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
-use log::{info, warn, error};
+use log::info;
+
+use crate::systems::inventory_system::{Inventory, InventorySystem};
+use crate::systems::item_registry::{
+    COAL, COBBLESTONE, CRAFTING_TABLE, GLASS, GOLD_INGOT, GOLD_ORE, IRON_INGOT, IRON_ORE,
+    OAK_LOG, OAK_PLANKS, SAND, STICK, STONE, WOODEN_PICKAXE,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CraftingRecipe {
@@ -10,6 +16,8 @@ pub struct CraftingRecipe {
     pub result: CraftingResult,
     pub crafting_table: bool,
     pub shapeless: bool,
+    #[serde(default)]
+    pub remainders: Vec<CraftingResult>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,16 +34,21 @@ pub struct CraftingResult {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct InventoryItem {
-    pub id: u32,
-    pub count: u32,
-    pub metadata: Option<serde_json::Value>,
+pub struct SmeltingRecipe {
+    pub input: u32,
+    pub output: CraftingResult,
+    pub cook_time: u32, // In ticks
+    pub experience: f32,
 }
 
+// How many items one piece of coal smelts
+const ITEMS_PER_FUEL: u32 = 8;
+
 #[derive(Debug)]
 pub struct CraftingSystem {
     recipes: HashMap<String, CraftingRecipe>,
     shapeless_recipes: Vec<CraftingRecipe>,
+    smelting_recipes: HashMap<u32, SmeltingRecipe>, // input item_id -> recipe
 }
 
 impl CraftingSystem {
@@ -43,9 +56,11 @@ impl CraftingSystem {
         let mut system = Self {
             recipes: HashMap::new(),
             shapeless_recipes: Vec::new(),
+            smelting_recipes: HashMap::new(),
         };
         
         system.initialize_default_recipes();
+        system.initialize_default_smelting_recipes();
         system
     }
 
@@ -58,7 +73,66 @@ impl CraftingSystem {
     }
 
     pub fn get_recipe(&self, recipe_id: &str) -> Option<&CraftingRecipe> {
-        self.recipes.get(recipe_id)
+        self.recipes
+            .get(recipe_id)
+            .or_else(|| self.shapeless_recipes.iter().find(|recipe| recipe.id == recipe_id))
+    }
+
+    pub fn remove_recipe(&mut self, recipe_id: &str) -> bool {
+        if self.recipes.remove(recipe_id).is_some() {
+            return true;
+        }
+
+        // Shapeless recipes aren't keyed, so fall back to scanning them
+        self.remove_shapeless_recipe(recipe_id)
+    }
+
+    pub fn remove_shapeless_recipe(&mut self, recipe_id: &str) -> bool {
+        let before = self.shapeless_recipes.len();
+        self.shapeless_recipes.retain(|recipe| recipe.id != recipe_id);
+        self.shapeless_recipes.len() != before
+    }
+
+    pub fn add_smelting_recipe(&mut self, recipe: SmeltingRecipe) {
+        self.smelting_recipes.insert(recipe.input, recipe);
+    }
+
+    pub fn smelt(&self, input: u32) -> Option<&SmeltingRecipe> {
+        self.smelting_recipes.get(&input)
+    }
+
+    // Furnaces have no block yet, so the coal burns straight out of the inventory.
+    // Returns the experience the batch is worth
+    pub fn smelt_item(
+        &self,
+        inventory: &mut Inventory,
+        input: u32,
+        count: u32,
+        infinite_items: bool,
+        inventory_system: &InventorySystem,
+    ) -> Result<f32, String> {
+        if count == 0 {
+            return Err("Smelt count must be at least 1".to_string());
+        }
+
+        let recipe = self.smelt(input).ok_or("That item can't be smelted")?;
+        let output_count = recipe.output.count.checked_mul(count).ok_or("Smelt count too large")?;
+        let fuel = count.div_ceil(ITEMS_PER_FUEL);
+
+        // Work on a copy so a failed batch consumes nothing
+        let mut working = inventory.clone();
+        if inventory_system.remove_item(&mut working, input, count, infinite_items)? > 0 {
+            return Err(format!("Not enough of item {}", input));
+        }
+        if inventory_system.remove_item(&mut working, COAL, fuel, infinite_items)? > 0 {
+            return Err("Not enough coal".to_string());
+        }
+        if inventory_system.add_item(&mut working, recipe.output.item_id, output_count, None)? > 0 {
+            return Err("Inventory is full".to_string());
+        }
+
+        *inventory = working;
+        Ok(recipe.experience * count as f32)
     }
 
     pub fn get_all_recipes(&self) -> Vec<&CraftingRecipe> {
@@ -97,30 +171,99 @@ impl CraftingSystem {
         None
     }
 
+    pub fn get_craftable_recipes(
+        &self,
+        inventory: &Inventory,
+        use_crafting_table: bool,
+        inventory_system: &InventorySystem,
+    ) -> Vec<&CraftingRecipe> {
+        self.recipes
+            .values()
+            .chain(self.shapeless_recipes.iter())
+            .filter(|recipe| use_crafting_table || !recipe.crafting_table)
+            .filter(|recipe| self.has_ingredients(inventory, recipe, inventory_system))
+            .collect()
+    }
+
+    // Players with infinite_items (set for creative) still need the ingredients in their inventory,
+    // but nothing is consumed, so no remainders are handed back either
     pub fn craft_item(
         &self,
-        inventory: &mut Vec<InventoryItem>,
+        inventory: &mut Inventory,
         recipe: &CraftingRecipe,
-    ) -> Result<Option<InventoryItem>, String> {
+        infinite_items: bool,
+        inventory_system: &InventorySystem,
+    ) -> Result<CraftingResult, String> {
         // Check if we have all ingredients
-        if !self.has_ingredients(inventory, recipe) {
+        if !self.has_ingredients(inventory, recipe, inventory_system) {
+            return Err("Not enough ingredients".to_string());
+        }
+
+        self.apply_craft(inventory, recipe, 1, infinite_items, inventory_system)
+    }
+
+    pub fn craft_item_n(
+        &self,
+        inventory: &mut Inventory,
+        recipe: &CraftingRecipe,
+        times: u32,
+        infinite_items: bool,
+        inventory_system: &InventorySystem,
+    ) -> Result<CraftingResult, String> {
+        if times == 0 {
+            return Err("Craft count must be at least 1".to_string());
+        }
+
+        // Check everything up front so a failed batch consumes nothing
+        if !self.has_ingredients_for(inventory, recipe, times, inventory_system) {
             return Err("Not enough ingredients".to_string());
         }
 
+        self.apply_craft(inventory, recipe, times, infinite_items, inventory_system)
+    }
+
+    fn apply_craft(
+        &self,
+        inventory: &mut Inventory,
+        recipe: &CraftingRecipe,
+        times: u32,
+        infinite_items: bool,
+        inventory_system: &InventorySystem,
+    ) -> Result<CraftingResult, String> {
+        let result_count = recipe
+            .result
+            .count
+            .checked_mul(times)
+            .ok_or("Craft count too large")?;
+
+        // Work on a copy so a full inventory leaves the original untouched
+        let mut working = inventory.clone();
+
         // Consume ingredients
-        self.consume_ingredients(inventory, recipe)?;
+        for ingredient in &recipe.ingredients {
+            let missing = inventory_system.remove_item(&mut working, ingredient.item_id, ingredient.count * times, infinite_items)?;
+            if missing > 0 {
+                return Err(format!("Not enough of item {}", ingredient.item_id));
+            }
+        }
 
-        // Create result item
-        let result_item = InventoryItem {
-            id: recipe.result.item_id,
-            count: recipe.result.count,
-            metadata: None,
-        };
+        // Add the result, then give back byproducts such as empty buckets
+        let mut outputs = vec![(recipe.result.item_id, result_count)];
+        if !infinite_items {
+            outputs.extend(recipe.remainders.iter().map(|r| (r.item_id, r.count.saturating_mul(times))));
+        }
+        for (item_id, count) in outputs {
+            if inventory_system.add_item(&mut working, item_id, count, None)? > 0 {
+                return Err("Inventory is full".to_string());
+            }
+        }
 
-        // Add to inventory
-        self.add_item_to_inventory(inventory, result_item.clone())?;
+        *inventory = working;
 
-        Ok(Some(result_item))
+        Ok(CraftingResult {
+            item_id: recipe.result.item_id,
+            count: result_count,
+        })
     }
 
     fn matches_shaped_recipe(
@@ -128,21 +271,38 @@ impl CraftingSystem {
         recipe: &CraftingRecipe,
         ingredients: &[[Option<u32>; 3]; 3],
     ) -> bool {
+        // Lay the recipe out on its own grid so both sides can be trimmed the same way
+        let mut pattern: [[Option<u32>; 3]; 3] = [[None; 3]; 3];
         for ingredient in &recipe.ingredients {
-            if let Some((x, y)) = ingredient.position {
-                if x >= 3 || y >= 3 {
-                    return false;
-                }
-                
-                match ingredients[y as usize][x as usize] {
-                    Some(item_id) if item_id == ingredient.item_id => {
-                        // Check count if needed
-                    }
-                    _ => return false,
+            match ingredient.position {
+                Some((x, y)) if x < 3 && y < 3 => {
+                    pattern[y as usize][x as usize] = Some(ingredient.item_id);
                 }
+                _ => return false,
             }
         }
-        true
+
+        // Compare relative shapes so the pattern can sit anywhere in the grid
+        Self::normalize_grid(&pattern) == Self::normalize_grid(ingredients)
+    }
+
+    fn normalize_grid(grid: &[[Option<u32>; 3]; 3]) -> Vec<Vec<Option<u32>>> {
+        let rows: Vec<usize> = (0..3)
+            .filter(|&y| grid[y].iter().any(|cell| cell.is_some()))
+            .collect();
+        let cols: Vec<usize> = (0..3)
+            .filter(|&x| grid.iter().any(|row| row[x].is_some()))
+            .collect();
+
+        let (Some(&min_y), Some(&max_y)) = (rows.first(), rows.last()) else {
+            return Vec::new();
+        };
+        let (min_x, max_x) = (cols[0], cols[cols.len() - 1]);
+
+        // Trim empty rows/columns from the edges only, keeping gaps inside the shape
+        (min_y..=max_y)
+            .map(|y| (min_x..=max_x).map(|x| grid[y][x]).collect())
+            .collect()
     }
 
     fn matches_shapeless_recipe(
@@ -150,100 +310,46 @@ impl CraftingSystem {
         recipe: &CraftingRecipe,
         ingredients: &[[Option<u32>; 3]; 3],
     ) -> bool {
-        let mut available_ingredients: Vec<u32> = Vec::new();
-        
-        // Collect all non-empty ingredients
+        // Count every non-empty grid cell as one unit of its item
+        let mut available: HashMap<u32, u32> = HashMap::new();
         for row in ingredients {
-            for item in row {
-                if let Some(item_id) = item {
-                    available_ingredients.push(item_id);
-                }
+            for item_id in row.iter().flatten() {
+                *available.entry(*item_id).or_insert(0) += 1;
             }
         }
 
-        // Check if we have all required ingredients
+        // The same item may be listed more than once in a recipe
+        let mut required: HashMap<u32, u32> = HashMap::new();
         for ingredient in &recipe.ingredients {
-            let required_count = ingredient.count as usize;
-            let available_count = available_ingredients
-                .iter()
-                .filter(|&&id| id == ingredient.item_id)
-                .count();
-            
-            if available_count < required_count {
-                return false;
-            }
+            *required.entry(ingredient.item_id).or_insert(0) += ingredient.count;
         }
 
-        true
+        // Exact match: no missing ingredients and no stray items left in the grid
+        available == required
     }
 
     fn has_ingredients(
         &self,
-        inventory: &[InventoryItem],
+        inventory: &Inventory,
         recipe: &CraftingRecipe,
+        inventory_system: &InventorySystem,
     ) -> bool {
-        for ingredient in &recipe.ingredients {
-            let available_count: u32 = inventory
-                .iter()
-                .filter(|item| item.id == ingredient.item_id)
-                .map(|item| item.count)
-                .sum();
-            
-            if available_count < ingredient.count {
-                return false;
-            }
-        }
-        true
+        self.has_ingredients_for(inventory, recipe, 1, inventory_system)
     }
 
-    fn consume_ingredients(
+    fn has_ingredients_for(
         &self,
-        inventory: &mut Vec<InventoryItem>,
+        inventory: &Inventory,
         recipe: &CraftingRecipe,
-    ) -> Result<(), String> {
-        for ingredient in &recipe.ingredients {
-            let mut remaining = ingredient.count;
-            
-            for item in inventory.iter_mut() {
-                if item.id == ingredient.item_id && remaining > 0 {
-                    let consume_amount = std::cmp::min(remaining, item.count);
-                    item.count -= consume_amount;
-                    remaining -= consume_amount;
-                    
-                    if item.count == 0 {
-                        // Remove empty items
-                        inventory.retain(|i| i.count > 0);
-                    }
-                    
-                    if remaining == 0 {
-                        break;
-                    }
-                }
-            }
-            
-            if remaining > 0 {
-                return Err(format!("Not enough of item {}", ingredient.item_id));
-            }
-        }
-        Ok(())
-    }
-
-    fn add_item_to_inventory(
-        &self,
-        inventory: &mut Vec<InventoryItem>,
-        new_item: InventoryItem,
-    ) -> Result<(), String> {
-        // Try to stack with existing items
-        for item in inventory.iter_mut() {
-            if item.id == new_item.id {
-                item.count += new_item.count;
-                return Ok(());
-            }
-        }
-        
-        // Add as new item
-        inventory.push(new_item);
-        Ok(())
+        times: u32,
+        inventory_system: &InventorySystem,
+    ) -> bool {
+        recipe.ingredients.iter().all(|ingredient| {
+            ingredient
+                .count
+                .checked_mul(times)
+                .is_some_and(|required_count| inventory_system.has_item(inventory, ingredient.item_id, required_count))
+        })
     }
 
     fn initialize_default_recipes(&mut self) {
@@ -253,17 +359,18 @@ impl CraftingSystem {
             name: "Wooden Planks".to_string(),
             ingredients: vec![
                 CraftingIngredient {
-                    item_id: 17, // Oak Log
+                    item_id: OAK_LOG,
                     count: 1,
                     position: None,
                 }
             ],
             result: CraftingResult {
-                item_id: 5, // Oak Planks
+                item_id: OAK_PLANKS,
                 count: 4,
             },
             crafting_table: false,
             shapeless: true,
+            remainders: vec![],
         });
 
         // Crafting Table
@@ -272,17 +379,18 @@ impl CraftingSystem {
             name: "Crafting Table".to_string(),
             ingredients: vec![
                 CraftingIngredient {
-                    item_id: 5, // Oak Planks
+                    item_id: OAK_PLANKS,
                     count: 4,
                     position: None,
                 }
             ],
             result: CraftingResult {
-                item_id: 58, // Crafting Table
+                item_id: CRAFTING_TABLE,
                 count: 1,
             },
             crafting_table: false,
             shapeless: true,
+            remainders: vec![],
         });
 
         // Wooden Pickaxe
@@ -291,22 +399,23 @@ impl CraftingSystem {
             name: "Wooden Pickaxe".to_string(),
             ingredients: vec![
                 CraftingIngredient {
-                    item_id: 5, // Oak Planks
+                    item_id: OAK_PLANKS,
                     count: 3,
                     position: Some((0, 0)),
                 },
                 CraftingIngredient {
-                    item_id: 280, // Stick
+                    item_id: STICK,
                     count: 2,
                     position: Some((1, 1)),
                 }
             ],
             result: CraftingResult {
-                item_id: 270, // Wooden Pickaxe
+                item_id: WOODEN_PICKAXE,
                 count: 1,
             },
             crafting_table: true,
             shapeless: false,
+            remainders: vec![],
         });
 
         // Stick
@@ -315,19 +424,227 @@ impl CraftingSystem {
             name: "Stick".to_string(),
             ingredients: vec![
                 CraftingIngredient {
-                    item_id: 5, // Oak Planks
+                    item_id: OAK_PLANKS,
                     count: 2,
                     position: None,
                 }
             ],
             result: CraftingResult {
-                item_id: 280, // Stick
+                item_id: STICK,
                 count: 4,
             },
             crafting_table: false,
             shapeless: true,
+            remainders: vec![],
         });
 
         info!("Initialized {} crafting recipes", self.recipes.len() + self.shapeless_recipes.len());
     }
+
+    fn initialize_default_smelting_recipes(&mut self) {
+        // Iron Ingot
+        self.add_smelting_recipe(SmeltingRecipe {
+            input: IRON_ORE,
+            output: CraftingResult {
+                item_id: IRON_INGOT,
+                count: 1,
+            },
+            cook_time: 200,
+            experience: 0.7,
+        });
+
+        // Gold Ingot
+        self.add_smelting_recipe(SmeltingRecipe {
+            input: GOLD_ORE,
+            output: CraftingResult {
+                item_id: GOLD_INGOT,
+                count: 1,
+            },
+            cook_time: 200,
+            experience: 1.0,
+        });
+
+        // Glass
+        self.add_smelting_recipe(SmeltingRecipe {
+            input: SAND,
+            output: CraftingResult {
+                item_id: GLASS,
+                count: 1,
+            },
+            cook_time: 200,
+            experience: 0.1,
+        });
+
+        // Stone
+        self.add_smelting_recipe(SmeltingRecipe {
+            input: COBBLESTONE,
+            output: CraftingResult {
+                item_id: STONE,
+                count: 1,
+            },
+            cook_time: 200,
+            experience: 0.1,
+        });
+
+        info!("Initialized {} smelting recipes", self.smelting_recipes.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::systems::inventory_system::InventoryItem;
+    use crate::systems::item_registry::ItemRegistry;
+
+    fn inventory_system() -> InventorySystem {
+        InventorySystem::new(Arc::new(ItemRegistry::new()))
+    }
+
+    fn inventory_of(size: usize, items: &[(u32, u32)]) -> Inventory {
+        let mut inventory = InventorySystem::create_inventory(size, 0);
+        for (slot, &(id, count)) in items.iter().enumerate() {
+            inventory.items[slot] = Some(InventoryItem { id, count, metadata: None, slot });
+        }
+        inventory
+    }
+
+    // One stone in, one cobblestone out, with a sand byproduct
+    fn recipe_with_remainder() -> CraftingRecipe {
+        CraftingRecipe {
+            id: "test_remainder".to_string(),
+            name: "Test Remainder".to_string(),
+            ingredients: vec![CraftingIngredient { item_id: STONE, count: 1, position: None }],
+            result: CraftingResult { item_id: COBBLESTONE, count: 1 },
+            crafting_table: false,
+            shapeless: true,
+            remainders: vec![CraftingResult { item_id: SAND, count: 1 }],
+        }
+    }
+
+    #[test]
+    fn smelting_burns_a_coal_per_eight_items() {
+        let (crafting, inventory_system) = (CraftingSystem::new(), inventory_system());
+        let mut inventory = inventory_of(4, &[(IRON_ORE, 9), (COAL, 3)]);
+
+        let experience = crafting.smelt_item(&mut inventory, IRON_ORE, 9, false, &inventory_system).unwrap();
+        assert!((experience - 6.3).abs() < 1e-4);
+        assert!(!inventory_system.has_item(&inventory, IRON_ORE, 1));
+        assert!(inventory_system.has_item(&inventory, COAL, 1) && !inventory_system.has_item(&inventory, COAL, 2));
+        assert!(inventory_system.has_item(&inventory, IRON_INGOT, 9));
+    }
+
+    #[test]
+    fn smelting_without_coal_consumes_nothing() {
+        let (crafting, inventory_system) = (CraftingSystem::new(), inventory_system());
+        let mut inventory = inventory_of(4, &[(SAND, 4)]);
+
+        assert_eq!(
+            crafting.smelt_item(&mut inventory, SAND, 4, false, &inventory_system),
+            Err("Not enough coal".to_string())
+        );
+        assert!(inventory_system.has_item(&inventory, SAND, 4));
+        assert!(crafting.smelt_item(&mut inventory, STICK, 1, false, &inventory_system).is_err());
+    }
+
+    #[test]
+    fn remainders_that_do_not_fit_undo_the_craft() {
+        let (crafting, inventory_system) = (CraftingSystem::new(), inventory_system());
+        let recipe = recipe_with_remainder();
+
+        // Stone frees its slot for the result, the byproduct needs a third one
+        let mut inventory = inventory_of(2, &[(STONE, 1), (GLASS, 64)]);
+        assert!(crafting.craft_item(&mut inventory, &recipe, false, &inventory_system).is_err());
+        assert_eq!(inventory_system.get_item_count(&inventory, STONE), 1);
+        assert_eq!(inventory_system.get_item_count(&inventory, COBBLESTONE), 0);
+
+        let mut inventory = inventory_of(3, &[(STONE, 1), (GLASS, 64)]);
+        crafting.craft_item(&mut inventory, &recipe, false, &inventory_system).unwrap();
+        assert!(inventory_system.has_item(&inventory, SAND, 1));
+        assert!(inventory_system.has_item(&inventory, COBBLESTONE, 1));
+    }
+
+    #[test]
+    fn infinite_items_still_need_the_ingredients() {
+        let (crafting, inventory_system) = (CraftingSystem::new(), inventory_system());
+        let recipe = recipe_with_remainder();
+
+        let mut empty = inventory_of(36, &[]);
+        assert!(crafting.craft_item(&mut empty, &recipe, true, &inventory_system).is_err());
+
+        let mut inventory = inventory_of(36, &[(STONE, 1)]);
+        crafting.craft_item(&mut inventory, &recipe, true, &inventory_system).unwrap();
+        assert_eq!(inventory_system.get_item_count(&inventory, STONE), 1);
+        assert!(!inventory_system.has_item(&inventory, SAND, 1));
+    }
+
+    #[test]
+    fn batch_crafts_all_or_nothing() {
+        let (crafting, inventory_system) = (CraftingSystem::new(), inventory_system());
+        let sticks = crafting.get_recipe("stick").unwrap();
+
+        let mut inventory = inventory_of(36, &[(OAK_PLANKS, 5)]);
+        assert!(crafting.craft_item_n(&mut inventory, sticks, 3, false, &inventory_system).is_err());
+        assert_eq!(inventory_system.get_item_count(&inventory, OAK_PLANKS), 5);
+
+        let crafted = crafting.craft_item_n(&mut inventory, sticks, 2, false, &inventory_system).unwrap();
+        assert_eq!((crafted.item_id, crafted.count), (STICK, 8));
+        assert_eq!(inventory_system.get_item_count(&inventory, OAK_PLANKS), 1);
+        assert_eq!(inventory_system.get_item_count(&inventory, STICK), 8);
+    }
+
+    // Gold and iron on the diagonals, so a mirrored layout is a different shape
+    fn two_by_two_recipe() -> CraftingRecipe {
+        let at = |item_id, x, y| CraftingIngredient { item_id, count: 1, position: Some((x, y)) };
+        CraftingRecipe {
+            id: "test_two_by_two".to_string(),
+            name: "Test Two By Two".to_string(),
+            ingredients: vec![at(GOLD_INGOT, 0, 0), at(IRON_INGOT, 1, 0), at(IRON_INGOT, 0, 1), at(GOLD_INGOT, 1, 1)],
+            result: CraftingResult { item_id: GLASS, count: 1 },
+            crafting_table: false,
+            shapeless: false,
+            remainders: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn two_by_two_pattern_matches_in_every_corner() {
+        let mut crafting = CraftingSystem::new();
+        crafting.add_recipe(two_by_two_recipe());
+
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let mut grid = [[None; 3]; 3];
+            grid[dy][dx] = Some(GOLD_INGOT);
+            grid[dy][dx + 1] = Some(IRON_INGOT);
+            grid[dy + 1][dx] = Some(IRON_INGOT);
+            grid[dy + 1][dx + 1] = Some(GOLD_INGOT);
+
+            let recipe = crafting.find_matching_recipe(&grid, false).map(|r| r.id.as_str());
+            assert_eq!(recipe, Some("test_two_by_two"), "offset ({}, {})", dx, dy);
+        }
+
+        let mut mirrored = [[None; 3]; 3];
+        mirrored[1][1] = Some(IRON_INGOT);
+        mirrored[1][2] = Some(GOLD_INGOT);
+        mirrored[2][1] = Some(GOLD_INGOT);
+        mirrored[2][2] = Some(IRON_INGOT);
+        assert!(crafting.find_matching_recipe(&mirrored, false).is_none());
+    }
+
+    #[test]
+    fn shapeless_recipe_with_a_stray_item_does_not_match() {
+        let crafting = CraftingSystem::new();
+
+        let mut grid = [[None; 3]; 3];
+        grid[0][0] = Some(OAK_PLANKS);
+        grid[0][2] = Some(OAK_PLANKS);
+        grid[1][1] = Some(OAK_PLANKS);
+        grid[2][0] = Some(OAK_PLANKS);
+        let recipe = crafting.find_matching_recipe(&grid, false).map(|r| r.id.as_str());
+        assert_eq!(recipe, Some("crafting_table"));
+
+        grid[2][2] = Some(STICK);
+        assert!(crafting.find_matching_recipe(&grid, false).is_none());
+    }
 }
\ No newline at end of file
@@ -1,26 +1,43 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use serde::{Deserialize, Serialize};
+use tokio::time::{interval, Duration};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use log::{info, warn, error};
+use log::{info, warn};
+
+use crate::database::entity_repository::EntityRepository;
+use crate::systems::loot_system::LootSystem;
+use crate::systems::status_effect_system::{self, EffectKind, StatusEffect};
+
+const SPATIAL_CELL_SIZE: f64 = 16.0; // Chunk-sized cells
+pub const MAX_QUERY_RADIUS: f64 = 256.0; // Bounds how many cells one query can walk
+pub const DEFAULT_ITEM_TTL_SECONDS: i64 = 300; // 5 minutes
+pub const DEFAULT_MAX_ENTITIES_PER_WORLD: usize = 2000;
+const CLEANUP_INTERVAL_SECONDS: u64 = 1;
+
+type CellKey = (i32, i32, i32);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entity {
     pub id: String,
-    pub entity_type: EntityType,
+    pub entity_type: EntityKind,
     pub position: [f64; 3],
     pub rotation: [f64; 3],
     pub velocity: [f64; 3],
     pub health: f32,
     pub max_health: f32,
+    #[serde(with = "crate::networking::protocol::json_field")]
     pub metadata: serde_json::Value,
     pub world_id: String,
     pub is_active: bool,
-    pub created_at: std::time::Instant,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub effects: Vec<StatusEffect>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EntityType {
     Player,
     Zombie,
@@ -36,29 +53,116 @@ pub enum EntityType {
     Vehicle,
 }
 
+// Serialized as a bare name either way, so a custom kind is indistinguishable from
+// a built-in one on the wire and in storage
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    BuiltIn(EntityType),
+    Custom(String), // Registered through EntityManager::register_entity_kind, never a built-in name
+}
+
+impl EntityKind {
+    pub fn is(&self, entity_type: EntityType) -> bool {
+        matches!(self, EntityKind::BuiltIn(t) if *t == entity_type)
+    }
+
+    // Built-in and custom mobs, everything but players, items, projectiles and vehicles
+    pub fn is_mob(&self) -> bool {
+        !matches!(
+            self,
+            EntityKind::BuiltIn(EntityType::Player | EntityType::Item | EntityType::Projectile | EntityType::Vehicle)
+        )
+    }
+}
+
+impl Serialize for EntityKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            // Binary formats would write a variant index, the deserializer always reads a name
+            EntityKind::BuiltIn(entity_type) => match serde_json::to_value(entity_type) {
+                Ok(serde_json::Value::String(name)) => serializer.serialize_str(&name),
+                _ => Err(serde::ser::Error::custom("Entity type has no name")),
+            },
+            EntityKind::Custom(id) => serializer.serialize_str(id),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EntityKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(match serde_json::from_value::<EntityType>(serde_json::Value::String(name.clone())) {
+            Ok(entity_type) => EntityKind::BuiltIn(entity_type),
+            Err(_) => EntityKind::Custom(name),
+        })
+    }
+}
+
+impl From<EntityType> for EntityKind {
+    fn from(entity_type: EntityType) -> Self {
+        EntityKind::BuiltIn(entity_type)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityProps {
+    pub default_health: f32,
+    pub despawnable: bool,
+}
+
 #[derive(Debug)]
 pub struct EntityManager {
     entities: HashMap<String, Entity>,
     entities_by_world: HashMap<String, Vec<String>>,
-    entity_counters: HashMap<EntityType, u32>,
+    entity_counters: HashMap<EntityKind, u32>,
+    entity_registry: HashMap<String, EntityProps>,
+    spatial_index: HashMap<String, HashMap<CellKey, HashSet<String>>>, // world_id -> cell -> entity ids
+    entity_repository: Arc<EntityRepository>,
+    loot_system: Arc<LootSystem>,
+    max_entities_per_world: usize,
+    world_entity_caps: HashMap<String, usize>, // world_id -> cap from WorldSettings
+    updated_entities: HashSet<String>, // Spawned or moved since the message handler last drained
+    despawned_entities: Vec<String>,
 }
 
 impl EntityManager {
-    pub fn new() -> Self {
+    pub fn new(entity_repository: Arc<EntityRepository>, loot_system: Arc<LootSystem>) -> Self {
         Self {
             entities: HashMap::new(),
             entities_by_world: HashMap::new(),
             entity_counters: HashMap::new(),
+            entity_registry: HashMap::new(),
+            spatial_index: HashMap::new(),
+            entity_repository,
+            loot_system,
+            max_entities_per_world: DEFAULT_MAX_ENTITIES_PER_WORLD,
+            world_entity_caps: HashMap::new(),
+            updated_entities: HashSet::new(),
+            despawned_entities: Vec::new(),
         }
     }
 
     pub async fn spawn_entity(
         &mut self,
-        entity_type: EntityType,
+        entity_type: EntityKind,
         position: [f64; 3],
         world_id: String,
         metadata: Option<serde_json::Value>,
-    ) -> String {
+    ) -> Result<String, String> {
+        if let EntityKind::Custom(id) = &entity_type {
+            if !self.entity_registry.contains_key(id) {
+                return Err(format!("Unknown entity kind: {}", id));
+            }
+        }
+
+        // Players always get in, everything else respects the world's cap
+        if !entity_type.is(EntityType::Player) {
+            let cap = self.get_world_entity_cap(&world_id);
+            if self.get_entity_count(&world_id) >= cap {
+                return Err(format!("World {} is at its entity cap of {}", world_id, cap));
+            }
+        }
+
         let entity_id = Uuid::new_v4().to_string();
         
         let entity = Entity {
@@ -72,23 +176,99 @@ impl EntityManager {
             metadata: metadata.unwrap_or(serde_json::json!({})),
             world_id: world_id.clone(),
             is_active: true,
-            created_at: std::time::Instant::now(),
+            created_at: Utc::now(),
+            effects: Vec::new(),
         };
 
-        self.entities.insert(entity_id.clone(), entity);
+        self.insert_entity(entity);
+
+        info!("Spawned entity: {:?} at {:?} in world {}", entity_type, position, world_id);
+        
+        Ok(entity_id)
+    }
+
+    pub fn set_max_entities_per_world(&mut self, max_entities: usize) {
+        self.max_entities_per_world = max_entities;
+    }
+
+    pub fn set_world_entity_cap(&mut self, world_id: &str, max_entities: Option<usize>) {
+        match max_entities {
+            Some(max_entities) => {
+                self.world_entity_caps.insert(world_id.to_string(), max_entities);
+            }
+            None => {
+                self.world_entity_caps.remove(world_id);
+            }
+        }
+    }
+
+    pub fn get_world_entity_cap(&self, world_id: &str) -> usize {
+        self.world_entity_caps
+            .get(world_id)
+            .copied()
+            .unwrap_or(self.max_entities_per_world)
+    }
+
+    pub fn get_entity_count(&self, world_id: &str) -> usize {
+        self.entities_by_world.get(world_id).map_or(0, |ids| ids.len())
+    }
+
+    fn insert_entity(&mut self, entity: Entity) {
+        // Add to spatial index
+        self.index_insert(&entity.world_id, &entity.id, Self::cell_of(entity.position));
         
         // Add to world index
         self.entities_by_world
-            .entry(world_id)
-            .or_insert_with(Vec::new)
-            .push(entity_id.clone());
+            .entry(entity.world_id.clone())
+            .or_default()
+            .push(entity.id.clone());
 
         // Update counter
-        *self.entity_counters.entry(entity_type).or_insert(0) += 1;
+        *self.entity_counters.entry(entity.entity_type.clone()).or_insert(0) += 1;
 
-        info!("Spawned entity: {:?} at {:?} in world {}", entity_type, position, world_id);
-        
-        entity_id
+        self.updated_entities.insert(entity.id.clone());
+        self.entities.insert(entity.id.clone(), entity);
+    }
+
+    // The entities a save of this world should write
+    pub async fn saved_world_entities(&self, world_id: &str) -> Vec<Entity> {
+        // Players are saved separately and projectiles are too short-lived to keep
+        self.get_entities_in_world(world_id)
+            .await
+            .into_iter()
+            .filter(|e| !e.entity_type.is(EntityType::Player) && !e.entity_type.is(EntityType::Projectile))
+            .collect()
+    }
+
+    pub async fn load_world_entities(&mut self, world_id: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let entities = self.entity_repository.load_world_entities(world_id).await?;
+        let mut loaded = 0;
+
+        for entity in entities {
+            if self.entities.contains_key(&entity.id) {
+                continue;
+            }
+
+            self.insert_entity(entity);
+            loaded += 1;
+        }
+
+        info!("Loaded {} entities in world {}", loaded, world_id);
+        Ok(loaded)
+    }
+
+    // Removes every entity of a deleted world, in memory and in the database
+    pub async fn delete_world_entities(&mut self, world_id: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let entity_ids = self.entities_by_world.get(world_id).cloned().unwrap_or_default();
+        for entity_id in &entity_ids {
+            self.despawn_entity(entity_id).await;
+        }
+        self.entities_by_world.remove(world_id);
+        self.spatial_index.remove(world_id);
+        self.world_entity_caps.remove(world_id);
+
+        self.entity_repository.delete_world_entities(world_id).await?;
+        Ok(entity_ids.len())
     }
 
     pub async fn despawn_entity(&mut self, entity_id: &str) -> bool {
@@ -98,6 +278,9 @@ impl EntityManager {
                 world_entities.retain(|id| id != entity_id);
             }
 
+            // Remove from spatial index
+            self.index_remove(&entity.world_id, entity_id, Self::cell_of(entity.position));
+
             // Update counter
             if let Some(counter) = self.entity_counters.get_mut(&entity.entity_type) {
                 if *counter > 0 {
@@ -105,6 +288,9 @@ impl EntityManager {
                 }
             }
 
+            self.updated_entities.remove(entity_id);
+            self.despawned_entities.push(entity_id.to_string());
+
             info!("Despawned entity: {} ({:?})", entity_id, entity.entity_type);
             true
         } else {
@@ -112,6 +298,16 @@ impl EntityManager {
         }
     }
 
+    // Drained by the message handler, which tells nearby players. Returns (updated, despawned)
+    pub fn take_entity_events(&mut self) -> (Vec<Entity>, Vec<String>) {
+        let updated = self
+            .updated_entities
+            .drain()
+            .filter_map(|entity_id| self.entities.get(&entity_id).cloned())
+            .collect();
+        (updated, std::mem::take(&mut self.despawned_entities))
+    }
+
     pub async fn get_entity(&self, entity_id: &str) -> Option<Entity> {
         self.entities.get(entity_id).cloned()
     }
@@ -127,25 +323,122 @@ impl EntityManager {
         }
     }
 
+    // Radii above MAX_QUERY_RADIUS are clamped to it, so callers needing more must query in pieces
     pub async fn get_entities_in_radius(
         &self,
         center: [f64; 3],
         radius: f64,
         world_id: &str,
     ) -> Vec<Entity> {
-        self.get_entities_in_world(world_id)
-            .await
-            .into_iter()
-            .filter(|entity| {
-                let dx = entity.position[0] - center[0];
-                let dy = entity.position[1] - center[1];
-                let dz = entity.position[2] - center[2];
-                let distance = (dx * dx + dy * dy + dz * dz).sqrt();
-                distance <= radius
-            })
-            .collect()
+        let cells = match self.spatial_index.get(world_id) {
+            Some(cells) => cells,
+            None => return Vec::new(),
+        };
+
+        // NaN and negative radii match nothing
+        if radius.is_nan() || radius < 0.0 {
+            return Vec::new();
+        }
+        let radius = radius.min(MAX_QUERY_RADIUS);
+
+        // Only scan the cells overlapping the query sphere's bounding box
+        let min = Self::cell_of([center[0] - radius, center[1] - radius, center[2] - radius]);
+        let max = Self::cell_of([center[0] + radius, center[1] + radius, center[2] + radius]);
+
+        let mut entities = Vec::new();
+        for cx in min.0..=max.0 {
+            for cy in min.1..=max.1 {
+                for cz in min.2..=max.2 {
+                    let entity_ids = match cells.get(&(cx, cy, cz)) {
+                        Some(entity_ids) => entity_ids,
+                        None => continue,
+                    };
+
+                    for entity in entity_ids.iter().filter_map(|id| self.entities.get(id)) {
+                        if Self::distance(entity.position, center) <= radius {
+                            entities.push(entity.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        entities
+    }
+
+    pub async fn nearest_entity(
+        &self,
+        from: [f64; 3],
+        world_id: &str,
+        filter: Option<EntityKind>,
+        max_range: f64,
+    ) -> Option<(Entity, f64)> {
+        let cells = self.spatial_index.get(world_id)?;
+        // Same cap as the radius query, an unbounded range would walk shells forever
+        if max_range.is_nan() || max_range < 0.0 {
+            return None;
+        }
+        let max_range = max_range.min(MAX_QUERY_RADIUS);
+        let center = Self::cell_of(from);
+        let max_ring = (max_range / SPATIAL_CELL_SIZE).floor() as i32 + 1;
+
+        let mut best: Option<(&Entity, f64)> = None;
+
+        // Search outwards one shell of cells at a time
+        for ring in 0..=max_ring {
+            for dx in -ring..=ring {
+                for dy in -ring..=ring {
+                    for dz in -ring..=ring {
+                        if dx.abs().max(dy.abs()).max(dz.abs()) != ring {
+                            continue;
+                        }
+
+                        let cell = (center.0 + dx, center.1 + dy, center.2 + dz);
+                        let entity_ids = match cells.get(&cell) {
+                            Some(entity_ids) => entity_ids,
+                            None => continue,
+                        };
+
+                        for entity in entity_ids.iter().filter_map(|id| self.entities.get(id)) {
+                            // The dead linger until the cleanup loop, they're never a target
+                            if !entity.is_active || filter.as_ref().is_some_and(|t| &entity.entity_type != t) {
+                                continue;
+                            }
+
+                            let distance = Self::distance(entity.position, from);
+                            if distance > max_range {
+                                continue;
+                            }
+
+                            // Break ties on id so the result doesn't depend on hash order
+                            let closer = match best {
+                                Some((best_entity, best_distance)) => {
+                                    distance < best_distance
+                                        || (distance == best_distance && entity.id < best_entity.id)
+                                }
+                                None => true,
+                            };
+                            if closer {
+                                best = Some((entity, distance));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Anything in the next shell is at least this far away
+            if let Some((_, best_distance)) = best {
+                if best_distance <= ring as f64 * SPATIAL_CELL_SIZE {
+                    break;
+                }
+            }
+        }
+
+        best.map(|(entity, distance)| (entity.clone(), distance))
     }
 
+    // Entities move through tick, tests place them directly
+    #[cfg(test)]
     pub async fn update_entity_position(
         &mut self,
         entity_id: &str,
@@ -153,10 +446,22 @@ impl EntityManager {
         rotation: Option<[f64; 3]>,
     ) -> bool {
         if let Some(entity) = self.entities.get_mut(entity_id) {
+            let old_cell = Self::cell_of(entity.position);
+            let new_cell = Self::cell_of(position);
+
+            if entity.position != position || rotation.is_some_and(|rot| rot != entity.rotation) {
+                self.updated_entities.insert(entity_id.to_string());
+            }
             entity.position = position;
             if let Some(rot) = rotation {
                 entity.rotation = rot;
             }
+
+            if old_cell != new_cell {
+                let world_id = entity.world_id.clone();
+                self.index_remove(&world_id, entity_id, old_cell);
+                self.index_insert(&world_id, entity_id, new_cell);
+            }
             true
         } else {
             false
@@ -176,21 +481,74 @@ impl EntityManager {
         }
     }
 
+    pub async fn tick(&mut self, dt: f64) {
+        let mut moved = Vec::new();
+
+        for entity in self.entities.values_mut() {
+            // Players are driven by client input, not velocity
+            if !entity.is_active || entity.entity_type.is(EntityType::Player) {
+                continue;
+            }
+            if entity.velocity == [0.0, 0.0, 0.0] {
+                continue;
+            }
+
+            let old_cell = Self::cell_of(entity.position);
+            for axis in 0..3 {
+                entity.position[axis] += entity.velocity[axis] * dt;
+            }
+            self.updated_entities.insert(entity.id.clone());
+
+            let new_cell = Self::cell_of(entity.position);
+            if old_cell != new_cell {
+                moved.push((entity.world_id.clone(), entity.id.clone(), old_cell, new_cell));
+            }
+        }
+
+        for (world_id, entity_id, old_cell, new_cell) in moved {
+            self.index_remove(&world_id, &entity_id, old_cell);
+            self.index_insert(&world_id, &entity_id, new_cell);
+        }
+    }
+
     pub async fn damage_entity(
         &mut self,
         entity_id: &str,
         damage: f32,
     ) -> Option<f32> {
-        if let Some(entity) = self.entities.get_mut(entity_id) {
-            entity.health = (entity.health - damage).max(0.0);
-            
-            if entity.health <= 0.0 {
-                entity.is_active = false;
+        let entity = self.entities.get_mut(entity_id)?;
+        let was_alive = entity.is_active;
+        entity.health = (entity.health - damage).max(0.0);
+        
+        if entity.health <= 0.0 {
+            entity.is_active = false;
+        }
+        
+        let health = entity.health;
+        if was_alive && health <= 0.0 {
+            let entity = entity.clone();
+            self.drop_loot(&entity).await;
+        }
+        
+        Some(health)
+    }
+
+    // Seeded from the entity id so a given death always drops the same items
+    async fn drop_loot(&mut self, entity: &Entity) {
+        let table_id = match &entity.entity_type {
+            EntityKind::BuiltIn(EntityType::Player | EntityType::Item | EntityType::Projectile | EntityType::Vehicle) => return,
+            EntityKind::BuiltIn(entity_type) => format!("entities/{:?}", entity_type).to_lowercase(),
+            EntityKind::Custom(id) => format!("entities/{}", id),
+        };
+        if self.loot_system.get_table(&table_id).is_none() {
+            return;
+        }
+
+        for item in self.loot_system.roll_loot(&table_id, LootSystem::seed_from(&entity.id)) {
+            let metadata = serde_json::json!({ "item_id": item.id, "count": item.count });
+            if let Err(e) = self.spawn_entity(EntityType::Item.into(), entity.position, entity.world_id.clone(), Some(metadata)).await {
+                warn!("Failed to drop loot for {}: {}", entity.id, e);
             }
-            
-            Some(entity.health)
-        } else {
-            None
         }
     }
 
@@ -207,16 +565,64 @@ impl EntityManager {
         }
     }
 
-    pub async fn update_entity_metadata(
+    // Same stacking rules as PlayerManager::apply_effect
+    pub async fn apply_effect(
         &mut self,
         entity_id: &str,
-        metadata: serde_json::Value,
+        kind: EffectKind,
+        amplifier: u8,
+        duration: chrono::Duration,
     ) -> bool {
-        if let Some(entity) = self.entities.get_mut(entity_id) {
-            entity.metadata = metadata;
-            true
-        } else {
-            false
+        match self.entities.get_mut(entity_id) {
+            Some(entity) => status_effect_system::merge_effect(
+                &mut entity.effects,
+                StatusEffect {
+                    kind,
+                    amplifier,
+                    expires_at: Utc::now() + duration,
+                },
+            ),
+            None => false,
+        }
+    }
+
+    pub async fn remove_effect(&mut self, entity_id: &str, kind: EffectKind) -> bool {
+        match self.entities.get_mut(entity_id) {
+            Some(entity) => {
+                let before = entity.effects.len();
+                entity.effects.retain(|e| e.kind != kind);
+                entity.effects.len() != before
+            }
+            None => false,
+        }
+    }
+
+    // Unlike players, entities can be poisoned to death
+    pub async fn tick_effects(&mut self) {
+        let now = Utc::now();
+        let mut changes = Vec::new();
+
+        for entity in self.entities.values_mut() {
+            if entity.effects.is_empty() {
+                continue;
+            }
+            status_effect_system::remove_expired(&mut entity.effects, now);
+
+            if !entity.is_active {
+                continue;
+            }
+            let change = status_effect_system::health_change(&entity.effects);
+            if change != 0.0 {
+                changes.push((entity.id.clone(), change));
+            }
+        }
+
+        for (entity_id, change) in changes {
+            if change > 0.0 {
+                self.heal_entity(&entity_id, change).await;
+            } else {
+                self.damage_entity(&entity_id, -change).await;
+            }
         }
     }
 
@@ -233,10 +639,95 @@ impl EntityManager {
             total_entities,
             active_entities,
             type_counts,
+            registered_kinds: self.entity_registry.len(),
+        }
+    }
+
+    fn cell_of(position: [f64; 3]) -> CellKey {
+        (
+            (position[0] / SPATIAL_CELL_SIZE).floor() as i32,
+            (position[1] / SPATIAL_CELL_SIZE).floor() as i32,
+            (position[2] / SPATIAL_CELL_SIZE).floor() as i32,
+        )
+    }
+
+    fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+        let dx = a[0] - b[0];
+        let dy = a[1] - b[1];
+        let dz = a[2] - b[2];
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+
+    fn index_insert(&mut self, world_id: &str, entity_id: &str, cell: CellKey) {
+        self.spatial_index
+            .entry(world_id.to_string())
+            .or_default()
+            .entry(cell)
+            .or_default()
+            .insert(entity_id.to_string());
+    }
+
+    fn index_remove(&mut self, world_id: &str, entity_id: &str, cell: CellKey) {
+        if let Some(cells) = self.spatial_index.get_mut(world_id) {
+            if let Some(entity_ids) = cells.get_mut(&cell) {
+                entity_ids.remove(entity_id);
+                if entity_ids.is_empty() {
+                    cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    // Kinds serialize untagged, so a custom id spelled like a built-in type would load back as
+    // the built-in one. Those ids are refused rather than changing the stored format
+    pub fn register_entity_kind(&mut self, id: &str, props: EntityProps) -> Result<(), String> {
+        if id.is_empty() {
+            return Err("Entity kind id can't be empty".to_string());
+        }
+        if serde_json::from_value::<EntityType>(serde_json::Value::String(id.to_string())).is_ok() {
+            return Err(format!("Entity kind {} collides with a built-in entity type", id));
+        }
+
+        self.entity_registry.insert(id.to_string(), props);
+        info!("Registered entity kind: {}", id);
+        Ok(())
+    }
+
+    pub fn get_entity_props(&self, id: &str) -> Option<&EntityProps> {
+        self.entity_registry.get(id)
+    }
+
+    // (width, height) of the collision box, centred on x/z with the position at its feet
+    pub fn get_entity_size(&self, entity_type: &EntityKind) -> (f64, f64) {
+        let entity_type = match entity_type {
+            EntityKind::BuiltIn(entity_type) => entity_type,
+            EntityKind::Custom(_) => return (0.6, 1.8),
+        };
+
+        match entity_type {
+            EntityType::Player => (0.6, 1.8),
+            EntityType::Zombie => (0.6, 1.95),
+            EntityType::Skeleton => (0.6, 1.99),
+            EntityType::Creeper => (0.6, 1.7),
+            EntityType::Spider => (1.4, 0.9),
+            EntityType::Cow => (0.9, 1.4),
+            EntityType::Pig => (0.9, 0.9),
+            EntityType::Sheep => (0.9, 1.3),
+            EntityType::Chicken => (0.4, 0.7),
+            EntityType::Item => (0.25, 0.25),
+            EntityType::Projectile => (0.5, 0.5),
+            EntityType::Vehicle => (1.4, 0.6),
         }
     }
 
-    fn get_default_health(&self, entity_type: &EntityType) -> f32 {
+    fn get_default_health(&self, entity_type: &EntityKind) -> f32 {
+        let entity_type = match entity_type {
+            EntityKind::BuiltIn(entity_type) => entity_type,
+            EntityKind::Custom(id) => {
+                return self.get_entity_props(id).map_or(20.0, |props| props.default_health);
+            }
+        };
+
         match entity_type {
             EntityType::Player => 20.0,
             EntityType::Zombie => 20.0,
@@ -253,7 +744,51 @@ impl EntityManager {
         }
     }
 
-    pub async fn cleanup_dead_entities(&mut self) {
+    pub async fn cleanup_expired_items(&mut self, ttl: chrono::Duration) -> usize {
+        let cutoff = Utc::now() - ttl;
+
+        // Dropped items plus any custom kinds registered as despawnable
+        let expired: Vec<String> = self
+            .entities
+            .values()
+            .filter(|e| self.is_despawnable(&e.entity_type) && e.created_at < cutoff)
+            .map(|e| e.id.clone())
+            .collect();
+
+        for entity_id in &expired {
+            self.despawn_entity(entity_id).await;
+        }
+
+        expired.len()
+    }
+
+    fn is_despawnable(&self, entity_type: &EntityKind) -> bool {
+        match entity_type {
+            EntityKind::BuiltIn(entity_type) => *entity_type == EntityType::Item,
+            EntityKind::Custom(id) => self.get_entity_props(id).is_some_and(|props| props.despawnable),
+        }
+    }
+
+    // Movement belongs to the physics tick, this one only clears out dead entities and expired items
+    pub async fn run(entity_manager: Arc<RwLock<EntityManager>>, item_ttl_seconds: i64) {
+        let ttl = chrono::Duration::seconds(item_ttl_seconds);
+        let mut ticker = interval(Duration::from_secs(CLEANUP_INTERVAL_SECONDS));
+        loop {
+            ticker.tick().await;
+
+            let mut entity_manager = entity_manager.write().await;
+            let dead = entity_manager.cleanup_dead_entities().await;
+            if dead > 0 {
+                info!("Removed {} dead entities", dead);
+            }
+            let expired = entity_manager.cleanup_expired_items(ttl).await;
+            if expired > 0 {
+                info!("Removed {} expired items", expired);
+            }
+        }
+    }
+
+    pub async fn cleanup_dead_entities(&mut self) -> usize {
         let mut to_remove = Vec::new();
         
         for (entity_id, entity) in &self.entities {
@@ -262,9 +797,11 @@ impl EntityManager {
             }
         }
         
-        for entity_id in to_remove {
-            self.despawn_entity(&entity_id).await;
+        for entity_id in &to_remove {
+            self.despawn_entity(entity_id).await;
         }
+
+        to_remove.len()
     }
 }
 
@@ -272,5 +809,223 @@ impl EntityManager {
 pub struct EntityStats {
     pub total_entities: usize,
     pub active_entities: usize,
-    pub type_counts: HashMap<EntityType, usize>,
-}
\ No newline at end of file
+    pub type_counts: HashMap<EntityKind, usize>,
+    pub registered_kinds: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::database_service::{DatabaseConfig, DatabaseService};
+
+    async fn test_manager() -> EntityManager {
+        let config = DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            ..DatabaseConfig::default()
+        };
+        let database_service = Arc::new(DatabaseService::new(&config).await.unwrap());
+        EntityManager::new(Arc::new(EntityRepository::new(database_service)), Arc::new(LootSystem::new()))
+    }
+
+    // Spreads entities over several cells, including negative coordinates, without an RNG
+    async fn scattered_manager(count: usize) -> EntityManager {
+        let mut manager = test_manager().await;
+        manager.set_max_entities_per_world(count);
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 20_000) as f64 / 100.0 - 100.0
+        };
+
+        for _ in 0..count {
+            let position = [next(), next() / 4.0 + 64.0, next()];
+            manager
+                .spawn_entity(EntityKind::BuiltIn(EntityType::Cow), position, "world".to_string(), None)
+                .await
+                .unwrap();
+        }
+        manager
+    }
+
+    fn sorted_ids(entities: Vec<Entity>) -> Vec<String> {
+        let mut ids: Vec<String> = entities.into_iter().map(|e| e.id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn radius_query_matches_a_linear_scan() {
+        let manager = scattered_manager(300).await;
+        let all = manager.get_entities_in_world("world").await;
+
+        for center in [[0.0, 64.0, 0.0], [-37.5, 60.0, 81.2], [99.0, 70.0, -99.0]] {
+            for radius in [0.0, 5.0, 16.0, 33.3, 120.0] {
+                let expected = sorted_ids(
+                    all.iter()
+                        .filter(|e| EntityManager::distance(e.position, center) <= radius)
+                        .cloned()
+                        .collect(),
+                );
+                let found = sorted_ids(manager.get_entities_in_radius(center, radius, "world").await);
+                assert_eq!(found, expected, "center {:?} radius {}", center, radius);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn radius_query_beats_a_linear_scan_at_10k_entities() {
+        let manager = scattered_manager(10_000).await;
+        let centers = [[0.0, 64.0, 0.0], [-60.0, 70.0, 45.0], [80.0, 58.0, -80.0]];
+        let radius = 12.0;
+
+        let linear_scan = |center: [f64; 3]| -> Vec<Entity> {
+            manager
+                .entities
+                .values()
+                .filter(|e| e.world_id == "world" && EntityManager::distance(e.position, center) <= radius)
+                .cloned()
+                .collect()
+        };
+
+        let started = std::time::Instant::now();
+        let mut expected = Vec::new();
+        for _ in 0..20 {
+            expected = centers.iter().map(|&center| sorted_ids(linear_scan(center))).collect();
+        }
+        let linear_time = started.elapsed();
+
+        let started = std::time::Instant::now();
+        let mut found = Vec::new();
+        for _ in 0..20 {
+            found = Vec::new();
+            for center in centers {
+                found.push(sorted_ids(manager.get_entities_in_radius(center, radius, "world").await));
+            }
+        }
+        let indexed_time = started.elapsed();
+
+        assert_eq!(found, expected);
+        assert!(indexed_time < linear_time, "index took {:?}, linear scan {:?}", indexed_time, linear_time);
+    }
+
+    #[tokio::test]
+    async fn radius_query_clamps_its_radius() {
+        let manager = scattered_manager(50).await;
+        let center = [0.0, 64.0, 0.0];
+
+        let capped = sorted_ids(manager.get_entities_in_radius(center, MAX_QUERY_RADIUS, "world").await);
+        assert_eq!(sorted_ids(manager.get_entities_in_radius(center, f64::INFINITY, "world").await), capped);
+        assert_eq!(sorted_ids(manager.get_entities_in_radius(center, 1e12, "world").await), capped);
+        assert!(manager.get_entities_in_radius(center, f64::NAN, "world").await.is_empty());
+        assert!(manager.get_entities_in_radius(center, -1.0, "world").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn nearest_entity_matches_a_linear_scan() {
+        let manager = scattered_manager(300).await;
+        let all = manager.get_entities_in_world("world").await;
+
+        for from in [[0.0, 64.0, 0.0], [-150.0, 64.0, 10.0], [42.0, 90.0, -7.5]] {
+            let expected = all
+                .iter()
+                .map(|e| (EntityManager::distance(e.position, from), e.id.clone()))
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then_with(|| a.1.cmp(&b.1)))
+                .map(|(_, id)| id);
+            let found = manager.nearest_entity(from, "world", None, 200.0).await.map(|(e, _)| e.id);
+            assert_eq!(found, expected, "from {:?}", from);
+        }
+    }
+
+    #[tokio::test]
+    async fn nearest_entity_clamps_its_range() {
+        let mut manager = test_manager().await;
+        manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Cow), [MAX_QUERY_RADIUS * 2.0, 64.0, 0.0], "world".to_string(), None)
+            .await
+            .unwrap();
+        let from = [0.0, 64.0, 0.0];
+
+        // Returns promptly instead of walking an endless number of shells
+        assert!(manager.nearest_entity(from, "world", None, f64::INFINITY).await.is_none());
+        assert!(manager.nearest_entity(from, "world", None, f64::MAX).await.is_none());
+        assert!(manager.nearest_entity(from, "world", None, f64::NAN).await.is_none());
+        assert!(manager.nearest_entity([MAX_QUERY_RADIUS * 2.0, 64.0, 0.0], "world", None, 1.0).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn custom_kinds_cannot_shadow_built_in_types() {
+        let mut manager = test_manager().await;
+        let props = || EntityProps { default_health: 10.0, despawnable: true };
+
+        assert!(manager.register_entity_kind("Zombie", props()).is_err());
+        assert!(manager.register_entity_kind("", props()).is_err());
+        assert!(manager.register_entity_kind("ghost", props()).is_ok());
+
+        // A registered custom kind survives the untagged round trip as a custom kind
+        let kind = EntityKind::Custom("ghost".to_string());
+        let json = serde_json::to_string(&kind).unwrap();
+        assert_eq!(serde_json::from_str::<EntityKind>(&json).unwrap(), kind);
+        let built_in = serde_json::to_string(&EntityKind::BuiltIn(EntityType::Zombie)).unwrap();
+        assert_eq!(serde_json::from_str::<EntityKind>(&built_in).unwrap(), EntityKind::BuiltIn(EntityType::Zombie));
+    }
+
+    #[tokio::test]
+    async fn tick_moves_entities_by_velocity_but_not_players() {
+        let mut manager = test_manager().await;
+        let cow = manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Cow), [0.0, 64.0, 0.0], "world".to_string(), None)
+            .await
+            .unwrap();
+        let player = manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Player), [0.0, 64.0, 0.0], "world".to_string(), None)
+            .await
+            .unwrap();
+        manager.update_entity_velocity(&cow, [40.0, 0.0, 0.0]).await;
+        manager.update_entity_velocity(&player, [40.0, 0.0, 0.0]).await;
+
+        manager.tick(0.5).await;
+
+        assert_eq!(manager.get_entity(&cow).await.unwrap().position, [20.0, 64.0, 0.0]);
+        assert_eq!(manager.get_entity(&player).await.unwrap().position, [0.0, 64.0, 0.0]);
+        // The spatial index follows the entity into its new cell
+        assert_eq!(sorted_ids(manager.get_entities_in_radius([20.0, 64.0, 0.0], 1.0, "world").await), vec![cow]);
+    }
+
+    #[tokio::test]
+    async fn cleanup_removes_only_dead_entities() {
+        let mut manager = test_manager().await;
+        let cow = manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Cow), [0.0, 64.0, 0.0], "world".to_string(), None)
+            .await
+            .unwrap();
+        let pig = manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Pig), [4.0, 64.0, 0.0], "world".to_string(), None)
+            .await
+            .unwrap();
+        manager.damage_entity(&cow, 100.0).await;
+
+        assert_eq!(manager.cleanup_dead_entities().await, 1);
+        assert!(manager.get_entity(&cow).await.is_none());
+        assert!(manager.get_entity(&pig).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn nearest_entity_skips_the_dead() {
+        let mut manager = test_manager().await;
+        let near = manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Creeper), [2.0, 64.0, 0.0], "world".to_string(), None)
+            .await
+            .unwrap();
+        let far = manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Creeper), [6.0, 64.0, 0.0], "world".to_string(), None)
+            .await
+            .unwrap();
+        manager.damage_entity(&near, 100.0).await;
+
+        let found = manager.nearest_entity([0.0, 64.0, 0.0], "world", Some(EntityType::Creeper.into()), 16.0).await;
+        assert_eq!(found.map(|(e, _)| e.id), Some(far));
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use log::{info, error};
+
+use crate::worlds::block_registry::{BlockRegistry, AIR, TNT};
+use crate::systems::{
+    world_manager::{WorldInfo, WorldManager},
+    chunk_manager::ChunkManager,
+    entity_manager::{EntityManager, EntityType},
+    fluid_system::FluidSystem,
+    player_manager::{GameMode, Player, PlayerManager},
+};
+
+const RADIUS_PER_POWER: f32 = 1.5; // Blocks of reach per point of power
+const DAMAGE_PER_POWER: f32 = 4.0; // Damage at the center per point of power
+const TICK_MILLIS: u64 = 250;
+const TNT_POWER: f32 = 4.0;
+const TNT_FUSE_MILLIS: u64 = 4000;
+const CHAIN_FUSE_MILLIS: u64 = 500; // TNT caught in a blast goes off sooner
+const CREEPER_POWER: f32 = 3.0;
+const CREEPER_TRIGGER_DISTANCE: f64 = 3.0;
+const CREEPER_FUSE_MILLIS: u64 = 1500; // Walking out of range before this defuses it
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplosionSource {
+    Mob, // Creepers, subject to allow_mob_griefing
+    Block, // TNT
+}
+
+#[derive(Debug)]
+struct PrimedTnt {
+    world_id: String,
+    position: (i32, i32, i32),
+    detonates_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct ExplosionSystem {
+    block_registry: Arc<BlockRegistry>,
+    primed: Vec<PrimedTnt>,
+    creeper_fuses: HashMap<String, Instant>, // creeper id -> when it started hissing
+    disturbed: HashMap<String, HashSet<(i32, i32, i32)>>, // world_id -> neighbors of destroyed blocks
+}
+
+impl ExplosionSystem {
+    pub fn new(block_registry: Arc<BlockRegistry>) -> Self {
+        Self {
+            block_registry,
+            primed: Vec::new(),
+            creeper_fuses: HashMap::new(),
+            disturbed: HashMap::new(),
+        }
+    }
+
+    // Drained after each tick so fluids next to the crater start flowing in
+    pub fn take_disturbed(&mut self) -> HashMap<String, HashSet<(i32, i32, i32)>> {
+        std::mem::take(&mut self.disturbed)
+    }
+
+    // Lights a TNT block, the caller has already removed it from the world
+    pub fn prime(&mut self, world_id: &str, x: i32, y: i32, z: i32) {
+        self.prime_with_fuse(world_id, (x, y, z), TNT_FUSE_MILLIS);
+    }
+
+    fn prime_with_fuse(&mut self, world_id: &str, position: (i32, i32, i32), fuse_millis: u64) {
+        self.primed.push(PrimedTnt {
+            world_id: world_id.to_string(),
+            position,
+            detonates_at: Instant::now() + Duration::from_millis(fuse_millis),
+        });
+    }
+
+    // Returns the destroyed block positions so callers can broadcast them. Positions are
+    // in world coordinates, set_block routes each one to its own chunk so blasts across
+    // chunk borders need no special handling, blocks in unloaded chunks are left alone
+    #[allow(clippy::too_many_arguments)]
+    pub async fn explode(
+        &mut self,
+        world: &WorldInfo,
+        center: [f64; 3],
+        power: f32,
+        source: ExplosionSource,
+        chunk_manager: &mut ChunkManager,
+        entity_manager: &mut EntityManager,
+        player_manager: &mut PlayerManager,
+    ) -> Result<Vec<(i32, i32, i32)>, Box<dyn std::error::Error>> {
+        if power <= 0.0 {
+            return Ok(Vec::new());
+        }
+
+        let radius = power * RADIUS_PER_POWER;
+        let mut destroyed = Vec::new();
+        let mut chained = Vec::new();
+
+        let breaks_blocks = source == ExplosionSource::Block || world.settings.allow_mob_griefing;
+        if breaks_blocks {
+            let reach = radius.ceil() as i32;
+            let [cx, cy, cz] = [center[0].floor() as i32, center[1].floor() as i32, center[2].floor() as i32];
+
+            for x in cx - reach..=cx + reach {
+                for y in cy - reach..=cy + reach {
+                    for z in cz - reach..=cz + reach {
+                        let distance = Self::distance(center, [x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5]);
+                        if distance > radius {
+                            continue;
+                        }
+
+                        // Out of range y and unloaded chunks both come back as None
+                        let block_id = match chunk_manager.get_block(&world.id, x, y, z).await {
+                            Some(AIR) | None => continue,
+                            Some(block_id) => block_id,
+                        };
+
+                        // Strength falls off linearly, each point of resistance soaks up a fifth of a point
+                        let strength = power * (1.0 - distance / radius);
+                        if strength > self.block_registry.blast_resistance(block_id) / 5.0 {
+                            let neighbors = chunk_manager.set_block(&world.id, x, y, z, AIR).await?;
+                            self.disturbed.entry(world.id.clone()).or_default().extend(neighbors);
+                            destroyed.push((x, y, z));
+                            if block_id == TNT {
+                                chained.push((x, y, z));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for entity in entity_manager.get_entities_in_radius(center, radius as f64, &world.id).await {
+            if !entity.is_active {
+                continue;
+            }
+            let damage = Self::damage_at(power, radius, Self::distance(center, entity.position));
+            if damage > 0.0 {
+                entity_manager.damage_entity(&entity.id, damage).await;
+            }
+        }
+
+        for player in player_manager.get_players_in_world(&world.id).await {
+            if player.health <= 0.0 || matches!(player.game_mode, GameMode::Creative) {
+                continue;
+            }
+            let damage = Self::damage_at(power, radius, Self::distance(center, player.position));
+            if damage > 0.0 {
+                player_manager.update_player_health(&player.id, player.health - damage).await?;
+            }
+        }
+
+        for position in chained {
+            self.prime_with_fuse(&world.id, position, CHAIN_FUSE_MILLIS);
+        }
+
+        info!(
+            "Explosion of power {} at {:?} in world {} destroyed {} blocks",
+            power, center, world.id, destroyed.len()
+        );
+
+        Ok(destroyed)
+    }
+
+    // Detonates TNT whose fuse ran out and creepers that stayed next to a player long enough
+    pub async fn tick(
+        &mut self,
+        worlds: &[WorldInfo],
+        chunk_manager: &mut ChunkManager,
+        entity_manager: &mut EntityManager,
+        player_manager: &mut PlayerManager,
+    ) {
+        let now = Instant::now();
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.primed)
+            .into_iter()
+            .partition(|tnt| tnt.detonates_at <= now);
+        self.primed = pending;
+
+        for tnt in due {
+            let world = match worlds.iter().find(|w| w.id == tnt.world_id) {
+                Some(world) => world,
+                None => continue, // World was deleted while the fuse burned
+            };
+            let (x, y, z) = tnt.position;
+            let center = [x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5];
+            if let Err(e) = self.explode(world, center, TNT_POWER, ExplosionSource::Block, chunk_manager, entity_manager, player_manager).await {
+                error!("TNT explosion failed in world {}: {}", world.id, e);
+            }
+        }
+
+        let mut hissing = HashMap::new();
+        let mut detonating = Vec::new();
+        for world in worlds.iter().filter(|w| w.is_online) {
+            let targets: Vec<[f64; 3]> = player_manager
+                .get_players_in_world(&world.id)
+                .await
+                .into_iter()
+                .filter(Self::is_creeper_target)
+                .map(|p| p.position)
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+
+            for creeper in entity_manager.get_entities_in_world(&world.id).await {
+                if !creeper.is_active || !creeper.entity_type.is(EntityType::Creeper) {
+                    continue;
+                }
+                let in_range = targets
+                    .iter()
+                    .any(|target| Self::distance(creeper.position, *target) as f64 <= CREEPER_TRIGGER_DISTANCE);
+                if !in_range {
+                    continue;
+                }
+
+                let started = self.creeper_fuses.get(&creeper.id).copied().unwrap_or(now);
+                if now.duration_since(started) >= Duration::from_millis(CREEPER_FUSE_MILLIS) {
+                    detonating.push((world, creeper));
+                } else {
+                    hissing.insert(creeper.id.clone(), started);
+                }
+            }
+        }
+        // Creepers that lost their target, died or detonated drop their fuse
+        self.creeper_fuses = hissing;
+
+        for (world, creeper) in detonating {
+            entity_manager.despawn_entity(&creeper.id).await;
+            if let Err(e) = self.explode(world, creeper.position, CREEPER_POWER, ExplosionSource::Mob, chunk_manager, entity_manager, player_manager).await {
+                error!("Creeper explosion failed in world {}: {}", world.id, e);
+            }
+        }
+    }
+
+    fn is_creeper_target(player: &Player) -> bool {
+        player.health > 0.0 && matches!(player.game_mode, GameMode::Survival)
+    }
+
+    // Checked under read locks so idle ticks don't take every write lock
+    async fn needs_tick(&self, worlds: &[WorldInfo], entity_manager: &EntityManager, player_manager: &PlayerManager) -> bool {
+        let now = Instant::now();
+        // A hissing creeper needs the tick even to be defused
+        if self.primed.iter().any(|tnt| tnt.detonates_at <= now) || !self.creeper_fuses.is_empty() {
+            return true;
+        }
+
+        for world in worlds.iter().filter(|w| w.is_online) {
+            for player in player_manager.get_players_in_world(&world.id).await {
+                if !Self::is_creeper_target(&player) {
+                    continue;
+                }
+                let creeper = entity_manager
+                    .nearest_entity(player.position, &world.id, Some(EntityType::Creeper.into()), CREEPER_TRIGGER_DISTANCE)
+                    .await;
+                if creeper.is_some() {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    pub async fn run(
+        explosion_system: Arc<RwLock<ExplosionSystem>>,
+        world_manager: Arc<RwLock<WorldManager>>,
+        chunk_manager: Arc<RwLock<ChunkManager>>,
+        entity_manager: Arc<RwLock<EntityManager>>,
+        player_manager: Arc<RwLock<PlayerManager>>,
+        fluid_system: Arc<RwLock<FluidSystem>>,
+    ) {
+        let mut ticker = interval(Duration::from_millis(TICK_MILLIS));
+        loop {
+            ticker.tick().await;
+
+            let worlds = world_manager.read().await.get_all_worlds().await;
+            {
+                let explosion_system = explosion_system.read().await;
+                let entity_manager = entity_manager.read().await;
+                let player_manager = player_manager.read().await;
+                if !explosion_system.needs_tick(&worlds, &entity_manager, &player_manager).await {
+                    continue;
+                }
+            }
+
+            let disturbed = {
+                // Same lock order as the physics loop: chunks, entities, players
+                let mut explosion_system = explosion_system.write().await;
+                let mut chunk_manager = chunk_manager.write().await;
+                let mut entity_manager = entity_manager.write().await;
+                let mut player_manager = player_manager.write().await;
+
+                explosion_system.tick(&worlds, &mut chunk_manager, &mut entity_manager, &mut player_manager).await;
+                explosion_system.take_disturbed()
+            };
+
+            let mut fluid_system = fluid_system.write().await;
+            for (world_id, positions) in disturbed {
+                fluid_system.schedule(&world_id, positions);
+            }
+        }
+    }
+
+    // Falls off linearly to nothing at the edge of the blast
+    fn damage_at(power: f32, radius: f32, distance: f32) -> f32 {
+        power * DAMAGE_PER_POWER * (1.0 - distance / radius)
+    }
+
+    fn distance(a: [f64; 3], b: [f64; 3]) -> f32 {
+        let (dx, dy, dz) = (a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+        (dx * dx + dy * dy + dz * dz).sqrt() as f32
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::auth::{auth_service::AuthService, jwt_service::JwtService};
+    use crate::database::{
+        database_service::{DatabaseConfig, DatabaseService},
+        entity_repository::EntityRepository,
+        player_repository::PlayerRepository,
+    };
+    use crate::systems::{
+        entity_manager::EntityKind,
+        loot_system::LootSystem,
+        world_manager::{Difficulty, GameMode as WorldGameMode, WorldSettings},
+    };
+    use crate::worlds::{
+        biome_system::BiomeSystem,
+        block_registry::{DIRT, OBSIDIAN, STONE},
+        structure_generator::StructureGenerator,
+        terrain_generator::TerrainParams,
+    };
+
+    const Y: i32 = 200; // Open air in any generated terrain
+
+    struct Fixture {
+        explosions: ExplosionSystem,
+        world: WorldInfo,
+        chunk_manager: ChunkManager,
+        entity_manager: EntityManager,
+        player_manager: PlayerManager,
+    }
+
+    // Chunks (0, 0) and (1, 0) are loaded, so blasts can straddle x = 16
+    async fn fixture(allow_mob_griefing: bool) -> Fixture {
+        let config = DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            ..DatabaseConfig::default()
+        };
+        let database_service = Arc::new(DatabaseService::new(&config).await.unwrap());
+        let player_repository = Arc::new(PlayerRepository::new(database_service.clone()));
+        let auth_service = Arc::new(AuthService::new(player_repository.clone(), Arc::new(JwtService::new("x".repeat(32)).unwrap())));
+        let loot_system = Arc::new(LootSystem::new());
+        let block_registry = Arc::new(BlockRegistry::new());
+
+        let mut chunk_manager = ChunkManager::new(
+            1,
+            TerrainParams::default(),
+            Arc::new(BiomeSystem::new()),
+            Arc::new(StructureGenerator::new(loot_system.clone())),
+            block_registry.clone(),
+            std::env::temp_dir().join("strixcraft-explosion-tests"),
+        );
+        chunk_manager.register_world("world", 7);
+        chunk_manager.get_chunk("world", 0, 0).await.unwrap();
+        chunk_manager.get_chunk("world", 1, 0).await.unwrap();
+
+        let world = WorldInfo {
+            id: "world".to_string(),
+            name: "world".to_string(),
+            seed: 7,
+            game_mode: WorldGameMode::Survival,
+            player_count: 0,
+            max_players: 10,
+            created_at: Utc::now(),
+            last_active: Utc::now(),
+            is_online: true,
+            settings: WorldSettings {
+                allow_pvp: false,
+                allow_mob_griefing,
+                keep_inventory: false,
+                natural_regeneration: true,
+                difficulty: Difficulty::Normal,
+                weather_enabled: false,
+                time_enabled: false,
+                mobs_enabled: false,
+                physics_enabled: false,
+                game_rules: HashMap::new(),
+                max_entities: None,
+            },
+            spawn_point: [0.0, 64.0, 0.0],
+            border: None,
+            whitelist: None,
+            whitelist_enabled: false,
+        };
+
+        Fixture {
+            explosions: ExplosionSystem::new(block_registry),
+            world,
+            chunk_manager,
+            entity_manager: EntityManager::new(Arc::new(EntityRepository::new(database_service)), loot_system),
+            player_manager: PlayerManager::new(player_repository, auth_service),
+        }
+    }
+
+    impl Fixture {
+        async fn place(&mut self, blocks: &[((i32, i32, i32), u8)]) {
+            for &((x, y, z), block_id) in blocks {
+                self.chunk_manager.set_block("world", x, y, z, block_id).await.unwrap();
+            }
+        }
+
+        async fn block(&self, (x, y, z): (i32, i32, i32)) -> u8 {
+            self.chunk_manager.get_block("world", x, y, z).await.unwrap()
+        }
+
+        async fn explode(&mut self, center: [f64; 3], power: f32, source: ExplosionSource) -> Vec<(i32, i32, i32)> {
+            self.explosions
+                .explode(&self.world, center, power, source, &mut self.chunk_manager, &mut self.entity_manager, &mut self.player_manager)
+                .await
+                .unwrap()
+        }
+
+        async fn cow_at(&mut self, position: [f64; 3]) -> String {
+            self.entity_manager
+                .spawn_entity(EntityKind::BuiltIn(EntityType::Cow), position, "world".to_string(), None)
+                .await
+                .unwrap()
+        }
+
+        async fn health(&self, entity_id: &str) -> f32 {
+            self.entity_manager.get_entity(entity_id).await.map_or(0.0, |e| e.health)
+        }
+
+        async fn needs_tick(&self) -> bool {
+            self.explosions.needs_tick(std::slice::from_ref(&self.world), &self.entity_manager, &self.player_manager).await
+        }
+    }
+
+    #[tokio::test]
+    async fn strength_falls_off_with_distance() {
+        let mut fixture = fixture(false).await;
+        // TNT power 4 reaches 6 blocks. Stone (resistance 6) only breaks within 4.2, dirt almost to the edge
+        fixture.place(&[((10, Y, 8), STONE), ((13, Y, 8), STONE), ((8, Y, 13), DIRT)]).await;
+        let near = fixture.cow_at([8.5, Y as f64 + 1.5, 8.5]).await;
+        let far = fixture.cow_at([8.5, Y as f64 + 0.5, 4.0]).await;
+        let (near_before, far_before) = (fixture.health(&near).await, fixture.health(&far).await);
+
+        let destroyed = fixture.explode([8.5, Y as f64 + 0.5, 8.5], TNT_POWER, ExplosionSource::Block).await;
+
+        assert!(destroyed.contains(&(10, Y, 8)));
+        assert_eq!(fixture.block((13, Y, 8)).await, STONE);
+        assert_eq!(fixture.block((8, Y, 13)).await, AIR);
+        let near_damage = near_before - fixture.health(&near).await;
+        let far_damage = far_before - fixture.health(&far).await;
+        assert!(near_damage > far_damage && far_damage > 0.0, "near {} far {}", near_damage, far_damage);
+        assert_eq!(ExplosionSystem::damage_at(TNT_POWER, 6.0, 6.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn blast_resistant_blocks_survive() {
+        let mut fixture = fixture(false).await;
+        fixture.place(&[((9, Y, 8), OBSIDIAN), ((7, Y, 8), STONE)]).await;
+
+        let destroyed = fixture.explode([8.5, Y as f64 + 0.5, 8.5], TNT_POWER, ExplosionSource::Block).await;
+
+        assert_eq!(destroyed, vec![(7, Y, 8)]);
+        assert_eq!(fixture.block((9, Y, 8)).await, OBSIDIAN);
+        // Fluids around the crater get a look on the next fluid tick
+        let disturbed = fixture.explosions.take_disturbed();
+        assert!(disturbed["world"].contains(&(6, Y, 8)) && disturbed["world"].contains(&(7, Y + 1, 8)));
+        assert!(fixture.explosions.take_disturbed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn mob_blasts_only_break_blocks_with_mob_griefing() {
+        let mut strict = fixture(false).await;
+        strict.place(&[((9, Y, 8), DIRT)]).await;
+        let cow = strict.cow_at([8.5, Y as f64 + 1.0, 8.5]).await;
+        let before = strict.health(&cow).await;
+
+        assert!(strict.explode([8.5, Y as f64 + 0.5, 8.5], CREEPER_POWER, ExplosionSource::Mob).await.is_empty());
+        assert_eq!(strict.block((9, Y, 8)).await, DIRT);
+        // Creatures are hurt either way
+        assert!(strict.health(&cow).await < before);
+
+        let mut griefing = fixture(true).await;
+        griefing.place(&[((9, Y, 8), DIRT)]).await;
+        assert_eq!(griefing.explode([8.5, Y as f64 + 0.5, 8.5], CREEPER_POWER, ExplosionSource::Mob).await, vec![(9, Y, 8)]);
+    }
+
+    #[tokio::test]
+    async fn blasts_straddle_chunk_borders() {
+        let mut fixture = fixture(false).await;
+        fixture.place(&[((15, Y, 8), STONE), ((16, Y, 8), STONE)]).await;
+
+        let mut destroyed = fixture.explode([16.0, Y as f64 + 0.5, 8.5], TNT_POWER, ExplosionSource::Block).await;
+        destroyed.sort();
+
+        assert_eq!(destroyed, vec![(15, Y, 8), (16, Y, 8)]);
+        assert_eq!(fixture.block((15, Y, 8)).await, AIR);
+        assert_eq!(fixture.block((16, Y, 8)).await, AIR);
+    }
+
+    #[tokio::test]
+    async fn caught_tnt_goes_off_on_a_short_fuse() {
+        let mut fixture = fixture(false).await;
+        fixture.place(&[((11, Y, 8), TNT), ((14, Y, 8), STONE)]).await;
+
+        fixture.explode([8.5, Y as f64 + 0.5, 8.5], TNT_POWER, ExplosionSource::Block).await;
+        assert_eq!(fixture.block((11, Y, 8)).await, AIR);
+        assert_eq!(fixture.explosions.primed.len(), 1);
+        let fuse = fixture.explosions.primed[0].detonates_at.duration_since(Instant::now());
+        assert!(fuse <= Duration::from_millis(CHAIN_FUSE_MILLIS));
+
+        // Not due yet, nothing happens
+        let worlds = vec![fixture.world.clone()];
+        fixture
+            .explosions
+            .tick(&worlds, &mut fixture.chunk_manager, &mut fixture.entity_manager, &mut fixture.player_manager)
+            .await;
+        assert_eq!(fixture.block((14, Y, 8)).await, STONE);
+
+        fixture.explosions.primed[0].detonates_at = Instant::now();
+        fixture
+            .explosions
+            .tick(&worlds, &mut fixture.chunk_manager, &mut fixture.entity_manager, &mut fixture.player_manager)
+            .await;
+        assert!(fixture.explosions.primed.is_empty());
+        assert_eq!(fixture.block((14, Y, 8)).await, AIR);
+    }
+
+    #[tokio::test]
+    async fn idle_ticks_are_skipped() {
+        let mut fixture = fixture(false).await;
+        let player = fixture.player_manager.register_player("alex", "correct horse battery").await.unwrap();
+        fixture.player_manager.authenticate_player("alex", "correct horse battery").await.unwrap().unwrap();
+        fixture.player_manager.set_player_world(&player.id, Some("world".to_string())).await.unwrap();
+        fixture.player_manager.teleport_player(&player.id, [8.5, Y as f64, 8.5]).await.unwrap();
+
+        assert!(!fixture.needs_tick().await);
+
+        // Primed but still burning
+        fixture.explosions.prime("world", 0, Y, 0);
+        assert!(!fixture.needs_tick().await);
+
+        let creeper = fixture
+            .entity_manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Creeper), [10.0, Y as f64, 8.5], "world".to_string(), None)
+            .await
+            .unwrap();
+        assert!(fixture.needs_tick().await);
+
+        fixture.entity_manager.update_entity_position(&creeper, [30.0, Y as f64, 8.5], None).await;
+        assert!(!fixture.needs_tick().await);
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use log::{info, warn};
+
+use crate::worlds::block_registry::{AIR, COBBLESTONE, LAVA, OBSIDIAN, STILL_LAVA, STILL_WATER, STONE, WATER};
+use crate::systems::chunk_manager::ChunkManager;
+
+const TICK_MILLIS: u64 = 250; // Water spreads one block per tick
+const LAVA_TICK_INTERVAL: u64 = 3; // Lava only moves every third tick
+const MAX_UPDATES_PER_TICK: usize = 512; // Leftover updates wait for the next tick
+const HORIZONTAL_OFFSETS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+type BlockPos = (i32, i32, i32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fluid {
+    Water,
+    Lava,
+}
+
+impl Fluid {
+    fn of(block_id: u8) -> Option<Fluid> {
+        match block_id {
+            WATER | STILL_WATER => Some(Fluid::Water),
+            LAVA | STILL_LAVA => Some(Fluid::Lava),
+            _ => None,
+        }
+    }
+
+    // Flowing blocks use the moving id, sources keep whatever id they were placed with
+    fn flowing_id(self) -> u8 {
+        match self {
+            Fluid::Water => WATER,
+            Fluid::Lava => LAVA,
+        }
+    }
+
+    // Furthest level a flow reaches from its source, the level lives in the block metadata
+    // with 0 for a source
+    fn max_level(self) -> u8 {
+        match self {
+            Fluid::Water => 7,
+            Fluid::Lava => 3,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FluidSystem {
+    pending: VecDeque<(String, BlockPos)>,
+    queued: HashSet<(String, BlockPos)>,
+    ticks: u64,
+}
+
+impl Default for FluidSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FluidSystem {
+    pub fn new() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            queued: HashSet::new(),
+            ticks: 0,
+        }
+    }
+
+    // Fed with the positions set_block hands back, only these get looked at on the next tick
+    pub fn schedule(&mut self, world_id: &str, positions: impl IntoIterator<Item = BlockPos>) {
+        for position in positions {
+            let key = (world_id.to_string(), position);
+            if self.queued.insert(key.clone()) {
+                self.pending.push_back(key);
+            }
+        }
+    }
+
+    pub fn pending_updates(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub async fn tick(&mut self, chunk_manager: &mut ChunkManager) -> usize {
+        self.ticks += 1;
+        let lava_tick = self.ticks.is_multiple_of(LAVA_TICK_INTERVAL);
+
+        // Updates scheduled while processing belong to the next tick, so flows advance one block at a time
+        let budget = self.pending.len().min(MAX_UPDATES_PER_TICK);
+        let batch: Vec<_> = self.pending.drain(..budget).collect();
+        for key in &batch {
+            self.queued.remove(key);
+        }
+
+        let mut processed = 0;
+        for (world_id, position) in batch {
+            let block_id = match Self::block_at(chunk_manager, &world_id, position).await {
+                Some(block_id) => block_id,
+                None => continue, // Unloaded, the chunk's fluids settle again once something changes nearby
+            };
+
+            let fluid = match Fluid::of(block_id) {
+                Some(fluid) => fluid,
+                None => continue,
+            };
+            if fluid == Fluid::Lava && !lava_tick {
+                self.schedule(&world_id, [position]);
+                continue;
+            }
+
+            if let Err(e) = self.update_fluid(chunk_manager, &world_id, position, fluid).await {
+                warn!("Fluid update at {:?} in world {} failed: {}", position, world_id, e);
+            }
+            processed += 1;
+        }
+
+        processed
+    }
+
+    async fn update_fluid(
+        &mut self,
+        chunk_manager: &mut ChunkManager,
+        world_id: &str,
+        position: BlockPos,
+        fluid: Fluid,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (x, y, z) = position;
+        let level = chunk_manager.get_block_metadata(world_id, x, y, z).await.unwrap_or(0);
+
+        if fluid == Fluid::Lava && self.quench_lava(chunk_manager, world_id, position, level).await? {
+            return Ok(());
+        }
+
+        // Flowing blocks keep only the level their best feeder allows and dry up without one
+        if level > 0 {
+            let expected = match self.fed_level(chunk_manager, world_id, position, fluid).await {
+                Some(expected) if expected <= fluid.max_level() => expected,
+                _ => {
+                    self.set_fluid(chunk_manager, world_id, position, AIR, 0).await?;
+                    return Ok(());
+                }
+            };
+            if expected != level {
+                self.set_fluid(chunk_manager, world_id, position, fluid.flowing_id(), expected).await?;
+                return Ok(());
+            }
+        }
+
+        // Falling takes priority, a column only spreads sideways once it lands
+        let below = (x, y - 1, z);
+        let below_block = Self::block_at(chunk_manager, world_id, below).await;
+        match below_block {
+            Some(AIR) => {
+                self.set_fluid(chunk_manager, world_id, below, fluid.flowing_id(), 1).await?;
+                return Ok(());
+            }
+            Some(block_id) if fluid == Fluid::Water && Fluid::of(block_id) == Some(Fluid::Lava) => {
+                self.set_fluid(chunk_manager, world_id, below, STONE, 0).await?;
+                return Ok(());
+            }
+            _ => {}
+        }
+        // Already a column below, spreading sideways from here would just flood the top
+        if below_block.and_then(Fluid::of) == Some(fluid)
+            && chunk_manager.get_block_metadata(world_id, x, y - 1, z).await.unwrap_or(0) > 0
+        {
+            return Ok(());
+        }
+
+        let next = level + 1;
+        if next > fluid.max_level() {
+            return Ok(());
+        }
+
+        for (dx, dz) in HORIZONTAL_OFFSETS {
+            let target = (x + dx, y, z + dz);
+            match Self::block_at(chunk_manager, world_id, target).await {
+                Some(AIR) => {
+                    self.set_fluid(chunk_manager, world_id, target, fluid.flowing_id(), next).await?;
+                }
+                Some(block_id) if Fluid::of(block_id) == Some(fluid) => {
+                    let target_level = chunk_manager.get_block_metadata(world_id, target.0, target.1, target.2).await.unwrap_or(0);
+                    if target_level > next {
+                        self.set_fluid(chunk_manager, world_id, target, fluid.flowing_id(), next).await?;
+                    }
+                }
+                _ => {} // Solid, unloaded or the other fluid, which quenches itself on its own update
+            }
+        }
+
+        Ok(())
+    }
+
+    // Lava touching water from the side or above hardens, sources into obsidian and flows into cobblestone
+    async fn quench_lava(
+        &mut self,
+        chunk_manager: &mut ChunkManager,
+        world_id: &str,
+        position: BlockPos,
+        level: u8,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let (x, y, z) = position;
+        let mut touching = HORIZONTAL_OFFSETS.iter().map(|(dx, dz)| (x + dx, y, z + dz)).collect::<Vec<_>>();
+        touching.push((x, y + 1, z));
+
+        for neighbor in touching {
+            if let Some(block_id) = Self::block_at(chunk_manager, world_id, neighbor).await {
+                if Fluid::of(block_id) == Some(Fluid::Water) {
+                    let hardened = if level == 0 { OBSIDIAN } else { COBBLESTONE };
+                    self.set_fluid(chunk_manager, world_id, position, hardened, 0).await?;
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    // Level a flowing block should have given its neighbors, None when nothing feeds it
+    async fn fed_level(&self, chunk_manager: &ChunkManager, world_id: &str, position: BlockPos, fluid: Fluid) -> Option<u8> {
+        let (x, y, z) = position;
+
+        if let Some(above) = Self::block_at(chunk_manager, world_id, (x, y + 1, z)).await {
+            if Fluid::of(above) == Some(fluid) {
+                return Some(1);
+            }
+        }
+
+        let mut best: Option<u8> = None;
+        for (dx, dz) in HORIZONTAL_OFFSETS {
+            let (nx, nz) = (x + dx, z + dz);
+            match Self::block_at(chunk_manager, world_id, (nx, y, nz)).await {
+                Some(block_id) if Fluid::of(block_id) == Some(fluid) => {}
+                _ => continue,
+            }
+
+            // Only blocks resting on something spread sideways, falling ones don't feed their neighbors
+            let resting = match Self::block_at(chunk_manager, world_id, (nx, y - 1, nz)).await {
+                Some(AIR) | None => false,
+                Some(_) => true,
+            };
+            if !resting {
+                continue;
+            }
+
+            let neighbor_level = chunk_manager.get_block_metadata(world_id, nx, y, nz).await.unwrap_or(0);
+            best = Some(best.map_or(neighbor_level + 1, |b| b.min(neighbor_level + 1)));
+        }
+
+        best
+    }
+
+    async fn set_fluid(
+        &mut self,
+        chunk_manager: &mut ChunkManager,
+        world_id: &str,
+        position: BlockPos,
+        block_id: u8,
+        level: u8,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (x, y, z) = position;
+        let neighbors = chunk_manager.set_block(world_id, x, y, z, block_id).await?;
+        chunk_manager.set_block_metadata(world_id, x, y, z, level).await?;
+
+        self.schedule(world_id, neighbors);
+        self.schedule(world_id, [position]);
+        Ok(())
+    }
+
+    async fn block_at(chunk_manager: &ChunkManager, world_id: &str, position: BlockPos) -> Option<u8> {
+        let (x, y, z) = position;
+        chunk_manager.get_block(world_id, x, y, z).await
+    }
+
+    pub async fn run(fluid_system: Arc<RwLock<FluidSystem>>, chunk_manager: Arc<RwLock<ChunkManager>>) {
+        let mut ticker = interval(Duration::from_millis(TICK_MILLIS));
+        loop {
+            ticker.tick().await;
+
+            if fluid_system.read().await.pending_updates() == 0 {
+                continue;
+            }
+
+            // Chunk lock first, same order as the message handler
+            let mut chunk_manager = chunk_manager.write().await;
+            let mut fluid_system = fluid_system.write().await;
+            let processed = fluid_system.tick(&mut chunk_manager).await;
+            if fluid_system.pending_updates() > MAX_UPDATES_PER_TICK {
+                info!("Fluid system processed {} updates, {} still pending", processed, fluid_system.pending_updates());
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::loot_system::LootSystem;
+    use crate::worlds::{
+        biome_system::BiomeSystem,
+        block_registry::BlockRegistry,
+        structure_generator::StructureGenerator,
+        terrain_generator::TerrainParams,
+    };
+
+    const Y: i32 = 200; // Open air in any generated terrain
+
+    // Chunk (0, 0) with a stone floor under Y
+    async fn floored_chunk() -> ChunkManager {
+        let loot_system = Arc::new(LootSystem::new());
+        let mut chunk_manager = ChunkManager::new(
+            1,
+            TerrainParams::default(),
+            Arc::new(BiomeSystem::new()),
+            Arc::new(StructureGenerator::new(loot_system)),
+            Arc::new(BlockRegistry::new()),
+            std::env::temp_dir().join("strixcraft-fluid-tests"),
+        );
+        chunk_manager.register_world("world", 7);
+        chunk_manager.get_chunk("world", 0, 0).await.unwrap();
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk_manager.set_block("world", x, Y - 1, z, STONE).await.unwrap();
+            }
+        }
+        chunk_manager
+    }
+
+    async fn place(fluids: &mut FluidSystem, chunk_manager: &mut ChunkManager, position: BlockPos, block_id: u8, level: u8) {
+        let (x, y, z) = position;
+        chunk_manager.set_block("world", x, y, z, block_id).await.unwrap();
+        chunk_manager.set_block_metadata("world", x, y, z, level).await.unwrap();
+        fluids.schedule("world", [position]);
+    }
+
+    async fn settle(fluids: &mut FluidSystem, chunk_manager: &mut ChunkManager) {
+        for _ in 0..200 {
+            if fluids.pending_updates() == 0 {
+                return;
+            }
+            fluids.tick(chunk_manager).await;
+        }
+        panic!("fluids never settled");
+    }
+
+    async fn block(chunk_manager: &ChunkManager, (x, y, z): BlockPos) -> (u8, u8) {
+        (
+            chunk_manager.get_block("world", x, y, z).await.unwrap(),
+            chunk_manager.get_block_metadata("world", x, y, z).await.unwrap_or(0),
+        )
+    }
+
+    #[tokio::test]
+    async fn water_spreads_one_level_per_block() {
+        let mut chunk_manager = floored_chunk().await;
+        let mut fluids = FluidSystem::new();
+        place(&mut fluids, &mut chunk_manager, (8, Y, 8), STILL_WATER, 0).await;
+
+        fluids.tick(&mut chunk_manager).await;
+        assert_eq!(block(&chunk_manager, (9, Y, 8)).await, (WATER, 1));
+        assert_eq!(block(&chunk_manager, (10, Y, 8)).await.0, AIR);
+
+        settle(&mut fluids, &mut chunk_manager).await;
+        assert_eq!(block(&chunk_manager, (8, Y, 8)).await, (STILL_WATER, 0));
+        assert_eq!(block(&chunk_manager, (11, Y, 8)).await, (WATER, 3));
+        assert_eq!(block(&chunk_manager, (10, Y, 10)).await, (WATER, 4));
+        assert_eq!(block(&chunk_manager, (1, Y, 8)).await, (WATER, 7));
+        assert_eq!(block(&chunk_manager, (0, Y, 8)).await.0, AIR);
+    }
+
+    #[tokio::test]
+    async fn solid_blocks_stop_the_flow() {
+        let mut chunk_manager = floored_chunk().await;
+        let mut fluids = FluidSystem::new();
+        for z in 0..16 {
+            chunk_manager.set_block("world", 10, Y, z, STONE).await.unwrap();
+        }
+        place(&mut fluids, &mut chunk_manager, (8, Y, 8), STILL_WATER, 0).await;
+
+        settle(&mut fluids, &mut chunk_manager).await;
+        assert_eq!(block(&chunk_manager, (9, Y, 8)).await, (WATER, 1));
+        assert_eq!(block(&chunk_manager, (10, Y, 8)).await.0, STONE);
+        for z in 0..16 {
+            assert_eq!(block(&chunk_manager, (11, Y, z)).await.0, AIR);
+        }
+    }
+
+    #[tokio::test]
+    async fn water_hardens_lava() {
+        let mut chunk_manager = floored_chunk().await;
+        let mut fluids = FluidSystem::new();
+        // A lava source and a lava flow, each with water beside it
+        place(&mut fluids, &mut chunk_manager, (2, Y, 2), STILL_LAVA, 0).await;
+        place(&mut fluids, &mut chunk_manager, (2, Y, 3), STILL_WATER, 0).await;
+        place(&mut fluids, &mut chunk_manager, (12, Y, 12), LAVA, 2).await;
+        place(&mut fluids, &mut chunk_manager, (12, Y, 13), STILL_WATER, 0).await;
+        // Water falling onto lava
+        place(&mut fluids, &mut chunk_manager, (6, Y, 12), STILL_LAVA, 0).await;
+        place(&mut fluids, &mut chunk_manager, (6, Y + 1, 12), STILL_WATER, 0).await;
+
+        for _ in 0..LAVA_TICK_INTERVAL {
+            fluids.tick(&mut chunk_manager).await;
+        }
+        assert_eq!(block(&chunk_manager, (2, Y, 2)).await.0, OBSIDIAN);
+        assert_eq!(block(&chunk_manager, (12, Y, 12)).await.0, COBBLESTONE);
+        assert_eq!(block(&chunk_manager, (6, Y, 12)).await.0, STONE);
+    }
+
+    #[tokio::test]
+    async fn each_tick_is_capped() {
+        let mut chunk_manager = floored_chunk().await;
+        let mut fluids = FluidSystem::new();
+        fluids.schedule("world", (0..600).map(|i| (i % 16, Y + 1 + i / 256, (i / 16) % 16)));
+        assert_eq!(fluids.pending_updates(), 600);
+
+        fluids.tick(&mut chunk_manager).await;
+        assert_eq!(fluids.pending_updates(), 600 - MAX_UPDATES_PER_TICK);
+        fluids.tick(&mut chunk_manager).await;
+        assert_eq!(fluids.pending_updates(), 0);
+    }
+}
@@ -1,10 +1,16 @@
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
-use log::{info, warn, error};
+
+use crate::systems::item_registry::ItemRegistry;
+
+pub const DEFAULT_INVENTORY_SLOTS: usize = 36; // Main inventory including the hotbar
+pub const HOTBAR_SLOTS: usize = 9;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InventoryItem {
     pub id: u32,
     pub count: u32,
+    #[serde(default, with = "crate::networking::protocol::json_field::option")]
     pub metadata: Option<serde_json::Value>,
     pub slot: usize,
 }
@@ -15,14 +21,27 @@ pub struct Inventory {
     pub size: usize,
     pub hotbar_size: usize,
     pub selected_slot: usize,
+    #[serde(default)]
+    pub armor: [Option<InventoryItem>; 4], // helmet, chestplate, leggings, boots
+    #[serde(default)]
+    pub offhand: Option<InventoryItem>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToolState {
+    Exempt, // Not a tool, marked unbreakable, or used in creative
+    Worn { remaining: u32 },
+    Broken, // Used up and removed from its slot
 }
 
 #[derive(Debug)]
-pub struct InventorySystem;
+pub struct InventorySystem {
+    item_registry: Arc<ItemRegistry>,
+}
 
 impl InventorySystem {
-    pub fn new() -> Self {
-        Self
+    pub fn new(item_registry: Arc<ItemRegistry>) -> Self {
+        Self { item_registry }
     }
 
     pub fn create_inventory(size: usize, hotbar_size: usize) -> Inventory {
@@ -31,6 +50,8 @@ impl InventorySystem {
             size,
             hotbar_size,
             selected_slot: 0,
+            armor: Default::default(),
+            offhand: None,
         }
     }
 
@@ -42,33 +63,14 @@ impl InventorySystem {
         metadata: Option<serde_json::Value>,
     ) -> Result<u32, String> {
         let mut remaining = count;
+        let max_stack = self.get_max_stack_size(item_id);
 
         // First, try to stack with existing items
-        for item in inventory.items.iter_mut() {
-            if let Some(existing_item) = item {
-                if existing_item.id == item_id && existing_item.count < 64 {
-                    let space_left = 64 - existing_item.count;
-                    let to_add = std::cmp::min(remaining, space_left);
-                    existing_item.count += to_add;
-                    remaining -= to_add;
-
-                    if remaining == 0 {
-                        return Ok(0);
-                    }
-                }
-            }
-        }
-
-        // Then, find empty slots
-        for (slot, item) in inventory.items.iter_mut().enumerate() {
-            if item.is_none() {
-                let to_add = std::cmp::min(remaining, 64);
-                *item = Some(InventoryItem {
-                    id: item_id,
-                    count: to_add,
-                    metadata: metadata.clone(),
-                    slot,
-                });
+        for existing_item in inventory.items.iter_mut().flatten() {
+            if existing_item.id == item_id && existing_item.count < max_stack {
+                let space_left = max_stack - existing_item.count;
+                let to_add = std::cmp::min(remaining, space_left);
+                existing_item.count += to_add;
                 remaining -= to_add;
 
                 if remaining == 0 {
@@ -77,15 +79,59 @@ impl InventorySystem {
             }
         }
 
+        // Then, fill empty slots
+        while remaining > 0 {
+            let slot = match self.find_empty_slot(inventory) {
+                Some(slot) => slot,
+                None => break,
+            };
+
+            let to_add = std::cmp::min(remaining, max_stack);
+            inventory.items[slot] = Some(InventoryItem {
+                id: item_id,
+                count: to_add,
+                metadata: metadata.clone(),
+                slot,
+            });
+            remaining -= to_add;
+        }
+
         Ok(remaining) // Return remaining items that couldn't be added
     }
 
+    pub fn find_empty_slot(&self, inventory: &Inventory) -> Option<usize> {
+        inventory.items.iter().position(|item| item.is_none())
+    }
+
+    pub fn count_free_space(&self, inventory: &Inventory, item_id: u32) -> u32 {
+        let max_stack = self.get_max_stack_size(item_id);
+
+        inventory
+            .items
+            .iter()
+            .map(|item| match item {
+                Some(existing_item) if existing_item.id == item_id => {
+                    max_stack.saturating_sub(existing_item.count)
+                }
+                Some(_) => 0,
+                None => max_stack,
+            })
+            .sum()
+    }
+
+    // With infinite_items (set for creative players) stacks are never depleted; the removal
+    // always succeeds and the inventory is left untouched
     pub fn remove_item(
         &self,
         inventory: &mut Inventory,
         item_id: u32,
         count: u32,
+        infinite_items: bool,
     ) -> Result<u32, String> {
+        if infinite_items {
+            return Ok(0);
+        }
+
         let mut remaining = count;
 
         for item in inventory.items.iter_mut() {
@@ -109,6 +155,117 @@ impl InventorySystem {
         Ok(remaining) // Return remaining items that couldn't be removed
     }
 
+    // Same creative rule as remove_item, creative players never wear their tools out
+    pub fn damage_tool(
+        &self,
+        inventory: &mut Inventory,
+        slot: usize,
+        amount: u32,
+        creative: bool,
+    ) -> ToolState {
+        if creative {
+            return ToolState::Exempt;
+        }
+
+        let item = match inventory.items.get_mut(slot).and_then(|item| item.as_mut()) {
+            Some(item) => item,
+            None => return ToolState::Exempt,
+        };
+
+        let state = self.wear_tool(item.id, &mut item.metadata, amount);
+        if state == ToolState::Broken {
+            inventory.items[slot] = None;
+        }
+        state
+    }
+
+    // Remaining uses live in the item's metadata as "durability", a fresh tool has none
+    // and starts at the registry's tool_durability. Leaves removing a broken tool to the caller
+    pub fn wear_tool(&self, item_id: u32, metadata: &mut Option<serde_json::Value>, amount: u32) -> ToolState {
+        let max_durability = match self.item_registry.get(item_id).tool_durability {
+            Some(durability) => durability,
+            None => return ToolState::Exempt,
+        };
+
+        let field = |name: &str| metadata.as_ref().and_then(|m| m.get(name)).cloned();
+        if field("unbreakable").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return ToolState::Exempt;
+        }
+
+        let durability = field("durability")
+            .and_then(|v| v.as_u64())
+            .map_or(max_durability, |d| d.min(max_durability as u64) as u32);
+        let remaining = durability.saturating_sub(amount);
+        if remaining == 0 {
+            return ToolState::Broken;
+        }
+
+        match metadata.as_mut().and_then(|m| m.as_object_mut()) {
+            Some(fields) => {
+                fields.insert("durability".to_string(), remaining.into());
+            }
+            None => *metadata = Some(serde_json::json!({ "durability": remaining })),
+        }
+
+        ToolState::Worn { remaining }
+    }
+
+    pub fn drop_item(
+        &self,
+        inventory: &mut Inventory,
+        slot: usize,
+        count: Option<u32>,
+    ) -> Option<InventoryItem> {
+        let item = inventory.items.get_mut(slot)?.as_mut()?;
+        let to_drop = count.map_or(item.count, |count| count.min(item.count));
+
+        if to_drop == 0 {
+            return None;
+        }
+
+        // Hand the whole stack over if nothing is left behind
+        if to_drop == item.count {
+            return inventory.items[slot].take();
+        }
+
+        item.count -= to_drop;
+
+        Some(InventoryItem {
+            id: item.id,
+            count: to_drop,
+            metadata: item.metadata.clone(),
+            slot,
+        })
+    }
+
+    pub fn transfer_item(
+        &self,
+        from: &mut Inventory,
+        from_slot: usize,
+        to: &mut Inventory,
+    ) -> Result<u32, String> {
+        if from_slot >= from.size {
+            return Err("Invalid slot".to_string());
+        }
+
+        let item = match from.items[from_slot].take() {
+            Some(item) => item,
+            None => return Err("Slot is empty".to_string()),
+        };
+
+        let remaining = self.add_item(to, item.id, item.count, item.metadata.clone())?;
+
+        // Whatever didn't fit (all of it if `to` is full) stays in the source slot
+        if remaining > 0 {
+            from.items[from_slot] = Some(InventoryItem {
+                count: remaining,
+                ..item
+            });
+        }
+
+        Ok(remaining)
+    }
+
     pub fn get_item_count(&self, inventory: &Inventory, item_id: u32) -> u32 {
         inventory
             .items
@@ -123,7 +280,7 @@ impl InventorySystem {
         self.get_item_count(inventory, item_id) >= count
     }
 
-    pub fn get_selected_item(&self, inventory: &Inventory) -> Option<&InventoryItem> {
+    pub fn get_selected_item<'a>(&self, inventory: &'a Inventory) -> Option<&'a InventoryItem> {
         if inventory.selected_slot < inventory.hotbar_size {
             inventory.items.get(inventory.selected_slot)?.as_ref()
         } else {
@@ -165,35 +322,145 @@ impl InventorySystem {
         Ok(())
     }
 
+    // Moves exactly `amount` into the target (or the first empty slot), the source keeps the rest.
+    // Unlike the old halving split a full inventory is an error rather than a silent no-op
     pub fn split_stack(
         &self,
         inventory: &mut Inventory,
         slot: usize,
+        amount: u32,
+        target: Option<usize>,
     ) -> Result<(), String> {
         if slot >= inventory.size {
             return Err("Invalid slot".to_string());
         }
 
-        if let Some(item) = &mut inventory.items[slot] {
-            if item.count > 1 {
-                let half = item.count / 2;
-                item.count -= half;
-
-                // Find an empty slot for the split stack
-                for (empty_slot, empty_item) in inventory.items.iter_mut().enumerate() {
-                    if empty_item.is_none() {
-                        *empty_item = Some(InventoryItem {
-                            id: item.id,
-                            count: half,
-                            metadata: item.metadata.clone(),
-                            slot: empty_slot,
-                        });
-                        break;
-                    }
+        let (item_id, count, metadata) = match &inventory.items[slot] {
+            Some(item) => (item.id, item.count, item.metadata.clone()),
+            None => return Err("Slot is empty".to_string()),
+        };
+
+        if amount == 0 || amount >= count {
+            return Err(format!("Cannot split {} from a stack of {}", amount, count));
+        }
+
+        // Auto-pick the first empty slot when no target is given
+        let target_slot = match target {
+            Some(target_slot) => target_slot,
+            None => self
+                .find_empty_slot(inventory)
+                .ok_or("No empty slot available")?,
+        };
+
+        if target_slot >= inventory.size || target_slot == slot {
+            return Err("Invalid target slot".to_string());
+        }
+
+        match &mut inventory.items[target_slot] {
+            Some(existing_item) => {
+                if existing_item.id != item_id {
+                    return Err("Target slot holds a different item".to_string());
                 }
+                if existing_item.count + amount > self.get_max_stack_size(item_id) {
+                    return Err("Target stack is full".to_string());
+                }
+                existing_item.count += amount;
+            }
+            empty => {
+                *empty = Some(InventoryItem {
+                    id: item_id,
+                    count: amount,
+                    metadata,
+                    slot: target_slot,
+                });
             }
         }
 
+        if let Some(item) = &mut inventory.items[slot] {
+            item.count -= amount;
+        }
+
+        Ok(())
+    }
+
+    // The original split: half rounded down moves out, so the source keeps the larger half
+    pub fn split_half(&self, inventory: &mut Inventory, slot: usize) -> Result<(), String> {
+        let count = inventory
+            .items
+            .get(slot)
+            .and_then(|item| item.as_ref())
+            .map_or(0, |item| item.count);
+        if count < 2 {
+            return Err("Stack is too small to split".to_string());
+        }
+
+        self.split_stack(inventory, slot, count / 2, None)
+    }
+
+    pub fn sort_inventory(&self, inventory: &mut Inventory, preserve_hotbar: bool) {
+        let start = if preserve_hotbar {
+            inventory.hotbar_size.min(inventory.items.len())
+        } else {
+            0
+        };
+
+        // Merge partial stacks of the same item (and metadata) into totals
+        let mut totals: Vec<(u32, Option<serde_json::Value>, u32)> = Vec::new();
+        for item in inventory.items[start..].iter_mut().filter_map(|item| item.take()) {
+            match totals
+                .iter_mut()
+                .find(|(id, metadata, _)| *id == item.id && *metadata == item.metadata)
+            {
+                Some((_, _, total)) => *total += item.count,
+                None => totals.push((item.id, item.metadata, item.count)),
+            }
+        }
+
+        totals.sort_by_key(|(id, _, _)| *id);
+
+        // Lay the stacks back out from the front, leaving empty slots at the back
+        let mut slot = start;
+        for (id, metadata, mut total) in totals {
+            while total > 0 && slot < inventory.items.len() {
+                let count = std::cmp::min(total, self.get_max_stack_size(id));
+                inventory.items[slot] = Some(InventoryItem {
+                    id,
+                    count,
+                    metadata: metadata.clone(),
+                    slot,
+                });
+                total -= count;
+                slot += 1;
+            }
+        }
+    }
+
+    // Wears one item_id from the inventory in the armor slot and returns the piece it replaced,
+    // which is left to the caller. Metadata such as durability moves with the piece
+    pub fn equip_armor(&self, inventory: &mut Inventory, slot: usize, item_id: u32) -> Result<Option<InventoryItem>, String> {
+        match self.get_armor_slot(item_id) {
+            Some(armor_slot) if armor_slot == slot => {}
+            Some(_) => return Err(format!("Item {} is not worn in armor slot {}", item_id, slot)),
+            None => return Err(format!("Item {} is not armor", item_id)),
+        }
+        let from_slot = inventory
+            .items
+            .iter()
+            .position(|item| item.as_ref().is_some_and(|item| item.id == item_id))
+            .ok_or_else(|| format!("No item {} in the inventory", item_id))?;
+
+        let piece = self.drop_item(inventory, from_slot, Some(1)).ok_or("Slot is empty")?;
+        Ok(inventory.armor[slot].replace(InventoryItem { slot, ..piece }))
+    }
+
+    pub fn unequip_armor(&self, inventory: &mut Inventory, armor_slot: usize) -> Result<(), String> {
+        if inventory.armor.get(armor_slot).and_then(|piece| piece.as_ref()).is_none() {
+            return Err("Nothing is worn in that slot".to_string());
+        }
+        let slot = self.find_empty_slot(inventory).ok_or("Inventory is full")?;
+
+        let piece = inventory.armor[armor_slot].take();
+        inventory.items[slot] = piece.map(|piece| InventoryItem { slot, ..piece });
         Ok(())
     }
 
@@ -217,6 +484,8 @@ impl InventorySystem {
 
     pub fn clear_inventory(&self, inventory: &mut Inventory) {
         inventory.items.fill(None);
+        inventory.armor = Default::default();
+        inventory.offhand = None;
     }
 
     pub fn serialize_inventory(&self, inventory: &Inventory) -> serde_json::Value {
@@ -224,7 +493,9 @@ impl InventorySystem {
             "items": inventory.items,
             "size": inventory.size,
             "hotbar_size": inventory.hotbar_size,
-            "selected_slot": inventory.selected_slot
+            "selected_slot": inventory.selected_slot,
+            "armor": inventory.armor,
+            "offhand": inventory.offhand
         })
     }
 
@@ -240,7 +511,8 @@ impl InventorySystem {
                     serde_json::from_value(item.clone()).map(Some)
                 }
             })
-            .collect::<Result<Vec<Option<InventoryItem>>, _>>()?;
+            .collect::<Result<Vec<Option<InventoryItem>>, _>>()
+            .map_err(|e| format!("Invalid inventory item: {}", e))?;
 
         let size = data["size"]
             .as_u64()
@@ -252,36 +524,251 @@ impl InventorySystem {
             .as_u64()
             .ok_or("Invalid selected slot")? as usize;
 
+        // Reject inconsistent data here rather than panicking on slot indexing later
+        if items.len() != size {
+            return Err(format!(
+                "Inventory has {} item slots but size is {}",
+                items.len(),
+                size
+            ));
+        }
+        if hotbar_size > size {
+            return Err(format!(
+                "Hotbar size {} exceeds inventory size {}",
+                hotbar_size, size
+            ));
+        }
+        if hotbar_size > 0 && selected_slot >= hotbar_size {
+            return Err(format!(
+                "Selected slot {} is outside the hotbar of size {}",
+                selected_slot, hotbar_size
+            ));
+        }
+
+        // Older saves predate equipment slots, so treat them as empty
+        let armor: [Option<InventoryItem>; 4] = if data["armor"].is_null() {
+            Default::default()
+        } else {
+            serde_json::from_value(data["armor"].clone())
+                .map_err(|e| format!("Invalid armor data: {}", e))?
+        };
+        let offhand = if data["offhand"].is_null() {
+            None
+        } else {
+            Some(
+                serde_json::from_value(data["offhand"].clone())
+                    .map_err(|e| format!("Invalid offhand data: {}", e))?,
+            )
+        };
+
+        // An unknown id would silently pick up the fallback stack size and weight
+        let unknown = items
+            .iter()
+            .chain(armor.iter())
+            .chain(std::iter::once(&offhand))
+            .flatten()
+            .find(|item| !self.item_registry.is_registered(item.id));
+        if let Some(item) = unknown {
+            return Err(format!("Unknown item id {}", item.id));
+        }
+
         Ok(Inventory {
             items,
             size,
             hotbar_size,
             selected_slot,
+            armor,
+            offhand,
         })
     }
 
+    fn get_armor_slot(&self, item_id: u32) -> Option<usize> {
+        self.item_registry.armor_slot(item_id)
+    }
+
+    fn get_max_stack_size(&self, item_id: u32) -> u32 {
+        self.item_registry.max_stack(item_id)
+    }
+
     fn get_item_weight(&self, item_id: u32) -> f32 {
-        match item_id {
-            1..=5 => 1.0,   // Stone blocks
-            17..=21 => 0.5, // Wood
-            263..=264 => 0.1, // Coal, Iron
-            265..=266 => 0.2, // Gold, Redstone
-            267..=268 => 0.3, // Diamond, Emerald
-            _ => 0.1, // Default weight
-        }
-    }
-
-    fn get_item_value(&self, item_id: u32) -> u32 {
-        match item_id {
-            1..=5 => 1,     // Stone blocks
-            17..=21 => 2,   // Wood
-            263 => 1,       // Coal
-            264 => 5,       // Iron
-            265 => 10,      // Gold
-            266 => 2,       // Redstone
-            267 => 50,      // Diamond
-            268 => 30,      // Emerald
-            _ => 1,         // Default value
+        self.item_registry.weight(item_id)
+    }
+
+    pub fn get_item_value(&self, item_id: u32) -> u32 {
+        self.item_registry.value(item_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::item_registry::{ItemProperties, DIRT, STONE};
+
+    fn inventory_system() -> InventorySystem {
+        InventorySystem::new(Arc::new(ItemRegistry::new()))
+    }
+
+    // 36 slots with the first 18 taken: 17 full stacks of stone and a partial one of 10
+    fn half_full(inventory_system: &InventorySystem) -> Inventory {
+        let mut inventory = InventorySystem::create_inventory(36, 9);
+        assert_eq!(inventory_system.add_item(&mut inventory, STONE, 17 * 64 + 10, None), Ok(0));
+        inventory
+    }
+
+    #[test]
+    fn find_empty_slot_skips_filled_slots() {
+        let inventory_system = inventory_system();
+        let inventory = half_full(&inventory_system);
+
+        assert_eq!(inventory_system.find_empty_slot(&inventory), Some(18));
+    }
+
+    #[test]
+    fn count_free_space_counts_partial_stacks_and_empty_slots() {
+        let inventory_system = inventory_system();
+        let inventory = half_full(&inventory_system);
+
+        assert_eq!(inventory_system.count_free_space(&inventory, STONE), 54 + 18 * 64);
+        assert_eq!(inventory_system.count_free_space(&inventory, DIRT), 18 * 64);
+    }
+
+    #[test]
+    fn split_half_keeps_the_larger_half() {
+        let inventory_system = inventory_system();
+        let mut inventory = half_full(&inventory_system);
+        inventory.items[17].as_mut().unwrap().count = 7;
+
+        inventory_system.split_half(&mut inventory, 17).unwrap();
+
+        assert_eq!(inventory.items[17].as_ref().map(|item| item.count), Some(4));
+        let split = inventory.items[18].as_ref().unwrap();
+        assert_eq!((split.id, split.count, split.slot), (STONE, 3, 18));
+    }
+
+    #[test]
+    fn split_in_a_full_inventory_leaves_the_stack_alone() {
+        let inventory_system = inventory_system();
+        let mut inventory = InventorySystem::create_inventory(36, 9);
+        inventory_system.add_item(&mut inventory, STONE, 36 * 64, None).unwrap();
+
+        assert!(inventory_system.split_half(&mut inventory, 0).is_err());
+        assert_eq!(inventory.items[0].as_ref().map(|item| item.count), Some(64));
+    }
+
+    #[test]
+    fn creative_inventory_stays_constant_after_repeated_removals() {
+        let inventory_system = inventory_system();
+        let mut inventory = half_full(&inventory_system);
+        let before = inventory_system.serialize_inventory(&inventory);
+
+        for _ in 0..100 {
+            assert_eq!(inventory_system.remove_item(&mut inventory, STONE, 64, true), Ok(0));
+        }
+        // Even items the inventory never held come back as fully removed
+        assert_eq!(inventory_system.remove_item(&mut inventory, DIRT, 5, true), Ok(0));
+
+        assert_eq!(inventory_system.serialize_inventory(&inventory), before);
+        assert_eq!(inventory_system.get_item_count(&inventory, STONE), 17 * 64 + 10);
+    }
+
+    #[test]
+    fn survival_removals_deplete_the_inventory() {
+        let inventory_system = inventory_system();
+        let mut inventory = half_full(&inventory_system);
+
+        assert_eq!(inventory_system.remove_item(&mut inventory, STONE, 64, false), Ok(0));
+        assert_eq!(inventory_system.get_item_count(&inventory, STONE), 16 * 64 + 10);
+    }
+
+    #[test]
+    fn deserialize_inventory_rejects_truncated_and_oversized_item_arrays() {
+        let inventory_system = inventory_system();
+        let data = inventory_system.serialize_inventory(&half_full(&inventory_system));
+        assert!(inventory_system.deserialize_inventory(data.clone()).is_ok());
+
+        let mut truncated = data.clone();
+        truncated["items"].as_array_mut().unwrap().pop();
+        let error = inventory_system.deserialize_inventory(truncated).unwrap_err();
+        assert_eq!(error, "Inventory has 35 item slots but size is 36");
+
+        let mut oversized = data;
+        oversized["items"].as_array_mut().unwrap().push(serde_json::Value::Null);
+        let error = inventory_system.deserialize_inventory(oversized).unwrap_err();
+        assert_eq!(error, "Inventory has 37 item slots but size is 36");
+    }
+
+    #[test]
+    fn deserialize_inventory_rejects_unknown_items() {
+        let inventory_system = inventory_system();
+        let mut data = inventory_system.serialize_inventory(&half_full(&inventory_system));
+        data["items"][20] = serde_json::json!({"id": 99999, "count": 1, "metadata": null, "slot": 20});
+
+        assert_eq!(inventory_system.deserialize_inventory(data).unwrap_err(), "Unknown item id 99999");
+    }
+
+    #[test]
+    fn deserialize_inventory_rejects_truncated_and_oversized_armor() {
+        let inventory_system = inventory_system();
+        let data = inventory_system.serialize_inventory(&half_full(&inventory_system));
+
+        let mut truncated = data.clone();
+        truncated["armor"] = serde_json::json!([null, null, null]);
+        assert!(inventory_system.deserialize_inventory(truncated).unwrap_err().starts_with("Invalid armor data"));
+
+        let mut oversized = data;
+        oversized["armor"] = serde_json::json!([null, null, null, null, null]);
+        assert!(inventory_system.deserialize_inventory(oversized).unwrap_err().starts_with("Invalid armor data"));
+    }
+
+    #[test]
+    fn armor_slots_come_from_the_item_registry() {
+        let mut item_registry = ItemRegistry::new();
+        item_registry.register_item(900, ItemProperties {
+            name: "custom_boots".to_string(),
+            max_stack: 1,
+            weight: 1.0,
+            value: 5,
+            is_tool: false,
+            tool_durability: None,
+            tool_kind: None,
+            mining_speed: 1.0,
+            armor_slot: Some(3),
+        });
+        let inventory_system = InventorySystem::new(Arc::new(item_registry));
+        let mut inventory = InventorySystem::create_inventory(36, 9);
+        // 311 is the diamond chestplate
+        for (slot, id) in [(0, 311), (1, 900), (2, STONE)] {
+            inventory.items[slot] = Some(InventoryItem { id, count: 1, metadata: None, slot });
         }
+
+        assert!(inventory_system.equip_armor(&mut inventory, 1, 311).unwrap().is_none());
+        assert!(inventory_system.equip_armor(&mut inventory, 3, 900).unwrap().is_none());
+        assert!(inventory_system.equip_armor(&mut inventory, 0, STONE).is_err());
+        // Missing from the inventory now that it is worn
+        assert!(inventory_system.equip_armor(&mut inventory, 1, 311).is_err());
+        assert_eq!(inventory.armor[1].as_ref().map(|piece| piece.id), Some(311));
+        assert_eq!(inventory.armor[3].as_ref().map(|piece| piece.id), Some(900));
+    }
+
+    #[test]
+    fn equipping_swaps_the_worn_piece_back_into_the_inventory() {
+        let inventory_system = inventory_system();
+        let mut inventory = InventorySystem::create_inventory(2, 1);
+        let worn = serde_json::json!({ "durability": 40 });
+        inventory.items[0] = Some(InventoryItem { id: 311, count: 1, metadata: Some(worn.clone()), slot: 0 });
+        assert!(inventory_system.equip_armor(&mut inventory, 1, 311).unwrap().is_none());
+        assert!(inventory.items[0].is_none());
+        // A chestplate doesn't go on the head
+        assert!(inventory_system.equip_armor(&mut inventory, 0, 311).is_err());
+
+        inventory.items[1] = Some(InventoryItem { id: 311, count: 1, metadata: None, slot: 1 });
+        let replaced = inventory_system.equip_armor(&mut inventory, 1, 311).unwrap().unwrap();
+        assert_eq!((replaced.id, replaced.metadata.clone()), (311, Some(worn)));
+        assert!(inventory.items[1].is_none());
+        assert_eq!(inventory.armor[1].as_ref().unwrap().metadata, None);
+
+        inventory_system.unequip_armor(&mut inventory, 1).unwrap();
+        assert!(inventory.armor[1].is_none() && inventory.items[0].is_some());
+        assert!(inventory_system.unequip_armor(&mut inventory, 1).is_err());
     }
 }
\ No newline at end of file
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+
+use crate::worlds::block_registry::ToolKind;
+
+// Block items share their block's id
+pub const STONE: u32 = 1;
+pub const GRASS: u32 = 2;
+pub const DIRT: u32 = 3;
+pub const COBBLESTONE: u32 = 4;
+pub const OAK_PLANKS: u32 = 5;
+pub const SAND: u32 = 12;
+pub const GOLD_ORE: u32 = 14;
+pub const IRON_ORE: u32 = 15;
+pub const OAK_LOG: u32 = 17;
+pub const GLASS: u32 = 20;
+pub const CRAFTING_TABLE: u32 = 58;
+pub const IRON_SHOVEL: u32 = 256;
+pub const IRON_PICKAXE: u32 = 257;
+pub const IRON_AXE: u32 = 258;
+pub const FLINT_AND_STEEL: u32 = 259;
+pub const BOW: u32 = 261;
+pub const COAL: u32 = 263;
+pub const IRON_INGOT: u32 = 265;
+pub const GOLD_INGOT: u32 = 266;
+pub const IRON_SWORD: u32 = 267;
+pub const WOODEN_SWORD: u32 = 268;
+pub const WOODEN_SHOVEL: u32 = 269;
+pub const WOODEN_PICKAXE: u32 = 270;
+pub const WOODEN_AXE: u32 = 271;
+pub const DIAMOND: u32 = 264;
+pub const STICK: u32 = 280;
+pub const SNOWBALL: u32 = 332;
+pub const EGG: u32 = 344;
+pub const ENDER_PEARL: u32 = 368;
+pub const EMERALD: u32 = 388;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemProperties {
+    pub name: String,
+    pub max_stack: u32,
+    pub weight: f32,
+    pub value: u32,
+    pub is_tool: bool,
+    pub tool_durability: Option<u32>, // Uses before breaking, tools only
+    pub tool_kind: Option<ToolKind>, // Which blocks the tool is meant for
+    pub mining_speed: f32, // Speed multiplier on matching blocks, 1.0 is bare hands
+    #[serde(default)]
+    pub armor_slot: Option<usize>, // 0 helmet, 1 chestplate, 2 leggings, 3 boots, armor only
+}
+
+#[derive(Debug)]
+pub struct ItemRegistry {
+    items: HashMap<u32, ItemProperties>,
+    fallback: ItemProperties, // Used for ids nobody registered
+}
+
+impl ItemRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            items: HashMap::new(),
+            fallback: ItemProperties {
+                name: "unknown".to_string(),
+                max_stack: 64,
+                weight: 0.1,
+                value: 1,
+                is_tool: false,
+                tool_durability: None,
+                tool_kind: None,
+                mining_speed: 1.0,
+                armor_slot: None,
+            },
+        };
+
+        registry.register_default_items();
+        registry
+    }
+
+    fn register_default_items(&mut self) {
+        // (id, name, max stack, weight, value)
+        let materials = [
+            (STONE, "stone", 64, 1.0, 1),
+            (GRASS, "grass", 64, 1.0, 1),
+            (DIRT, "dirt", 64, 1.0, 1),
+            (COBBLESTONE, "cobblestone", 64, 1.0, 1),
+            (OAK_PLANKS, "oak_planks", 64, 1.0, 1),
+            (SAND, "sand", 64, 1.0, 1),
+            (GOLD_ORE, "gold_ore", 64, 1.0, 8),
+            (IRON_ORE, "iron_ore", 64, 1.0, 4),
+            (OAK_LOG, "oak_log", 64, 0.5, 2),
+            (GLASS, "glass", 64, 0.5, 2),
+            (CRAFTING_TABLE, "crafting_table", 64, 1.0, 4),
+            (COAL, "coal", 64, 0.1, 1),
+            (DIAMOND, "diamond", 64, 0.1, 50),
+            (IRON_INGOT, "iron_ingot", 64, 0.2, 5),
+            (GOLD_INGOT, "gold_ingot", 64, 0.2, 10),
+            (EMERALD, "emerald", 64, 0.3, 30),
+            (STICK, "stick", 64, 0.1, 1),
+            (SNOWBALL, "snowball", 16, 0.1, 1),
+            (EGG, "egg", 16, 0.1, 1),
+            (ENDER_PEARL, "ender_pearl", 16, 0.1, 20),
+        ];
+
+        for (id, name, max_stack, weight, value) in materials {
+            self.register_item(id, ItemProperties {
+                name: name.to_string(),
+                max_stack,
+                weight,
+                value,
+                is_tool: false,
+                tool_durability: None,
+                tool_kind: None,
+                mining_speed: 1.0,
+                armor_slot: None,
+            });
+        }
+
+        // (id, name, durability, value, kind, mining speed), wood mines at 2x and iron at 6x
+        let tools = [
+            (IRON_SHOVEL, "iron_shovel", 250, 5, Some(ToolKind::Shovel), 6.0),
+            (IRON_PICKAXE, "iron_pickaxe", 250, 15, Some(ToolKind::Pickaxe), 6.0),
+            (IRON_AXE, "iron_axe", 250, 15, Some(ToolKind::Axe), 6.0),
+            (FLINT_AND_STEEL, "flint_and_steel", 64, 5, None, 1.0),
+            (BOW, "bow", 384, 10, None, 1.0),
+            (IRON_SWORD, "iron_sword", 250, 10, Some(ToolKind::Sword), 1.5),
+            (WOODEN_SWORD, "wooden_sword", 59, 2, Some(ToolKind::Sword), 1.5),
+            (WOODEN_SHOVEL, "wooden_shovel", 59, 1, Some(ToolKind::Shovel), 2.0),
+            (WOODEN_PICKAXE, "wooden_pickaxe", 59, 3, Some(ToolKind::Pickaxe), 2.0),
+            (WOODEN_AXE, "wooden_axe", 59, 3, Some(ToolKind::Axe), 2.0),
+        ];
+
+        for (id, name, durability, value, tool_kind, mining_speed) in tools {
+            self.register_item(id, ItemProperties {
+                name: name.to_string(),
+                max_stack: 1,
+                weight: 0.5,
+                value,
+                is_tool: true,
+                tool_durability: Some(durability),
+                tool_kind,
+                mining_speed,
+                armor_slot: None,
+            });
+        }
+
+        // Leather, chainmail, iron, diamond and gold sets, four pieces each
+        let armor_sets = ["leather", "chainmail", "iron", "diamond", "gold"];
+        let armor_pieces = ["helmet", "chestplate", "leggings", "boots"];
+        for (set, material) in armor_sets.iter().enumerate() {
+            for (piece, slot) in armor_pieces.iter().enumerate() {
+                self.register_item(298 + (set * 4 + piece) as u32, ItemProperties {
+                    name: format!("{}_{}", material, slot),
+                    max_stack: 1,
+                    weight: 1.0,
+                    value: 5,
+                    is_tool: false,
+                    tool_durability: None,
+                    tool_kind: None,
+                    mining_speed: 1.0,
+                    armor_slot: Some(piece),
+                });
+            }
+        }
+
+        info!("Registered {} items", self.items.len());
+    }
+
+    pub fn register_item(&mut self, id: u32, properties: ItemProperties) {
+        if properties.max_stack == 0 {
+            warn!("Item {} registered with a max stack of 0, using 1", id);
+        }
+        let properties = ItemProperties {
+            max_stack: properties.max_stack.max(1),
+            ..properties
+        };
+        self.items.insert(id, properties);
+    }
+
+    pub fn get(&self, id: u32) -> &ItemProperties {
+        self.items.get(&id).unwrap_or(&self.fallback)
+    }
+
+    pub fn is_registered(&self, id: u32) -> bool {
+        self.items.contains_key(&id)
+    }
+
+    pub fn max_stack(&self, id: u32) -> u32 {
+        self.get(id).max_stack
+    }
+
+    pub fn weight(&self, id: u32) -> f32 {
+        self.get(id).weight
+    }
+
+    pub fn value(&self, id: u32) -> u32 {
+        self.get(id).value
+    }
+
+    pub fn armor_slot(&self, id: u32) -> Option<usize> {
+        self.get(id).armor_slot
+    }
+}
\ No newline at end of file
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+
+use crate::systems::inventory_system::InventoryItem;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LootEntry {
+    pub item_id: u32,
+    pub weight: u32,
+    pub min_count: u32,
+    pub max_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LootTable {
+    pub id: String,
+    pub rolls: u32, // How many entries are drawn per roll_loot call
+    pub entries: Vec<LootEntry>,
+}
+
+// (table, rolls, [(item id, weight, min count, max count)])
+type DefaultTable = (&'static str, u32, &'static [(u32, u32, u32, u32)]);
+
+#[derive(Debug)]
+pub struct LootSystem {
+    tables: HashMap<String, LootTable>,
+}
+
+impl LootSystem {
+    pub fn new() -> Self {
+        let mut system = Self {
+            tables: HashMap::new(),
+        };
+
+        system.register_default_tables();
+        system
+    }
+
+    fn register_default_tables(&mut self) {
+        let defaults: [DefaultTable; 9] = [
+            ("entities/zombie", 1, &[(367, 1, 0, 2)]), // Rotten flesh
+            ("entities/skeleton", 2, &[(262, 1, 0, 2), (352, 1, 0, 2)]), // Arrows, bones
+            ("entities/creeper", 1, &[(289, 1, 0, 2)]), // Gunpowder
+            ("entities/spider", 2, &[(287, 1, 0, 2), (375, 1, 0, 1)]), // String, spider eye
+            ("entities/cow", 2, &[(363, 1, 1, 3), (334, 1, 0, 2)]), // Raw beef, leather
+            ("entities/pig", 1, &[(319, 1, 1, 3)]), // Raw porkchop
+            ("entities/sheep", 2, &[(35, 1, 1, 1), (423, 1, 1, 2)]), // Wool, raw mutton
+            ("entities/chicken", 2, &[(365, 1, 1, 1), (288, 1, 0, 2)]), // Raw chicken, feathers
+            (
+                "chests/dungeon",
+                4,
+                &[(296, 10, 1, 4), (265, 6, 1, 4), (331, 6, 1, 4), (322, 2, 1, 1), (329, 1, 1, 1), (266, 3, 1, 3)],
+            ), // Wheat, iron, redstone, golden apple, saddle, gold
+        ];
+
+        for (id, rolls, entries) in defaults {
+            self.register_table(LootTable {
+                id: id.to_string(),
+                rolls,
+                entries: entries
+                    .iter()
+                    .map(|&(item_id, weight, min_count, max_count)| LootEntry { item_id, weight, min_count, max_count })
+                    .collect(),
+            });
+        }
+    }
+
+    pub fn register_table(&mut self, table: LootTable) {
+        info!("Registered loot table: {}", table.id);
+        self.tables.insert(table.id.clone(), table);
+    }
+
+    pub fn get_table(&self, table_id: &str) -> Option<&LootTable> {
+        self.tables.get(table_id)
+    }
+
+    // The same table and seed always produce the same items. Draws of the same
+    // item are merged, zero-count draws are dropped
+    pub fn roll_loot(&self, table_id: &str, seed: u64) -> Vec<InventoryItem> {
+        let table = match self.tables.get(table_id) {
+            Some(table) => table,
+            None => {
+                warn!("Unknown loot table: {}", table_id);
+                return Vec::new();
+            }
+        };
+
+        let total_weight: u64 = table.entries.iter().map(|e| e.weight as u64).sum();
+        if total_weight == 0 {
+            return Vec::new();
+        }
+
+        let mut state = seed;
+        let mut items: Vec<InventoryItem> = Vec::new();
+        for _ in 0..table.rolls {
+            let mut pick = Self::next(&mut state) % total_weight;
+            let entry = table
+                .entries
+                .iter()
+                .find(|e| {
+                    if pick < e.weight as u64 {
+                        return true;
+                    }
+                    pick -= e.weight as u64;
+                    false
+                })
+                .expect("pick is below the total weight");
+
+            let span = entry.max_count.saturating_sub(entry.min_count) as u64 + 1;
+            let count = entry.min_count + (Self::next(&mut state) % span) as u32;
+            if count == 0 {
+                continue;
+            }
+
+            match items.iter_mut().find(|item| item.id == entry.item_id) {
+                Some(item) => item.count += count,
+                None => items.push(InventoryItem {
+                    id: entry.item_id,
+                    count,
+                    metadata: None,
+                    slot: items.len(),
+                }),
+            }
+        }
+
+        items
+    }
+
+    // Stable seed for callers that only have a string id, such as an entity id.
+    // FNV-1a, so the same id rolls the same loot across builds and Rust versions
+    pub fn seed_from(key: &str) -> u64 {
+        key.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        })
+    }
+
+    // splitmix64
+    fn next(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_from_matches_fnv1a_reference_values() {
+        assert_eq!(LootSystem::seed_from(""), 0xCBF2_9CE4_8422_2325);
+        assert_eq!(LootSystem::seed_from("a"), 0xAF63_DC4C_8601_EC8C);
+        assert_eq!(LootSystem::seed_from("foobar"), 0x8594_4171_F739_67E8);
+    }
+
+    #[test]
+    fn same_entity_id_rolls_the_same_loot() {
+        let loot = LootSystem::new();
+        let seed = LootSystem::seed_from("entity-42");
+
+        let first = loot.roll_loot("chests/dungeon", seed);
+        let second = loot.roll_loot("chests/dungeon", LootSystem::seed_from("entity-42"));
+
+        assert_eq!(seed, LootSystem::seed_from("entity-42"));
+        assert_eq!(
+            first.iter().map(|i| (i.id, i.count)).collect::<Vec<_>>(),
+            second.iter().map(|i| (i.id, i.count)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn different_ids_give_different_seeds() {
+        assert_ne!(LootSystem::seed_from("entity-1"), LootSystem::seed_from("entity-2"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use log::warn;
+
+use crate::worlds::block_registry::{BlockRegistry, ToolKind};
+use crate::systems::item_registry::ItemRegistry;
+use crate::systems::player_manager::{GameMode, Player};
+
+const BASE_MINING_FACTOR: f32 = 1.5; // Seconds per point of hardness with bare hands
+const WRONG_TOOL_PENALTY: f32 = 5.0; // Replaces the base factor for rock without a pickaxe
+const MINING_TOLERANCE: f32 = 0.8; // Share of the mining time a break must take, covers latency
+
+type BlockPos = (i32, i32, i32);
+
+#[derive(Debug)]
+struct Dig {
+    world_id: String,
+    position: BlockPos,
+    started_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct MiningSystem {
+    block_registry: Arc<BlockRegistry>,
+    item_registry: Arc<ItemRegistry>,
+    digs: HashMap<String, Dig>, // player_id -> block they started breaking
+}
+
+impl MiningSystem {
+    pub fn new(block_registry: Arc<BlockRegistry>, item_registry: Arc<ItemRegistry>) -> Self {
+        Self {
+            block_registry,
+            item_registry,
+            digs: HashMap::new(),
+        }
+    }
+
+    // How long a survival player takes to break block_id holding tool_item_id,
+    // Duration::MAX for blocks that can't be broken at all
+    pub fn mining_time(&self, block_id: u8, tool_item_id: Option<u32>) -> Duration {
+        let block = self.block_registry.get(block_id);
+        if block.hardness.is_infinite() {
+            return Duration::MAX;
+        }
+
+        let tool = tool_item_id.map(|id| self.item_registry.get(id));
+        let matches = block.tool.is_some() && tool.is_some_and(|t| t.tool_kind == block.tool);
+
+        let seconds = if matches {
+            block.hardness * BASE_MINING_FACTOR / tool.map_or(1.0, |t| t.mining_speed)
+        } else if block.tool == Some(ToolKind::Pickaxe) {
+            block.hardness * WRONG_TOOL_PENALTY
+        } else {
+            block.hardness * BASE_MINING_FACTOR
+        };
+
+        Duration::from_secs_f32(seconds.max(0.0))
+    }
+
+    pub fn start_breaking(&mut self, player_id: &str, world_id: &str, x: i32, y: i32, z: i32) {
+        self.digs.insert(
+            player_id.to_string(),
+            Dig {
+                world_id: world_id.to_string(),
+                position: (x, y, z),
+                started_at: Instant::now(),
+            },
+        );
+    }
+
+    // Checks a break against the dig the player started on the same block,
+    // creative players break anything instantly
+    pub fn validate_break(&mut self, player: &Player, world_id: &str, x: i32, y: i32, z: i32, block_id: u8) -> Result<(), String> {
+        if matches!(player.game_mode, GameMode::Creative) {
+            return Ok(());
+        }
+        if self.block_registry.is_unbreakable(block_id) {
+            return Err(format!("{} can't be broken", self.block_registry.get(block_id).name));
+        }
+
+        let dig = self
+            .digs
+            .remove(&player.id)
+            .filter(|dig| dig.world_id == world_id && dig.position == (x, y, z))
+            .ok_or("Block was broken without being mined")?;
+
+        let held = player.inventory.items.get(player.inventory.selected_slot).and_then(|item| item.as_ref()).map(|item| item.id);
+        let required = self.mining_time(block_id, held).mul_f32(MINING_TOLERANCE);
+        if dig.started_at.elapsed() < required {
+            warn!(
+                "Player {} broke block {} at ({}, {}, {}) in {:?}, expected at least {:?}",
+                player.username, block_id, x, y, z, dig.started_at.elapsed(), required
+            );
+            return Err("Block was broken too quickly".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn forget_player(&mut self, player_id: &str) {
+        self.digs.remove(player_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::systems::inventory_system::{InventoryItem, InventorySystem};
+    use crate::systems::item_registry::IRON_PICKAXE;
+    use crate::systems::player_manager::{PlayerRole, PlayerStatistics};
+    use crate::worlds::block_registry::{BEDROCK, DIRT, STONE};
+
+    fn mining() -> MiningSystem {
+        MiningSystem::new(Arc::new(BlockRegistry::new()), Arc::new(ItemRegistry::new()))
+    }
+
+    fn player(game_mode: GameMode, held: Option<u32>) -> Player {
+        let mut inventory = InventorySystem::create_inventory(36, 9);
+        inventory.items[0] = held.map(|id| InventoryItem { id, count: 1, metadata: None, slot: 0 });
+        Player {
+            id: "miner".to_string(),
+            username: "miner".to_string(),
+            position: [0.0, 64.0, 0.0],
+            rotation: [0.0, 0.0, 0.0],
+            health: 20.0,
+            max_health: 20.0,
+            hunger: 20.0,
+            max_hunger: 20.0,
+            experience: 0,
+            level: 1,
+            inventory,
+            game_mode,
+            role: PlayerRole::Player,
+            world_id: Some("world".to_string()),
+            is_online: true,
+            last_seen: Utc::now(),
+            created_at: Utc::now(),
+            statistics: PlayerStatistics::default(),
+            infinite_items: false,
+            effects: Vec::new(),
+        }
+    }
+
+    // A dig on (0, 64, 0) that started `ago` in the past
+    fn dig_started(mining: &mut MiningSystem, ago: Duration) {
+        mining.digs.insert(
+            "miner".to_string(),
+            Dig {
+                world_id: "world".to_string(),
+                position: (0, 64, 0),
+                started_at: Instant::now() - ago,
+            },
+        );
+    }
+
+    #[test]
+    fn mining_time_depends_on_the_tool() {
+        let mining = mining();
+        // Stone is 1.5 hardness: 7.5s by hand, 1.5 * 1.5 / 6 with an iron pickaxe
+        assert_eq!(mining.mining_time(STONE, None), Duration::from_secs_f32(7.5));
+        assert_eq!(mining.mining_time(STONE, Some(IRON_PICKAXE)), Duration::from_secs_f32(0.375));
+        // Dirt isn't rock, bare hands aren't penalised and the wrong tool is no faster
+        assert_eq!(mining.mining_time(DIRT, None), Duration::from_secs_f32(0.75));
+        assert_eq!(mining.mining_time(DIRT, Some(IRON_PICKAXE)), Duration::from_secs_f32(0.75));
+    }
+
+    #[test]
+    fn bedrock_is_unbreakable() {
+        let mut mining = mining();
+        assert_eq!(mining.mining_time(BEDROCK, Some(IRON_PICKAXE)), Duration::MAX);
+
+        dig_started(&mut mining, Duration::from_secs(3600));
+        let survival = player(GameMode::Survival, Some(IRON_PICKAXE));
+        assert!(mining.validate_break(&survival, "world", 0, 64, 0, BEDROCK).is_err());
+
+        let creative = player(GameMode::Creative, None);
+        assert!(mining.validate_break(&creative, "world", 0, 64, 0, BEDROCK).is_ok());
+    }
+
+    #[test]
+    fn breaks_must_follow_a_long_enough_dig() {
+        let mut mining = mining();
+        let miner = player(GameMode::Survival, Some(IRON_PICKAXE));
+
+        assert_eq!(
+            mining.validate_break(&miner, "world", 0, 64, 0, STONE),
+            Err("Block was broken without being mined".to_string())
+        );
+
+        dig_started(&mut mining, Duration::ZERO);
+        assert_eq!(
+            mining.validate_break(&miner, "world", 0, 64, 0, STONE),
+            Err("Block was broken too quickly".to_string())
+        );
+
+        // A dig on another block doesn't count
+        dig_started(&mut mining, Duration::from_secs(1));
+        assert!(mining.validate_break(&miner, "world", 1, 64, 0, STONE).is_err());
+
+        dig_started(&mut mining, Duration::from_secs(1));
+        assert_eq!(mining.validate_break(&miner, "world", 0, 64, 0, STONE), Ok(()));
+        // The dig is used up by the break
+        assert!(mining.validate_break(&miner, "world", 0, 64, 0, STONE).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast::{self, error::TryRecvError}, RwLock};
+use tokio::time::{interval, Duration};
+use rand::Rng;
+use log::{info, warn};
+
+use crate::worlds::block_registry::{AIR, GRASS};
+use crate::systems::{
+    world_manager::{Difficulty, WorldInfo, WorldManager},
+    chunk_manager::ChunkManager,
+    entity_manager::{EntityKind, EntityManager, EntityType},
+    player_manager::PlayerManager,
+    time_system::{DayPhase, PhaseChange, TimeSystem},
+    weather_system::WeatherSystem,
+};
+
+const SPAWN_ATTEMPTS_PER_PLAYER: usize = 4;
+const MIN_SPAWN_DISTANCE: f64 = 24.0; // Never pop in right next to a player
+const MAX_SPAWN_DISTANCE: f64 = 64.0;
+const SPAWN_SEARCH_HEIGHT: i32 = 16; // Blocks above and below the player to look for ground
+const MAX_HOSTILE_LIGHT: u8 = 7;
+const MIN_PASSIVE_LIGHT: u8 = 9;
+const DUSK_SKY_DARKENING: u8 = 5;
+const NIGHT_SKY_DARKENING: u8 = 11; // Leaves open sky at light 4, dark enough for hostiles
+const RAIN_SKY_DARKENING: u8 = 3;
+const THUNDER_SKY_DARKENING: u8 = 10; // Thunderstorms let hostiles spawn in daylight
+const HOSTILE_MOBS: [EntityType; 4] = [EntityType::Zombie, EntityType::Skeleton, EntityType::Creeper, EntityType::Spider];
+const PASSIVE_MOBS: [EntityType; 4] = [EntityType::Cow, EntityType::Pig, EntityType::Sheep, EntityType::Chicken];
+
+#[derive(Debug)]
+pub struct MobSystem {
+    enabled: bool,
+    spawn_interval_seconds: u64,
+    phases: HashMap<String, DayPhase>, // Fed by TimeSystem phase events, worlds start at dawn
+    storm_darkening: HashMap<String, u8>,
+}
+
+impl MobSystem {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            spawn_interval_seconds: 1,
+            phases: HashMap::new(),
+            storm_darkening: HashMap::new(),
+        }
+    }
+
+    pub fn new_disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::new()
+        }
+    }
+
+    pub fn set_phase(&mut self, world_id: &str, phase: DayPhase) {
+        self.phases.insert(world_id.to_string(), phase);
+    }
+
+    // Applies every phase change queued since the last call
+    pub fn apply_phase_events(&mut self, events: &mut broadcast::Receiver<PhaseChange>) {
+        loop {
+            match events.try_recv() {
+                Ok(change) => self.set_phase(&change.world_id, change.phase),
+                Err(TryRecvError::Lagged(skipped)) => warn!("Mob system missed {} phase changes", skipped),
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+    }
+
+    pub fn update_weather(&mut self, world_id: &str, weather_system: &WeatherSystem) {
+        let darkening = if weather_system.is_thundering(world_id) {
+            THUNDER_SKY_DARKENING
+        } else if weather_system.is_raining(world_id) {
+            RAIN_SKY_DARKENING
+        } else {
+            0
+        };
+        self.storm_darkening.insert(world_id.to_string(), darkening);
+    }
+
+    // How far the sky light drops in this world, so hostiles only spawn on the surface at night or in storms
+    fn sky_darkening(&self, world_id: &str) -> u8 {
+        let time = match self.phases.get(world_id).copied().unwrap_or(DayPhase::Dawn) {
+            DayPhase::Dawn | DayPhase::Day => 0,
+            DayPhase::Dusk => DUSK_SKY_DARKENING,
+            DayPhase::Night => NIGHT_SKY_DARKENING,
+        };
+        time.saturating_add(self.storm_darkening.get(world_id).copied().unwrap_or(0))
+    }
+
+    fn choose_mob(allow_hostile: bool, light: u8, ground: u8, mob_roll: usize) -> Option<EntityType> {
+        if allow_hostile && light <= MAX_HOSTILE_LIGHT {
+            Some(HOSTILE_MOBS[mob_roll % HOSTILE_MOBS.len()].clone())
+        } else if light >= MIN_PASSIVE_LIGHT && ground == GRASS {
+            Some(PASSIVE_MOBS[mob_roll % PASSIVE_MOBS.len()].clone())
+        } else {
+            None
+        }
+    }
+
+    // Returns how many mobs were spawned in this world
+    pub async fn try_spawn(
+        &self,
+        world: &WorldInfo,
+        chunk_manager: &ChunkManager,
+        entity_manager: &mut EntityManager,
+        player_manager: &PlayerManager,
+    ) -> usize {
+        if !self.enabled || !world.settings.mobs_enabled {
+            return 0;
+        }
+
+        let players = player_manager.get_players_in_world(&world.id).await;
+        let candidates = Self::pick_candidates(players.iter().map(|p| p.position).collect());
+        let allow_hostile = !matches!(world.settings.difficulty, Difficulty::Peaceful);
+        let sky_darkening = self.sky_darkening(&world.id);
+
+        let mut spawned = 0;
+        for (x, z, start_y, mob_roll) in candidates {
+            if entity_manager.get_entity_count(&world.id) >= entity_manager.get_world_entity_cap(&world.id) {
+                break;
+            }
+
+            let (y, ground) = match Self::find_spawn_height(chunk_manager, &world.id, x, start_y, z).await {
+                Some(found) => found,
+                None => continue,
+            };
+            let light = chunk_manager.get_block_light(&world.id, x, y, z, sky_darkening).await.unwrap_or(15);
+
+            let mob = match Self::choose_mob(allow_hostile, light, ground, mob_roll) {
+                Some(mob) => mob,
+                None => continue,
+            };
+
+            let position = [x as f64 + 0.5, y as f64, z as f64 + 0.5];
+            match entity_manager.spawn_entity(EntityKind::BuiltIn(mob), position, world.id.clone(), None).await {
+                Ok(_) => spawned += 1,
+                Err(e) => {
+                    warn!("Mob spawn failed in world {}: {}", world.id, e);
+                    break;
+                }
+            }
+        }
+
+        spawned
+    }
+
+    // Random columns in a ring around each player, drawn up front so no RNG is held across awaits
+    fn pick_candidates(player_positions: Vec<[f64; 3]>) -> Vec<(i32, i32, i32, usize)> {
+        let mut rng = rand::thread_rng();
+        let mut candidates = Vec::new();
+
+        for position in player_positions {
+            for _ in 0..SPAWN_ATTEMPTS_PER_PLAYER {
+                let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+                let distance = rng.gen_range(MIN_SPAWN_DISTANCE..MAX_SPAWN_DISTANCE);
+                candidates.push((
+                    (position[0] + angle.cos() * distance).floor() as i32,
+                    (position[2] + angle.sin() * distance).floor() as i32,
+                    position[1].floor() as i32 + SPAWN_SEARCH_HEIGHT,
+                    rng.gen::<usize>(),
+                ));
+            }
+        }
+
+        candidates
+    }
+
+    // Walks down to the first solid block with two air blocks above it,
+    // returns the feet position and the block stood on
+    async fn find_spawn_height(
+        chunk_manager: &ChunkManager,
+        world_id: &str,
+        x: i32,
+        start_y: i32,
+        z: i32,
+    ) -> Option<(i32, u8)> {
+        let mut above = chunk_manager.get_block(world_id, x, start_y + 1, z).await.unwrap_or(AIR);
+        let mut current = chunk_manager.get_block(world_id, x, start_y, z).await?;
+
+        for y in (start_y - SPAWN_SEARCH_HEIGHT * 2..start_y).rev() {
+            let below = chunk_manager.get_block(world_id, x, y, z).await?;
+            if below != AIR && current == AIR && above == AIR {
+                return Some((y + 1, below));
+            }
+            above = current;
+            current = below;
+        }
+
+        None
+    }
+
+    pub async fn run(
+        mob_system: Arc<RwLock<MobSystem>>,
+        world_manager: Arc<RwLock<WorldManager>>,
+        chunk_manager: Arc<RwLock<ChunkManager>>,
+        entity_manager: Arc<RwLock<EntityManager>>,
+        player_manager: Arc<RwLock<PlayerManager>>,
+        time_system: Arc<RwLock<TimeSystem>>,
+        weather_system: Arc<RwLock<WeatherSystem>>,
+    ) {
+        let spawn_interval_seconds = {
+            let mob_system = mob_system.read().await;
+            if !mob_system.enabled {
+                info!("Mob system disabled");
+                return;
+            }
+            mob_system.spawn_interval_seconds
+        };
+
+        let mut phase_events = time_system.read().await.subscribe();
+        let mut ticker = interval(Duration::from_secs(spawn_interval_seconds));
+        loop {
+            ticker.tick().await;
+
+            let worlds = world_manager.read().await.get_all_worlds().await;
+            {
+                let weather_system = weather_system.read().await;
+                let mut mob_system = mob_system.write().await;
+                mob_system.apply_phase_events(&mut phase_events);
+                for world in &worlds {
+                    mob_system.update_weather(&world.id, &weather_system);
+                }
+            }
+            // Same lock order as the physics loop: chunks, entities, players
+            let mob_system = mob_system.read().await;
+            let chunk_manager = chunk_manager.read().await;
+            let mut entity_manager = entity_manager.write().await;
+            let player_manager = player_manager.read().await;
+
+            for world in worlds.iter().filter(|w| w.is_online) {
+                mob_system.try_spawn(world, &chunk_manager, &mut entity_manager, &player_manager).await;
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::weather_system::Weather;
+
+    const OPEN_SKY: u8 = 15;
+
+    #[test]
+    fn hostile_surface_spawns_wait_for_night() {
+        let mut time_system = TimeSystem::new();
+        let mut events = time_system.subscribe();
+        let mut mob_system = MobSystem::new();
+
+        let surface_mob = |mob_system: &MobSystem| {
+            let light = OPEN_SKY.saturating_sub(mob_system.sky_darkening("world"));
+            MobSystem::choose_mob(true, light, GRASS, 0)
+        };
+        assert_eq!(surface_mob(&mob_system), Some(EntityType::Cow));
+
+        time_system.set_time("world", 13000);
+        mob_system.apply_phase_events(&mut events);
+        assert_eq!(surface_mob(&mob_system), Some(EntityType::Zombie));
+
+        // Other worlds keep their own clock
+        assert_eq!(mob_system.sky_darkening("other"), 0);
+
+        time_system.set_time("world", 1000);
+        mob_system.apply_phase_events(&mut events);
+        assert_eq!(surface_mob(&mob_system), Some(EntityType::Cow));
+    }
+
+    #[test]
+    fn thunderstorms_let_hostiles_spawn_in_daylight() {
+        let mut weather_system = WeatherSystem::new();
+        let mut mob_system = MobSystem::new();
+        let surface_light = |mob_system: &MobSystem| OPEN_SKY.saturating_sub(mob_system.sky_darkening("world"));
+
+        weather_system.set_weather("world", Weather::Rain, Some(60));
+        mob_system.update_weather("world", &weather_system);
+        assert_eq!(MobSystem::choose_mob(true, surface_light(&mob_system), GRASS, 0), Some(EntityType::Cow));
+
+        weather_system.set_weather("world", Weather::Thunder, Some(60));
+        mob_system.update_weather("world", &weather_system);
+        assert_eq!(MobSystem::choose_mob(true, surface_light(&mob_system), GRASS, 0), Some(EntityType::Zombie));
+
+        weather_system.set_weather("world", Weather::Clear, Some(60));
+        mob_system.update_weather("world", &weather_system);
+        assert_eq!(surface_light(&mob_system), OPEN_SKY);
+    }
+
+    #[test]
+    fn peaceful_worlds_never_get_hostiles() {
+        assert_eq!(MobSystem::choose_mob(false, 0, GRASS, 0), None);
+        assert_eq!(MobSystem::choose_mob(true, 0, GRASS, 2), Some(EntityType::Creeper));
+    }
+}
@@ -10,4 +10,11 @@ pub mod physics_system;
 pub mod mob_system;
 pub mod weather_system;
 pub mod time_system;
-pub mod save_system;
\ No newline at end of file
+pub mod save_system;
+pub mod loot_system;
+pub mod trade_system;
+pub mod explosion_system;
+pub mod fluid_system;
+pub mod item_registry;
+pub mod mining_system;
+pub mod status_effect_system;
\ No newline at end of file
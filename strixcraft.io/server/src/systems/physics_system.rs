@@ -0,0 +1,471 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use log::{info, error};
+
+use crate::worlds::block_registry::BlockRegistry;
+use crate::systems::{
+    world_manager::{WorldInfo, WorldManager},
+    chunk_manager::ChunkManager,
+    entity_manager::{EntityKind, EntityManager, EntityType},
+    player_manager::{GameMode, PlayerManager},
+};
+
+const TICK_MILLIS: u64 = 50; // 20 ticks per second
+const GRAVITY: f64 = 32.0; // Blocks per second squared
+const TERMINAL_VELOCITY: f64 = 78.4;
+const SAFE_FALL_DISTANCE: f64 = 3.0; // One point of damage per block fallen past this
+const COLLISION_STEP: f64 = 0.25; // Longest move checked at once, well under a block so nothing tunnels
+const COLLISION_EPSILON: f64 = 1e-6;
+
+#[derive(Debug)]
+pub struct PhysicsSystem {
+    enabled: bool,
+    block_registry: Arc<BlockRegistry>,
+    entity_falls: HashMap<String, f64>, // entity_id -> highest y since leaving the ground
+    player_falls: HashMap<String, f64>, // player_id -> highest y since leaving the ground
+}
+
+impl PhysicsSystem {
+    pub fn new(block_registry: Arc<BlockRegistry>) -> Self {
+        Self {
+            enabled: true,
+            block_registry,
+            entity_falls: HashMap::new(),
+            player_falls: HashMap::new(),
+        }
+    }
+
+    pub fn new_disabled(block_registry: Arc<BlockRegistry>) -> Self {
+        Self {
+            enabled: false,
+            ..Self::new(block_registry)
+        }
+    }
+
+    pub async fn tick(
+        &mut self,
+        world: &WorldInfo,
+        dt: f64,
+        chunk_manager: &ChunkManager,
+        entity_manager: &mut EntityManager,
+        player_manager: &mut PlayerManager,
+    ) {
+        if !self.enabled || !world.is_online || !world.settings.physics_enabled {
+            Self::hold_entities(world, entity_manager).await;
+            return;
+        }
+
+        self.tick_entities(world, dt, chunk_manager, entity_manager).await;
+        let player_size = entity_manager.get_entity_size(&EntityKind::BuiltIn(EntityType::Player));
+        self.tick_players(world, player_size, chunk_manager, player_manager).await;
+    }
+
+    // Forgets falls of entities that were despawned and players who left mid-air
+    pub async fn prune_falls(&mut self, entity_manager: &EntityManager, player_manager: &PlayerManager) {
+        let mut gone = Vec::new();
+        for entity_id in self.entity_falls.keys() {
+            if entity_manager.get_entity(entity_id).await.is_none() {
+                gone.push(entity_id.clone());
+            }
+        }
+        for entity_id in gone {
+            self.entity_falls.remove(&entity_id);
+        }
+
+        let mut gone = Vec::new();
+        for player_id in self.player_falls.keys() {
+            if !player_manager.get_player(player_id).await.is_some_and(|p| p.is_online && p.world_id.is_some()) {
+                gone.push(player_id.clone());
+            }
+        }
+        for player_id in gone {
+            self.player_falls.remove(&player_id);
+        }
+    }
+
+    // EntityManager::tick moves everything with a velocity, so worlds that aren't simulated are stopped
+    async fn hold_entities(world: &WorldInfo, entity_manager: &mut EntityManager) {
+        for entity in entity_manager.get_entities_in_world(&world.id).await {
+            if entity.velocity != [0.0, 0.0, 0.0] && !entity.entity_type.is(EntityType::Player) {
+                entity_manager.update_entity_velocity(&entity.id, [0.0, 0.0, 0.0]).await;
+            }
+        }
+    }
+
+    async fn tick_entities(&mut self, world: &WorldInfo, dt: f64, chunk_manager: &ChunkManager, entity_manager: &mut EntityManager) {
+        for entity in entity_manager.get_entities_in_world(&world.id).await {
+            // Players move themselves, their falls are tracked separately
+            if !entity.is_active || entity.entity_type.is(EntityType::Player) {
+                continue;
+            }
+
+            let [x, y, z] = entity.position;
+            let [vx, mut vy, vz] = entity.velocity;
+            let size = entity_manager.get_entity_size(&entity.entity_type);
+
+            // Entities in unloaded chunks stay frozen until the chunk comes back
+            if Self::block_at(chunk_manager, &world.id, x, y - 0.01, z).await.is_none() {
+                entity_manager.update_entity_velocity(&entity.id, [0.0, 0.0, 0.0]).await;
+                continue;
+            }
+            let grounded = self.is_box_blocked(chunk_manager, &world.id, [x, y - 0.01, z], size).await;
+            if grounded && vy <= 0.0 {
+                vy = 0.0;
+            } else {
+                vy = (vy - GRAVITY * dt).max(-TERMINAL_VELOCITY);
+            }
+
+            let (new_position, hit) = self
+                .move_with_collisions(chunk_manager, &world.id, entity.position, size, [vx * dt, vy * dt, vz * dt])
+                .await;
+            // A blocked axis only closes the gap to the wall, EntityManager::tick applies the move
+            let mut velocity = [vx, vy, vz];
+            for axis in 0..3 {
+                if hit[axis] {
+                    velocity[axis] = (new_position[axis] - entity.position[axis]) / dt;
+                }
+            }
+
+            let landed = hit[1] && vy < 0.0;
+            if hit[1] {
+                vy = 0.0;
+            }
+
+            if landed {
+                if let Some(start) = self.entity_falls.remove(&entity.id) {
+                    let damage = Self::fall_damage(start - new_position[1]);
+                    if damage > 0.0 {
+                        entity_manager.damage_entity(&entity.id, damage).await;
+                    }
+                }
+            } else if vy < 0.0 {
+                let start = self.entity_falls.entry(entity.id.clone()).or_insert(y);
+                *start = start.max(y);
+            }
+
+            entity_manager.update_entity_velocity(&entity.id, velocity).await;
+        }
+    }
+
+    // Player positions come from the client, so only watch for landings
+    async fn tick_players(
+        &mut self,
+        world: &WorldInfo,
+        player_size: (f64, f64),
+        chunk_manager: &ChunkManager,
+        player_manager: &mut PlayerManager,
+    ) {
+        for player in player_manager.get_players_in_world(&world.id).await {
+            if matches!(player.game_mode, GameMode::Creative) || player.health <= 0.0 {
+                self.player_falls.remove(&player.id);
+                continue;
+            }
+
+            let [x, y, z] = player.position;
+            if Self::block_at(chunk_manager, &world.id, x, y - 0.01, z).await.is_none() {
+                continue;
+            }
+            let feet = Self::block_at(chunk_manager, &world.id, x, y, z).await.unwrap_or(0);
+
+            // Water breaks any fall
+            if BlockRegistry::is_water(feet) {
+                self.player_falls.remove(&player.id);
+                continue;
+            }
+
+            // Anything under the footprint holds the player up, not just the block below its centre
+            let standing = self.is_box_blocked(chunk_manager, &world.id, [x, y - 0.01, z], (player_size.0, 0.01)).await;
+            if !standing {
+                let start = self.player_falls.entry(player.id.clone()).or_insert(y);
+                *start = start.max(y);
+                continue;
+            }
+
+            if let Some(start) = self.player_falls.remove(&player.id) {
+                let damage = Self::fall_damage(start - y);
+                if damage > 0.0 {
+                    if let Err(e) = player_manager.update_player_health(&player.id, player.health - damage).await {
+                        error!("Failed to apply fall damage to {}: {}", player.username, e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn fall_damage(distance: f64) -> f32 {
+        (distance - SAFE_FALL_DISTANCE).max(0.0).ceil() as f32
+    }
+
+    async fn block_at(chunk_manager: &ChunkManager, world_id: &str, x: f64, y: f64, z: f64) -> Option<u8> {
+        chunk_manager.get_block(world_id, x.floor() as i32, y.floor() as i32, z.floor() as i32).await
+    }
+
+    // Moves a (width, height) box standing at position by displacement, stopping each axis at the
+    // first solid block it would enter. Long moves are split into sub-block steps so fast
+    // projectiles can't skip over a one block wall. Returns the new position and which axes hit
+    pub async fn move_with_collisions(
+        &self,
+        chunk_manager: &ChunkManager,
+        world_id: &str,
+        position: [f64; 3],
+        size: (f64, f64),
+        displacement: [f64; 3],
+    ) -> ([f64; 3], [bool; 3]) {
+        let mut position = position;
+        let mut hit = [false; 3];
+
+        let longest = displacement.iter().fold(0.0_f64, |max, d| max.max(d.abs()));
+        let steps = (longest / COLLISION_STEP).ceil().max(1.0) as usize;
+
+        for _ in 0..steps {
+            // Vertical first so entities land before sliding along the ground
+            for axis in [1, 0, 2] {
+                if hit[axis] || displacement[axis] == 0.0 {
+                    continue;
+                }
+
+                let delta = displacement[axis] / steps as f64;
+
+                let mut moved = position;
+                moved[axis] += delta;
+                if !self.is_box_blocked(chunk_manager, world_id, moved, size).await {
+                    position = moved;
+                    continue;
+                }
+
+                // A step is shorter than a block, so the leading face crossed at most one boundary
+                let (min, max) = Self::box_extent(position, size, axis);
+                if delta > 0.0 {
+                    let boundary = (max + delta).floor();
+                    position[axis] += (boundary - max).max(0.0);
+                } else {
+                    let boundary = (min + delta).floor() + 1.0;
+                    position[axis] += (boundary - min).min(0.0);
+                }
+                hit[axis] = true;
+            }
+        }
+
+        (position, hit)
+    }
+
+    // Lower and upper edge of the box along one axis
+    fn box_extent(position: [f64; 3], size: (f64, f64), axis: usize) -> (f64, f64) {
+        let (width, height) = size;
+        if axis == 1 {
+            (position[1], position[1] + height)
+        } else {
+            (position[axis] - width / 2.0, position[axis] + width / 2.0)
+        }
+    }
+
+    // Unloaded chunks and the world's floor and ceiling count as walls
+    async fn is_box_blocked(&self, chunk_manager: &ChunkManager, world_id: &str, position: [f64; 3], size: (f64, f64)) -> bool {
+        let mut range = [(0, 0); 3];
+        for (axis, bounds) in range.iter_mut().enumerate() {
+            let (min, max) = Self::box_extent(position, size, axis);
+            // Touching a face isn't overlapping it
+            *bounds = (min.floor() as i32, (max - COLLISION_EPSILON).floor() as i32);
+        }
+
+        for bx in range[0].0..=range[0].1 {
+            for by in range[1].0..=range[1].1 {
+                for bz in range[2].0..=range[2].1 {
+                    let blocked = chunk_manager
+                        .get_block(world_id, bx, by, bz)
+                        .await
+                        .is_none_or(|block| self.is_solid(block));
+                    if blocked {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    fn is_solid(&self, block_id: u8) -> bool {
+        self.block_registry.is_solid(block_id)
+    }
+
+    pub async fn run(
+        physics_system: Arc<RwLock<PhysicsSystem>>,
+        world_manager: Arc<RwLock<WorldManager>>,
+        chunk_manager: Arc<RwLock<ChunkManager>>,
+        entity_manager: Arc<RwLock<EntityManager>>,
+        player_manager: Arc<RwLock<PlayerManager>>,
+    ) {
+        // Keeps ticking when disabled, worlds are held still but EntityManager::tick must run
+        if !physics_system.read().await.enabled {
+            info!("Physics system disabled");
+        }
+
+        let dt = TICK_MILLIS as f64 / 1000.0;
+        let mut ticker = interval(Duration::from_millis(TICK_MILLIS));
+        loop {
+            ticker.tick().await;
+
+            let worlds = world_manager.read().await.get_all_worlds().await;
+            let mut physics_system = physics_system.write().await;
+            let chunk_manager = chunk_manager.read().await;
+            let mut entity_manager = entity_manager.write().await;
+            let mut player_manager = player_manager.write().await;
+
+            for world in &worlds {
+                physics_system.tick(world, dt, &chunk_manager, &mut entity_manager, &mut player_manager).await;
+            }
+            physics_system.prune_falls(&entity_manager, &player_manager).await;
+            entity_manager.tick(dt).await;
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::auth::{auth_service::AuthService, jwt_service::JwtService};
+    use crate::database::{
+        database_service::{DatabaseConfig, DatabaseService},
+        entity_repository::EntityRepository,
+        player_repository::PlayerRepository,
+    };
+    use crate::systems::{
+        loot_system::LootSystem,
+        world_manager::{Difficulty, GameMode as WorldGameMode, WorldSettings},
+    };
+    use crate::worlds::{
+        biome_system::BiomeSystem,
+        block_registry::STONE,
+        structure_generator::StructureGenerator,
+        terrain_generator::TerrainParams,
+    };
+
+    const LEDGE: i32 = 200; // Open air in any generated terrain
+
+    struct Fixture {
+        physics: PhysicsSystem,
+        world: WorldInfo,
+        chunk_manager: ChunkManager,
+        entity_manager: EntityManager,
+        player_manager: PlayerManager,
+    }
+
+    // One loaded chunk with a single stone block at (4, LEDGE, 4)
+    async fn fixture() -> Fixture {
+        let config = DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            ..DatabaseConfig::default()
+        };
+        let database_service = Arc::new(DatabaseService::new(&config).await.unwrap());
+        let player_repository = Arc::new(PlayerRepository::new(database_service.clone()));
+        let auth_service = Arc::new(AuthService::new(player_repository.clone(), Arc::new(JwtService::new("x".repeat(32)).unwrap())));
+        let loot_system = Arc::new(LootSystem::new());
+        let block_registry = Arc::new(BlockRegistry::new());
+
+        let mut chunk_manager = ChunkManager::new(
+            1,
+            TerrainParams::default(),
+            Arc::new(BiomeSystem::new()),
+            Arc::new(StructureGenerator::new(loot_system.clone())),
+            block_registry.clone(),
+            std::env::temp_dir().join("strixcraft-physics-tests"),
+        );
+        chunk_manager.register_world("world", 7);
+        chunk_manager.get_chunk("world", 0, 0).await.unwrap();
+        chunk_manager.set_block("world", 4, LEDGE, 4, STONE).await.unwrap();
+
+        let world = WorldInfo {
+            id: "world".to_string(),
+            name: "world".to_string(),
+            seed: 7,
+            game_mode: WorldGameMode::Survival,
+            player_count: 0,
+            max_players: 10,
+            created_at: Utc::now(),
+            last_active: Utc::now(),
+            is_online: true,
+            settings: WorldSettings {
+                allow_pvp: false,
+                allow_mob_griefing: false,
+                keep_inventory: false,
+                natural_regeneration: true,
+                difficulty: Difficulty::Normal,
+                weather_enabled: false,
+                time_enabled: false,
+                mobs_enabled: false,
+                physics_enabled: true,
+                game_rules: HashMap::new(),
+                max_entities: None,
+            },
+            spawn_point: [0.0, 64.0, 0.0],
+            border: None,
+            whitelist: None,
+            whitelist_enabled: false,
+        };
+
+        Fixture {
+            physics: PhysicsSystem::new(block_registry),
+            world,
+            chunk_manager,
+            entity_manager: EntityManager::new(Arc::new(EntityRepository::new(database_service)), loot_system),
+            player_manager: PlayerManager::new(player_repository, auth_service),
+        }
+    }
+
+    impl Fixture {
+        async fn tick(&mut self) {
+            self.physics
+                .tick(&self.world, 0.05, &self.chunk_manager, &mut self.entity_manager, &mut self.player_manager)
+                .await;
+            self.physics.prune_falls(&self.entity_manager, &self.player_manager).await;
+        }
+
+        async fn player_at(&mut self, position: [f64; 3]) -> String {
+            let player = self.player_manager.register_player("alex", "correct horse battery").await.unwrap();
+            self.player_manager.authenticate_player("alex", "correct horse battery").await.unwrap().unwrap();
+            self.player_manager.set_player_world(&player.id, Some("world".to_string())).await.unwrap();
+            self.player_manager.teleport_player(&player.id, position).await.unwrap();
+            player.id
+        }
+    }
+
+    #[tokio::test]
+    async fn a_player_on_the_edge_of_a_block_is_standing() {
+        let mut fixture = fixture().await;
+        // Centre over the air next to the block, the footprint still overlaps it
+        let player_id = fixture.player_at([5.2, (LEDGE + 1) as f64, 4.5]).await;
+
+        fixture.tick().await;
+        assert!(!fixture.physics.player_falls.contains_key(&player_id));
+
+        fixture.player_manager.teleport_player(&player_id, [5.5, (LEDGE + 1) as f64, 4.5]).await.unwrap();
+        fixture.tick().await;
+        assert!(fixture.physics.player_falls.contains_key(&player_id));
+    }
+
+    #[tokio::test]
+    async fn falls_are_forgotten_on_despawn_and_disconnect() {
+        let mut fixture = fixture().await;
+        let player_id = fixture.player_at([8.5, (LEDGE + 20) as f64, 8.5]).await;
+        let cow = fixture
+            .entity_manager
+            .spawn_entity(EntityKind::BuiltIn(EntityType::Cow), [10.5, (LEDGE + 20) as f64, 10.5], "world".to_string(), None)
+            .await
+            .unwrap();
+        fixture.entity_manager.update_entity_velocity(&cow, [0.0, -1.0, 0.0]).await;
+
+        fixture.tick().await;
+        assert!(fixture.physics.entity_falls.contains_key(&cow));
+        assert!(fixture.physics.player_falls.contains_key(&player_id));
+
+        fixture.entity_manager.despawn_entity(&cow).await;
+        fixture.player_manager.player_disconnect(&player_id).await.unwrap();
+        fixture.tick().await;
+        assert!(fixture.physics.entity_falls.is_empty());
+        assert!(fixture.physics.player_falls.is_empty());
+    }
+}
@@ -1,13 +1,29 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
-use serde::{Deserialize, Serialize};
+use tokio::time::{interval, Duration};
+use serde::{Deserialize, Deserializer, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use log::{info, warn, error};
 
 use crate::auth::auth_service::AuthService;
 use crate::database::player_repository::PlayerRepository;
+use crate::systems::chunk_manager::ChunkManager;
+use crate::systems::inventory_system::{Inventory, InventoryItem, InventorySystem, ToolState, DEFAULT_INVENTORY_SLOTS, HOTBAR_SLOTS};
+use crate::systems::status_effect_system::{self, EffectKind, StatusEffect};
+use crate::systems::world_manager::WorldManager;
+
+const DEFAULT_SPAWN_POINT: [f64; 3] = [0.0, 64.0, 0.0];
+
+// Survival rates, applied once per `tick_survival` call
+const HUNGER_DEPLETION_PER_TICK: f32 = 0.005;
+const STARVATION_DAMAGE_PER_TICK: f32 = 0.05;
+const REGENERATION_PER_TICK: f32 = 0.05;
+const REGENERATION_HUNGER_THRESHOLD: f32 = 18.0;
+const SURVIVAL_TICK_MILLIS: u64 = 1000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
@@ -21,20 +37,108 @@ pub struct Player {
     pub max_hunger: f32,
     pub experience: i32,
     pub level: i32,
-    pub inventory: Vec<InventoryItem>,
-    pub selected_slot: usize,
+    #[serde(deserialize_with = "saved_inventory")]
+    pub inventory: Inventory,
     pub game_mode: GameMode,
+    pub role: PlayerRole,
     pub world_id: Option<String>,
     pub is_online: bool,
     pub last_seen: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub statistics: PlayerStatistics,
+    #[serde(default)]
+    pub infinite_items: bool,
+    #[serde(default)]
+    pub effects: Vec<StatusEffect>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct InventoryItem {
-    pub id: u32,
-    pub count: u32,
-    pub metadata: Option<serde_json::Value>,
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerStatistics {
+    pub blocks_broken: u64,
+    pub blocks_placed: u64,
+    pub distance_travelled: f64,
+    pub deaths: u32,
+    pub mobs_killed: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatKind {
+    BlocksBroken,
+    BlocksPlaced,
+    DistanceTravelled,
+    Deaths,
+    MobsKilled,
+}
+
+impl StatKind {
+    pub const ALL: [StatKind; 5] = [
+        StatKind::BlocksBroken,
+        StatKind::BlocksPlaced,
+        StatKind::DistanceTravelled,
+        StatKind::Deaths,
+        StatKind::MobsKilled,
+    ];
+
+    // Matches the PlayerStatistics field names
+    pub fn name(self) -> &'static str {
+        match self {
+            StatKind::BlocksBroken => "blocks_broken",
+            StatKind::BlocksPlaced => "blocks_placed",
+            StatKind::DistanceTravelled => "distance_travelled",
+            StatKind::Deaths => "deaths",
+            StatKind::MobsKilled => "mobs_killed",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<StatKind> {
+        Self::ALL.into_iter().find(|stat| stat.name() == name)
+    }
+}
+
+impl PlayerStatistics {
+    pub fn get(&self, stat: StatKind) -> f64 {
+        match stat {
+            StatKind::BlocksBroken => self.blocks_broken as f64,
+            StatKind::BlocksPlaced => self.blocks_placed as f64,
+            StatKind::DistanceTravelled => self.distance_travelled,
+            StatKind::Deaths => self.deaths as f64,
+            StatKind::MobsKilled => self.mobs_killed as f64,
+        }
+    }
+}
+
+// Saves from before players had an Inventory hold a bare list of stacks, emptied slots as zero counts
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedInventory {
+    Slots(Box<Inventory>),
+    Stacks(Vec<SavedStack>),
+}
+
+#[derive(Deserialize)]
+struct SavedStack {
+    id: u32,
+    count: u32,
+    metadata: Option<serde_json::Value>,
+}
+
+fn saved_inventory<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Inventory, D::Error> {
+    let stacks = match SavedInventory::deserialize(deserializer)? {
+        SavedInventory::Slots(inventory) => return Ok(*inventory),
+        SavedInventory::Stacks(stacks) => stacks,
+    };
+
+    let mut inventory = InventorySystem::create_inventory(stacks.len().max(DEFAULT_INVENTORY_SLOTS), HOTBAR_SLOTS);
+    for (slot, stack) in stacks.into_iter().enumerate().filter(|(_, stack)| stack.count > 0) {
+        inventory.items[slot] = Some(InventoryItem {
+            id: stack.id,
+            count: stack.count,
+            metadata: stack.metadata,
+            slot,
+        });
+    }
+    Ok(inventory)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,12 +147,150 @@ pub enum GameMode {
     Creative,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlayerRole {
+    Guest,
+    Player,
+    Moderator,
+    Admin,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BanInfo {
+    pub reason: String,
+    pub until: Option<DateTime<Utc>>, // None for permanent bans
+    pub by: String,
+}
+
+// Speeds are in blocks per second, generous enough that sprint-jumping and lag spikes pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MovementLimits {
+    pub enabled: bool,
+    pub max_horizontal_speed: f64,
+    pub max_ascent_speed: f64,
+    pub max_descent_speed: f64, // Above terminal velocity, falls are never rejected
+    pub exempt_creative: bool, // Creative players can fly
+    pub exempt_players: HashSet<String>, // Usernames allowed to move freely, e.g. for testing
+    pub flag_threshold: u32, // Rejected moves before the player is reported
+}
+
+impl Default for MovementLimits {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_horizontal_speed: 10.0,
+            max_ascent_speed: 9.0,
+            max_descent_speed: 80.0,
+            exempt_creative: true,
+            exempt_players: HashSet::new(),
+            flag_threshold: 20,
+        }
+    }
+}
+
+// A full budget covers this many seconds of movement, enough for lag spikes to catch up
+const MOVEMENT_BURST_SECONDS: f64 = 1.0;
+
+// Distance a player may still cover, refilled in real time at the speed limits. Bursts of
+// packets spend from the same budget, so sending more often never allows moving faster
+#[derive(Debug, Clone)]
+struct MovementBudget {
+    horizontal: f64,
+    ascent: f64,
+    descent: f64,
+    refilled_at: std::time::Instant,
+}
+
+impl MovementBudget {
+    fn full(limits: &MovementLimits, speed: f64) -> Self {
+        Self {
+            horizontal: limits.max_horizontal_speed * speed * MOVEMENT_BURST_SECONDS,
+            ascent: limits.max_ascent_speed * MOVEMENT_BURST_SECONDS,
+            descent: limits.max_descent_speed * MOVEMENT_BURST_SECONDS,
+            refilled_at: std::time::Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, limits: &MovementLimits, speed: f64, now: std::time::Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        let full = Self::full(limits, speed);
+        self.horizontal = (self.horizontal + limits.max_horizontal_speed * speed * elapsed).min(full.horizontal);
+        self.ascent = (self.ascent + limits.max_ascent_speed * elapsed).min(full.ascent);
+        self.descent = (self.descent + limits.max_descent_speed * elapsed).min(full.descent);
+        self.refilled_at = now;
+    }
+
+    // Spends the move when there's enough left, a rejected move costs nothing
+    fn try_spend(&mut self, horizontal: f64, dy: f64) -> bool {
+        let (ascent, descent) = (dy.max(0.0), (-dy).max(0.0));
+        if horizontal > self.horizontal || ascent > self.ascent || descent > self.descent {
+            return false;
+        }
+        self.horizontal -= horizontal;
+        self.ascent -= ascent;
+        self.descent -= descent;
+        true
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ExperienceCurve {
+    Linear { per_level: i32 },
+    Table(Vec<i32>), // Total experience needed to reach level 2, 3, ...
+}
+
+impl ExperienceCurve {
+    pub fn level_for_experience(&self, experience: i32) -> i32 {
+        match self {
+            ExperienceCurve::Linear { per_level } => {
+                experience.max(0) / (*per_level).max(1) + 1
+            }
+            ExperienceCurve::Table(thresholds) => {
+                thresholds.iter().take_while(|&&needed| experience >= needed).count() as i32 + 1
+            }
+        }
+    }
+
+    pub fn experience_for_level(&self, level: i32) -> i32 {
+        if level <= 1 {
+            return 0;
+        }
+
+        match self {
+            ExperienceCurve::Linear { per_level } => {
+                (level - 1).saturating_mul((*per_level).max(1))
+            }
+            ExperienceCurve::Table(thresholds) => {
+                // Levels past the end of the table can't be reached
+                thresholds.get((level - 2) as usize).copied().unwrap_or(i32::MAX)
+            }
+        }
+    }
+}
+
+impl Default for ExperienceCurve {
+    fn default() -> Self {
+        ExperienceCurve::Linear { per_level: 100 }
+    }
+}
+
 #[derive(Debug)]
 pub struct PlayerManager {
     players: HashMap<String, Player>,
     online_players: HashMap<String, String>, // session_id -> player_id
+    banned: HashMap<String, BanInfo>, // player_id -> ban
+    role_permissions: HashMap<PlayerRole, Vec<String>>,
     auth_service: Arc<AuthService>,
     player_repository: Arc<PlayerRepository>,
+    experience_curve: ExperienceCurve,
+    saved_snapshots: Mutex<HashMap<String, u64>>, // player_id -> hash of the last saved state
+    kicked: Vec<(String, String)>, // (player_id, reason) whose sockets still need closing
+    left_worlds: Vec<String>, // world ids whose player count still needs lowering
+    movement_limits: MovementLimits,
+    movement_violations: HashMap<String, u32>, // player_id -> rejected moves this session
+    movement_budgets: HashMap<String, MovementBudget>, // player_id -> distance left to move
+    inventory_slots: usize, // Size of a new player's inventory
 }
 
 impl PlayerManager {
@@ -56,12 +298,45 @@ impl PlayerManager {
         player_repository: Arc<PlayerRepository>,
         auth_service: Arc<AuthService>,
     ) -> Self {
-        Self {
+        let mut manager = Self {
             players: HashMap::new(),
             online_players: HashMap::new(),
+            banned: HashMap::new(),
+            role_permissions: HashMap::new(),
             auth_service,
             player_repository,
-        }
+            experience_curve: ExperienceCurve::default(),
+            saved_snapshots: Mutex::new(HashMap::new()),
+            kicked: Vec::new(),
+            left_worlds: Vec::new(),
+            movement_limits: MovementLimits::default(),
+            movement_violations: HashMap::new(),
+            movement_budgets: HashMap::new(),
+            inventory_slots: DEFAULT_INVENTORY_SLOTS,
+        };
+
+        manager.initialize_default_permissions();
+        manager
+    }
+
+    pub fn set_experience_curve(&mut self, curve: ExperienceCurve) {
+        self.experience_curve = curve;
+    }
+
+    pub fn set_movement_limits(&mut self, limits: MovementLimits) {
+        self.movement_limits = limits;
+    }
+
+    pub fn set_inventory_slots(&mut self, inventory_slots: usize) {
+        self.inventory_slots = inventory_slots;
+    }
+
+    pub fn experience_for_level(&self, level: i32) -> i32 {
+        self.experience_curve.experience_for_level(level)
+    }
+
+    fn new_inventory(&self) -> Inventory {
+        InventorySystem::create_inventory(self.inventory_slots, HOTBAR_SLOTS.min(self.inventory_slots))
     }
 
     pub async fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
@@ -71,33 +346,66 @@ impl PlayerManager {
         let existing_players = self.player_repository.get_all_players().await?;
         
         for player_data in existing_players {
-            let player = Player {
-                id: player_data.id,
-                username: player_data.username,
-                position: [0.0, 64.0, 0.0],
-                rotation: [0.0, 0.0, 0.0],
-                health: 20.0,
-                max_health: 20.0,
-                hunger: 20.0,
-                max_hunger: 20.0,
-                experience: 0,
-                level: 1,
-                inventory: vec![],
-                selected_slot: 0,
-                game_mode: GameMode::Survival,
-                world_id: None,
-                is_online: false,
-                last_seen: player_data.last_seen,
-                created_at: player_data.created_at,
+            let role = match player_data.role.as_str() {
+                "guest" => PlayerRole::Guest,
+                "moderator" => PlayerRole::Moderator,
+                "admin" => PlayerRole::Admin,
+                _ => PlayerRole::Player,
             };
-            
+
+            let player = match player_data.data {
+                // Role, last seen and statistics are also written on their own, so the columns win.
+                // Nobody is connected yet, so nobody is in a world either
+                Some(saved) => Player {
+                    id: player_data.id,
+                    username: player_data.username,
+                    role,
+                    world_id: None,
+                    is_online: false,
+                    last_seen: player_data.last_seen,
+                    created_at: player_data.created_at,
+                    statistics: player_data.statistics,
+                    ..saved
+                },
+                None => Player {
+                    id: player_data.id,
+                    username: player_data.username,
+                    position: DEFAULT_SPAWN_POINT,
+                    rotation: [0.0, 0.0, 0.0],
+                    health: 20.0,
+                    max_health: 20.0,
+                    hunger: 20.0,
+                    max_hunger: 20.0,
+                    experience: 0,
+                    level: 1,
+                    inventory: self.new_inventory(),
+                    game_mode: GameMode::Survival,
+                    role,
+                    world_id: None,
+                    is_online: false,
+                    last_seen: player_data.last_seen,
+                    created_at: player_data.created_at,
+                    statistics: player_data.statistics,
+                    infinite_items: false,
+                    effects: Vec::new(),
+                },
+            };
+
+            // What was just loaded is what's saved, so untouched players never look dirty
+            let snapshot = Self::snapshot_hash(&player)?;
+            self.saved_snapshots.lock().unwrap().insert(player.id.clone(), snapshot);
             self.players.insert(player.id.clone(), player);
         }
+
+        self.player_repository.delete_expired_bans(Utc::now()).await?;
+        self.banned = self.player_repository.get_all_bans().await?.into_iter().collect();
         
-        info!("Player manager initialized with {} players", self.players.len());
+        info!("Player manager initialized with {} players and {} bans", self.players.len(), self.banned.len());
         Ok(())
     }
 
+    // Sessions log in through connect_session, this shortcut is left for test fixtures
+    #[cfg(test)]
     pub async fn authenticate_player(
         &mut self,
         username: &str,
@@ -105,6 +413,10 @@ impl PlayerManager {
     ) -> Result<Option<Player>, Box<dyn std::error::Error>> {
         match self.auth_service.authenticate(username, password).await? {
             Some(player_id) => {
+                if let Some(ban) = self.get_active_ban(&player_id) {
+                    return Err(format!("You are banned: {}", ban.reason).into());
+                }
+
                 if let Some(player) = self.players.get_mut(&player_id) {
                     player.is_online = true;
                     player.last_seen = Utc::now();
@@ -137,7 +449,7 @@ impl PlayerManager {
         let player = Player {
             id: player_id.clone(),
             username: username.to_string(),
-            position: [0.0, 64.0, 0.0],
+            position: DEFAULT_SPAWN_POINT,
             rotation: [0.0, 0.0, 0.0],
             health: 20.0,
             max_health: 20.0,
@@ -145,13 +457,16 @@ impl PlayerManager {
             max_hunger: 20.0,
             experience: 0,
             level: 1,
-            inventory: vec![],
-            selected_slot: 0,
+            inventory: self.new_inventory(),
             game_mode: GameMode::Survival,
+            role: PlayerRole::Player,
             world_id: None,
             is_online: false,
             last_seen: now,
             created_at: now,
+            statistics: PlayerStatistics::default(),
+            infinite_items: false,
+            effects: Vec::new(),
         };
 
         // Create player in database
@@ -161,6 +476,8 @@ impl PlayerManager {
         self.auth_service.create_user(username, password, &player_id).await?;
         
         // Add to memory
+        let snapshot = Self::snapshot_hash(&player)?;
+        self.saved_snapshots.lock().unwrap().insert(player_id.clone(), snapshot);
         self.players.insert(player_id.clone(), player.clone());
         
         info!("Registered new player: {} (ID: {})", username, player_id);
@@ -180,68 +497,248 @@ impl PlayerManager {
         self.players.values().filter(|p| p.is_online).cloned().collect()
     }
 
+    // Client reported movement, false when the move was too fast to be legitimate or crosses
+    // the world border, and the player stays where they were so the caller can snap the client back
     pub async fn update_player_position(
         &mut self,
         player_id: &str,
         position: [f64; 3],
         rotation: [f64; 3],
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(player) = self.players.get_mut(player_id) {
-            player.position = position;
-            player.rotation = rotation;
-            player.last_seen = Utc::now();
+        world_manager: &WorldManager,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+
+        if let Some(world_id) = &player.world_id {
+            if !world_manager.is_within_border(world_id, position) {
+                return Ok(false);
+            }
         }
+
+        if !Self::is_plausible_move(&self.movement_limits, &mut self.movement_budgets, player, position) {
+            let violations = self.movement_violations.entry(player_id.to_string()).or_insert(0);
+            *violations += 1;
+            if *violations == self.movement_limits.flag_threshold {
+                warn!("Player {} has made {} implausible moves, possible movement hack", player.username, violations);
+            }
+            return Ok(false);
+        }
+
+        let dx = position[0] - player.position[0];
+        let dy = position[1] - player.position[1];
+        let dz = position[2] - player.position[2];
+        player.statistics.distance_travelled += (dx * dx + dy * dy + dz * dz).sqrt();
+
+        player.position = position;
+        player.rotation = rotation;
+        player.last_seen = Utc::now();
         
+        Ok(true)
+    }
+
+    // Server initiated moves such as /tp skip the movement check
+    pub async fn teleport_player(&mut self, player_id: &str, position: [f64; 3]) -> Result<(), Box<dyn std::error::Error>> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+        player.position = position;
+        player.last_seen = Utc::now();
+        self.movement_budgets.remove(player_id);
         Ok(())
     }
 
+    fn is_plausible_move(
+        limits: &MovementLimits,
+        budgets: &mut HashMap<String, MovementBudget>,
+        player: &Player,
+        position: [f64; 3],
+    ) -> bool {
+        if position.iter().any(|axis| !axis.is_finite()) {
+            return false;
+        }
+        if !limits.enabled
+            || (limits.exempt_creative && matches!(player.game_mode, GameMode::Creative))
+            || limits.exempt_players.contains(&player.username)
+        {
+            return true;
+        }
+
+        let dx = position[0] - player.position[0];
+        let dy = position[1] - player.position[1];
+        let dz = position[2] - player.position[2];
+        let horizontal = (dx * dx + dz * dz).sqrt();
+
+        // Speed effects raise the limit, slowness doesn't lower it so lag can't trip it
+        let speed = status_effect_system::speed_multiplier(&player.effects).max(1.0);
+
+        let budget = budgets
+            .entry(player.id.clone())
+            .or_insert_with(|| MovementBudget::full(limits, speed));
+        budget.refill(limits, speed, std::time::Instant::now());
+        budget.try_spend(horizontal, dy)
+    }
+
     pub async fn update_player_health(
         &mut self,
         player_id: &str,
         health: f32,
     ) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(player) = self.players.get_mut(player_id) {
+            let was_alive = player.health > 0.0;
             player.health = health.max(0.0).min(player.max_health);
+
+            if was_alive && player.health <= 0.0 {
+                player.statistics.deaths += 1;
+            }
         }
         
         Ok(())
     }
 
-    pub async fn update_player_hunger(
+    pub async fn respawn_player(
         &mut self,
         player_id: &str,
-        hunger: f32,
+        world_manager: &WorldManager,
+        inventory_system: &InventorySystem,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(player) = self.players.get_mut(player_id) {
-            player.hunger = hunger.max(0.0).min(player.max_hunger);
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+
+        if player.health > 0.0 {
+            return Err("Player is not dead".into());
+        }
+
+        let world_id = player.world_id.as_deref();
+        player.health = player.max_health;
+        player.hunger = player.max_hunger;
+        player.effects.clear();
+        player.position = world_id.and_then(|id| world_manager.get_spawn_point(id)).unwrap_or(DEFAULT_SPAWN_POINT);
+        self.movement_budgets.remove(player_id);
+        player.rotation = [0.0, 0.0, 0.0];
+
+        if world_id.and_then(|id| world_manager.get_bool_rule(id, "keep_inventory")) != Some(true) {
+            inventory_system.clear_inventory(&mut player.inventory);
         }
+
+        info!("Player {} respawned at {:?}", player.username, player.position);
         
         Ok(())
     }
 
-    pub async fn update_player_experience(
+    pub async fn tick_survival(&mut self, world_manager: &WorldManager) {
+        for player in self.players.values_mut() {
+            if !player.is_online || player.health <= 0.0 || !matches!(player.game_mode, GameMode::Survival) {
+                continue;
+            }
+
+            player.hunger = (player.hunger - HUNGER_DEPLETION_PER_TICK).max(0.0);
+
+            if player.hunger <= 0.0 {
+                player.health = (player.health - STARVATION_DAMAGE_PER_TICK).max(0.0);
+                if player.health <= 0.0 {
+                    player.statistics.deaths += 1;
+                    info!("Player {} starved to death", player.username);
+                }
+                continue;
+            }
+
+            if player.hunger < REGENERATION_HUNGER_THRESHOLD || player.health >= player.max_health {
+                continue;
+            }
+
+            let natural_regeneration = match &player.world_id {
+                Some(world_id) => world_manager
+                    .get_world(world_id)
+                    .await
+                    .is_none_or(|world| world.settings.natural_regeneration),
+                None => true,
+            };
+
+            if natural_regeneration {
+                player.health = (player.health + REGENERATION_PER_TICK).min(player.max_health);
+            }
+        }
+    }
+
+    // Returns false when a stronger effect of the same kind is already active
+    pub async fn apply_effect(
         &mut self,
         player_id: &str,
-        experience: i32,
+        kind: EffectKind,
+        amplifier: u8,
+        duration: chrono::Duration,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+        let effect = StatusEffect {
+            kind,
+            amplifier,
+            expires_at: Utc::now() + duration,
+        };
+        Ok(status_effect_system::merge_effect(&mut player.effects, effect))
+    }
+
+    pub async fn remove_effect(&mut self, player_id: &str, kind: EffectKind) -> Result<bool, Box<dyn std::error::Error>> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+        let before = player.effects.len();
+        player.effects.retain(|e| e.kind != kind);
+        Ok(player.effects.len() != before)
+    }
+
+    // Drops expired effects, then heals or hurts online players. Poison never kills
+    pub async fn tick_effects(&mut self) {
+        let now = Utc::now();
+        let mut changes = Vec::new();
+
+        for player in self.players.values_mut() {
+            if player.effects.is_empty() {
+                continue;
+            }
+            status_effect_system::remove_expired(&mut player.effects, now);
+
+            if !player.is_online || player.health <= 0.0 {
+                continue;
+            }
+            let mut change = status_effect_system::health_change(&player.effects);
+            if matches!(player.game_mode, GameMode::Creative) {
+                change = change.max(0.0);
+            }
+            if change != 0.0 {
+                let health = (player.health + change).max(player.health.min(1.0));
+                changes.push((player.id.clone(), health));
+            }
+        }
+
+        for (player_id, health) in changes {
+            if let Err(e) = self.update_player_health(&player_id, health).await {
+                error!("Failed to apply status effects to {}: {}", player_id, e);
+            }
+        }
+    }
+
+    pub async fn add_experience(
+        &mut self,
+        player_id: &str,
+        delta: i32,
     ) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(player) = self.players.get_mut(player_id) {
-            player.experience = experience;
-            
-            // Calculate level based on experience
-            let new_level = (experience as f32 / 100.0).floor() as i32 + 1;
-            if new_level != player.level {
-                player.level = new_level;
-                info!("Player {} leveled up to level {}", player.username, new_level);
-            }
+            let experience = player.experience.saturating_add(delta).max(0);
+            Self::apply_experience(player, experience, &self.experience_curve);
         }
         
         Ok(())
     }
 
+    fn apply_experience(player: &mut Player, experience: i32, curve: &ExperienceCurve) {
+        player.experience = experience;
+
+        // Calculate level based on experience
+        let new_level = curve.level_for_experience(experience);
+        if new_level > player.level {
+            info!("Player {} leveled up to level {}", player.username, new_level);
+        }
+        player.level = new_level;
+    }
+
     pub async fn update_player_inventory(
         &mut self,
         player_id: &str,
-        inventory: Vec<InventoryItem>,
+        inventory: Inventory,
     ) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(player) = self.players.get_mut(player_id) {
             player.inventory = inventory;
@@ -250,6 +747,75 @@ impl PlayerManager {
         Ok(())
     }
 
+    // Slot changes themselves are InventorySystem's, this only hands out the player's inventory
+    pub fn get_inventory_mut(&mut self, player_id: &str) -> Option<&mut Inventory> {
+        self.players.get_mut(player_id).map(|player| &mut player.inventory)
+    }
+
+    // Takes one of the placed block from the selected slot, creative and infinite_items players build from nothing
+    pub fn take_placed_block(&mut self, player_id: &str, block_id: u8, inventory_system: &InventorySystem) -> Result<(), String> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+        if matches!(player.game_mode, GameMode::Creative) || player.infinite_items {
+            return Ok(());
+        }
+
+        match inventory_system.get_selected_item(&player.inventory) {
+            Some(item) if item.id == block_id as u32 => {
+                let slot = player.inventory.selected_slot;
+                inventory_system.drop_item(&mut player.inventory, slot, Some(1));
+                Ok(())
+            }
+            _ => Err("You aren't holding that block".to_string()),
+        }
+    }
+
+    // Wears down whatever the player is holding, a broken tool disappears from the inventory
+    pub async fn damage_held_tool(
+        &mut self,
+        player_id: &str,
+        amount: u32,
+        inventory_system: &InventorySystem,
+    ) -> Result<ToolState, Box<dyn std::error::Error>> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+        let slot = player.inventory.selected_slot;
+        let creative = matches!(player.game_mode, GameMode::Creative);
+
+        let state = inventory_system.damage_tool(&mut player.inventory, slot, amount, creative);
+        if state == ToolState::Broken {
+            info!("Player {}'s tool in slot {} broke", player.username, slot);
+        }
+
+        Ok(state)
+    }
+
+    pub async fn set_game_mode(
+        &mut self,
+        player_id: &str,
+        mode: GameMode,
+    ) -> Result<GameMode, Box<dyn std::error::Error>> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+
+        match mode {
+            GameMode::Creative => {
+                player.health = player.max_health;
+                player.hunger = player.max_hunger;
+                player.infinite_items = true;
+            }
+            GameMode::Survival => {
+                // Hunger resumes on its own since tick_survival only runs for survival players
+                player.infinite_items = false;
+            }
+        }
+
+        let previous = std::mem::replace(&mut player.game_mode, mode);
+
+        info!("Player {} switched game mode from {:?} to {:?}", player.username, previous, player.game_mode);
+        
+        Ok(previous)
+    }
+
+    // Skips the world's player count, real moves go through teleport_to_world
+    #[cfg(test)]
     pub async fn set_player_world(
         &mut self,
         player_id: &str,
@@ -262,20 +828,427 @@ impl PlayerManager {
         Ok(())
     }
 
+    pub async fn teleport_to_world(
+        &mut self,
+        player_id: &str,
+        target_world_id: &str,
+        world_manager: &mut WorldManager,
+        chunk_manager: &mut ChunkManager,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+
+        if player.world_id.as_deref() == Some(target_world_id) {
+            return Err("Player is already in that world".into());
+        }
+
+        // Join first so a full destination leaves the player where they are
+        let target_world = world_manager.join_world(target_world_id, player_id, chunk_manager).await?;
+
+        if let Some(previous_world_id) = &player.world_id {
+            if let Err(e) = world_manager.leave_world(previous_world_id).await {
+                // Undo the join so the player is still counted in exactly one world
+                if let Err(undo) = world_manager.leave_world(target_world_id).await {
+                    error!("Failed to undo joining world {}: {}", target_world_id, undo);
+                }
+                self.movement_budgets.remove(player_id);
+                return Err(e);
+            }
+        }
+
+        player.world_id = Some(target_world.id.clone());
+        player.position = target_world.spawn_point;
+        player.rotation = [0.0, 0.0, 0.0];
+        player.last_seen = Utc::now();
+        self.movement_budgets.remove(player_id);
+
+        info!("Player {} teleported to world {}", player.username, target_world.name);
+        
+        Ok(())
+    }
+
+    // Token comes from the websocket handshake, the session stays registered until disconnect
+    pub async fn connect_session(
+        &mut self,
+        session_id: &str,
+        token: &str,
+    ) -> Result<Player, Box<dyn std::error::Error>> {
+        let player_id = self.auth_service.verify_token(token).await?.ok_or("Invalid token")?;
+
+        if let Some(ban) = self.get_active_ban(&player_id) {
+            return Err(format!("You are banned: {}", ban.reason).into());
+        }
+
+        let player = self.players.get_mut(&player_id).ok_or("Player not found")?;
+        player.is_online = true;
+        player.last_seen = Utc::now();
+        let player = player.clone();
+
+        self.online_players.insert(session_id.to_string(), player_id.clone());
+        self.player_repository.update_player_last_seen(&player_id).await?;
+
+        info!("Player connected: {} (session {})", player.username, session_id);
+        Ok(player)
+    }
+
+    pub async fn disconnect_session(&mut self, session_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(player_id) = self.online_players.remove(session_id) {
+            // Stay online while any other session of this player is open
+            if !self.online_players.values().any(|id| id == &player_id) {
+                self.player_disconnect(&player_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn player_disconnect(&mut self, player_id: &str) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(player) = self.players.get_mut(player_id) {
             player.is_online = false;
             player.last_seen = Utc::now();
-            
-            // Update in database
-            self.player_repository.update_player_last_seen(player_id).await?;
-            
+            if let Some(world_id) = player.world_id.take() {
+                self.left_worlds.push(world_id);
+            }
+            self.movement_budgets.remove(player_id);
+
+            // Everything since the last autosave would be lost otherwise. A failed write
+            // leaves them dirty, so the next save outside any world retries it
+            let snapshot = Self::snapshot_hash(player)?;
+            self.player_repository.save_player(player).await?;
+            self.saved_snapshots.lock().unwrap().insert(player_id.to_string(), snapshot);
+
             info!("Player disconnected: {} (ID: {})", player.username, player_id);
         }
         
         Ok(())
     }
 
+    pub async fn kick_player(
+        &mut self,
+        player_id: &str,
+        reason: &str,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        if !self.players.get(player_id).is_some_and(|p| p.is_online) {
+            return Ok(false);
+        }
+
+        info!("Kicking player {}: {}", player_id, reason);
+
+        // Drop the sessions now so the socket closing later doesn't disconnect them twice
+        self.online_players.retain(|_, id| id != player_id);
+        self.player_disconnect(player_id).await?;
+        self.kicked.push((player_id.to_string(), reason.to_string()));
+        
+        Ok(true)
+    }
+
+    // Kicks everyone in a deleted world and forgets it as their last world, so they
+    // come back to the lobby instead of a world that no longer exists
+    pub async fn evict_world(&mut self, world_id: &str, reason: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let player_ids: Vec<String> = self
+            .players
+            .values()
+            .filter(|p| p.world_id.as_deref() == Some(world_id))
+            .map(|p| p.id.clone())
+            .collect();
+
+        let mut kicked = 0;
+        for player_id in &player_ids {
+            if self.kick_player(player_id, reason).await? {
+                kicked += 1;
+            }
+            if let Some(player) = self.players.get_mut(player_id) {
+                player.world_id = None;
+            }
+        }
+        // The world is going away, there's no player count left to lower
+        self.left_worlds.retain(|left| left != world_id);
+
+        Ok(kicked)
+    }
+
+    // Drained by the message handler, which owns the sockets
+    pub fn take_kicked_players(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.kicked)
+    }
+
+    // Drained by the message handler, which holds the world manager
+    pub fn take_left_worlds(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.left_worlds)
+    }
+
+    pub async fn ban_player(
+        &mut self,
+        player_id: &str,
+        reason: &str,
+        duration_minutes: Option<u32>,
+        by: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.players.contains_key(player_id) {
+            return Err("Player not found".into());
+        }
+
+        let until = duration_minutes
+            .map(|minutes| Utc::now() + chrono::Duration::minutes(minutes as i64));
+
+        let ban = BanInfo {
+            reason: reason.to_string(),
+            until,
+            by: by.to_string(),
+        };
+        self.player_repository.save_ban(player_id, &ban).await?;
+        self.banned.insert(player_id.to_string(), ban);
+        self.prune_expired_bans().await?;
+
+        // Banned players shouldn't stay connected
+        self.kick_player(player_id, reason).await?;
+
+        match duration_minutes {
+            Some(minutes) => info!("Banned player {} for {} minutes: {}", player_id, minutes, reason),
+            None => info!("Permanently banned player {}: {}", player_id, reason),
+        }
+        
+        Ok(())
+    }
+
+    pub async fn unban_player(&mut self, player_id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.banned.remove(player_id).is_none() {
+            return Ok(false);
+        }
+        self.player_repository.delete_ban(player_id).await?;
+        Ok(true)
+    }
+
+    pub async fn prune_expired_bans(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let before = self.banned.len();
+        self.banned.retain(|_, ban| ban.until.is_none_or(|until| until >= now));
+        self.player_repository.delete_expired_bans(now).await?;
+        Ok(before - self.banned.len())
+    }
+
+    pub fn is_banned(&self, player_id: &str) -> bool {
+        self.get_active_ban(player_id).is_some()
+    }
+
+    fn get_active_ban(&self, player_id: &str) -> Option<&BanInfo> {
+        let ban = self.banned.get(player_id)?;
+        match ban.until {
+            // Expired bans are dropped by prune_expired_bans
+            Some(until) if Utc::now() > until => None,
+            _ => Some(ban),
+        }
+    }
+
+    pub async fn set_player_role(
+        &mut self,
+        player_id: &str,
+        role: PlayerRole,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let player = self.players.get_mut(player_id).ok_or("Player not found")?;
+        player.role = role;
+
+        // Update in database
+        self.player_repository.update_player_role(player_id, role).await?;
+
+        info!("Set role of player {} to {:?}", player.username, role);
+        
+        Ok(())
+    }
+
+    pub fn has_permission(&self, player_id: &str, permission: &str) -> bool {
+        let role = match self.players.get(player_id) {
+            Some(player) => player.role,
+            None => return false,
+        };
+
+        self.role_permissions.get(&role).is_some_and(|permissions| {
+            permissions.iter().any(|p| p == "*" || p == permission)
+        })
+    }
+
+    pub fn grant_permission(&mut self, role: PlayerRole, permission: &str) {
+        let permissions = self.role_permissions.entry(role).or_default();
+        if !permissions.iter().any(|p| p == permission) {
+            permissions.push(permission.to_string());
+        }
+    }
+
+    pub fn record_block_break(&mut self, player_id: &str) {
+        if let Some(player) = self.players.get_mut(player_id) {
+            player.statistics.blocks_broken += 1;
+        }
+    }
+
+    pub fn record_block_place(&mut self, player_id: &str) {
+        if let Some(player) = self.players.get_mut(player_id) {
+            player.statistics.blocks_placed += 1;
+        }
+    }
+
+    pub fn record_mob_kill(&mut self, player_id: &str) {
+        if let Some(player) = self.players.get_mut(player_id) {
+            player.statistics.mobs_killed += 1;
+        }
+    }
+
+    pub async fn get_leaderboard(&self, stat: StatKind, top: usize) -> Vec<Player> {
+        let mut players: Vec<&Player> = self.players.values().collect();
+        players.sort_by(|a, b| {
+            b.statistics
+                .get(stat)
+                .partial_cmp(&a.statistics.get(stat))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        players.into_iter().take(top).cloned().collect()
+    }
+
+    // Every loaded player, online or not, for when nothing may be left unsaved
+    pub async fn save_all_players(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut saved = Vec::with_capacity(self.players.len());
+
+        for player in self.players.values() {
+            let snapshot = Self::snapshot_hash(player)?;
+            if let Err(e) = self.player_repository.save_player(player).await {
+                self.mark_players_saved(&saved);
+                return Err(e);
+            }
+            saved.push((player.clone(), snapshot));
+        }
+        self.mark_players_saved(&saved);
+
+        if !saved.is_empty() {
+            info!("Saved {} players", saved.len());
+        }
+
+        Ok(saved.len())
+    }
+
+    // Dirty players are snapshotted under the read lock, which is released before
+    // writing so gameplay isn't blocked on the database
+    pub async fn save_dirty_players(player_manager: &RwLock<PlayerManager>) -> Result<usize, Box<dyn std::error::Error>> {
+        let (player_repository, dirty) = {
+            let player_manager = player_manager.read().await;
+            (player_manager.player_repository.clone(), player_manager.changed_players(|_| true)?)
+        };
+
+        let mut saved = Vec::with_capacity(dirty.len());
+        for (player, snapshot) in dirty {
+            let result = player_repository.save_player(&player).await.map_err(|e| e.to_string());
+            if let Err(e) = result {
+                player_manager.read().await.mark_players_saved(&saved);
+                return Err(e.into());
+            }
+            saved.push((player, snapshot));
+        }
+
+        player_manager.read().await.mark_players_saved(&saved);
+        Ok(saved.len())
+    }
+
+    // Players in a world, or outside any world for None (offline players included), whose state
+    // changed since their last save. Each comes with the snapshot to record once it's been written
+    pub fn dirty_players(&self, world_id: Option<&str>) -> Result<Vec<(Player, u64)>, Box<dyn std::error::Error>> {
+        self.changed_players(|player| player.world_id.as_deref() == world_id)
+    }
+
+    fn changed_players(&self, include: impl Fn(&Player) -> bool) -> Result<Vec<(Player, u64)>, Box<dyn std::error::Error>> {
+        let saved_snapshots = self.saved_snapshots.lock().unwrap();
+        let mut dirty = Vec::new();
+
+        for player in self.players.values().filter(|p| include(p)) {
+            let snapshot = Self::snapshot_hash(player)?;
+            if saved_snapshots.get(&player.id) != Some(&snapshot) {
+                dirty.push((player.clone(), snapshot));
+            }
+        }
+
+        Ok(dirty)
+    }
+
+    pub fn online_world_ids(&self) -> HashSet<String> {
+        self.players
+            .values()
+            .filter(|p| p.is_online)
+            .filter_map(|p| p.world_id.clone())
+            .collect()
+    }
+
+    pub fn mark_players_saved(&self, saved: &[(Player, u64)]) {
+        let mut saved_snapshots = self.saved_snapshots.lock().unwrap();
+        for (player, snapshot) in saved {
+            saved_snapshots.insert(player.id.clone(), *snapshot);
+        }
+    }
+
+    // Players are mutated from too many places to mark them by hand, so dirtiness
+    // is judged against a hash of what was last written
+    fn snapshot_hash(player: &Player) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_vec(player)?.hash(&mut hasher);
+        Ok(hasher.finish())
+    }
+
+    pub async fn purge_inactive(
+        &mut self,
+        older_than: chrono::Duration,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let cutoff = Utc::now() - older_than;
+
+        // Never purge players who are currently online
+        let to_purge: Vec<String> = self
+            .players
+            .values()
+            .filter(|p| !p.is_online && p.last_seen < cutoff)
+            .map(|p| p.id.clone())
+            .collect();
+
+        for player_id in &to_purge {
+            // Delete from database
+            self.player_repository.delete_player(player_id).await?;
+
+            self.players.remove(player_id);
+            self.banned.remove(player_id);
+            self.saved_snapshots.lock().unwrap().remove(player_id);
+        }
+
+        if !to_purge.is_empty() {
+            info!("Purged {} inactive players", to_purge.len());
+        }
+
+        Ok(to_purge.len())
+    }
+
+    // Autosaves, then purges players unseen for longer than the retention when one is set
+    pub async fn run(player_manager: Arc<RwLock<PlayerManager>>, save_interval: u64, retention: Option<chrono::Duration>) {
+        let mut ticker = interval(Duration::from_secs(save_interval));
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = Self::save_dirty_players(&player_manager).await {
+                error!("Failed to autosave players: {}", e);
+            }
+            if let Some(retention) = retention {
+                if let Err(e) = player_manager.write().await.purge_inactive(retention).await {
+                    error!("Failed to purge inactive players: {}", e);
+                }
+            }
+        }
+    }
+
+    // Hunger and natural regeneration, world lock taken before the player lock
+    pub async fn run_survival(player_manager: Arc<RwLock<PlayerManager>>, world_manager: Arc<RwLock<WorldManager>>) {
+        let mut ticker = interval(Duration::from_millis(SURVIVAL_TICK_MILLIS));
+
+        loop {
+            ticker.tick().await;
+
+            let world_manager = world_manager.read().await;
+            player_manager.write().await.tick_survival(&world_manager).await;
+        }
+    }
+
     pub async fn get_players_in_world(&self, world_id: &str) -> Vec<Player> {
         self.players
             .values()
@@ -301,6 +1274,26 @@ impl PlayerManager {
             average_level,
         }
     }
+
+    fn initialize_default_permissions(&mut self) {
+        // Each role inherits everything the role below it can do
+        let player_permissions = ["chat", "build", "break"];
+        let moderator_permissions = ["mute", "kick", "teleport"];
+
+        self.grant_permission(PlayerRole::Guest, "chat");
+
+        for permission in player_permissions {
+            self.grant_permission(PlayerRole::Player, permission);
+            self.grant_permission(PlayerRole::Moderator, permission);
+        }
+
+        for permission in moderator_permissions {
+            self.grant_permission(PlayerRole::Moderator, permission);
+        }
+
+        // Admins can do everything
+        self.grant_permission(PlayerRole::Admin, "*");
+    }
 }
 
 #[derive(Debug)]
@@ -309,4 +1302,275 @@ pub struct PlayerStats {
     pub online_players: usize,
     pub total_experience: i32,
     pub average_level: f32,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::jwt_service::JwtService;
+    use crate::database::database_service::{DatabaseConfig, DatabaseService};
+
+    #[test]
+    fn rapid_messages_share_one_movement_budget() {
+        let limits = MovementLimits::default();
+        let mut budget = MovementBudget::full(&limits, 1.0);
+        let now = budget.refilled_at;
+
+        // Twenty messages in the same instant can't add up to more than a second of walking
+        let step = limits.max_horizontal_speed / 20.0;
+        for _ in 0..20 {
+            budget.refill(&limits, 1.0, now);
+            assert!(budget.try_spend(step, 0.0));
+        }
+        budget.refill(&limits, 1.0, now);
+        assert!(!budget.try_spend(step, 0.0));
+    }
+
+    #[test]
+    fn budget_refills_in_real_time_up_to_the_burst() {
+        let limits = MovementLimits::default();
+        let mut budget = MovementBudget::full(&limits, 1.0);
+        let start = budget.refilled_at;
+        assert!(budget.try_spend(limits.max_horizontal_speed, 0.0));
+
+        budget.refill(&limits, 1.0, start + std::time::Duration::from_millis(500));
+        assert!(!budget.try_spend(limits.max_horizontal_speed * 0.6, 0.0));
+        assert!(budget.try_spend(limits.max_horizontal_speed * 0.5, 0.0));
+
+        // Idling never banks more than the burst
+        budget.refill(&limits, 1.0, start + std::time::Duration::from_secs(60));
+        assert!(!budget.try_spend(limits.max_horizontal_speed * MOVEMENT_BURST_SECONDS + 0.1, 0.0));
+    }
+
+    #[test]
+    fn rejected_moves_cost_nothing() {
+        let limits = MovementLimits::default();
+        let mut budget = MovementBudget::full(&limits, 1.0);
+        assert!(!budget.try_spend(0.0, limits.max_ascent_speed + 1.0));
+        assert!(budget.try_spend(0.0, limits.max_ascent_speed));
+    }
+
+    async fn test_database() -> Arc<DatabaseService> {
+        let config = DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            ..DatabaseConfig::default()
+        };
+        Arc::new(DatabaseService::new(&config).await.unwrap())
+    }
+
+    // A fresh manager over the same database, like a server restart
+    fn manager_on(database_service: &Arc<DatabaseService>) -> PlayerManager {
+        let player_repository = Arc::new(PlayerRepository::new(database_service.clone()));
+        let jwt_service = Arc::new(JwtService::new("x".repeat(32)).unwrap());
+        let auth_service = Arc::new(AuthService::new(player_repository.clone(), jwt_service));
+        PlayerManager::new(player_repository, auth_service)
+    }
+
+    #[tokio::test]
+    async fn disconnecting_saves_the_player() {
+        let database_service = test_database().await;
+        let mut before = manager_on(&database_service);
+        let player_id = before.register_player("alex", "correct horse battery").await.unwrap().id;
+
+        let player = before.players.get_mut(&player_id).unwrap();
+        player.position = [4.0, 80.0, 9.5];
+        player.health = 3.0;
+        player.inventory.items[2] = Some(item(5, 12, 2));
+        player.world_id = Some("world".to_string());
+        player.is_online = true;
+        before.player_disconnect(&player_id).await.unwrap();
+        assert!(before.dirty_players(None).unwrap().is_empty());
+
+        let mut after = manager_on(&database_service);
+        after.initialize().await.unwrap();
+        let loaded = after.get_player(&player_id).await.unwrap();
+
+        assert_eq!(loaded.position, [4.0, 80.0, 9.5]);
+        assert_eq!(loaded.health, 3.0);
+        assert_eq!(slots(&loaded.inventory), vec![(2, 5, 12)]);
+        // Freshly loaded players match what's saved
+        assert!(after.dirty_players(None).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn autosave_writes_only_changed_players() {
+        let database_service = test_database().await;
+        let mut manager = manager_on(&database_service);
+        let changed = manager.register_player("alex", "correct horse battery").await.unwrap().id;
+        manager.register_player("steve", "correct horse battery").await.unwrap();
+        manager.players.get_mut(&changed).unwrap().experience = 250;
+
+        let manager = RwLock::new(manager);
+        assert_eq!(PlayerManager::save_dirty_players(&manager).await.unwrap(), 1);
+        assert_eq!(PlayerManager::save_dirty_players(&manager).await.unwrap(), 0);
+
+        let mut after = manager_on(&database_service);
+        after.initialize().await.unwrap();
+        assert_eq!(after.get_player(&changed).await.unwrap().experience, 250);
+    }
+
+    #[tokio::test]
+    async fn saved_state_survives_a_restart() {
+        let database_service = test_database().await;
+        let mut before = manager_on(&database_service);
+        let mut player = before.register_player("steve", "correct horse battery").await.unwrap();
+
+        player.position = [12.5, 70.0, -3.25];
+        player.health = 7.5;
+        player.hunger = 11.0;
+        player.experience = 345;
+        player.level = 9;
+        player.inventory.items[0] = Some(item(1, 64, 0));
+        player.inventory.items[4] = Some(item(278, 1, 4));
+        player.inventory.selected_slot = 4;
+        player.game_mode = GameMode::Creative;
+        player.world_id = Some("world".to_string());
+        player.is_online = true;
+        before.player_repository.save_player(&player).await.unwrap();
+        before.set_player_role(&player.id, PlayerRole::Moderator).await.unwrap();
+
+        let mut after = manager_on(&database_service);
+        after.initialize().await.unwrap();
+        let loaded = after.get_player(&player.id).await.unwrap();
+
+        assert_eq!(loaded.username, "steve");
+        assert_eq!(loaded.position, [12.5, 70.0, -3.25]);
+        assert_eq!((loaded.health, loaded.hunger), (7.5, 11.0));
+        assert_eq!((loaded.experience, loaded.level), (345, 9));
+        assert_eq!(slots(&loaded.inventory), vec![(0, 1, 64), (4, 278, 1)]);
+        assert_eq!(loaded.inventory.selected_slot, 4);
+        assert!(matches!(loaded.game_mode, GameMode::Creative));
+        // The role column was written after the snapshot and wins
+        assert_eq!(loaded.role, PlayerRole::Moderator);
+        assert_eq!(loaded.world_id, None);
+        assert!(!loaded.is_online);
+    }
+
+    #[tokio::test]
+    async fn unreadable_saved_state_falls_back_to_defaults() {
+        let database_service = test_database().await;
+        let mut before = manager_on(&database_service);
+        let player = before.register_player("alex", "correct horse battery").await.unwrap();
+
+        sqlx::query("UPDATE players SET data = ? WHERE id = ?")
+            .bind("{not json")
+            .bind(&player.id)
+            .execute(database_service.pool())
+            .await
+            .unwrap();
+
+        let mut after = manager_on(&database_service);
+        after.initialize().await.unwrap();
+        let loaded = after.get_player(&player.id).await.unwrap();
+
+        assert_eq!(loaded.username, "alex");
+        assert_eq!(loaded.position, DEFAULT_SPAWN_POINT);
+        assert_eq!(loaded.health, 20.0);
+        assert!(slots(&loaded.inventory).is_empty());
+        assert_eq!(loaded.inventory.size, DEFAULT_INVENTORY_SLOTS);
+    }
+
+    #[test]
+    fn saved_stack_lists_load_into_slots() {
+        let saved = serde_json::json!([
+            { "id": 1, "count": 64, "metadata": null },
+            { "id": 0, "count": 0, "metadata": null },
+            { "id": 278, "count": 1, "metadata": { "durability": 12 } },
+        ]);
+
+        let inventory = saved_inventory(saved).unwrap();
+        assert_eq!(inventory.size, DEFAULT_INVENTORY_SLOTS);
+        assert_eq!(slots(&inventory), vec![(0, 1, 64), (2, 278, 1)]);
+        assert_eq!(inventory.items[2].as_ref().unwrap().metadata, Some(serde_json::json!({ "durability": 12 })));
+    }
+
+    fn item(id: u32, count: u32, slot: usize) -> InventoryItem {
+        InventoryItem { id, count, metadata: None, slot }
+    }
+
+    // (slot, id, count) of every filled slot
+    fn slots(inventory: &Inventory) -> Vec<(usize, u32, u32)> {
+        inventory.items.iter().flatten().map(|i| (i.slot, i.id, i.count)).collect()
+    }
+
+    #[tokio::test]
+    async fn placing_takes_one_block_from_the_selected_slot() {
+        let inventory_system = InventorySystem::new(Arc::new(crate::systems::item_registry::ItemRegistry::new()));
+        let mut manager = manager_on(&test_database().await);
+        let id = manager.register_player("builder", "correct horse battery").await.unwrap().id;
+
+        let inventory = manager.get_inventory_mut(&id).unwrap();
+        inventory.items[1] = Some(item(1, 2, 1));
+        inventory.items[5] = Some(item(3, 8, 5));
+        inventory.selected_slot = 1;
+
+        assert!(manager.take_placed_block(&id, 3, &inventory_system).is_err());
+        manager.take_placed_block(&id, 1, &inventory_system).unwrap();
+        manager.take_placed_block(&id, 1, &inventory_system).unwrap();
+        assert_eq!(slots(&manager.players[&id].inventory), vec![(5, 3, 8)]);
+        assert!(manager.take_placed_block(&id, 1, &inventory_system).is_err());
+    }
+
+    #[tokio::test]
+    async fn effects_heal_and_hurt_online_players_but_poison_never_kills() {
+        let mut manager = manager_on(&test_database().await);
+        let id = manager.register_player("patient", "correct horse battery").await.unwrap().id;
+        let player = manager.players.get_mut(&id).unwrap();
+        player.is_online = true;
+        player.health = 10.0;
+
+        manager.apply_effect(&id, EffectKind::Regeneration, 1, chrono::Duration::seconds(30)).await.unwrap();
+        manager.tick_effects().await;
+        assert_eq!(manager.players[&id].health, 11.0);
+
+        manager.remove_effect(&id, EffectKind::Regeneration).await.unwrap();
+        manager.apply_effect(&id, EffectKind::Poison, 4, chrono::Duration::seconds(30)).await.unwrap();
+        for _ in 0..10 {
+            manager.tick_effects().await;
+        }
+        assert_eq!(manager.players[&id].health, 1.0);
+        assert_eq!(manager.players[&id].statistics.deaths, 0);
+    }
+
+    #[tokio::test]
+    async fn experience_adds_up_and_levels_follow_the_curve() {
+        let mut manager = manager_on(&test_database().await);
+        manager.set_experience_curve(ExperienceCurve::Table(vec![10, 30, 60]));
+        let id = manager.register_player("grinder", "correct horse battery").await.unwrap().id;
+
+        manager.add_experience(&id, 8).await.unwrap();
+        assert_eq!((manager.players[&id].experience, manager.players[&id].level), (8, 1));
+        manager.add_experience(&id, 25).await.unwrap();
+        assert_eq!((manager.players[&id].experience, manager.players[&id].level), (33, 3));
+        assert_eq!(manager.experience_for_level(4), 60);
+
+        // Losing experience drops levels but never goes below zero
+        manager.add_experience(&id, -100).await.unwrap();
+        assert_eq!((manager.players[&id].experience, manager.players[&id].level), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn purging_drops_long_gone_offline_players_only() {
+        let database_service = test_database().await;
+        let mut manager = manager_on(&database_service);
+        let long_ago = Utc::now() - chrono::Duration::days(90);
+        let mut ids = Vec::new();
+        for username in ["gone", "online", "recent"] {
+            ids.push(manager.register_player(username, "correct horse battery").await.unwrap().id);
+        }
+        manager.players.get_mut(&ids[0]).unwrap().last_seen = long_ago;
+        let online = manager.players.get_mut(&ids[1]).unwrap();
+        online.last_seen = long_ago;
+        online.is_online = true;
+
+        assert_eq!(manager.purge_inactive(chrono::Duration::days(30)).await.unwrap(), 1);
+        assert!(manager.get_player(&ids[0]).await.is_none());
+
+        let mut reloaded = manager_on(&database_service);
+        reloaded.initialize().await.unwrap();
+        assert!(reloaded.get_player(&ids[0]).await.is_none());
+        assert!(reloaded.get_player(&ids[1]).await.is_some());
+        assert!(reloaded.get_player(&ids[2]).await.is_some());
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use serde::{Deserialize, Serialize};
+use log::{info, error};
+
+use crate::database::{
+    database_service::DatabaseService,
+    world_repository::WorldRepository,
+    player_repository::PlayerRepository,
+    entity_repository::EntityRepository,
+};
+use crate::systems::{
+    world_manager::{WorldManager, WorldUpdate},
+    player_manager::PlayerManager,
+    chunk_manager::ChunkManager,
+    entity_manager::EntityManager,
+};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SaveReport {
+    pub chunks: usize,
+    pub players: usize,
+    pub worlds: usize,
+    pub entities: usize,
+}
+
+impl SaveReport {
+    pub fn total(&self) -> usize {
+        self.chunks + self.players + self.worlds + self.entities
+    }
+
+    fn add(&mut self, other: SaveReport) {
+        self.chunks += other.chunks;
+        self.players += other.players;
+        self.worlds += other.worlds;
+        self.entities += other.entities;
+    }
+}
+
+#[derive(Debug)]
+pub struct SaveSystem {
+    world_manager: Arc<RwLock<WorldManager>>,
+    player_manager: Arc<RwLock<PlayerManager>>,
+    chunk_manager: Arc<RwLock<ChunkManager>>,
+    entity_manager: Arc<RwLock<EntityManager>>,
+    database_service: Arc<DatabaseService>,
+    world_repository: Arc<WorldRepository>,
+    player_repository: Arc<PlayerRepository>,
+    entity_repository: Arc<EntityRepository>,
+    save_interval: u64,
+}
+
+impl SaveSystem {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        world_manager: Arc<RwLock<WorldManager>>,
+        player_manager: Arc<RwLock<PlayerManager>>,
+        chunk_manager: Arc<RwLock<ChunkManager>>,
+        entity_manager: Arc<RwLock<EntityManager>>,
+        database_service: Arc<DatabaseService>,
+        world_repository: Arc<WorldRepository>,
+        player_repository: Arc<PlayerRepository>,
+        entity_repository: Arc<EntityRepository>,
+        save_interval: u64,
+    ) -> Self {
+        Self {
+            world_manager,
+            player_manager,
+            chunk_manager,
+            entity_manager,
+            database_service,
+            world_repository,
+            player_repository,
+            entity_repository,
+            save_interval,
+        }
+    }
+
+    // Writes only what changed since the last save, anything unsaved stays dirty for the next cycle.
+    //
+    // Transactional: each world's metadata, entities and the players in it are written in one
+    // database transaction, its chunk files are staged beforehand and only moved into place after
+    // the commit, a failure rolls all of them back. A crash between the commit and the moves
+    // leaves chunks one save behind but never half-written.
+    // Best-effort: players outside any world are written on their own, and PlayerManager's
+    // autosave also writes changed players between world saves.
+    pub async fn save_now(&self) -> Result<SaveReport, Box<dyn std::error::Error>> {
+        let mut report = SaveReport::default();
+        let mut failures = Vec::new();
+
+        for world_id in self.worlds_to_save().await {
+            match self.save_world(&world_id).await {
+                Ok(world_report) => report.add(world_report),
+                Err(e) => failures.push(format!("world {}: {}", world_id, e)),
+            }
+        }
+
+        match self.save_players_outside_worlds().await {
+            Ok(count) => report.players += count,
+            Err(e) => failures.push(format!("players: {}", e)),
+        }
+
+        if !failures.is_empty() {
+            return Err(format!("Save incomplete ({})", failures.join(", ")).into());
+        }
+
+        Ok(report)
+    }
+
+    async fn worlds_to_save(&self) -> HashSet<String> {
+        let mut world_ids: HashSet<String> = self.world_manager.read().await.dirty_world_ids().into_iter().collect();
+        world_ids.extend(self.chunk_manager.read().await.modified_world_ids());
+        world_ids.extend(self.player_manager.read().await.online_world_ids());
+        world_ids
+    }
+
+    async fn save_world(&self, world_id: &str) -> Result<SaveReport, Box<dyn std::error::Error>> {
+        let world_state = self.world_manager.read().await.dirty_world_state(world_id);
+        let players = self.player_manager.read().await.dirty_players(Some(world_id))?;
+        let staged = self.chunk_manager.read().await.stage_world_chunks(world_id).await?;
+        // Entities aren't tracked for changes, so every save of the world rewrites its snapshot
+        let entities = self.entity_manager.read().await.saved_world_entities(world_id).await;
+        let entity_count = entities.len();
+
+        let world_repository = self.world_repository.clone();
+        let player_repository = self.player_repository.clone();
+        let entity_repository = self.entity_repository.clone();
+        let tx_world_id = world_id.to_string();
+        let tx_players: Vec<_> = players.iter().map(|(player, _)| player.clone()).collect();
+        let updates: Vec<WorldUpdate> = world_state
+            .map(|(last_active, is_online)| vec![WorldUpdate::LastActive(last_active), WorldUpdate::IsOnline(is_online)])
+            .unwrap_or_default();
+
+        let result = self
+            .database_service
+            .with_transaction(move |conn| {
+                Box::pin(async move {
+                    for update in &updates {
+                        world_repository.update_world_in(&mut *conn, &tx_world_id, update).await?;
+                    }
+                    for player in &tx_players {
+                        player_repository.save_player_in(&mut *conn, player).await?;
+                    }
+                    entity_repository.save_world_entities_in(&mut *conn, &tx_world_id, &entities).await?;
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|e| e.to_string());
+
+        if let Err(e) = result {
+            ChunkManager::discard_staged_chunks(&staged).await;
+            return Err(e.into());
+        }
+
+        let chunks = self.chunk_manager.write().await.commit_staged_chunks(staged).await?;
+        self.player_manager.read().await.mark_players_saved(&players);
+        if let Some(state) = world_state {
+            self.world_manager.write().await.mark_world_saved(world_id, state);
+        }
+
+        Ok(SaveReport {
+            chunks,
+            players: players.len(),
+            worlds: world_state.map_or(0, |_| 1),
+            entities: entity_count,
+        })
+    }
+
+    // Players in the lobby have no world state to stay consistent with
+    async fn save_players_outside_worlds(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let players = self.player_manager.read().await.dirty_players(None)?;
+
+        for (player, _) in &players {
+            self.player_repository.save_player(player).await?;
+        }
+        self.player_manager.read().await.mark_players_saved(&players);
+
+        Ok(players.len())
+    }
+
+    pub async fn run(save_system: Arc<RwLock<SaveSystem>>) {
+        let save_interval = save_system.read().await.save_interval;
+        let mut ticker = interval(Duration::from_secs(save_interval));
+
+        loop {
+            ticker.tick().await;
+
+            match save_system.read().await.save_now().await {
+                Ok(report) if report.total() > 0 => info!(
+                    "Saved {} items ({} chunks, {} players, {} worlds, {} entities)",
+                    report.total(),
+                    report.chunks,
+                    report.players,
+                    report.worlds,
+                    report.entities
+                ),
+                Ok(_) => {}
+                Err(e) => error!("Autosave failed: {}", e),
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use log::info;
+
+use crate::systems::entity_manager::EntityManager;
+use crate::systems::player_manager::PlayerManager;
+
+const TICK_MILLIS: u64 = 1000; // Effects apply once a second
+const HEALTH_PER_LEVEL: f32 = 0.5; // Regeneration heals and poison hurts this much per tick per level
+const SPEED_PER_LEVEL: f64 = 0.2; // Speed and slowness change movement by 20% per level
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EffectKind {
+    Regeneration,
+    Poison,
+    Speed,
+    Slowness,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    pub amplifier: u8, // 0 is level I
+    pub expires_at: DateTime<Utc>,
+}
+
+impl StatusEffect {
+    fn level(&self) -> f32 {
+        self.amplifier as f32 + 1.0
+    }
+}
+
+// Adds an effect to a player's or entity's list, one per kind. A stronger effect replaces
+// a weaker one, an equal one extends it and a weaker one is ignored. Returns whether it took
+pub fn merge_effect(effects: &mut Vec<StatusEffect>, effect: StatusEffect) -> bool {
+    match effects.iter_mut().find(|e| e.kind == effect.kind) {
+        Some(existing) if effect.amplifier > existing.amplifier => {
+            *existing = effect;
+            true
+        }
+        Some(existing) if effect.amplifier == existing.amplifier => {
+            existing.expires_at = existing.expires_at.max(effect.expires_at);
+            true
+        }
+        Some(_) => false,
+        None => {
+            effects.push(effect);
+            true
+        }
+    }
+}
+
+pub fn remove_expired(effects: &mut Vec<StatusEffect>, now: DateTime<Utc>) {
+    effects.retain(|e| e.expires_at > now);
+}
+
+// Net health change for one tick, positive heals
+pub fn health_change(effects: &[StatusEffect]) -> f32 {
+    effects
+        .iter()
+        .map(|e| match e.kind {
+            EffectKind::Regeneration => e.level() * HEALTH_PER_LEVEL,
+            EffectKind::Poison => -e.level() * HEALTH_PER_LEVEL,
+            EffectKind::Speed | EffectKind::Slowness => 0.0,
+        })
+        .sum()
+}
+
+// Multiplier on movement speed, never below zero
+pub fn speed_multiplier(effects: &[StatusEffect]) -> f64 {
+    let change: f64 = effects
+        .iter()
+        .map(|e| match e.kind {
+            EffectKind::Speed => e.level() as f64 * SPEED_PER_LEVEL,
+            EffectKind::Slowness => -(e.level() as f64) * SPEED_PER_LEVEL,
+            EffectKind::Regeneration | EffectKind::Poison => 0.0,
+        })
+        .sum();
+    (1.0 + change).max(0.0)
+}
+
+#[derive(Debug)]
+pub struct StatusEffectSystem;
+
+impl StatusEffectSystem {
+    pub async fn run(entity_manager: Arc<RwLock<EntityManager>>, player_manager: Arc<RwLock<PlayerManager>>) {
+        info!("Status effects ticking every {}ms", TICK_MILLIS);
+
+        let mut ticker = interval(Duration::from_millis(TICK_MILLIS));
+        loop {
+            ticker.tick().await;
+
+            let mut entity_manager = entity_manager.write().await;
+            let mut player_manager = player_manager.write().await;
+            entity_manager.tick_effects().await;
+            player_manager.tick_effects().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(kind: EffectKind, amplifier: u8, seconds: i64) -> StatusEffect {
+        StatusEffect {
+            kind,
+            amplifier,
+            expires_at: Utc::now() + chrono::Duration::seconds(seconds),
+        }
+    }
+
+    #[test]
+    fn stronger_effects_replace_equal_ones_extend_and_weaker_ones_are_ignored() {
+        let mut effects = vec![effect(EffectKind::Speed, 1, 30)];
+
+        let longer = effect(EffectKind::Speed, 1, 60);
+        assert!(merge_effect(&mut effects, longer.clone()));
+        assert_eq!(effects[0].expires_at, longer.expires_at);
+        // An equal but shorter effect never cuts the running one short
+        assert!(merge_effect(&mut effects, effect(EffectKind::Speed, 1, 10)));
+        assert_eq!(effects[0].expires_at, longer.expires_at);
+
+        assert!(!merge_effect(&mut effects, effect(EffectKind::Speed, 0, 600)));
+        assert_eq!((effects[0].amplifier, effects[0].expires_at), (1, longer.expires_at));
+
+        let stronger = effect(EffectKind::Speed, 2, 5);
+        assert!(merge_effect(&mut effects, stronger.clone()));
+        assert_eq!((effects[0].amplifier, effects[0].expires_at), (2, stronger.expires_at));
+
+        assert!(merge_effect(&mut effects, effect(EffectKind::Poison, 0, 5)));
+        assert_eq!(effects.len(), 2);
+    }
+
+    #[test]
+    fn expired_effects_are_removed() {
+        let mut effects = vec![effect(EffectKind::Poison, 0, -1), effect(EffectKind::Regeneration, 0, 10)];
+        remove_expired(&mut effects, Utc::now());
+        assert_eq!(effects.len(), 1);
+        assert_eq!(effects[0].kind, EffectKind::Regeneration);
+
+        remove_expired(&mut effects, Utc::now() + chrono::Duration::seconds(11));
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn regeneration_heals_and_poison_hurts_by_level() {
+        assert_eq!(health_change(&[effect(EffectKind::Regeneration, 0, 10)]), 0.5);
+        assert_eq!(health_change(&[effect(EffectKind::Poison, 2, 10)]), -1.5);
+        assert_eq!(
+            health_change(&[effect(EffectKind::Regeneration, 1, 10), effect(EffectKind::Poison, 0, 10), effect(EffectKind::Speed, 3, 10)]),
+            0.5
+        );
+        assert_eq!(speed_multiplier(&[effect(EffectKind::Slowness, 9, 10)]), 0.0);
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tokio::time::{interval, Duration};
+use serde::{Deserialize, Serialize};
+use log::info;
+
+use crate::systems::world_manager::WorldManager;
+
+pub const TICKS_PER_DAY: u64 = 24000;
+const TICKS_PER_SECOND: u64 = 20;
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DayPhase {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+impl DayPhase {
+    pub fn from_ticks(time_of_day: u64) -> Self {
+        match time_of_day % TICKS_PER_DAY {
+            0..=999 => DayPhase::Dawn,
+            1000..=11999 => DayPhase::Day,
+            12000..=12999 => DayPhase::Dusk,
+            _ => DayPhase::Night,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PhaseChange {
+    pub world_id: String,
+    pub phase: DayPhase,
+}
+
+#[derive(Debug)]
+pub struct TimeSystem {
+    enabled: bool,
+    world_times: HashMap<String, u64>, // world_id -> ticks since creation
+    phase_events: broadcast::Sender<PhaseChange>,
+}
+
+impl TimeSystem {
+    pub fn new() -> Self {
+        let (phase_events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            enabled: true,
+            world_times: HashMap::new(),
+            phase_events,
+        }
+    }
+
+    pub fn new_disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::new()
+        }
+    }
+
+    // Ticks into the current day, 0..TICKS_PER_DAY
+    pub fn get_time(&self, world_id: &str) -> u64 {
+        self.world_times.get(world_id).copied().unwrap_or(0) % TICKS_PER_DAY
+    }
+
+    pub fn get_day(&self, world_id: &str) -> u64 {
+        self.world_times.get(world_id).copied().unwrap_or(0) / TICKS_PER_DAY
+    }
+
+    // Moves to the given time of day without rewinding the day counter
+    pub fn set_time(&mut self, world_id: &str, time_of_day: u64) {
+        let day = self.get_day(world_id);
+        let previous_phase = self.get_phase(world_id);
+
+        self.world_times.insert(world_id.to_string(), day * TICKS_PER_DAY + time_of_day % TICKS_PER_DAY);
+        self.notify_phase_change(world_id, previous_phase);
+    }
+
+    pub fn get_phase(&self, world_id: &str) -> DayPhase {
+        DayPhase::from_ticks(self.get_time(world_id))
+    }
+
+    pub fn is_day(&self, world_id: &str) -> bool {
+        matches!(self.get_phase(world_id), DayPhase::Dawn | DayPhase::Day)
+    }
+
+    pub fn is_night(&self, world_id: &str) -> bool {
+        !self.is_day(world_id)
+    }
+
+    // Mob spawning and other phase-gated systems listen here
+    pub fn subscribe(&self) -> broadcast::Receiver<PhaseChange> {
+        self.phase_events.subscribe()
+    }
+
+    pub fn advance(&mut self, world_id: &str, ticks: u64) {
+        let previous_phase = self.get_phase(world_id);
+        *self.world_times.entry(world_id.to_string()).or_insert(0) += ticks;
+        self.notify_phase_change(world_id, previous_phase);
+    }
+
+    fn notify_phase_change(&self, world_id: &str, previous_phase: DayPhase) {
+        let phase = self.get_phase(world_id);
+        if phase != previous_phase {
+            // Sending only fails when nobody is subscribed
+            let _ = self.phase_events.send(PhaseChange {
+                world_id: world_id.to_string(),
+                phase,
+            });
+        }
+    }
+
+    pub fn remove_world(&mut self, world_id: &str) {
+        self.world_times.remove(world_id);
+    }
+
+    pub async fn run(time_system: Arc<RwLock<TimeSystem>>, world_manager: Arc<RwLock<WorldManager>>) {
+        if !time_system.read().await.enabled {
+            info!("Time system disabled");
+            return;
+        }
+
+        let mut ticker = interval(Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+
+            let worlds = world_manager.read().await.get_all_worlds().await;
+            let mut time_system = time_system.write().await;
+            for world in worlds.iter().filter(|w| w.is_online && w.settings.time_enabled) {
+                time_system.advance(&world.id, TICKS_PER_SECOND);
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_time_keeps_the_day_counter() {
+        let mut time_system = TimeSystem::new();
+        time_system.advance("world", TICKS_PER_DAY * 2 + 500);
+
+        time_system.set_time("world", 18000);
+        assert_eq!(time_system.get_day("world"), 2);
+        assert_eq!(time_system.get_time("world"), 18000);
+        assert!(time_system.is_night("world"));
+        assert_eq!(time_system.get_day("other"), 0);
+    }
+
+    #[test]
+    fn only_phase_changes_are_announced() {
+        let mut time_system = TimeSystem::new();
+        let mut events = time_system.subscribe();
+
+        time_system.advance("world", 500);
+        assert!(events.try_recv().is_err());
+
+        time_system.advance("world", 11600);
+        let change = events.try_recv().unwrap();
+        assert_eq!(change.world_id, "world");
+        assert_eq!(change.phase, DayPhase::Dusk);
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+use log::info;
+
+use crate::systems::inventory_system::{Inventory, InventoryItem, InventorySystem};
+
+const TRADE_TIMEOUT_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeStack {
+    pub item_id: u32,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub id: String,
+    pub proposer: String,
+    pub recipient: String,
+    pub offered: Vec<TradeStack>, // Proposer gives
+    pub requested: Vec<TradeStack>, // Recipient gives
+    pub offered_value: u32,
+    pub requested_value: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct TradeSystem {
+    trades: HashMap<String, Trade>,
+}
+
+impl TradeSystem {
+    pub fn new() -> Self {
+        Self {
+            trades: HashMap::new(),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn propose_trade(
+        &mut self,
+        proposer: &str,
+        recipient: &str,
+        offered: Vec<TradeStack>,
+        requested: Vec<TradeStack>,
+        proposer_inventory: &Inventory,
+        proposer_infinite: bool,
+        inventory_system: &InventorySystem,
+    ) -> Result<Trade, String> {
+        if proposer == recipient {
+            return Err("Cannot trade with yourself".to_string());
+        }
+        if offered.is_empty() && requested.is_empty() {
+            return Err("Trade is empty".to_string());
+        }
+        if offered.iter().chain(requested.iter()).any(|stack| stack.count == 0) {
+            return Err("Trade stacks must have a positive count".to_string());
+        }
+        if self.trades.values().any(|t| t.proposer == proposer && t.recipient == recipient) {
+            return Err("You already have a pending trade with this player".to_string());
+        }
+
+        // Fail early on the proposer's side, both sides are checked again on accept
+        Self::check_has_items(proposer_inventory, &offered, proposer_infinite, inventory_system)?;
+
+        let offered_value = Self::stack_value(&offered, inventory_system)?;
+        let requested_value = Self::stack_value(&requested, inventory_system)?;
+
+        let trade = Trade {
+            id: Uuid::new_v4().to_string(),
+            proposer: proposer.to_string(),
+            recipient: recipient.to_string(),
+            offered_value,
+            requested_value,
+            offered,
+            requested,
+            created_at: Utc::now(),
+        };
+
+        info!("Player {} proposed trade {} to {}", proposer, trade.id, recipient);
+        self.trades.insert(trade.id.clone(), trade.clone());
+
+        Ok(trade)
+    }
+
+    // Both inventories are only written if every removal and addition succeeds.
+    // The infinite flags come from Player::infinite_items, that side gives without losing anything
+    #[allow(clippy::too_many_arguments)]
+    pub fn accept_trade(
+        &mut self,
+        trade_id: &str,
+        player_id: &str,
+        proposer_inventory: &mut Inventory,
+        proposer_infinite: bool,
+        recipient_inventory: &mut Inventory,
+        recipient_infinite: bool,
+        inventory_system: &InventorySystem,
+    ) -> Result<Trade, String> {
+        let trade = self.trades.get(trade_id).ok_or("Trade not found")?;
+        if trade.recipient != player_id {
+            return Err("Only the recipient can accept this trade".to_string());
+        }
+        if Self::is_expired(trade) {
+            self.trades.remove(trade_id);
+            return Err("Trade has expired".to_string());
+        }
+
+        Self::check_has_items(proposer_inventory, &trade.offered, proposer_infinite, inventory_system)?;
+        Self::check_has_items(recipient_inventory, &trade.requested, recipient_infinite, inventory_system)?;
+
+        let mut proposer_after = proposer_inventory.clone();
+        let mut recipient_after = recipient_inventory.clone();
+
+        // Take both sides first so freed slots can hold what comes back
+        let from_proposer = Self::take_stacks(&mut proposer_after, &trade.offered, proposer_infinite)?;
+        let from_recipient = Self::take_stacks(&mut recipient_after, &trade.requested, recipient_infinite)?;
+        Self::give_stacks(&mut proposer_after, from_recipient, inventory_system)
+            .map_err(|_| "Proposer's inventory is too full for this trade".to_string())?;
+        Self::give_stacks(&mut recipient_after, from_proposer, inventory_system)
+            .map_err(|_| "Recipient's inventory is too full for this trade".to_string())?;
+
+        *proposer_inventory = proposer_after;
+        *recipient_inventory = recipient_after;
+
+        let trade = self.trades.remove(trade_id).ok_or("Trade not found")?;
+        info!("Trade {} between {} and {} completed", trade.id, trade.proposer, trade.recipient);
+
+        Ok(trade)
+    }
+
+    // Either side can back out before the trade is accepted
+    pub fn cancel_trade(&mut self, trade_id: &str, player_id: &str) -> Result<Trade, String> {
+        let trade = self.trades.get(trade_id).ok_or("Trade not found")?;
+        if trade.proposer != player_id && trade.recipient != player_id {
+            return Err("You are not part of this trade".to_string());
+        }
+
+        let trade = self.trades.remove(trade_id).ok_or("Trade not found")?;
+        info!("Trade {} cancelled by {}", trade.id, player_id);
+
+        Ok(trade)
+    }
+
+    pub fn get_pending_trades(&self, player_id: &str) -> Vec<Trade> {
+        self.trades
+            .values()
+            .filter(|t| (t.proposer == player_id || t.recipient == player_id) && !Self::is_expired(t))
+            .cloned()
+            .collect()
+    }
+
+    // Drop every trade involving a player, e.g. when they disconnect, and return them so the other side can be told
+    pub fn cancel_trades_for_player(&mut self, player_id: &str) -> Vec<Trade> {
+        let trade_ids: Vec<String> = self
+            .trades
+            .values()
+            .filter(|t| t.proposer == player_id || t.recipient == player_id)
+            .map(|t| t.id.clone())
+            .collect();
+        trade_ids.iter().filter_map(|id| self.trades.remove(id)).collect()
+    }
+
+    pub fn cleanup_expired_trades(&mut self) -> usize {
+        let before = self.trades.len();
+        self.trades.retain(|_, t| !Self::is_expired(t));
+        before - self.trades.len()
+    }
+
+    fn is_expired(trade: &Trade) -> bool {
+        Utc::now() - trade.created_at > Duration::seconds(TRADE_TIMEOUT_SECONDS)
+    }
+
+    fn check_has_items(inventory: &Inventory, stacks: &[TradeStack], infinite_items: bool, inventory_system: &InventorySystem) -> Result<(), String> {
+        // Sum duplicates so offering the same item twice can't exceed what's held
+        let mut totals: HashMap<u32, u32> = HashMap::new();
+        for stack in stacks {
+            let total = totals.entry(stack.item_id).or_insert(0);
+            *total = total.checked_add(stack.count).ok_or("Trade amounts are too large")?;
+        }
+
+        if infinite_items {
+            return Ok(());
+        }
+
+        for (item_id, count) in totals {
+            if !inventory_system.has_item(inventory, item_id, count) {
+                return Err(format!("Missing {} of item {}", count, item_id));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Lifts the traded items out slot by slot, keeping each piece's metadata.
+    // An infinite supply hands out fresh stacks and leaves the inventory alone
+    fn take_stacks(inventory: &mut Inventory, stacks: &[TradeStack], infinite_items: bool) -> Result<Vec<InventoryItem>, String> {
+        if infinite_items {
+            return Ok(stacks
+                .iter()
+                .map(|stack| InventoryItem { id: stack.item_id, count: stack.count, metadata: None, slot: 0 })
+                .collect());
+        }
+
+        let mut taken = Vec::new();
+
+        for stack in stacks {
+            let mut remaining = stack.count;
+            for slot in inventory.items.iter_mut() {
+                if remaining == 0 {
+                    break;
+                }
+                let item = match slot {
+                    Some(item) if item.id == stack.item_id => item,
+                    _ => continue,
+                };
+
+                let count = remaining.min(item.count);
+                item.count -= count;
+                remaining -= count;
+                taken.push(InventoryItem {
+                    id: item.id,
+                    count,
+                    metadata: item.metadata.clone(),
+                    slot: item.slot,
+                });
+                if item.count == 0 {
+                    *slot = None;
+                }
+            }
+
+            if remaining > 0 {
+                return Err(format!("Missing item {}", stack.item_id));
+            }
+        }
+
+        Ok(taken)
+    }
+
+    // Plain items stack as usual, items carrying metadata (worn tools, named items) keep a slot of their own
+    fn give_stacks(inventory: &mut Inventory, items: Vec<InventoryItem>, inventory_system: &InventorySystem) -> Result<(), String> {
+        for item in items {
+            if item.metadata.is_none() {
+                if inventory_system.add_item(inventory, item.id, item.count, None)? > 0 {
+                    return Err(format!("No room for item {}", item.id));
+                }
+                continue;
+            }
+
+            let slot = inventory_system
+                .find_empty_slot(inventory)
+                .ok_or_else(|| format!("No room for item {}", item.id))?;
+            inventory.items[slot] = Some(InventoryItem { slot, ..item });
+        }
+        Ok(())
+    }
+
+    fn stack_value(stacks: &[TradeStack], inventory_system: &InventorySystem) -> Result<u32, String> {
+        stacks.iter().try_fold(0u32, |total, stack| {
+            inventory_system
+                .get_item_value(stack.item_id)
+                .checked_mul(stack.count)
+                .and_then(|value| total.checked_add(value))
+                .ok_or_else(|| "Trade amounts are too large".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::systems::item_registry::{ItemRegistry, DIAMOND, IRON_PICKAXE, STONE};
+
+    fn inventory_system() -> InventorySystem {
+        InventorySystem::new(Arc::new(ItemRegistry::new()))
+    }
+
+    fn stack(item_id: u32, count: u32) -> TradeStack {
+        TradeStack { item_id, count }
+    }
+
+    #[test]
+    fn traded_items_keep_their_metadata() {
+        let inventory_system = inventory_system();
+        let mut trades = TradeSystem::new();
+        let mut proposer = InventorySystem::create_inventory(36, 9);
+        let mut recipient = InventorySystem::create_inventory(36, 9);
+        let durability = serde_json::json!({ "durability": 17 });
+        inventory_system.add_item(&mut proposer, IRON_PICKAXE, 1, Some(durability.clone())).unwrap();
+        inventory_system.add_item(&mut recipient, STONE, 10, None).unwrap();
+
+        let trade = trades
+            .propose_trade("alice", "bob", vec![stack(IRON_PICKAXE, 1)], vec![stack(STONE, 10)], &proposer, false, &inventory_system)
+            .unwrap();
+        trades.accept_trade(&trade.id, "bob", &mut proposer, false, &mut recipient, false, &inventory_system).unwrap();
+
+        let pickaxe = recipient.items.iter().flatten().find(|item| item.id == IRON_PICKAXE).unwrap();
+        assert_eq!(pickaxe.metadata, Some(durability));
+        assert_eq!(recipient.items[pickaxe.slot].as_ref().map(|item| item.slot), Some(pickaxe.slot));
+        assert_eq!(inventory_system.get_item_count(&proposer, STONE), 10);
+        assert_eq!(inventory_system.get_item_count(&proposer, IRON_PICKAXE), 0);
+    }
+
+    #[test]
+    fn overflowing_counts_are_rejected() {
+        let inventory_system = inventory_system();
+        let mut trades = TradeSystem::new();
+        let mut proposer = InventorySystem::create_inventory(36, 9);
+        inventory_system.add_item(&mut proposer, STONE, 1, None).unwrap();
+
+        // Two huge stacks of the same item wrap around to a small total without checked_add
+        let offered = vec![stack(STONE, u32::MAX), stack(STONE, 2)];
+        assert!(trades.propose_trade("alice", "bob", offered, Vec::new(), &proposer, false, &inventory_system).is_err());
+    }
+
+    #[test]
+    fn overflowing_value_is_rejected() {
+        let inventory_system = inventory_system();
+        let mut trades = TradeSystem::new();
+        let proposer = InventorySystem::create_inventory(36, 9);
+
+        // Diamonds are worth more than one each, so u32::MAX of them overflows the value
+        let requested = vec![stack(DIAMOND, u32::MAX)];
+        assert!(trades.propose_trade("alice", "bob", Vec::new(), requested, &proposer, false, &inventory_system).is_err());
+        assert!(trades.get_pending_trades("alice").is_empty());
+    }
+
+    #[test]
+    fn infinite_side_gives_without_losing_items() {
+        let inventory_system = inventory_system();
+        let mut trades = TradeSystem::new();
+        let mut proposer = InventorySystem::create_inventory(36, 9);
+        let mut recipient = InventorySystem::create_inventory(36, 9);
+        inventory_system.add_item(&mut proposer, DIAMOND, 5, None).unwrap();
+
+        let trade = trades
+            .propose_trade("alice", "bob", vec![stack(DIAMOND, 3)], Vec::new(), &proposer, true, &inventory_system)
+            .unwrap();
+        trades.accept_trade(&trade.id, "bob", &mut proposer, true, &mut recipient, false, &inventory_system).unwrap();
+
+        assert_eq!(inventory_system.get_item_count(&proposer, DIAMOND), 5);
+        assert_eq!(inventory_system.get_item_count(&recipient, DIAMOND), 3);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+use serde::{Deserialize, Serialize};
+use rand::Rng;
+use log::info;
+
+use crate::networking::{message_handler::MessageHandler, protocol::ServerMessage};
+use crate::systems::world_manager::WorldManager;
+
+const MIN_WEATHER_SECONDS: u64 = 300; // 5 minutes
+const MAX_WEATHER_SECONDS: u64 = 900; // 15 minutes
+const THUNDER_CHANCE: f64 = 0.2; // Share of storms that turn into thunder
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Weather {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+#[derive(Debug, Clone)]
+struct WorldWeather {
+    weather: Weather,
+    remaining_seconds: u64,
+}
+
+#[derive(Debug)]
+pub struct WeatherSystem {
+    enabled: bool,
+    worlds: HashMap<String, WorldWeather>,
+}
+
+impl WeatherSystem {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            worlds: HashMap::new(),
+        }
+    }
+
+    pub fn new_disabled() -> Self {
+        Self {
+            enabled: false,
+            worlds: HashMap::new(),
+        }
+    }
+
+    pub fn get_weather(&self, world_id: &str) -> Weather {
+        self.worlds.get(world_id).map_or(Weather::Clear, |w| w.weather)
+    }
+
+    // Duration defaults to a random spell like natural changes
+    pub fn set_weather(&mut self, world_id: &str, weather: Weather, duration_seconds: Option<u64>) {
+        let remaining_seconds = duration_seconds.unwrap_or_else(Self::random_duration);
+        self.worlds.insert(
+            world_id.to_string(),
+            WorldWeather {
+                weather,
+                remaining_seconds,
+            },
+        );
+        info!("Weather in world {} set to {:?} for {}s", world_id, weather, remaining_seconds);
+    }
+
+    // Thunder counts as rain, so both dampen fire and keep hostile mobs alive in daylight
+    pub fn is_raining(&self, world_id: &str) -> bool {
+        self.get_weather(world_id) != Weather::Clear
+    }
+
+    pub fn is_thundering(&self, world_id: &str) -> bool {
+        self.get_weather(world_id) == Weather::Thunder
+    }
+
+    pub fn remove_world(&mut self, world_id: &str) {
+        self.worlds.remove(world_id);
+    }
+
+    // Returns the new weather when it changed this tick
+    fn tick_world(&mut self, world_id: &str, elapsed_seconds: u64) -> Option<Weather> {
+        let state = self.worlds.entry(world_id.to_string()).or_insert_with(|| WorldWeather {
+            weather: Weather::Clear,
+            remaining_seconds: Self::random_duration(),
+        });
+
+        state.remaining_seconds = state.remaining_seconds.saturating_sub(elapsed_seconds);
+        if state.remaining_seconds > 0 {
+            return None;
+        }
+
+        state.weather = match state.weather {
+            Weather::Clear if rand::thread_rng().gen_bool(THUNDER_CHANCE) => Weather::Thunder,
+            Weather::Clear => Weather::Rain,
+            Weather::Rain | Weather::Thunder => Weather::Clear,
+        };
+        state.remaining_seconds = Self::random_duration();
+        Some(state.weather)
+    }
+
+    // Worlds with weather turned off are held at clear skies
+    fn clear_world(&mut self, world_id: &str) -> Option<Weather> {
+        match self.worlds.remove(world_id) {
+            Some(state) if state.weather != Weather::Clear => Some(Weather::Clear),
+            _ => None,
+        }
+    }
+
+    fn random_duration() -> u64 {
+        rand::thread_rng().gen_range(MIN_WEATHER_SECONDS..=MAX_WEATHER_SECONDS)
+    }
+
+    pub async fn run(
+        weather_system: Arc<RwLock<WeatherSystem>>,
+        world_manager: Arc<RwLock<WorldManager>>,
+        message_handler: Arc<MessageHandler>,
+    ) {
+        if !weather_system.read().await.enabled {
+            info!("Weather system disabled");
+            return;
+        }
+
+        let mut ticker = interval(Duration::from_secs(1));
+        loop {
+            ticker.tick().await;
+
+            let worlds = world_manager.read().await.get_all_worlds().await;
+            let mut changes = Vec::new();
+            {
+                let mut weather_system = weather_system.write().await;
+                for world in worlds.iter().filter(|w| w.is_online) {
+                    let change = if world.settings.weather_enabled {
+                        weather_system.tick_world(&world.id, 1)
+                    } else {
+                        weather_system.clear_world(&world.id)
+                    };
+                    if let Some(weather) = change {
+                        changes.push((world.id.clone(), weather));
+                    }
+                }
+            }
+
+            for (world_id, weather) in changes {
+                message_handler
+                    .broadcast_to_world(&world_id, ServerMessage::WeatherChange { world_id: world_id.clone(), weather }, None)
+                    .await;
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_weather_is_per_world_and_thunder_counts_as_rain() {
+        let mut weather_system = WeatherSystem::new();
+        weather_system.set_weather("world", Weather::Thunder, Some(60));
+
+        assert_eq!(weather_system.get_weather("world"), Weather::Thunder);
+        assert!(weather_system.is_raining("world"));
+        assert!(weather_system.is_thundering("world"));
+        assert_eq!(weather_system.get_weather("other"), Weather::Clear);
+        assert!(!weather_system.is_raining("other"));
+
+        weather_system.set_weather("world", Weather::Rain, Some(60));
+        assert!(weather_system.is_raining("world"));
+        assert!(!weather_system.is_thundering("world"));
+    }
+
+    #[test]
+    fn weather_changes_once_the_spell_runs_out() {
+        let mut weather_system = WeatherSystem::new();
+        weather_system.set_weather("world", Weather::Rain, Some(10));
+
+        assert_eq!(weather_system.tick_world("world", 9), None);
+        assert_eq!(weather_system.tick_world("world", 1), Some(Weather::Clear));
+        assert_eq!(weather_system.get_weather("world"), Weather::Clear);
+    }
+
+    #[test]
+    fn disabling_weather_clears_the_sky() {
+        let mut weather_system = WeatherSystem::new();
+        assert_eq!(weather_system.clear_world("world"), None);
+
+        weather_system.set_weather("world", Weather::Thunder, Some(60));
+        assert_eq!(weather_system.clear_world("world"), Some(Weather::Clear));
+        assert_eq!(weather_system.get_weather("world"), Weather::Clear);
+    }
+}
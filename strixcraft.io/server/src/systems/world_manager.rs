@@ -1,18 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use log::{info, warn, error};
+use log::{info, error};
 
-use crate::worlds::{
-    terrain_generator::TerrainGenerator,
-    biome_system::BiomeSystem,
-    structure_generator::StructureGenerator,
-};
+use crate::worlds::terrain_generator::{TerrainGenerator, TerrainParams};
 
-use crate::database::world_repository::WorldRepository;
+use crate::database::world_repository::{WorldFilter, WorldRecord, WorldRepository};
+use crate::systems::chunk_manager::ChunkManager;
+use crate::systems::entity_manager::EntityManager;
+
+const SPAWN_PRELOAD_RADIUS: i32 = 4; // In chunks
+const WORLD_LOAD_PAGE_SIZE: usize = 500;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorldInfo {
@@ -26,9 +26,37 @@ pub struct WorldInfo {
     pub last_active: DateTime<Utc>,
     pub is_online: bool,
     pub settings: WorldSettings,
+    #[serde(default = "default_spawn_point")]
+    pub spawn_point: [f64; 3],
+    #[serde(default)]
+    pub border: Option<WorldBorder>,
+    #[serde(default)]
+    pub whitelist: Option<HashSet<String>>,
+    #[serde(default = "default_whitelist_enabled")]
+    pub whitelist_enabled: bool,
+}
+
+fn default_spawn_point() -> [f64; 3] {
+    [0.0, 64.0, 0.0]
+}
+
+fn default_whitelist_enabled() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldBorder {
+    pub center: [f64; 2], // x, z
+    pub radius: f64,      // Horizontal distance from the center, the border is a circle
+}
+
+impl WorldBorder {
+    pub fn contains(&self, position: [f64; 3]) -> bool {
+        (position[0] - self.center[0]).hypot(position[2] - self.center[1]) <= self.radius
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameMode {
     Survival,
     Creative,
@@ -45,6 +73,62 @@ pub struct WorldSettings {
     pub time_enabled: bool,
     pub mobs_enabled: bool,
     pub physics_enabled: bool,
+    #[serde(default)]
+    pub game_rules: HashMap<String, GameRuleValue>,
+    #[serde(default)]
+    pub max_entities: Option<usize>, // Applied via EntityManager::set_world_entity_cap
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+impl WorldSettings {
+    // The named fields are exposed as well-known rule keys so both APIs stay in sync
+    pub fn get_rule(&self, key: &str) -> Option<GameRuleValue> {
+        let value = match key {
+            "allow_pvp" => self.allow_pvp,
+            "allow_mob_griefing" => self.allow_mob_griefing,
+            "keep_inventory" => self.keep_inventory,
+            "natural_regeneration" => self.natural_regeneration,
+            "weather_enabled" => self.weather_enabled,
+            "time_enabled" => self.time_enabled,
+            "mobs_enabled" => self.mobs_enabled,
+            "physics_enabled" => self.physics_enabled,
+            _ => return self.game_rules.get(key).cloned(),
+        };
+
+        Some(GameRuleValue::Bool(value))
+    }
+
+    pub fn set_rule(&mut self, key: &str, value: GameRuleValue) -> Result<(), String> {
+        let field = match key {
+            "allow_pvp" => &mut self.allow_pvp,
+            "allow_mob_griefing" => &mut self.allow_mob_griefing,
+            "keep_inventory" => &mut self.keep_inventory,
+            "natural_regeneration" => &mut self.natural_regeneration,
+            "weather_enabled" => &mut self.weather_enabled,
+            "time_enabled" => &mut self.time_enabled,
+            "mobs_enabled" => &mut self.mobs_enabled,
+            "physics_enabled" => &mut self.physics_enabled,
+            _ => {
+                self.game_rules.insert(key.to_string(), value);
+                return Ok(());
+            }
+        };
+
+        match value {
+            GameRuleValue::Bool(value) => {
+                *field = value;
+                Ok(())
+            }
+            _ => Err(format!("Game rule {} must be a boolean", key)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,67 +143,107 @@ pub enum Difficulty {
 pub struct WorldManager {
     worlds: HashMap<String, WorldInfo>,
     world_repository: Arc<WorldRepository>,
-    terrain_generator: Arc<TerrainGenerator>,
-    biome_system: Arc<BiomeSystem>,
-    structure_generator: Arc<StructureGenerator>,
+    terrain_params: TerrainParams,
+    server_max_players: usize,
+    dirty_worlds: HashSet<String>, // Worlds whose online state or last activity hasn't been persisted
 }
 
 impl WorldManager {
     pub fn new(
         world_repository: Arc<WorldRepository>,
-        terrain_generator: Arc<TerrainGenerator>,
-        biome_system: Arc<BiomeSystem>,
-        structure_generator: Arc<StructureGenerator>,
+        terrain_params: TerrainParams,
+        server_max_players: usize,
     ) -> Self {
         Self {
             worlds: HashMap::new(),
             world_repository,
-            terrain_generator,
-            biome_system,
-            structure_generator,
+            terrain_params,
+            server_max_players,
+            dirty_worlds: HashSet::new(),
         }
     }
 
-    pub async fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn initialize(
+        &mut self,
+        chunk_manager: &mut ChunkManager,
+        entity_manager: &mut EntityManager,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         info!("Initializing world manager...");
         
-        // Load existing worlds from database
-        let existing_worlds = self.world_repository.get_all_worlds().await?;
-        
-        for world_data in existing_worlds {
-            let world_info = WorldInfo {
-                id: world_data.id,
-                name: world_data.name,
-                seed: world_data.seed,
-                game_mode: match world_data.game_mode.as_str() {
-                    "survival" => GameMode::Survival,
-                    "creative" => GameMode::Creative,
-                    _ => GameMode::Survival,
-                },
-                player_count: 0,
-                max_players: world_data.max_players,
-                created_at: world_data.created_at,
-                last_active: world_data.last_active,
-                is_online: false,
-                settings: serde_json::from_value(world_data.settings)?,
-            };
-            
-            self.worlds.insert(world_info.id.clone(), world_info);
+        // Load existing worlds from database a page at a time
+        let mut offset = 0;
+        loop {
+            let (page, rows_read) = self.world_repository.get_worlds_page(offset, WORLD_LOAD_PAGE_SIZE, &WorldFilter::default()).await?;
+
+            for world_data in page {
+                let world_info = Self::world_from_record(world_data)?;
+                chunk_manager.register_world(&world_info.id, world_info.seed);
+                entity_manager.set_world_entity_cap(&world_info.id, world_info.settings.max_entities);
+                if let Err(e) = entity_manager.load_world_entities(&world_info.id).await {
+                    error!("Failed to load entities for world {}: {}", world_info.name, e);
+                }
+                self.worlds.insert(world_info.id.clone(), world_info);
+            }
+
+            // Skipped rows still count, otherwise a bad row would end loading early
+            if rows_read < WORLD_LOAD_PAGE_SIZE {
+                break;
+            }
+            offset += rows_read;
         }
         
         info!("World manager initialized with {} worlds", self.worlds.len());
         Ok(())
     }
 
+    fn world_from_record(world_data: WorldRecord) -> Result<WorldInfo, Box<dyn std::error::Error>> {
+        Ok(WorldInfo {
+            id: world_data.id,
+            name: world_data.name,
+            seed: world_data.seed,
+            game_mode: match world_data.game_mode.as_str() {
+                "survival" => GameMode::Survival,
+                "creative" => GameMode::Creative,
+                _ => GameMode::Survival,
+            },
+            player_count: 0,
+            max_players: world_data.max_players,
+            created_at: world_data.created_at,
+            last_active: world_data.last_active,
+            is_online: false,
+            settings: serde_json::from_value(world_data.settings)?,
+            spawn_point: world_data.spawn_point,
+            border: world_data.border,
+            whitelist: world_data.whitelist,
+            whitelist_enabled: world_data.whitelist_enabled,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_world(
         &mut self,
         name: String,
         seed: i64,
         game_mode: GameMode,
         settings: WorldSettings,
+        max_players: usize,
+        chunk_manager: &mut ChunkManager,
+        entity_manager: &mut EntityManager,
     ) -> Result<WorldInfo, Box<dyn std::error::Error>> {
+        if max_players == 0 || max_players > self.server_max_players {
+            return Err(format!(
+                "Max players must be between 1 and {}",
+                self.server_max_players
+            )
+            .into());
+        }
+
         let world_id = Uuid::new_v4().to_string();
         let now = Utc::now();
+
+        // Spawn on the surface at the origin
+        let terrain_generator = TerrainGenerator::new(seed, self.terrain_params.clone());
+        let surface_height = terrain_generator.get_height(0, 0).await;
         
         let world_info = WorldInfo {
             id: world_id.clone(),
@@ -127,17 +251,23 @@ impl WorldManager {
             seed,
             game_mode: game_mode.clone(),
             player_count: 0,
-            max_players: 20,
+            max_players,
             created_at: now,
             last_active: now,
             is_online: false,
             settings,
+            spawn_point: [0.0, (surface_height + 1) as f64, 0.0],
+            border: None,
+            whitelist: None,
+            whitelist_enabled: true,
         };
 
         // Save to database
         self.world_repository.create_world(&world_info).await?;
         
         // Add to memory
+        chunk_manager.register_world(&world_id, seed);
+        entity_manager.set_world_entity_cap(&world_id, world_info.settings.max_entities);
         self.worlds.insert(world_id.clone(), world_info.clone());
         
         info!("Created new world: {} (ID: {})", name, world_id);
@@ -145,28 +275,100 @@ impl WorldManager {
         Ok(world_info)
     }
 
+    pub async fn clone_world(
+        &mut self,
+        source_id: &str,
+        new_name: String,
+        chunk_manager: &mut ChunkManager,
+        entity_manager: &mut EntityManager,
+    ) -> Result<WorldInfo, Box<dyn std::error::Error>> {
+        let source = self.worlds.get(source_id).ok_or("Source world not found")?;
+
+        let world_id = Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let world_info = WorldInfo {
+            id: world_id.clone(),
+            name: new_name.clone(),
+            player_count: 0,
+            created_at: now,
+            last_active: now,
+            is_online: false,
+            ..source.clone()
+        };
+
+        // Save to database and copy over the source's saved chunks
+        self.world_repository.create_world(&world_info).await?;
+        chunk_manager.copy_world_chunks(source_id, &world_id).await?;
+
+        // Add to memory
+        chunk_manager.register_world(&world_id, world_info.seed);
+        entity_manager.set_world_entity_cap(&world_id, world_info.settings.max_entities);
+        self.worlds.insert(world_id.clone(), world_info.clone());
+
+        info!("Cloned world {} into {} (ID: {})", source_id, new_name, world_id);
+
+        Ok(world_info)
+    }
+
     pub async fn get_world(&self, world_id: &str) -> Option<WorldInfo> {
         self.worlds.get(world_id).cloned()
     }
 
+    pub fn get_server_max_players(&self) -> usize {
+        self.server_max_players
+    }
+
     pub async fn get_all_worlds(&self) -> Vec<WorldInfo> {
         self.worlds.values().cloned().collect()
     }
 
+    // Filtered from memory, so online status and player counts are live
+    pub async fn query_worlds(&self, filter: WorldQuery, offset: usize, limit: usize) -> WorldPage {
+        let name = filter.name.map(|name| name.to_lowercase());
+
+        let mut matches: Vec<&WorldInfo> = self
+            .worlds
+            .values()
+            .filter(|w| name.as_ref().is_none_or(|name| w.name.to_lowercase().contains(name)))
+            .filter(|w| filter.game_mode.as_ref().is_none_or(|mode| &w.game_mode == mode))
+            .filter(|w| filter.is_online.is_none_or(|online| w.is_online == online))
+            .filter(|w| !filter.has_space || w.player_count < w.max_players)
+            .collect();
+
+        // Most recently active first
+        matches.sort_by_key(|w| std::cmp::Reverse(w.last_active));
+
+        WorldPage {
+            total: matches.len(),
+            worlds: matches.into_iter().skip(offset).take(limit).cloned().collect(),
+        }
+    }
+
     pub async fn update_world(&mut self, world_id: &str, updates: WorldUpdate) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(world) = self.worlds.get_mut(world_id) {
-            match updates {
+            match &updates {
                 WorldUpdate::PlayerCount(count) => {
-                    world.player_count = count;
+                    world.player_count = *count;
                 }
                 WorldUpdate::LastActive(time) => {
-                    world.last_active = time;
+                    world.last_active = *time;
                 }
                 WorldUpdate::IsOnline(online) => {
-                    world.is_online = online;
+                    world.is_online = *online;
                 }
                 WorldUpdate::Settings(settings) => {
-                    world.settings = settings;
+                    world.settings = settings.clone();
+                }
+                WorldUpdate::Border(border) => {
+                    world.border = border.clone();
+                }
+                WorldUpdate::SpawnPoint(spawn_point) => {
+                    world.spawn_point = *spawn_point;
+                }
+                WorldUpdate::Whitelist { entries, enabled } => {
+                    world.whitelist = entries.clone();
+                    world.whitelist_enabled = *enabled;
                 }
             }
             
@@ -177,10 +379,120 @@ impl WorldManager {
         Ok(())
     }
 
-    pub async fn delete_world(&mut self, world_id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    pub async fn set_world_border(
+        &mut self,
+        world_id: &str,
+        center: [f64; 2],
+        radius: f64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.worlds.contains_key(world_id) {
+            return Err("World not found".into());
+        }
+
+        // A radius of 0 removes the border
+        let border = if radius > 0.0 {
+            Some(WorldBorder { center, radius })
+        } else {
+            None
+        };
+
+        self.update_world(world_id, WorldUpdate::Border(border)).await
+    }
+
+    pub fn get_bool_rule(&self, world_id: &str, key: &str) -> Option<bool> {
+        match self.worlds.get(world_id)?.settings.get_rule(key)? {
+            GameRuleValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_int_rule(&self, world_id: &str, key: &str) -> Option<i64> {
+        match self.worlds.get(world_id)?.settings.get_rule(key)? {
+            GameRuleValue::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_string_rule(&self, world_id: &str, key: &str) -> Option<String> {
+        match self.worlds.get(world_id)?.settings.get_rule(key)? {
+            GameRuleValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    // Settings carry the entity cap, so the entity manager is updated alongside
+    pub async fn set_world_settings(
+        &mut self,
+        world_id: &str,
+        settings: WorldSettings,
+        entity_manager: &mut EntityManager,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.worlds.contains_key(world_id) {
+            return Err("World not found".into());
+        }
+
+        let max_entities = settings.max_entities;
+        self.update_world(world_id, WorldUpdate::Settings(settings)).await?;
+        entity_manager.set_world_entity_cap(world_id, max_entities);
+        Ok(())
+    }
+
+    pub async fn set_rule(
+        &mut self,
+        world_id: &str,
+        key: &str,
+        value: GameRuleValue,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut settings = self
+            .worlds
+            .get(world_id)
+            .ok_or("World not found")?
+            .settings
+            .clone();
+
+        settings.set_rule(key, value)?;
+
+        self.update_world(world_id, WorldUpdate::Settings(settings)).await
+    }
+
+    pub async fn set_spawn_point(
+        &mut self,
+        world_id: &str,
+        spawn_point: [f64; 3],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.worlds.contains_key(world_id) {
+            return Err("World not found".into());
+        }
+
+        if !self.is_within_border(world_id, spawn_point) {
+            return Err("Spawn point is outside the world border".into());
+        }
+
+        self.update_world(world_id, WorldUpdate::SpawnPoint(spawn_point)).await
+    }
+
+    pub fn get_spawn_point(&self, world_id: &str) -> Option<[f64; 3]> {
+        self.worlds.get(world_id).map(|world| world.spawn_point)
+    }
+
+    pub fn is_within_border(&self, world_id: &str, position: [f64; 3]) -> bool {
+        match self.worlds.get(world_id) {
+            Some(world) => world.border.as_ref().is_none_or(|border| border.contains(position)),
+            None => false,
+        }
+    }
+
+    pub async fn delete_world(
+        &mut self,
+        world_id: &str,
+        chunk_manager: &mut ChunkManager,
+        entity_manager: &mut EntityManager,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
         if let Some(world) = self.worlds.remove(world_id) {
-            // Delete from database
+            // Delete from database, then the chunk files
             self.world_repository.delete_world(world_id).await?;
+            chunk_manager.delete_world_chunks(world_id).await?;
+            entity_manager.delete_world_entities(world_id).await?;
             
             info!("Deleted world: {} (ID: {})", world.name, world_id);
             Ok(true)
@@ -189,15 +501,34 @@ impl WorldManager {
         }
     }
 
-    pub async fn join_world(&mut self, world_id: &str) -> Result<WorldInfo, Box<dyn std::error::Error>> {
+    pub async fn join_world(
+        &mut self,
+        world_id: &str,
+        player_id: &str,
+        chunk_manager: &mut ChunkManager,
+    ) -> Result<WorldInfo, Box<dyn std::error::Error>> {
         if let Some(world) = self.worlds.get_mut(world_id) {
+            if let Some(whitelist) = world.whitelist.as_ref().filter(|_| world.whitelist_enabled) {
+                if !whitelist.contains(player_id) {
+                    return Err("You are not whitelisted on this world".into());
+                }
+            }
+
             if world.player_count >= world.max_players {
                 return Err("World is full".into());
             }
             
+            // First player in brings the world online, generate the spawn area up front
+            if !world.is_online {
+                let spawn_chunk_x = (world.spawn_point[0].floor() as i32) >> 4;
+                let spawn_chunk_z = (world.spawn_point[2].floor() as i32) >> 4;
+                chunk_manager.preload_area(world_id, spawn_chunk_x, spawn_chunk_z, SPAWN_PRELOAD_RADIUS).await;
+            }
+            
             world.player_count += 1;
             world.last_active = Utc::now();
             world.is_online = true;
+            self.dirty_worlds.insert(world_id.to_string());
             
             // Update in database
             self.world_repository.update_world(world_id, &WorldUpdate::PlayerCount(world.player_count)).await?;
@@ -208,6 +539,51 @@ impl WorldManager {
         }
     }
 
+    pub async fn add_to_whitelist(
+        &mut self,
+        world_id: &str,
+        player_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let world = self.worlds.get(world_id).ok_or("World not found")?;
+
+        let mut entries = world.whitelist.clone().unwrap_or_default();
+        entries.insert(player_id.to_string());
+
+        let enabled = world.whitelist_enabled;
+        self.update_world(world_id, WorldUpdate::Whitelist { entries: Some(entries), enabled }).await
+    }
+
+    pub async fn remove_from_whitelist(
+        &mut self,
+        world_id: &str,
+        player_id: &str,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let world = self.worlds.get(world_id).ok_or("World not found")?;
+
+        let mut entries = match world.whitelist.clone() {
+            Some(entries) => entries,
+            None => return Ok(false),
+        };
+        if !entries.remove(player_id) {
+            return Ok(false);
+        }
+
+        let enabled = world.whitelist_enabled;
+        self.update_world(world_id, WorldUpdate::Whitelist { entries: Some(entries), enabled }).await?;
+        Ok(true)
+    }
+
+    pub async fn set_whitelist_enabled(
+        &mut self,
+        world_id: &str,
+        enabled: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Toggling keeps the list itself intact
+        let entries = self.worlds.get(world_id).ok_or("World not found")?.whitelist.clone();
+
+        self.update_world(world_id, WorldUpdate::Whitelist { entries, enabled }).await
+    }
+
     pub async fn leave_world(&mut self, world_id: &str) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(world) = self.worlds.get_mut(world_id) {
             if world.player_count > 0 {
@@ -218,6 +594,7 @@ impl WorldManager {
                 }
                 
                 world.last_active = Utc::now();
+                self.dirty_worlds.insert(world_id.to_string());
                 
                 // Update in database
                 self.world_repository.update_world(world_id, &WorldUpdate::PlayerCount(world.player_count)).await?;
@@ -227,6 +604,29 @@ impl WorldManager {
         Ok(())
     }
 
+    pub fn dirty_world_ids(&self) -> Vec<String> {
+        self.dirty_worlds.iter().cloned().collect()
+    }
+
+    // Last activity and online state a save of this world should write, None when nothing changed
+    pub fn dirty_world_state(&self, world_id: &str) -> Option<(DateTime<Utc>, bool)> {
+        if !self.dirty_worlds.contains(world_id) {
+            return None;
+        }
+        self.worlds.get(world_id).map(|world| (world.last_active, world.is_online))
+    }
+
+    // Stays dirty if the world changed again while its save was in flight
+    pub fn mark_world_saved(&mut self, world_id: &str, saved: (DateTime<Utc>, bool)) {
+        let unchanged = self
+            .worlds
+            .get(world_id)
+            .is_none_or(|world| (world.last_active, world.is_online) == saved);
+        if unchanged {
+            self.dirty_worlds.remove(world_id);
+        }
+    }
+
     pub async fn get_world_stats(&self) -> WorldStats {
         let total_worlds = self.worlds.len();
         let online_worlds = self.worlds.values().filter(|w| w.is_online).count();
@@ -246,6 +646,27 @@ pub enum WorldUpdate {
     LastActive(DateTime<Utc>),
     IsOnline(bool),
     Settings(WorldSettings),
+    Border(Option<WorldBorder>),
+    SpawnPoint([f64; 3]),
+    Whitelist {
+        entries: Option<HashSet<String>>,
+        enabled: bool,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WorldQuery {
+    pub name: Option<String>,
+    pub game_mode: Option<GameMode>,
+    pub is_online: Option<bool>,
+    #[serde(default)]
+    pub has_space: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorldPage {
+    pub worlds: Vec<WorldInfo>,
+    pub total: usize,
 }
 
 #[derive(Debug)]
@@ -253,4 +674,105 @@ pub struct WorldStats {
     pub total_worlds: usize,
     pub online_worlds: usize,
     pub total_players: usize,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{
+        database_service::{DatabaseConfig, DatabaseService},
+        entity_repository::EntityRepository,
+    };
+    use crate::systems::loot_system::LootSystem;
+    use crate::worlds::{biome_system::BiomeSystem, block_registry::BlockRegistry, structure_generator::StructureGenerator};
+
+    async fn test_database() -> Arc<DatabaseService> {
+        let config = DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            ..DatabaseConfig::default()
+        };
+        Arc::new(DatabaseService::new(&config).await.unwrap())
+    }
+
+    async fn manager_with_world() -> (WorldManager, String) {
+        let loot_system = Arc::new(LootSystem::new());
+        let structure_generator = Arc::new(StructureGenerator::new(loot_system.clone()));
+        let mut world_manager = WorldManager::new(
+            Arc::new(WorldRepository::new(test_database().await)),
+            TerrainParams::default(),
+            10,
+        );
+        let mut chunk_manager = ChunkManager::new(
+            1,
+            TerrainParams::default(),
+            Arc::new(BiomeSystem::new()),
+            structure_generator,
+            Arc::new(BlockRegistry::new()),
+            std::env::temp_dir().join("strixcraft-world-tests"),
+        );
+        let mut entity_manager = EntityManager::new(Arc::new(EntityRepository::new(test_database().await)), loot_system);
+        let settings = WorldSettings {
+            allow_pvp: false,
+            allow_mob_griefing: false,
+            keep_inventory: false,
+            natural_regeneration: true,
+            difficulty: Difficulty::Peaceful,
+            weather_enabled: false,
+            time_enabled: false,
+            mobs_enabled: false,
+            physics_enabled: false,
+            game_rules: HashMap::new(),
+            max_entities: None,
+        };
+        let world = world_manager
+            .create_world("bordered".to_string(), 1, GameMode::Survival, settings, 10, &mut chunk_manager, &mut entity_manager)
+            .await
+            .unwrap();
+        (world_manager, world.id)
+    }
+
+    #[test]
+    fn the_border_is_a_circle() {
+        let border = WorldBorder { center: [10.0, -10.0], radius: 100.0 };
+        assert!(border.contains([110.0, 64.0, -10.0]));
+        assert!(border.contains([80.0, 0.0, 60.0]));
+        // Inside the bounding square but past the radius on the diagonal
+        assert!(!border.contains([85.0, 64.0, 65.0]));
+        assert!(!border.contains([10.0, 64.0, 90.5]));
+    }
+
+    #[tokio::test]
+    async fn set_world_border_limits_the_world_until_cleared() {
+        let (mut world_manager, world_id) = manager_with_world().await;
+        assert!(world_manager.is_within_border(&world_id, [5000.0, 64.0, 5000.0]));
+
+        world_manager.set_world_border(&world_id, [0.0, 0.0], 50.0).await.unwrap();
+        assert!(world_manager.is_within_border(&world_id, [30.0, 64.0, -40.0]));
+        assert!(!world_manager.is_within_border(&world_id, [40.0, 64.0, -40.0]));
+        assert!(world_manager.set_spawn_point(&world_id, [60.0, 64.0, 0.0]).await.is_err());
+
+        // A radius of 0 removes the border
+        world_manager.set_world_border(&world_id, [0.0, 0.0], 0.0).await.unwrap();
+        assert!(world_manager.get_world(&world_id).await.unwrap().border.is_none());
+        assert!(world_manager.is_within_border(&world_id, [40.0, 64.0, -40.0]));
+
+        assert!(world_manager.set_world_border("missing", [0.0, 0.0], 50.0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn spawn_points_stay_inside_the_border() {
+        let (mut world_manager, world_id) = manager_with_world().await;
+        let surface = world_manager.get_spawn_point(&world_id).unwrap();
+        assert_eq!((surface[0], surface[2]), (0.0, 0.0));
+
+        world_manager.set_world_border(&world_id, [0.0, 0.0], 50.0).await.unwrap();
+        world_manager.set_spawn_point(&world_id, [20.5, 70.0, -4.5]).await.unwrap();
+        assert_eq!(world_manager.get_spawn_point(&world_id), Some([20.5, 70.0, -4.5]));
+
+        assert!(world_manager.set_spawn_point(&world_id, [60.0, 64.0, 0.0]).await.is_err());
+        assert_eq!(world_manager.get_spawn_point(&world_id), Some([20.5, 70.0, -4.5]));
+        assert!(world_manager.set_spawn_point("missing", [0.0, 64.0, 0.0]).await.is_err());
+        assert_eq!(world_manager.get_spawn_point("missing"), None);
+    }
+}
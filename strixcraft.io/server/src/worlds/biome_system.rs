@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::worlds::terrain_generator::TerrainGenerator;
+
+const CLIMATE_FREQUENCY: f64 = 1.0 / 256.0; // Biomes span a few hundred blocks
+const TEMPERATURE_SEED: i64 = 0x7E_4D_9A;
+const MOISTURE_SEED: i64 = 0x30_15_7C;
+const COLD_BELOW: f64 = -0.3;
+const HOT_ABOVE: f64 = 0.3;
+const DRY_BELOW: f64 = 0.0;
+const WET_ABOVE: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Biome {
+    Plains,
+    Forest,
+    Desert,
+    Tundra,
+}
+
+// Two smooth climate maps, temperature and moisture, picked apart by fixed thresholds.
+// Both are mixed with the world seed, so each world gets its own biome layout
+#[derive(Debug)]
+pub struct BiomeSystem {
+    frequency: f64,
+}
+
+impl BiomeSystem {
+    pub fn new() -> Self {
+        Self {
+            frequency: CLIMATE_FREQUENCY,
+        }
+    }
+
+    pub async fn get_biome(&self, seed: i64, x: i32, z: i32) -> Biome {
+        let (temperature, moisture) = self.climate(seed, x, z);
+        Self::classify(temperature, moisture)
+    }
+
+    // Both values in [-1, 1]
+    pub fn climate(&self, seed: i64, x: i32, z: i32) -> (f64, f64) {
+        let (fx, fz) = (x as f64 * self.frequency, z as f64 * self.frequency);
+        (
+            TerrainGenerator::value_noise_2d(seed ^ TEMPERATURE_SEED, fx, fz),
+            TerrainGenerator::value_noise_2d(seed ^ MOISTURE_SEED, fx, fz),
+        )
+    }
+
+    // Thresholds are exclusive, a value sitting exactly on one stays in the milder biome
+    pub fn classify(temperature: f64, moisture: f64) -> Biome {
+        if temperature < COLD_BELOW {
+            Biome::Tundra
+        } else if temperature > HOT_ABOVE && moisture < DRY_BELOW {
+            Biome::Desert
+        } else if moisture > WET_ABOVE {
+            Biome::Forest
+        } else {
+            Biome::Plains
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: i64 = 12345;
+
+    #[test]
+    fn thresholds_are_exclusive() {
+        assert_eq!(BiomeSystem::classify(COLD_BELOW, 0.0), Biome::Plains);
+        assert_eq!(BiomeSystem::classify(COLD_BELOW - 1e-9, 0.0), Biome::Tundra);
+        assert_eq!(BiomeSystem::classify(HOT_ABOVE, -0.5), Biome::Plains);
+        assert_eq!(BiomeSystem::classify(HOT_ABOVE + 1e-9, -0.5), Biome::Desert);
+        assert_eq!(BiomeSystem::classify(HOT_ABOVE + 1e-9, DRY_BELOW), Biome::Plains);
+        assert_eq!(BiomeSystem::classify(0.0, WET_ABOVE), Biome::Plains);
+        assert_eq!(BiomeSystem::classify(0.0, WET_ABOVE + 1e-9), Biome::Forest);
+        // Cold wins over wet, heat only matters when it's dry
+        assert_eq!(BiomeSystem::classify(-0.9, 0.9), Biome::Tundra);
+        assert_eq!(BiomeSystem::classify(0.9, 0.9), Biome::Forest);
+    }
+
+    #[tokio::test]
+    async fn boundaries_follow_the_climate_maps() {
+        let biomes = BiomeSystem::new();
+        let mut crossings = 0;
+        let mut previous = biomes.get_biome(SEED, -4096, 0).await;
+
+        for x in -4095..4096 {
+            let biome = biomes.get_biome(SEED, x, 0).await;
+            let (temperature, moisture) = biomes.climate(SEED, x, 0);
+            assert_eq!(biome, BiomeSystem::classify(temperature, moisture));
+
+            if biome != previous {
+                crossings += 1;
+                // The maps are smooth, so neighbours on either side of a border sit near a threshold
+                let (t0, m0) = biomes.climate(SEED, x - 1, 0);
+                assert!((temperature - t0).abs() < 0.05 && (moisture - m0).abs() < 0.05);
+            }
+            previous = biome;
+        }
+
+        assert!(crossings > 0, "an 8k block line should cross at least one biome border");
+        assert!(crossings < 200, "biomes should be large, got {} borders", crossings);
+    }
+
+    #[tokio::test]
+    async fn biomes_are_stable() {
+        let first = BiomeSystem::new();
+        let second = BiomeSystem::new();
+        for (x, z) in [(0, 0), (-1000, 250), (31, -77)] {
+            assert_eq!(first.get_biome(SEED, x, z).await, second.get_biome(SEED, x, z).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn the_world_seed_changes_the_layout() {
+        let biomes = BiomeSystem::new();
+        let mut differences = 0;
+        for x in (-4096..4096).step_by(64) {
+            if biomes.get_biome(1, x, 0).await != biomes.get_biome(2, x, 0).await {
+                differences += 1;
+            }
+        }
+        assert!(differences > 0, "two seeds produced the same biomes along an 8k block line");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use log::warn;
+
+pub const AIR: u8 = 0;
+pub const STONE: u8 = 1;
+pub const GRASS: u8 = 2;
+pub const DIRT: u8 = 3;
+pub const COBBLESTONE: u8 = 4;
+pub const PLANKS: u8 = 5;
+pub const BEDROCK: u8 = 7;
+pub const WATER: u8 = 8;
+pub const STILL_WATER: u8 = 9;
+pub const LAVA: u8 = 10;
+pub const STILL_LAVA: u8 = 11;
+pub const SAND: u8 = 12;
+pub const LOG: u8 = 17;
+pub const LEAVES: u8 = 18;
+pub const TNT: u8 = 46;
+pub const MOSSY_COBBLESTONE: u8 = 48;
+pub const OBSIDIAN: u8 = 49;
+pub const TORCH: u8 = 50;
+pub const SPAWNER: u8 = 52;
+pub const CHEST: u8 = 54;
+pub const SNOW: u8 = 80;
+pub const GLOWSTONE: u8 = 89;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Sword,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockProperties {
+    pub name: String,
+    pub solid: bool, // Collides with entities
+    pub transparent: bool, // Lets light through
+    pub hardness: f32, // Infinite for blocks survival players can't break
+    pub tool: Option<ToolKind>, // Mines faster with this tool
+    pub blast_resistance: f32,
+    pub light_emission: u8, // 0-15
+}
+
+#[derive(Debug)]
+pub struct BlockRegistry {
+    blocks: HashMap<u8, BlockProperties>,
+    fallback: BlockProperties, // Used for ids nobody registered
+}
+
+impl BlockRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            blocks: HashMap::new(),
+            fallback: BlockProperties {
+                name: "unknown".to_string(),
+                solid: true,
+                transparent: false,
+                hardness: 1.0,
+                tool: None,
+                blast_resistance: 3.0,
+                light_emission: 0,
+            },
+        };
+
+        registry.register_default_blocks();
+        registry
+    }
+
+    fn register_default_blocks(&mut self) {
+        // (id, name, solid, transparent, hardness, blast resistance, light emission, tool)
+        let defaults = [
+            (AIR, "air", false, true, 0.0, 0.0, 0, None),
+            (STONE, "stone", true, false, 1.5, 6.0, 0, Some(ToolKind::Pickaxe)),
+            (GRASS, "grass", true, false, 0.6, 0.6, 0, Some(ToolKind::Shovel)),
+            (DIRT, "dirt", true, false, 0.5, 0.5, 0, Some(ToolKind::Shovel)),
+            (COBBLESTONE, "cobblestone", true, false, 2.0, 6.0, 0, Some(ToolKind::Pickaxe)),
+            (PLANKS, "planks", true, false, 2.0, 3.0, 0, Some(ToolKind::Axe)),
+            (BEDROCK, "bedrock", true, false, f32::INFINITY, f32::INFINITY, 0, None),
+            (WATER, "water", false, true, 100.0, 100.0, 0, None),
+            (STILL_WATER, "still_water", false, true, 100.0, 100.0, 0, None),
+            (LAVA, "lava", false, true, 100.0, 100.0, 15, None),
+            (STILL_LAVA, "still_lava", false, true, 100.0, 100.0, 15, None),
+            (SAND, "sand", true, false, 0.5, 0.5, 0, Some(ToolKind::Shovel)),
+            (LOG, "log", true, false, 2.0, 2.0, 0, Some(ToolKind::Axe)),
+            (LEAVES, "leaves", true, true, 0.2, 0.2, 0, Some(ToolKind::Sword)),
+            (TNT, "tnt", true, false, 0.0, 0.0, 0, None),
+            (MOSSY_COBBLESTONE, "mossy_cobblestone", true, false, 2.0, 6.0, 0, Some(ToolKind::Pickaxe)),
+            (OBSIDIAN, "obsidian", true, false, 50.0, 1200.0, 0, Some(ToolKind::Pickaxe)),
+            (TORCH, "torch", false, true, 0.0, 0.0, 14, None),
+            (SPAWNER, "spawner", true, true, 5.0, 5.0, 0, Some(ToolKind::Pickaxe)),
+            (CHEST, "chest", true, false, 2.5, 2.5, 0, Some(ToolKind::Axe)),
+            (SNOW, "snow", true, false, 0.2, 0.1, 0, Some(ToolKind::Shovel)),
+            (GLOWSTONE, "glowstone", true, false, 0.3, 0.3, 15, None),
+        ];
+
+        for (id, name, solid, transparent, hardness, blast_resistance, light_emission, tool) in defaults {
+            self.register_block(id, BlockProperties {
+                name: name.to_string(),
+                solid,
+                transparent,
+                hardness,
+                tool,
+                blast_resistance,
+                light_emission,
+            });
+        }
+    }
+
+    pub fn register_block(&mut self, id: u8, properties: BlockProperties) {
+        if self.blocks.insert(id, properties).is_some() {
+            warn!("Block {} was registered twice, keeping the latest", id);
+        }
+    }
+
+    pub fn get(&self, id: u8) -> &BlockProperties {
+        self.blocks.get(&id).unwrap_or(&self.fallback)
+    }
+
+    pub fn is_solid(&self, id: u8) -> bool {
+        self.get(id).solid
+    }
+
+    pub fn is_transparent(&self, id: u8) -> bool {
+        self.get(id).transparent
+    }
+
+    pub fn is_unbreakable(&self, id: u8) -> bool {
+        self.get(id).hardness.is_infinite()
+    }
+
+    pub fn blast_resistance(&self, id: u8) -> f32 {
+        self.get(id).blast_resistance
+    }
+
+    pub fn light_emission(&self, id: u8) -> u8 {
+        self.get(id).light_emission
+    }
+
+    pub fn is_water(id: u8) -> bool {
+        matches!(id, WATER | STILL_WATER)
+    }
+}
\ No newline at end of file
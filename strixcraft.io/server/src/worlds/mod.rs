@@ -0,0 +1,4 @@
+pub mod terrain_generator;
+pub mod biome_system;
+pub mod structure_generator;
+pub mod block_registry;
\ No newline at end of file
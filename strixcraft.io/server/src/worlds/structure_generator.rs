@@ -0,0 +1,202 @@
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::item::InventoryItem;
+use crate::systems::loot_system::LootSystem;
+use crate::worlds::{
+    block_registry::{AIR, CHEST, COBBLESTONE, LEAVES, LOG, MOSSY_COBBLESTONE, PLANKS, SPAWNER},
+    terrain_generator::TerrainGenerator,
+};
+
+// Every structure fits inside this many chunks of its origin chunk
+const MAX_STRUCTURE_REACH: i32 = 1;
+const STRUCTURE_SEED_OFFSET: i64 = 0x57_2C_7E;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StructureKind {
+    Tree,
+    Hut,
+    Dungeon,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlacedStructure {
+    pub blocks: Vec<([i32; 3], u8)>, // World coordinates, air entries hollow out terrain
+    pub containers: Vec<([i32; 3], Vec<InventoryItem>)>, // Chest contents by world position
+}
+
+#[derive(Debug)]
+pub struct StructureGenerator {
+    loot_system: Arc<LootSystem>,
+    tree_chance: f64,
+    hut_chance: f64,
+    dungeon_chance: f64,
+}
+
+impl StructureGenerator {
+    pub fn new(loot_system: Arc<LootSystem>) -> Self {
+        Self {
+            loot_system,
+            tree_chance: 0.35,
+            hut_chance: 0.01,
+            dungeon_chance: 0.02,
+        }
+    }
+
+    // Every chunk decides its own structure purely from the seed and its coordinates,
+    // so a chunk can rebuild the parts of its neighbours' structures that reach into it
+    // without them being loaded and regeneration always produces the same result
+    pub async fn structures_overlapping(
+        &self,
+        terrain_generator: &TerrainGenerator,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Vec<PlacedStructure> {
+        let mut structures = Vec::new();
+
+        for origin_x in chunk_x - MAX_STRUCTURE_REACH..=chunk_x + MAX_STRUCTURE_REACH {
+            for origin_z in chunk_z - MAX_STRUCTURE_REACH..=chunk_z + MAX_STRUCTURE_REACH {
+                if let Some(structure) = self.structure_at(terrain_generator, origin_x, origin_z).await {
+                    let overlaps = structure
+                        .blocks
+                        .iter()
+                        .any(|([x, _, z], _)| x >> 4 == chunk_x && z >> 4 == chunk_z);
+                    if overlaps {
+                        structures.push(structure);
+                    }
+                }
+            }
+        }
+
+        structures
+    }
+
+    // At most one structure starts in each chunk
+    async fn structure_at(&self, terrain_generator: &TerrainGenerator, chunk_x: i32, chunk_z: i32) -> Option<PlacedStructure> {
+        let seed = terrain_generator.seed().wrapping_add(STRUCTURE_SEED_OFFSET);
+        let roll = Self::random(seed, chunk_x, chunk_z, 0);
+
+        let kind = if roll < self.dungeon_chance {
+            StructureKind::Dungeon
+        } else if roll < self.dungeon_chance + self.hut_chance {
+            StructureKind::Hut
+        } else if roll < self.dungeon_chance + self.hut_chance + self.tree_chance {
+            StructureKind::Tree
+        } else {
+            return None;
+        };
+
+        let x = chunk_x * 16 + (Self::random(seed, chunk_x, chunk_z, 1) * 16.0) as i32;
+        let z = chunk_z * 16 + (Self::random(seed, chunk_x, chunk_z, 2) * 16.0) as i32;
+        let surface = terrain_generator.get_height(x, z).await;
+
+        let blocks = match kind {
+            StructureKind::Tree => {
+                let trunk_height = 4 + (Self::random(seed, chunk_x, chunk_z, 3) * 3.0) as i32;
+                Self::tree([x, surface + 1, z], trunk_height)
+            }
+            StructureKind::Hut => Self::hut([x, surface + 1, z]),
+            StructureKind::Dungeon => {
+                // Buried well below the surface, never down into bedrock
+                let depth = 12 + (Self::random(seed, chunk_x, chunk_z, 3) * 20.0) as i32;
+                Self::dungeon([x, (surface - depth).max(2), z])
+            }
+        };
+
+        let containers = blocks
+            .iter()
+            .filter(|(_, block)| *block == CHEST)
+            .map(|(position, _)| {
+                let loot_seed = Self::hash(seed, chunk_x, chunk_z, 4);
+                (*position, self.loot_system.roll_loot("chests/dungeon", loot_seed))
+            })
+            .collect();
+
+        Some(PlacedStructure { blocks, containers })
+    }
+
+    fn tree(origin: [i32; 3], trunk_height: i32) -> Vec<([i32; 3], u8)> {
+        let [x, y, z] = origin;
+        let mut blocks = Vec::new();
+        let top = y + trunk_height;
+
+        for dy in -2..=1 {
+            let radius: i32 = if dy >= 0 { 1 } else { 2 };
+            for dx in -radius..=radius {
+                for dz in -radius..=radius {
+                    // Trim the canopy corners
+                    if dx.abs() == radius && dz.abs() == radius && radius > 1 {
+                        continue;
+                    }
+                    blocks.push(([x + dx, top + dy, z + dz], LEAVES));
+                }
+            }
+        }
+
+        // Trunk goes last so it overwrites the leaves it passes through
+        for dy in 0..trunk_height {
+            blocks.push(([x, y + dy, z], LOG));
+        }
+
+        blocks
+    }
+
+    fn hut(origin: [i32; 3]) -> Vec<([i32; 3], u8)> {
+        let [x, y, z] = origin;
+        let mut blocks = Vec::new();
+
+        for dx in -2..=2 {
+            for dz in -2..=2 {
+                blocks.push(([x + dx, y - 1, z + dz], COBBLESTONE));
+                for dy in 0..3 {
+                    let wall = dx.abs() == 2 || dz.abs() == 2;
+                    let doorway = dx == 0 && dz == -2 && dy < 2;
+                    blocks.push(([x + dx, y + dy, z + dz], if wall && !doorway { PLANKS } else { AIR }));
+                }
+                blocks.push(([x + dx, y + 3, z + dz], LOG));
+            }
+        }
+
+        blocks
+    }
+
+    fn dungeon(origin: [i32; 3]) -> Vec<([i32; 3], u8)> {
+        let [x, y, z] = origin;
+        let mut blocks = Vec::new();
+
+        for dx in -3i32..=3 {
+            for dz in -3i32..=3 {
+                for dy in -1..=4 {
+                    let shell = dx.abs() == 3 || dz.abs() == 3 || dy == -1 || dy == 4;
+                    let block = if !shell {
+                        AIR
+                    } else if (dx + dy + dz) % 3 == 0 {
+                        MOSSY_COBBLESTONE
+                    } else {
+                        COBBLESTONE
+                    };
+                    blocks.push(([x + dx, y + dy, z + dz], block));
+                }
+            }
+        }
+        blocks.push(([x, y, z], SPAWNER));
+        blocks.push(([x + 2, y, z], CHEST));
+
+        blocks
+    }
+
+    // Deterministic value in [0, 1) for a chunk and a per-decision salt
+    fn random(seed: i64, chunk_x: i32, chunk_z: i32, salt: u64) -> f64 {
+        (Self::hash(seed, chunk_x, chunk_z, salt) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn hash(seed: i64, chunk_x: i32, chunk_z: i32, salt: u64) -> u64 {
+        let mut h = (seed as u64)
+            ^ (chunk_x as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (chunk_z as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ salt.wrapping_mul(0x1656_67B1_9E37_79F9);
+        h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        h ^ (h >> 31)
+    }
+}
\ No newline at end of file
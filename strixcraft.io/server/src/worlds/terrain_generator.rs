@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+const MIN_HEIGHT: i32 = 1; // Keep at least one block above bedrock
+const MAX_HEIGHT: i32 = 255;
+const CAVE_CRUST: i32 = 4; // Solid blocks kept under the surface so caves don't open into holes everywhere
+const CAVE_SEED_OFFSET: i64 = 0x5EED_CA7E;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerrainParams {
+    pub base_height: i32,
+    pub amplitude: f64, // Max blocks above or below base_height
+    pub frequency: f64, // Features per block for the first octave
+    pub octaves: u32,
+    pub persistence: f64, // Amplitude multiplier per octave
+    pub lacunarity: f64, // Frequency multiplier per octave
+    pub cave_density: f64, // 0 disables caves, around 0.15 is already very hollow
+    pub cave_frequency: f64,
+}
+
+impl Default for TerrainParams {
+    fn default() -> Self {
+        Self {
+            base_height: 64,
+            amplitude: 24.0,
+            frequency: 0.01,
+            octaves: 4,
+            persistence: 0.5,
+            lacunarity: 2.0,
+            cave_density: 0.08,
+            cave_frequency: 0.05,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TerrainGenerator {
+    seed: i64,
+    params: TerrainParams,
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: i64, params: TerrainParams) -> Self {
+        Self { seed, params }
+    }
+
+    pub fn seed(&self) -> i64 {
+        self.seed
+    }
+
+    pub async fn get_height(&self, x: i32, z: i32) -> i32 {
+        let noise = self.fractal_noise_2d(x as f64, z as f64);
+        let height = self.params.base_height as f64 + noise * self.params.amplitude;
+        (height.round() as i32).clamp(MIN_HEIGHT, MAX_HEIGHT)
+    }
+
+    // Tunnels follow where two independent noise fields both cross zero, which
+    // gives long connected tubes instead of isolated bubbles
+    pub fn is_cave(&self, x: i32, y: i32, z: i32, surface_height: i32) -> bool {
+        // Bedrock at y=0 is never carved
+        if y <= 0 || y > surface_height - CAVE_CRUST || self.params.cave_density <= 0.0 {
+            return false;
+        }
+
+        let frequency = self.params.cave_frequency;
+        let (fx, fy, fz) = (x as f64 * frequency, y as f64 * frequency, z as f64 * frequency);
+        let seed = self.seed.wrapping_add(CAVE_SEED_OFFSET);
+
+        let first = Self::value_noise_3d(seed, fx, fy, fz);
+        let second = Self::value_noise_3d(seed.wrapping_add(1), fx, fy, fz);
+        first.abs() < self.params.cave_density && second.abs() < self.params.cave_density
+    }
+
+    // Sum of octaves normalized back into [-1, 1]
+    fn fractal_noise_2d(&self, x: f64, z: f64) -> f64 {
+        let mut total = 0.0;
+        let mut max_value = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = self.params.frequency;
+
+        for octave in 0..self.params.octaves.max(1) {
+            // Offset each octave's seed so layers don't line up
+            let seed = self.seed.wrapping_add(octave as i64 * 1_000_003);
+            total += Self::value_noise_2d(seed, x * frequency, z * frequency) * amplitude;
+            max_value += amplitude;
+            amplitude *= self.params.persistence;
+            frequency *= self.params.lacunarity;
+        }
+
+        total / max_value
+    }
+
+    // Also drives the biome climate maps
+    pub(crate) fn value_noise_2d(seed: i64, x: f64, z: f64) -> f64 {
+        let (x0, z0) = (x.floor(), z.floor());
+        let (tx, tz) = (Self::smooth(x - x0), Self::smooth(z - z0));
+        let (ix, iz) = (x0 as i64, z0 as i64);
+
+        let top = Self::lerp(Self::lattice(seed, ix, 0, iz), Self::lattice(seed, ix + 1, 0, iz), tx);
+        let bottom = Self::lerp(Self::lattice(seed, ix, 0, iz + 1), Self::lattice(seed, ix + 1, 0, iz + 1), tx);
+        Self::lerp(top, bottom, tz)
+    }
+
+    fn value_noise_3d(seed: i64, x: f64, y: f64, z: f64) -> f64 {
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (tx, ty, tz) = (Self::smooth(x - x0), Self::smooth(y - y0), Self::smooth(z - z0));
+        let (ix, iy, iz) = (x0 as i64, y0 as i64, z0 as i64);
+
+        let layer = |iy: i64| {
+            let top = Self::lerp(Self::lattice(seed, ix, iy, iz), Self::lattice(seed, ix + 1, iy, iz), tx);
+            let bottom = Self::lerp(Self::lattice(seed, ix, iy, iz + 1), Self::lattice(seed, ix + 1, iy, iz + 1), tx);
+            Self::lerp(top, bottom, tz)
+        };
+        Self::lerp(layer(iy), layer(iy + 1), ty)
+    }
+
+    // Deterministic pseudo-random value in [-1, 1] for an integer lattice point
+    fn lattice(seed: i64, x: i64, y: i64, z: i64) -> f64 {
+        let mut h = (seed as u64)
+            ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+        // splitmix64 finalizer
+        h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        h ^= h >> 31;
+
+        (h >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+    }
+
+    fn smooth(t: f64) -> f64 {
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    fn lerp(a: f64, b: f64, t: f64) -> f64 {
+        a + (b - a) * t
+    }
+}
\ No newline at end of file